@@ -0,0 +1,142 @@
+//! 比较回收图实例和每次从共享模板实例化的buff添加吞吐量。
+//!
+//! cargo bench -p ability
+//!
+//! 设置GRAPH_INSTANCE_BASELINE为文件路径时，文件不存在则保存这次的结果作为基线，
+//! 存在则和基线比较，修改图实例化和回收的代码前后各运行一次：
+//!
+//! GRAPH_INSTANCE_BASELINE=target/graph_instance.baseline cargo bench -p ability
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use ability::graph::{
+    context::{EffectGraphContext, GraphRef},
+    event::{EffectGraphAddEvent, EffectGraphRemoveEvent},
+    graph_map::EffectGraphMap,
+    node::{
+        implement::timer::{EffectNodeTimer, EffectNodeTimerPlugin},
+        plugin::EffectNodePlugin,
+        EffectNodeExecuteState,
+    },
+    pool::EffectGraphPool,
+    state::{EffectGraphState, EffectGraphTickState},
+    EffectGraphOwner, EffectGraphPlugin,
+};
+use bevy::prelude::*;
+
+const GRAPH_CLASS: &str = "bench_graph";
+const NODES: usize = 16;
+const OWNERS: usize = 256;
+const ROUNDS: usize = 20;
+
+fn new_app(pool_capacity: usize) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(EffectGraphPlugin)
+        .add_plugins(EffectNodePlugin)
+        .add_plugins(EffectNodeTimerPlugin);
+    app.world_mut().resource_mut::<EffectGraphPool>().capacity = pool_capacity;
+
+    let template = app.world_mut().spawn_empty().id();
+    let mut context = EffectGraphContext::new();
+    for _ in 0..NODES {
+        let node = app
+            .world_mut()
+            .spawn((
+                EffectNodeExecuteState::default(),
+                EffectNodeTimer::default(),
+            ))
+            .set_parent(template)
+            .id();
+        context.insert_state_node(node);
+        context.set_entry_node(node);
+    }
+    context.set_graph_ref(GraphRef::new(template));
+    app.world_mut().entity_mut(template).insert((
+        context,
+        EffectGraphState::Inactive,
+        EffectGraphTickState::Ticked,
+    ));
+    app.world_mut()
+        .resource_mut::<EffectGraphMap>()
+        .insert_graph(GRAPH_CLASS.into(), GraphRef::new(template));
+    app
+}
+
+/// 每轮给所有owner添加图，然后全部移除。
+fn bench(pool_capacity: usize) -> f64 {
+    let mut app = new_app(pool_capacity);
+    let owners: Vec<Entity> = (0..OWNERS)
+        .map(|_| app.world_mut().spawn(EffectGraphOwner).id())
+        .collect();
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        for owner in owners.iter() {
+            app.world_mut().trigger_targets(
+                EffectGraphAddEvent {
+                    graph_class: GRAPH_CLASS.into(),
+                },
+                *owner,
+            );
+        }
+        app.update();
+        for owner in owners.iter() {
+            app.world_mut()
+                .trigger_targets(EffectGraphRemoveEvent, *owner);
+        }
+        app.update();
+    }
+    start.elapsed().as_nanos() as f64 / (OWNERS * ROUNDS) as f64
+}
+
+/// 每行为`名字\t纳秒`。
+fn read_baseline(path: &Path) -> Option<HashMap<String, f64>> {
+    let content = std::fs::read_to_string(path).ok()?;
+    Some(
+        content
+            .lines()
+            .filter_map(|line| {
+                let (name, ns) = line.split_once('\t')?;
+                Some((name.to_string(), ns.parse().ok()?))
+            })
+            .collect(),
+    )
+}
+
+fn main() {
+    let results = [
+        ("instantiate from template", bench(0)),
+        ("recycle from pool", bench(OWNERS)),
+    ];
+
+    let baseline_path = std::env::var_os("GRAPH_INSTANCE_BASELINE").map(PathBuf::from);
+    let baseline = baseline_path.as_deref().and_then(read_baseline);
+    for (name, ns) in results.iter() {
+        match baseline.as_ref().and_then(|baseline| baseline.get(*name)) {
+            Some(base) => println!(
+                "{:<32} {:>10.1} ns/graph  baseline {:>10.1} ns/graph  {:+.1}%",
+                name,
+                ns,
+                base,
+                (ns / base - 1.0) * 100.0
+            ),
+            None => println!("{:<32} {:>10.1} ns/graph", name, ns),
+        }
+    }
+
+    if let (Some(path), None) = (baseline_path, baseline) {
+        let content: String = results
+            .iter()
+            .map(|(name, ns)| format!("{}\t{}\n", name, ns))
+            .collect();
+        match std::fs::write(&path, content) {
+            Ok(()) => println!("save baseline to {:?}", path),
+            Err(e) => eprintln!("save baseline to {:?} failed: {}", path, e),
+        }
+    }
+}
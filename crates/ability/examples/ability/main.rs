@@ -88,7 +88,7 @@ fn cast_base_skill(
     if input.just_pressed(KeyCode::KeyQ) {
         info!("just_pressed: cast_base_skill");
         for entity in ability_query.iter() {
-            commands.trigger_targets(AbilityStartEvent, entity);
+            commands.trigger_targets(AbilityStartEvent::default(), entity);
         }
     }
 }
@@ -9,8 +9,9 @@ use crate::{
 };
 
 use super::{
-    comp::{Ability, AbilityExecuteState, AbilityTickState},
+    comp::{Ability, AbilityCooldown, AbilityExecuteState, AbilityTickState},
     layertag::bundle::{AbilityAbortTagBundle, AbilityStartTagBundle},
+    trigger::AbilityTriggers,
 };
 
 #[derive(Bundle, Default)]
@@ -24,11 +25,13 @@ pub struct AbilityOwnerBundle<T: AttributeSet> {
 pub struct AbilityBundle {
     pub execute_state: AbilityExecuteState,
     pub tick_state: AbilityTickState,
+    pub cooldown: AbilityCooldown,
     pub ability: Ability,
     pub ability_row: TbAbilityRow,
     pub effect_graph_owner: EffectGraphOwner,
     pub start_tag_bundle: AbilityStartTagBundle,
     pub abort_tag_bundle: AbilityAbortTagBundle,
+    pub triggers: AbilityTriggers,
 }
 
 impl AbilityBundle {
@@ -48,10 +51,13 @@ impl AbilityBundle {
             state_registry,
         );
 
+        let triggers = AbilityTriggers::new(&data.triggers, state_registry);
+
         Self {
             ability_row,
             start_tag_bundle,
             abort_tag_bundle,
+            triggers,
             ..Default::default()
         }
     }
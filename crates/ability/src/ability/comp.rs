@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::graph::state::{EffectGraphState, EffectGraphTickState};
 
@@ -6,7 +7,9 @@ use crate::graph::state::{EffectGraphState, EffectGraphTickState};
 #[derive(Debug, Component, Default, Reflect, Copy, Clone)]
 pub struct Ability;
 
-#[derive(Debug, Component, Default, PartialEq, Eq, Reflect, Copy, Clone)]
+#[derive(
+    Debug, Component, Default, PartialEq, Eq, Reflect, Copy, Clone, Serialize, Deserialize,
+)]
 pub enum AbilityExecuteState {
     #[default]
     Inactive,
@@ -24,6 +27,26 @@ pub enum AbilityTickState {
 #[derive(Debug, Component, Default, Reflect)]
 pub struct AbilityData;
 
+/// 技能开始时按照表中的cd设置，冷却中的技能不能开始。
+#[derive(Debug, Component, Default, Reflect, Clone, Copy, PartialEq)]
+pub struct AbilityCooldown {
+    pub remaining: f32,
+}
+
+impl AbilityCooldown {
+    pub fn is_ready(&self) -> bool {
+        self.remaining <= 0.0
+    }
+}
+
+pub fn update_ability_cooldown(mut query: Query<&mut AbilityCooldown>, time: Res<Time>) {
+    for mut cooldown in query.iter_mut() {
+        if cooldown.remaining > 0.0 {
+            cooldown.remaining = (cooldown.remaining - time.delta_secs()).max(0.0);
+        }
+    }
+}
+
 /// 根据子图的状态更新技能的状态。
 /// 如果有至少一个子图正在执行，那么这个技能就是执行中的。
 /// 如果有至少一个子图Idle那么这个技能就是Idle的。
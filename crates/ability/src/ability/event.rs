@@ -1,35 +1,50 @@
-use bevy::prelude::*;
+use bevy::{ecs::query::QueryItem, prelude::*};
 use datatables::effect::TbAbilityRow;
-use layertag::container_op::{
-    LayerTagContainerConditionRequired, LayerTagContainerConditionWithout, LayerTagContainerOpAdd,
-    LayerTagContainerOpRemove,
+use layertag::{
+    container_op::{
+        LayerTagContainerConditionRequired, LayerTagContainerConditionWithout,
+        LayerTagContainerOpAdd, LayerTagContainerOpRemove,
+    },
+    query::LayerTagQuery,
 };
 
 use crate::{
     ability::node::ability_entry::EffectNodeAbilityEntry,
     graph::{
+        context::EffectGraphContext,
         event::{EffectGraphExecEvent, EffectGraphRemoveEvent, EffectGraphTickableEvent},
         state::EffectGraphState,
     },
-    stateset::StateLayerTagContainer,
+    stateset::{LayerTagQueryChanged, StateLayerTagContainer, StateLayerTagWatcher},
 };
 
 use super::{
-    comp::{Ability, AbilityExecuteState},
+    comp::{Ability, AbilityCooldown, AbilityExecuteState},
     layertag::tag::{
-        AbilityAbortDisableLayerTagContainer, AbilityAbortRequiredLayerTagContainer,
-        AbilityAddedLayerTagContainer, AbilityRemovedLayerTagContainer,
-        AbilityStartDisableLayerTagContainer, AbilityStartRequiredLayerTagContainer,
+        AbilityAbortDisableLayerTagContainer, AbilityAbortLayerTagQuery,
+        AbilityAbortRequiredLayerTagContainer, AbilityAddedLayerTagContainer,
+        AbilityRemovedLayerTagContainer, AbilityStartDisableLayerTagContainer,
+        AbilityStartLayerTagQuery, AbilityStartRequiredLayerTagContainer,
     },
+    target::AbilityTargetData,
 };
 
 use crate::graph::event::EffectGraphAddEvent;
 
+/// 技能订阅owner的中断条件，条件满足时自动中断激活的技能。
+pub const ABILITY_ABORT_WATCH: &str = "ability_abort";
+
 #[derive(Debug, Event)]
 pub struct AbilityReadyEvent;
 
-#[derive(Debug, Event)]
-pub struct AbilityStartEvent;
+/// target为验证后的目标数据，targets为目标实体，见`AbilityTargetRequestEvent`。
+/// predicted为客户端预测的开始，只设置冷却和执行表现节点，见`EffectGraphContext::predicted`。
+#[derive(Debug, Event, Default, Clone)]
+pub struct AbilityStartEvent {
+    pub target: AbilityTargetData,
+    pub targets: Vec<Entity>,
+    pub predicted: bool,
+}
 
 // 需要后续处理，等待技能执行完毕。
 // TODO: 如果处于激活状态下，需要触发中断事件。
@@ -45,38 +60,60 @@ pub struct AbilityTickableEvent {
     pub tickable: bool,
 }
 
+/// 技能的开始条件，见`can_start_ability`。
+pub type AbilityStartCondition = (
+    &'static AbilityStartRequiredLayerTagContainer,
+    &'static AbilityStartDisableLayerTagContainer,
+    &'static AbilityStartLayerTagQuery,
+);
+
+/// owner的状态标签是否满足技能的开始条件，例如没有被眩晕或者沉默。
+/// 准备、触发、被动和网络请求的开始都需要先检查。
+pub fn can_start_ability(
+    state_layer_tag_container: &StateLayerTagContainer,
+    (required_tag, disable_tag, query): QueryItem<'_, AbilityStartCondition>,
+) -> bool {
+    state_layer_tag_container
+        .0
+        .condition(LayerTagContainerConditionRequired, &required_tag.0)
+        && state_layer_tag_container
+            .0
+            .condition(LayerTagContainerConditionWithout, &disable_tag.0)
+        && query.0.matches(&state_layer_tag_container.0)
+}
+
+/// 技能实体的开始条件，没有开始条件的技能不检查，owner没有状态标签时按照空的容器判断。
+pub fn can_start_ability_entity(
+    start_condition_query: &Query<AbilityStartCondition>,
+    ability_entity: Entity,
+    state_layer_tag_container: Option<&StateLayerTagContainer>,
+) -> bool {
+    let Ok(condition) = start_condition_query.get(ability_entity) else {
+        return true;
+    };
+    match state_layer_tag_container {
+        Some(state_layer_tag_container) => can_start_ability(state_layer_tag_container, condition),
+        None => can_start_ability(&StateLayerTagContainer::default(), condition),
+    }
+}
+
 #[allow(clippy::type_complexity)]
 pub fn trigger_ability_ready(
     triger: Trigger<AbilityReadyEvent>,
     state_set_query: Query<&StateLayerTagContainer>,
     mut commands: Commands,
-    ability_query: Query<
-        (
-            &Parent,
-            &AbilityExecuteState,
-            &AbilityStartRequiredLayerTagContainer,
-            &AbilityStartDisableLayerTagContainer,
-        ),
-        With<Ability>,
-    >,
+    ability_query: Query<(&Parent, &AbilityExecuteState, AbilityStartCondition), With<Ability>>,
 ) {
     let ability_entity = triger.entity();
 
-    if let Ok((parent, state, required_tag, disable_tag)) = ability_query.get(ability_entity) {
+    if let Ok((parent, state, condition)) = ability_query.get(ability_entity) {
         if *state == AbilityExecuteState::ToRemove {
             return;
         }
 
         let state_layer_tag_container = state_set_query.get(parent.get()).unwrap();
 
-        let can_start = state_layer_tag_container
-            .0
-            .condition(LayerTagContainerConditionRequired, &required_tag.0)
-            && state_layer_tag_container
-                .0
-                .condition(LayerTagContainerConditionWithout, &disable_tag.0);
-
-        if can_start {
+        if can_start_ability(state_layer_tag_container, condition) {
             info!("trigger_ability_ready: {:?}", ability_entity);
             commands.trigger_targets(
                 EffectGraphExecEvent {
@@ -95,23 +132,41 @@ pub fn trigger_ability_start(
     trigger: Trigger<AbilityStartEvent>,
     mut state_set_query: Query<&mut StateLayerTagContainer>,
     mut commands: Commands,
-    ability_query: Query<
+    mut ability_query: Query<
         (
             &Parent,
             &AbilityExecuteState,
             &AbilityAddedLayerTagContainer,
             &AbilityRemovedLayerTagContainer,
+            Option<&Children>,
+            Option<&mut AbilityCooldown>,
+            &TbAbilityRow,
         ),
         With<Ability>,
     >,
+    mut graph_query: Query<(&mut EffectGraphContext, &EffectGraphState)>,
 ) {
     let ability_entity = trigger.entity();
+    let event = trigger.event();
 
-    if let Ok((parent, state, added_tag, removed_tag)) = ability_query.get(ability_entity) {
+    if let Ok((parent, state, added_tag, removed_tag, children, cooldown, ability_row)) =
+        ability_query.get_mut(ability_entity)
+    {
         if *state == AbilityExecuteState::ToRemove {
             return;
         }
 
+        if let Some(mut cooldown) = cooldown {
+            if !cooldown.is_ready() {
+                info!(
+                    "ability {:?} is cooling down: {}",
+                    ability_entity, cooldown.remaining
+                );
+                return;
+            }
+            cooldown.remaining = ability_row.data.as_ref().map_or(0.0, |data| data.cd);
+        }
+
         let mut state_layer_tag_container = state_set_query.get_mut(parent.get()).unwrap();
 
         state_layer_tag_container
@@ -121,12 +176,28 @@ pub fn trigger_ability_start(
         state_layer_tag_container
             .0
             .receive_op(LayerTagContainerOpRemove, &removed_tag.layer_tag_container);
-        info!("trigger_ability_start: {:?}", ability_entity);
+
+        // 目标数据写入即将执行的图的黑板。
+        for child in children.into_iter().flatten() {
+            if let Ok((mut context, graph_state)) = graph_query.get_mut(*child) {
+                if *graph_state == EffectGraphState::Inactive {
+                    event
+                        .target
+                        .write_to_blackboard(&event.targets, &mut context.blackboard);
+                    context.predicted = event.predicted;
+                }
+            }
+        }
+
+        info!(
+            "trigger_ability_start: {:?} {:?}",
+            ability_entity, event.target
+        );
         commands.trigger_targets(
             EffectGraphExecEvent {
                 entry_exec_pin: EffectNodeAbilityEntry::OUTPUT_EXEC_START.into(),
                 execute_in_graph_state: Some(EffectGraphState::Inactive),
-                slot_value_map: None,
+                slot_value_map: Some(event.target.slot_value_map(&event.targets)),
             },
             ability_entity,
         );
@@ -185,12 +256,15 @@ pub fn trigger_ability_abort(
             &AbilityExecuteState,
             &AbilityAbortRequiredLayerTagContainer,
             &AbilityAbortDisableLayerTagContainer,
+            &AbilityAbortLayerTagQuery,
         ),
         With<Ability>,
     >,
 ) {
     let ability_entity = triger.entity();
-    if let Ok((parent, state, required_tag, disable_tag)) = ability_query.get_mut(ability_entity) {
+    if let Ok((parent, state, required_tag, disable_tag, query)) =
+        ability_query.get_mut(ability_entity)
+    {
         if *state == AbilityExecuteState::ToRemove {
             return;
         }
@@ -201,7 +275,8 @@ pub fn trigger_ability_abort(
             .condition(LayerTagContainerConditionRequired, &required_tag.0)
             && state_layer_tag_container
                 .0
-                .condition(LayerTagContainerConditionWithout, &disable_tag.0);
+                .condition(LayerTagContainerConditionWithout, &disable_tag.0)
+            && query.0.matches(&state_layer_tag_container.0);
 
         if can_abort {
             info!("trigger_ability_abort: {:?}", ability_entity);
@@ -238,14 +313,45 @@ pub fn trigger_ability_tickable(
     }
 }
 
+/// 中断条件的订阅查询，没有中断条件时返回None。
+pub fn ability_abort_watch_query(
+    required_tag: &AbilityAbortRequiredLayerTagContainer,
+    disable_tag: &AbilityAbortDisableLayerTagContainer,
+    query: &AbilityAbortLayerTagQuery,
+) -> Option<LayerTagQuery> {
+    if required_tag.0.is_empty() && disable_tag.0.is_empty() && query.0.is_empty() {
+        return None;
+    }
+
+    Some(LayerTagQuery::All(vec![
+        LayerTagQuery::from_condition(&required_tag.0, &disable_tag.0),
+        query.0.clone(),
+    ]))
+}
+
 // add to ability entity observer
+#[allow(clippy::type_complexity)]
 pub fn trigger_ability_add(
     trigger: Trigger<OnAdd, Ability>,
     mut commands: Commands,
-    query: Query<&TbAbilityRow, With<Ability>>,
+    query: Query<
+        (
+            &TbAbilityRow,
+            &AbilityAbortRequiredLayerTagContainer,
+            &AbilityAbortDisableLayerTagContainer,
+            &AbilityAbortLayerTagQuery,
+        ),
+        With<Ability>,
+    >,
 ) {
     let ability_entity = trigger.entity();
-    let ability_row = query.get(ability_entity).unwrap();
+    let (ability_row, required_tag, disable_tag, query) = query.get(ability_entity).unwrap();
+
+    if let Some(abort_query) = ability_abort_watch_query(required_tag, disable_tag, query) {
+        let mut watcher = StateLayerTagWatcher::default();
+        watcher.watch(ABILITY_ABORT_WATCH, None, abort_query);
+        commands.entity(ability_entity).insert(watcher);
+    }
 
     if let Some(data) = ability_row.data.clone() {
         commands.trigger_targets(
@@ -256,3 +362,20 @@ pub fn trigger_ability_add(
         );
     }
 }
+
+/// 中断条件满足时，中断激活的技能。
+pub fn trigger_ability_abort_watch(
+    trigger: Trigger<LayerTagQueryChanged>,
+    mut commands: Commands,
+    ability_query: Query<&AbilityExecuteState, With<Ability>>,
+) {
+    let event = trigger.event();
+    if event.name != ABILITY_ABORT_WATCH || !event.matched {
+        return;
+    }
+
+    let ability_entity = trigger.entity();
+    if let Ok(AbilityExecuteState::Active) = ability_query.get(ability_entity) {
+        commands.trigger_targets(AbilityAbortEvent, ability_entity);
+    }
+}
@@ -4,22 +4,44 @@ use bevy::{
     reflect::Reflect,
 };
 use datatables::effect::RevertableLayerTag;
-use layertag::container_op::LayerTagContainer;
+use layertag::{container_op::LayerTagContainer, query::LayerTagQuery};
 
 use crate::stateset::StateLayerTagRegistry;
 
 use super::tag::{
-    AbilityAbortDisableLayerTagContainer, AbilityAbortRequiredLayerTagContainer,
-    AbilityAddedLayerTagContainer, AbilityRemovedLayerTagContainer,
-    AbilityStartDisableLayerTagContainer, AbilityStartRequiredLayerTagContainer,
+    AbilityAbortDisableLayerTagContainer, AbilityAbortLayerTagQuery,
+    AbilityAbortRequiredLayerTagContainer, AbilityAddedLayerTagContainer,
+    AbilityRemovedLayerTagContainer, AbilityStartDisableLayerTagContainer,
+    AbilityStartLayerTagQuery, AbilityStartRequiredLayerTagContainer,
 };
 
+/// 解析`*_layertags`列中的查询表达式，required中的表达式需要满足，disable中的表达式不能满足。
+/// 普通的LayerTag返回false，由调用者添加到容器中。
+fn push_layertag_query(
+    queries: &mut Vec<LayerTagQuery>,
+    raw_layertag: &str,
+    disable: bool,
+    state_registry: &Res<StateLayerTagRegistry>,
+) -> bool {
+    if !LayerTagQuery::is_expression(raw_layertag) {
+        return false;
+    }
+
+    match LayerTagQuery::parse(raw_layertag, &state_registry.0) {
+        Ok(query) if disable => queries.push(LayerTagQuery::None(vec![query])),
+        Ok(query) => queries.push(query),
+        Err(e) => warn!("layertag query {} parse failed: {}", raw_layertag, e),
+    }
+    true
+}
+
 #[derive(Debug, Default, Bundle, Reflect)]
 pub struct AbilityStartTagBundle {
     pub required_layertags: AbilityStartRequiredLayerTagContainer,
     pub disable_layertags: AbilityStartDisableLayerTagContainer,
     pub added_layertags: AbilityAddedLayerTagContainer,
     pub removed_layertags: AbilityRemovedLayerTagContainer,
+    pub query: AbilityStartLayerTagQuery,
 }
 
 impl AbilityStartTagBundle {
@@ -31,8 +53,12 @@ impl AbilityStartTagBundle {
         state_registry: &Res<StateLayerTagRegistry>,
     ) -> Self {
         let mut bundle = AbilityStartTagBundle::default();
+        let mut queries = Vec::new();
 
         for raw_layertag in required_layertags.iter() {
+            if push_layertag_query(&mut queries, raw_layertag, false, state_registry) {
+                continue;
+            }
             match state_registry.0.request_from_raw(raw_layertag) {
                 Some(layertag) => {
                     bundle.required_layertags.0.add_layertag(layertag);
@@ -44,6 +70,9 @@ impl AbilityStartTagBundle {
         }
 
         for raw_layertag in disable_layertags.iter() {
+            if push_layertag_query(&mut queries, raw_layertag, true, state_registry) {
+                continue;
+            }
             match state_registry.0.request_from_raw(raw_layertag) {
                 Some(layertag) => {
                     bundle.disable_layertags.0.add_layertag(layertag);
@@ -90,6 +119,8 @@ impl AbilityStartTagBundle {
             }
         }
 
+        bundle.query.0 = LayerTagQuery::All(queries);
+
         bundle
     }
 }
@@ -98,6 +129,7 @@ impl AbilityStartTagBundle {
 pub struct AbilityAbortTagBundle {
     pub required_layer_tag: AbilityAbortRequiredLayerTagContainer,
     pub disable_layer_tag: AbilityAbortDisableLayerTagContainer,
+    pub query: AbilityAbortLayerTagQuery,
 }
 
 impl AbilityAbortTagBundle {
@@ -107,8 +139,12 @@ impl AbilityAbortTagBundle {
         state_registry: &Res<StateLayerTagRegistry>,
     ) -> Self {
         let mut bundle = AbilityAbortTagBundle::default();
+        let mut queries = Vec::new();
 
         for raw_layertag in required_layertags.iter() {
+            if push_layertag_query(&mut queries, raw_layertag, false, state_registry) {
+                continue;
+            }
             match state_registry.0.request_from_raw(raw_layertag) {
                 Some(layertag) => {
                     bundle.required_layer_tag.0.add_layertag(layertag);
@@ -120,6 +156,9 @@ impl AbilityAbortTagBundle {
         }
 
         for raw_layertag in disable_layertags.iter() {
+            if push_layertag_query(&mut queries, raw_layertag, true, state_registry) {
+                continue;
+            }
             match state_registry.0.request_from_raw(raw_layertag) {
                 Some(layertag) => {
                     bundle.disable_layer_tag.0.add_layertag(layertag);
@@ -130,6 +169,8 @@ impl AbilityAbortTagBundle {
             }
         }
 
+        bundle.query.0 = LayerTagQuery::All(queries);
+
         bundle
     }
 }
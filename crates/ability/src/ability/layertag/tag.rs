@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use layertag::count_container::CountLayerTagContainer;
+use layertag::{count_container::CountLayerTagContainer, query::LayerTagQuery};
 
 #[derive(Component, Debug, Default, Reflect)]
 pub struct AbilityStartRequiredLayerTagContainer(pub CountLayerTagContainer);
@@ -13,6 +13,13 @@ pub struct AbilityAbortRequiredLayerTagContainer(pub CountLayerTagContainer);
 #[derive(Component, Debug, Default, Reflect)]
 pub struct AbilityAbortDisableLayerTagContainer(pub CountLayerTagContainer);
 
+/// `*_layertags`中的查询表达式，和容器的条件一起判断。
+#[derive(Component, Debug, Default, Reflect)]
+pub struct AbilityStartLayerTagQuery(pub LayerTagQuery);
+
+#[derive(Component, Debug, Default, Reflect)]
+pub struct AbilityAbortLayerTagQuery(pub LayerTagQuery);
+
 #[derive(Debug, Default, Reflect, PartialEq, Eq)]
 pub enum AbilityLayerTagContainerRevert {
     #[default]
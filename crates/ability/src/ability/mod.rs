@@ -4,6 +4,8 @@ pub mod event;
 pub mod layertag;
 pub mod node;
 pub mod plugin;
+pub mod target;
+pub mod trigger;
 
 // 主动和被动技能不需要区分，因为技能可以根据是否是用户特定的事件来触发来决定。
 
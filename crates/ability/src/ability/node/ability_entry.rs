@@ -1,8 +1,10 @@
 use bevy::app::{App, Plugin};
 use bevy::prelude::*;
 
+use crate::graph::node::pin::ReflectEffectNodePinGroup;
 use crate::graph::node::plugin::TypedComponentIds;
 use crate::graph::node::EffectNode;
+use crate::graph::node::ReflectStateEffectNodeInstance;
 use crate::{graph::node::StateEffectNode, impl_effect_node_pin_group};
 
 ///////////////////////// Plugin /////////////////////////
@@ -25,14 +27,20 @@ impl Plugin for EffectNodeAbilityEntryPlugin {
 
 ///////////////////////// Node Component /////////////////////////
 
-#[derive(Debug, Default, Component, Reflect)]
-#[reflect(Component)]
+/// start的输出slot为技能目标数据，同时写入黑板，见`ability::target`。
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component, Default, EffectNodePinGroup, StateEffectNodeInstance)]
 pub struct EffectNodeAbilityEntry;
 
 impl_effect_node_pin_group!(EffectNodeAbilityEntry,
     output => (
         ready => (),
-        start => (),
+        start => (
+            target: Entity,
+            target_point: Vec3,
+            target_direction: Vec3,
+            targets: Vec<Entity>
+        ),
         abort => ()
     )
 );
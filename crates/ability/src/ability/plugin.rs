@@ -1,14 +1,27 @@
 use bevy::prelude::*;
+use datatables::{
+    effect::{TbAbility, TbAbilityRow},
+    TableRowChangeKind, TableRowChanged,
+};
 
-use crate::graph::{state::update_to_despawn_effect_graph, EffectGraphUpdateSystemSet};
+use crate::{
+    graph::{state::update_to_despawn_effect_graph, EffectGraphUpdateSystemSet},
+    stateset::{StateLayerTagRegistry, StateLayerTagWatcher},
+};
 
 use super::{
-    comp::{update_ability_state, update_ability_tick_state, Ability, AbilityExecuteState},
+    comp::{
+        update_ability_cooldown, update_ability_state, update_ability_tick_state, Ability,
+        AbilityCooldown, AbilityExecuteState,
+    },
     event::{
-        trigger_ability_abort, trigger_ability_add, trigger_ability_ready, trigger_ability_remove,
-        trigger_ability_start, trigger_ability_tickable, AbilityAbortEvent, AbilityReadyEvent,
-        AbilityRemoveEvent, AbilityStartEvent, AbilityTickableEvent,
+        ability_abort_watch_query, trigger_ability_abort, trigger_ability_abort_watch,
+        trigger_ability_add, trigger_ability_ready, trigger_ability_remove, trigger_ability_start,
+        trigger_ability_tickable, AbilityAbortEvent, AbilityReadyEvent, AbilityRemoveEvent,
+        AbilityStartEvent, AbilityTickableEvent, ABILITY_ABORT_WATCH,
     },
+    layertag::bundle::{AbilityAbortTagBundle, AbilityStartTagBundle},
+    trigger::AbilityTriggers,
 };
 
 #[derive(Debug, Default)]
@@ -16,7 +29,8 @@ pub struct AbilityPlugin;
 
 impl Plugin for AbilityPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<AbilityStartEvent>()
+        app.register_type::<AbilityCooldown>()
+            .add_event::<AbilityStartEvent>()
             .add_event::<AbilityReadyEvent>()
             .add_event::<AbilityAbortEvent>()
             .add_event::<AbilityRemoveEvent>()
@@ -26,6 +40,7 @@ impl Plugin for AbilityPlugin {
                 (update_ability_state, update_ability_tick_state)
                     .after(EffectGraphUpdateSystemSet::UpdateState),
             )
+            .add_systems(Update, (update_ability_cooldown, update_ability_table_rows))
             .add_systems(
                 Last,
                 update_to_despawn_ability.after(update_to_despawn_effect_graph),
@@ -35,7 +50,8 @@ impl Plugin for AbilityPlugin {
             .add_observer(trigger_ability_ready)
             .add_observer(trigger_ability_start)
             .add_observer(trigger_ability_remove)
-            .add_observer(trigger_ability_abort);
+            .add_observer(trigger_ability_abort)
+            .add_observer(trigger_ability_abort_watch);
     }
 }
 
@@ -49,3 +65,163 @@ pub fn update_to_despawn_ability(
         }
     }
 }
+
+/// 热加载修改了技能行时，重新构建已经存在的技能的LayerTag和触发条件，下一次启动时生效。
+/// 中断条件的订阅立即替换。
+pub fn update_ability_table_rows(
+    mut commands: Commands,
+    mut event_reader: EventReader<TableRowChanged<TbAbility>>,
+    query: Query<(Entity, &TbAbilityRow, Option<&StateLayerTagWatcher>), With<Ability>>,
+    state_registry: Res<StateLayerTagRegistry>,
+) {
+    for event in event_reader.read() {
+        for (entity, ability_row, watcher) in
+            query.iter().filter(|(_, row, _)| row.key == event.key)
+        {
+            let Some(data) = ability_row.get_data() else {
+                warn!("ability {:?} row {} is removed", entity, event.key);
+                continue;
+            };
+            if event.kind != TableRowChangeKind::Modified {
+                continue;
+            }
+
+            let abort_tag_bundle = AbilityAbortTagBundle::new(
+                &data.abort_required_layertags,
+                &data.abort_disabled_layertags,
+                &state_registry,
+            );
+            let abort_query = ability_abort_watch_query(
+                &abort_tag_bundle.required_layer_tag,
+                &abort_tag_bundle.disable_layer_tag,
+                &abort_tag_bundle.query,
+            );
+
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.insert((
+                AbilityStartTagBundle::new(
+                    &data.start_required_layertags,
+                    &data.start_disabled_layertags,
+                    &data.start_added_layertags,
+                    &data.start_removed_layertags,
+                    &state_registry,
+                ),
+                abort_tag_bundle,
+                AbilityTriggers::new(&data.triggers, &state_registry),
+            ));
+            StateLayerTagWatcher::replace_watch(
+                &mut entity_commands,
+                watcher,
+                ABILITY_ABORT_WATCH,
+                abort_query,
+            );
+            info!("ability {:?} row {} reloaded", entity, event.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+    use datatables::{
+        effect::{Ability as AbilityData, AbilityType, TbAbility, TbAbilityRow},
+        TableRowChangeKind, TableRowChanged,
+    };
+    use layertag::container_op::LayerTagContainer;
+
+    use crate::{
+        ability::{
+            comp::{Ability, AbilityExecuteState},
+            event::{trigger_ability_abort_watch, AbilityAbortEvent},
+            layertag::bundle::AbilityAbortTagBundle,
+        },
+        stateset::{update_state_layertag_changes, StateLayerTagContainer, StateLayerTagRegistry},
+    };
+
+    use super::update_ability_table_rows;
+
+    const ABILITY_ID: i32 = 1;
+
+    #[derive(Resource, Default)]
+    struct Aborted(Vec<Entity>);
+
+    fn ability_data(abort_required_layertags: Vec<String>) -> Arc<AbilityData> {
+        Arc::new(AbilityData {
+            id: ABILITY_ID,
+            name: String::new(),
+            desc: String::new(),
+            graph_class: String::new(),
+            activation_type: AbilityType::Active,
+            cd: 0.0,
+            start_required_layertags: vec![],
+            start_disabled_layertags: vec![],
+            start_added_layertags: vec![],
+            start_removed_layertags: vec![],
+            abort_required_layertags,
+            abort_disabled_layertags: vec![],
+            triggers: vec![],
+        })
+    }
+
+    #[test]
+    fn reload_abort_condition() {
+        let mut registry = StateLayerTagRegistry::default();
+        registry.0.register_raw("reload.stunned");
+        let stunned = registry.0.request_from_raw("reload.stunned").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(registry)
+            .init_resource::<Aborted>()
+            .add_event::<TableRowChanged<TbAbility>>()
+            .add_systems(
+                Update,
+                (update_ability_table_rows, update_state_layertag_changes).chain(),
+            )
+            .add_observer(trigger_ability_abort_watch)
+            .add_observer(
+                |trigger: Trigger<AbilityAbortEvent>, mut aborted: ResMut<Aborted>| {
+                    aborted.0.push(trigger.entity());
+                },
+            );
+
+        let owner = app
+            .world_mut()
+            .spawn(StateLayerTagContainer::default())
+            .id();
+        let ability = app
+            .world_mut()
+            .spawn((
+                Ability,
+                AbilityExecuteState::Active,
+                TbAbilityRow::new(ABILITY_ID, Some(ability_data(vec![]))),
+                AbilityAbortTagBundle::default(),
+            ))
+            .set_parent(owner)
+            .id();
+        app.update();
+
+        app.world_mut()
+            .get_mut::<TbAbilityRow>(ability)
+            .unwrap()
+            .set_data(Some(ability_data(vec!["reload.stunned".into()])));
+        app.world_mut().send_event(TableRowChanged::<TbAbility> {
+            table: "TbAbility",
+            key: ABILITY_ID,
+            kind: TableRowChangeKind::Modified,
+        });
+        app.update();
+        app.update();
+        assert!(app.world().resource::<Aborted>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(stunned);
+        app.update();
+        assert_eq!(app.world().resource::<Aborted>().0, vec![ability]);
+    }
+}
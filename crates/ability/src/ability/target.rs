@@ -0,0 +1,582 @@
+use avian3d::prelude::{Collider, SpatialQuery, SpatialQueryFilter};
+use bevy::{ecs::system::SystemParam, prelude::*, utils::HashMap};
+use datatables::{
+    tables_system_param::TableReader,
+    unit::{RelationShipType, TbMonsterRow, TbNpcRow, TbPlayerRow, TbRelationShip},
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    ability::node::ability_entry::EffectNodeAbilityEntry,
+    graph::{
+        blackboard::{EffectBlackboard, EffectValue},
+        context::EffectGraphContext,
+        node::pin::EffectNodeSlot,
+    },
+};
+
+use super::{comp::Ability, event::AbilityStartEvent};
+
+#[derive(Debug, Default)]
+pub struct AbilityTargetPlugin;
+
+impl Plugin for AbilityTargetPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AbilityTargetData>()
+            .register_type::<AbilityTargetRule>()
+            .add_event::<AbilityTargetRequestEvent>()
+            .add_event::<AbilityTargetRejectedEvent>()
+            .add_observer(trigger_ability_target_request);
+    }
+}
+
+/// 目标数据写入黑板和技能entry节点start pin的key，和entry节点的输出slot同名。
+pub const ABILITY_TARGET_KEY: &str = EffectNodeAbilityEntry::OUTPUT_SLOT_TARGET;
+pub const ABILITY_TARGET_POINT_KEY: &str = EffectNodeAbilityEntry::OUTPUT_SLOT_TARGET_POINT;
+pub const ABILITY_TARGET_DIRECTION_KEY: &str = EffectNodeAbilityEntry::OUTPUT_SLOT_TARGET_DIRECTION;
+pub const ABILITY_TARGETS_KEY: &str = EffectNodeAbilityEntry::OUTPUT_SLOT_TARGETS;
+
+/// 技能的目标数据，由客户端选择，服务器验证后随AbilityStartEvent传入技能图。
+#[derive(Debug, Default, Clone, PartialEq, Reflect, Serialize, Deserialize)]
+pub enum AbilityTargetData {
+    #[default]
+    None,
+    Entity(Entity),
+    Point(Vec3),
+    Direction(Vec3),
+    /// 以origin为顶点，朝向direction，半角为half_angle(弧度)的圆锥。
+    Cone {
+        origin: Vec3,
+        direction: Vec3,
+        half_angle: f32,
+        range: f32,
+    },
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    Box {
+        center: Vec3,
+        half_extents: Vec3,
+        rotation: Quat,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum AbilityTargetType {
+    None,
+    Entity,
+    Point,
+    Direction,
+    Cone,
+    Sphere,
+    Box,
+}
+
+impl AbilityTargetData {
+    pub fn target_type(&self) -> AbilityTargetType {
+        match self {
+            AbilityTargetData::None => AbilityTargetType::None,
+            AbilityTargetData::Entity(_) => AbilityTargetType::Entity,
+            AbilityTargetData::Point(_) => AbilityTargetType::Point,
+            AbilityTargetData::Direction(_) => AbilityTargetType::Direction,
+            AbilityTargetData::Cone { .. } => AbilityTargetType::Cone,
+            AbilityTargetData::Sphere { .. } => AbilityTargetType::Sphere,
+            AbilityTargetData::Box { .. } => AbilityTargetType::Box,
+        }
+    }
+
+    pub fn is_area(&self) -> bool {
+        matches!(
+            self,
+            AbilityTargetData::Cone { .. }
+                | AbilityTargetData::Sphere { .. }
+                | AbilityTargetData::Box { .. }
+        )
+    }
+
+    /// 用于距离和视线检查的位置，实体目标需要外部查询位置。
+    pub fn point(&self) -> Option<Vec3> {
+        match self {
+            AbilityTargetData::Point(point) => Some(*point),
+            AbilityTargetData::Cone { origin, .. } => Some(*origin),
+            AbilityTargetData::Sphere { center, .. } => Some(*center),
+            AbilityTargetData::Box { center, .. } => Some(*center),
+            _ => None,
+        }
+    }
+
+    pub fn direction(&self) -> Option<Vec3> {
+        match self {
+            AbilityTargetData::Direction(direction) => Some(direction.normalize_or_zero()),
+            AbilityTargetData::Cone { direction, .. } => Some(direction.normalize_or_zero()),
+            _ => None,
+        }
+    }
+
+    /// 区域目标是否包含某个点，非区域目标总是返回false。
+    pub fn contains(&self, point: Vec3) -> bool {
+        match self {
+            AbilityTargetData::Cone {
+                origin,
+                direction,
+                half_angle,
+                range,
+            } => {
+                let offset = point - *origin;
+                let distance = offset.length();
+                if distance > *range {
+                    return false;
+                }
+                if distance <= f32::EPSILON {
+                    return true;
+                }
+                offset.angle_between(*direction) <= *half_angle
+            }
+            AbilityTargetData::Sphere { center, radius } => point.distance(*center) <= *radius,
+            AbilityTargetData::Box {
+                center,
+                half_extents,
+                rotation,
+            } => {
+                let local = rotation.inverse() * (point - *center);
+                local.abs().cmple(*half_extents).all()
+            }
+            _ => false,
+        }
+    }
+
+    /// 目标数据对应的entry输出slot和值，targets为验证后的目标实体。
+    pub fn slot_values(&self, targets: &[Entity]) -> Vec<(EffectNodeSlot, EffectValue)> {
+        let mut values = Vec::new();
+        if let AbilityTargetData::Entity(target) = self {
+            values.push((
+                EffectNodeSlot::new::<Entity>(ABILITY_TARGET_KEY),
+                EffectValue::Entity(*target),
+            ));
+        }
+        if let Some(point) = self.point() {
+            values.push((
+                EffectNodeSlot::new::<Vec3>(ABILITY_TARGET_POINT_KEY),
+                EffectValue::Vec3(point),
+            ));
+        }
+        if let Some(direction) = self.direction() {
+            values.push((
+                EffectNodeSlot::new::<Vec3>(ABILITY_TARGET_DIRECTION_KEY),
+                EffectValue::Vec3(direction),
+            ));
+        }
+        if *self != AbilityTargetData::None {
+            values.push((
+                EffectNodeSlot::new::<Vec<Entity>>(ABILITY_TARGETS_KEY),
+                EffectValue::VecEntity(targets.to_vec()),
+            ));
+        }
+        values
+    }
+
+    pub fn slot_value_map(&self, targets: &[Entity]) -> HashMap<EffectNodeSlot, EffectValue> {
+        self.slot_values(targets).into_iter().collect()
+    }
+
+    /// 写入黑板，先移除上一次释放的目标数据。
+    pub fn write_to_blackboard(&self, targets: &[Entity], blackboard: &mut EffectBlackboard) {
+        for key in [
+            ABILITY_TARGET_KEY,
+            ABILITY_TARGET_POINT_KEY,
+            ABILITY_TARGET_DIRECTION_KEY,
+            ABILITY_TARGETS_KEY,
+        ] {
+            blackboard.remove_value(key);
+        }
+        for (slot, value) in self.slot_values(targets) {
+            blackboard.set_value(slot.name, value);
+        }
+    }
+}
+
+/// 从黑板中读取单体目标，节点的target输入没有连接时使用。
+pub fn blackboard_target(context: &EffectGraphContext) -> Option<Entity> {
+    match context.blackboard.get_value(ABILITY_TARGET_KEY) {
+        Some(EffectValue::Entity(target)) => Some(*target),
+        _ => None,
+    }
+}
+
+/// 目标和技能拥有者的阵营关系。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum AbilityTargetRelationship {
+    #[default]
+    Any,
+    None,
+    Hostility,
+    Friendly,
+}
+
+impl From<&RelationShipType> for AbilityTargetRelationship {
+    fn from(value: &RelationShipType) -> Self {
+        match value {
+            RelationShipType::None => AbilityTargetRelationship::None,
+            RelationShipType::Hostility => AbilityTargetRelationship::Hostility,
+            RelationShipType::Friendly => AbilityTargetRelationship::Friendly,
+        }
+    }
+}
+
+/// 技能的目标规则，添加到技能实体上。没有规则的技能只检查目标实体是否存在。
+#[derive(Debug, Default, Clone, Component, Reflect)]
+#[reflect(Component, Default)]
+pub struct AbilityTargetRule {
+    /// 可以接受的目标类型，为空时接受所有类型。
+    pub target_types: Vec<AbilityTargetType>,
+    /// 技能拥有者到目标的最大距离。
+    pub max_range: Option<f32>,
+    /// 单体目标和区域内目标需要满足的阵营关系。
+    pub relationship: AbilityTargetRelationship,
+    /// 是否需要拥有者到目标之间没有阻挡。
+    pub line_of_sight: bool,
+}
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum AbilityTargetError {
+    #[error("ability {ability:?} not found")]
+    AbilityNotFound { ability: Entity },
+    #[error("target type {target_type:?} is not accepted")]
+    TargetTypeNotAccepted { target_type: AbilityTargetType },
+    #[error("target {target:?} not found")]
+    TargetNotFound { target: Entity },
+    #[error("owner {owner:?} has no transform")]
+    OwnerNotFound { owner: Entity },
+    #[error("target direction {direction} is invalid")]
+    InvalidDirection { direction: Vec3 },
+    #[error("target is out of range: {distance} > {max_range}")]
+    OutOfRange { distance: f32, max_range: f32 },
+    #[error("target {target:?} relationship is not {expected:?}")]
+    RelationshipMismatch {
+        target: Entity,
+        expected: AbilityTargetRelationship,
+    },
+    #[error("line of sight is blocked by {blocker:?}")]
+    LineOfSightBlocked { blocker: Entity },
+}
+
+/// 客户端请求使用目标释放技能，服务器验证通过后触发AbilityStartEvent。
+#[derive(Debug, Event, Clone)]
+pub struct AbilityTargetRequestEvent {
+    pub target: AbilityTargetData,
+}
+
+/// 目标验证失败，触发在技能实体上。
+#[derive(Debug, Event, Clone)]
+pub struct AbilityTargetRejectedEvent {
+    pub target: AbilityTargetData,
+    pub error: AbilityTargetError,
+}
+
+#[allow(clippy::type_complexity)]
+#[derive(SystemParam)]
+pub struct AbilityTargetValidator<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    transform_query: Query<'w, 's, &'static GlobalTransform>,
+    unit_query: Query<
+        'w,
+        's,
+        (
+            Option<&'static TbPlayerRow>,
+            Option<&'static TbNpcRow>,
+            Option<&'static TbMonsterRow>,
+        ),
+    >,
+    relationship_reader: TableReader<'w, TbRelationShip>,
+}
+
+impl AbilityTargetValidator<'_, '_> {
+    /// 验证目标，返回目标实体列表。区域目标返回区域内满足阵营关系的实体。
+    pub fn validate(
+        &self,
+        owner: Entity,
+        rule: Option<&AbilityTargetRule>,
+        target: &AbilityTargetData,
+    ) -> Result<Vec<Entity>, AbilityTargetError> {
+        let default_rule = AbilityTargetRule::default();
+        let rule = rule.unwrap_or(&default_rule);
+
+        if !rule.target_types.is_empty() && !rule.target_types.contains(&target.target_type()) {
+            return Err(AbilityTargetError::TargetTypeNotAccepted {
+                target_type: target.target_type(),
+            });
+        }
+
+        // 方向为零或者不是有限值时无法计算朝向。
+        if let AbilityTargetData::Direction(direction) | AbilityTargetData::Cone { direction, .. } =
+            target
+        {
+            if Dir3::new(*direction).is_err() {
+                return Err(AbilityTargetError::InvalidDirection {
+                    direction: *direction,
+                });
+            }
+        }
+
+        let target_point = match target {
+            AbilityTargetData::Entity(entity) => Some(
+                self.transform_query
+                    .get(*entity)
+                    .map_err(|_| AbilityTargetError::TargetNotFound { target: *entity })?
+                    .translation(),
+            ),
+            _ => target.point(),
+        };
+
+        if let Some(target_point) =
+            target_point.filter(|_| rule.max_range.is_some() || rule.line_of_sight)
+        {
+            let owner_point = self
+                .transform_query
+                .get(owner)
+                .map_err(|_| AbilityTargetError::OwnerNotFound { owner })?
+                .translation();
+            if let Some(max_range) = rule.max_range {
+                // 坐标不是有限值时距离为NaN，同样超出范围。
+                let distance = owner_point.distance(target_point);
+                if distance.is_nan() || distance > max_range {
+                    return Err(AbilityTargetError::OutOfRange {
+                        distance,
+                        max_range,
+                    });
+                }
+            }
+
+            if rule.line_of_sight {
+                let mut excluded = vec![owner];
+                if let AbilityTargetData::Entity(entity) = target {
+                    excluded.push(*entity);
+                }
+                self.check_line_of_sight(owner_point, target_point, excluded)?;
+            }
+        }
+
+        match target {
+            AbilityTargetData::Entity(entity) => {
+                if !self.is_relationship(owner, *entity, rule.relationship) {
+                    return Err(AbilityTargetError::RelationshipMismatch {
+                        target: *entity,
+                        expected: rule.relationship,
+                    });
+                }
+                Ok(vec![*entity])
+            }
+            _ if target.is_area() => Ok(self.area_targets(owner, rule.relationship, target)),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn check_line_of_sight(
+        &self,
+        from: Vec3,
+        to: Vec3,
+        excluded: Vec<Entity>,
+    ) -> Result<(), AbilityTargetError> {
+        let Ok(direction) = Dir3::new(to - from) else {
+            return Ok(());
+        };
+        let filter = SpatialQueryFilter::from_excluded_entities(excluded);
+        match self
+            .spatial_query
+            .cast_ray(from, direction, from.distance(to), true, &filter)
+        {
+            Some(hit) => Err(AbilityTargetError::LineOfSightBlocked {
+                blocker: hit.entity,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn area_targets(
+        &self,
+        owner: Entity,
+        relationship: AbilityTargetRelationship,
+        target: &AbilityTargetData,
+    ) -> Vec<Entity> {
+        let filter = SpatialQueryFilter::from_excluded_entities([owner]);
+        let entities = match target {
+            AbilityTargetData::Cone { origin, range, .. } => self
+                .spatial_query
+                .shape_intersections(&Collider::sphere(*range), *origin, Quat::IDENTITY, &filter),
+            AbilityTargetData::Sphere { center, radius } => self.spatial_query.shape_intersections(
+                &Collider::sphere(*radius),
+                *center,
+                Quat::IDENTITY,
+                &filter,
+            ),
+            AbilityTargetData::Box {
+                center,
+                half_extents,
+                rotation,
+            } => {
+                let size = *half_extents * 2.0;
+                self.spatial_query.shape_intersections(
+                    &Collider::cuboid(size.x, size.y, size.z),
+                    *center,
+                    *rotation,
+                    &filter,
+                )
+            }
+            _ => Vec::new(),
+        };
+
+        entities
+            .into_iter()
+            .filter(|entity| {
+                // 圆锥使用球体粗筛，再检查角度。
+                !matches!(target, AbilityTargetData::Cone { .. })
+                    || self
+                        .transform_query
+                        .get(*entity)
+                        .is_ok_and(|transform| target.contains(transform.translation()))
+            })
+            .filter(|entity| self.is_relationship(owner, *entity, relationship))
+            .collect()
+    }
+
+    fn get_camp(&self, entity: Entity) -> Option<i32> {
+        let (player_row, npc_row, monster_row) = self.unit_query.get(entity).ok()?;
+        if let Some(player_row) = player_row {
+            player_row.data.as_ref().map(|data| data.camp)
+        } else if let Some(npc_row) = npc_row {
+            npc_row.data.as_ref().map(|data| data.camp)
+        } else if let Some(monster_row) = monster_row {
+            monster_row.data.as_ref().map(|data| data.camp)
+        } else {
+            None
+        }
+    }
+
+    fn is_relationship(
+        &self,
+        owner: Entity,
+        target: Entity,
+        relationship: AbilityTargetRelationship,
+    ) -> bool {
+        if relationship == AbilityTargetRelationship::Any {
+            return true;
+        }
+
+        let (Some(camp), Some(target_camp)) = (self.get_camp(owner), self.get_camp(target)) else {
+            return false;
+        };
+        self.relationship_reader
+            .get_row_by_key(&(camp, target_camp))
+            .is_some_and(|row| {
+                AbilityTargetRelationship::from(&row.relationship_type) == relationship
+            })
+    }
+}
+
+pub fn trigger_ability_target_request(
+    trigger: Trigger<AbilityTargetRequestEvent>,
+    mut commands: Commands,
+    ability_query: Query<(&Parent, Option<&AbilityTargetRule>), With<Ability>>,
+    validator: AbilityTargetValidator,
+) {
+    let ability_entity = trigger.entity();
+    let target = trigger.event().target.clone();
+
+    let result = match ability_query.get(ability_entity) {
+        Ok((parent, rule)) => validator.validate(parent.get(), rule, &target),
+        Err(_) => Err(AbilityTargetError::AbilityNotFound {
+            ability: ability_entity,
+        }),
+    };
+
+    match result {
+        Ok(targets) => {
+            commands.trigger_targets(
+                AbilityStartEvent {
+                    target,
+                    targets,
+                    ..default()
+                },
+                ability_entity,
+            );
+        }
+        Err(error) => {
+            warn!(
+                "ability {:?} target {:?} rejected: {}",
+                ability_entity, target, error
+            );
+            commands.trigger_targets(AbilityTargetRejectedEvent { target, error }, ability_entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_4;
+
+    use bevy::prelude::*;
+
+    use crate::graph::blackboard::{EffectBlackboard, EffectValue};
+
+    use super::{
+        AbilityTargetData, ABILITY_TARGETS_KEY, ABILITY_TARGET_DIRECTION_KEY, ABILITY_TARGET_KEY,
+        ABILITY_TARGET_POINT_KEY,
+    };
+
+    #[test]
+    fn area_contains() {
+        let cone = AbilityTargetData::Cone {
+            origin: Vec3::ZERO,
+            direction: Vec3::X,
+            half_angle: FRAC_PI_4,
+            range: 10.0,
+        };
+        assert!(cone.contains(Vec3::new(5.0, 1.0, 0.0)));
+        assert!(!cone.contains(Vec3::new(1.0, 5.0, 0.0)));
+        assert!(!cone.contains(Vec3::new(11.0, 0.0, 0.0)));
+
+        let sphere = AbilityTargetData::Sphere {
+            center: Vec3::ONE,
+            radius: 2.0,
+        };
+        assert!(sphere.contains(Vec3::new(2.0, 2.0, 1.0)));
+        assert!(!sphere.contains(Vec3::new(3.0, 3.0, 3.0)));
+
+        let rotated_box = AbilityTargetData::Box {
+            center: Vec3::ZERO,
+            half_extents: Vec3::new(4.0, 1.0, 1.0),
+            rotation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+        };
+        assert!(rotated_box.contains(Vec3::new(0.0, 0.0, 3.0)));
+        assert!(!rotated_box.contains(Vec3::new(3.0, 0.0, 0.0)));
+
+        assert!(!AbilityTargetData::Point(Vec3::ZERO).contains(Vec3::ZERO));
+    }
+
+    #[test]
+    fn write_to_blackboard() {
+        let target = Entity::from_raw(7);
+        let mut blackboard = EffectBlackboard::default();
+        AbilityTargetData::Entity(target).write_to_blackboard(&[target], &mut blackboard);
+        assert_eq!(
+            blackboard.get_value(ABILITY_TARGET_KEY),
+            Some(&EffectValue::Entity(target))
+        );
+        assert_eq!(
+            blackboard.get_value(ABILITY_TARGETS_KEY),
+            Some(&EffectValue::VecEntity(vec![target]))
+        );
+
+        // 新的目标覆盖上一次释放的目标数据。
+        AbilityTargetData::Direction(Vec3::new(0.0, 0.0, 2.0))
+            .write_to_blackboard(&[], &mut blackboard);
+        assert_eq!(blackboard.get_value(ABILITY_TARGET_KEY), None);
+        assert_eq!(blackboard.get_value(ABILITY_TARGET_POINT_KEY), None);
+        assert_eq!(
+            blackboard.get_value(ABILITY_TARGET_DIRECTION_KEY),
+            Some(&EffectValue::Vec3(Vec3::Z))
+        );
+    }
+}
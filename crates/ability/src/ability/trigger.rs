@@ -0,0 +1,633 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use datatables::effect::{AbilityType, TbAbilityRow};
+use layertag::{layertag::LayerTag, registry::LayerTagRegistry};
+use thiserror::Error;
+
+use crate::{
+    graph::{
+        blackboard::{EffectBlackboard, EffectValue},
+        context::EffectGraphContext,
+        state::EffectGraphState,
+    },
+    stateset::{LayerTagAdded, StateLayerTagContainer, StateLayerTagRegistry},
+};
+
+use super::{
+    comp::{Ability, AbilityExecuteState},
+    event::{can_start_ability_entity, AbilityStartCondition, AbilityStartEvent},
+    target::AbilityTargetData,
+};
+
+#[derive(Debug, Default)]
+pub struct AbilityTriggerPlugin;
+
+impl Plugin for AbilityTriggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AbilityTriggers>()
+            .register_type::<AbilityPassivePending>()
+            .add_event::<AbilityTriggerEvent>()
+            .add_systems(Update, activate_passive_abilities)
+            .add_observer(trigger_passive_ability_add)
+            .add_observer(trigger_ability_trigger)
+            .add_observer(trigger_ability_trigger_layertag_added);
+    }
+}
+
+/// 触发数据写入黑板的key。
+pub const ABILITY_TRIGGER_TYPE_KEY: &str = "trigger_type";
+pub const ABILITY_TRIGGER_INSTIGATOR_KEY: &str = "trigger_instigator";
+pub const ABILITY_TRIGGER_TARGET_KEY: &str = "trigger_target";
+pub const ABILITY_TRIGGER_MAGNITUDE_KEY: &str = "trigger_magnitude";
+pub const ABILITY_TRIGGER_LAYERTAG_KEY: &str = "trigger_layertag";
+pub const ABILITY_TRIGGER_POINT_KEY: &str = "trigger_point";
+pub const ABILITY_TRIGGER_PROJECTILE_CLASS_KEY: &str = "trigger_projectile_class";
+
+/// 技能的触发条件，满足任意一个时，未激活的技能自动开始。
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum AbilityTrigger {
+    DamageTaken {
+        min_damage: f32,
+    },
+    CriticalHitDealt,
+    LayerTagAdded(LayerTag),
+    /// 生命值比例从不低于ratio变为低于ratio。
+    HpBelow {
+        ratio: f32,
+    },
+    /// projectile_class为None时匹配任意投射物。
+    ProjectileHit {
+        projectile_class: Option<Cow<'static, str>>,
+    },
+}
+
+/// 触发时的数据，写入技能图的黑板。
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub enum AbilityTriggerPayload {
+    DamageTaken {
+        instigator: Entity,
+        damage: f32,
+    },
+    CriticalHitDealt {
+        target: Entity,
+        damage: f32,
+    },
+    LayerTagAdded(LayerTag),
+    HpChanged {
+        previous_ratio: f32,
+        ratio: f32,
+    },
+    ProjectileHit {
+        projectile_class: Cow<'static, str>,
+        target: Entity,
+        point: Vec3,
+    },
+}
+
+/// 触发在拥有技能的实体上。
+/// 添加LayerTag由本模块转发，受到伤害、暴击、生命值变化和投射物命中由游戏层触发。
+#[derive(Debug, Event, Clone)]
+pub struct AbilityTriggerEvent(pub AbilityTriggerPayload);
+
+/// 由TbAbility的`triggers`列构建，见`AbilityTrigger::parse`。
+#[derive(Component, Debug, Default, Clone, Reflect)]
+pub struct AbilityTriggers(pub Vec<AbilityTrigger>);
+
+impl AbilityTriggers {
+    pub fn new(raw_triggers: &[String], state_registry: &Res<StateLayerTagRegistry>) -> Self {
+        Self(
+            raw_triggers
+                .iter()
+                .filter_map(|raw_trigger| {
+                    AbilityTrigger::parse(raw_trigger, &state_registry.0)
+                        .inspect_err(|e| {
+                            warn!("ability trigger {} parse failed: {}", raw_trigger, e)
+                        })
+                        .ok()
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AbilityTriggerParseError {
+    #[error("unknown trigger type: {0}")]
+    UnknownType(String),
+    #[error("trigger {0} requires a parameter")]
+    MissingParam(String),
+    #[error("trigger {trigger_type} has invalid parameter: {param}")]
+    InvalidParam { trigger_type: String, param: String },
+}
+
+/// 被动技能等待技能图创建完成后开始。
+#[derive(Component, Debug, Default, Clone, Copy, Reflect)]
+pub struct AbilityPassivePending;
+
+impl AbilityTrigger {
+    /// 格式为`类型:参数`，例如`damage_taken:10`、`critical_hit_dealt`、`layertag_added:A.B`、
+    /// `hp_below:0.3`和`projectile_hit:arrow`，投射物的参数可以省略。
+    pub fn parse(raw: &str, registry: &LayerTagRegistry) -> Result<Self, AbilityTriggerParseError> {
+        let (trigger_type, param) = match raw.trim().split_once(':') {
+            Some((trigger_type, param)) => (trigger_type.trim(), Some(param.trim())),
+            None => (raw.trim(), None),
+        };
+        let require_param =
+            || param.ok_or_else(|| AbilityTriggerParseError::MissingParam(trigger_type.into()));
+        let invalid_param = |param: &str| AbilityTriggerParseError::InvalidParam {
+            trigger_type: trigger_type.into(),
+            param: param.into(),
+        };
+        let parse_f32 = |param: &str| param.parse::<f32>().map_err(|_| invalid_param(param));
+
+        match trigger_type {
+            "damage_taken" => Ok(AbilityTrigger::DamageTaken {
+                min_damage: param.map_or(Ok(0.0), parse_f32)?,
+            }),
+            "critical_hit_dealt" => Ok(AbilityTrigger::CriticalHitDealt),
+            "layertag_added" => {
+                let param = require_param()?;
+                registry
+                    .request_from_raw(param)
+                    .map(AbilityTrigger::LayerTagAdded)
+                    .ok_or_else(|| invalid_param(param))
+            }
+            "hp_below" => Ok(AbilityTrigger::HpBelow {
+                ratio: parse_f32(require_param()?)?,
+            }),
+            "projectile_hit" => Ok(AbilityTrigger::ProjectileHit {
+                projectile_class: param
+                    .filter(|param| !param.is_empty())
+                    .map(|param| Cow::Owned(param.to_string())),
+            }),
+            _ => Err(AbilityTriggerParseError::UnknownType(trigger_type.into())),
+        }
+    }
+
+    pub fn matches(&self, payload: &AbilityTriggerPayload) -> bool {
+        match (self, payload) {
+            (
+                AbilityTrigger::DamageTaken { min_damage },
+                AbilityTriggerPayload::DamageTaken { damage, .. },
+            ) => damage >= min_damage,
+            (AbilityTrigger::CriticalHitDealt, AbilityTriggerPayload::CriticalHitDealt { .. }) => {
+                true
+            }
+            (
+                AbilityTrigger::LayerTagAdded(layertag),
+                AbilityTriggerPayload::LayerTagAdded(added),
+            ) => layertag.exact_match(added),
+            (
+                AbilityTrigger::HpBelow { ratio: threshold },
+                AbilityTriggerPayload::HpChanged {
+                    previous_ratio,
+                    ratio,
+                },
+            ) => previous_ratio >= threshold && ratio < threshold,
+            (
+                AbilityTrigger::ProjectileHit { projectile_class },
+                AbilityTriggerPayload::ProjectileHit {
+                    projectile_class: hit_class,
+                    ..
+                },
+            ) => projectile_class
+                .as_ref()
+                .is_none_or(|projectile_class| projectile_class == hit_class),
+            _ => false,
+        }
+    }
+}
+
+impl AbilityTriggerPayload {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            AbilityTriggerPayload::DamageTaken { .. } => "damage_taken",
+            AbilityTriggerPayload::CriticalHitDealt { .. } => "critical_hit_dealt",
+            AbilityTriggerPayload::LayerTagAdded(_) => "layertag_added",
+            AbilityTriggerPayload::HpChanged { .. } => "hp_changed",
+            AbilityTriggerPayload::ProjectileHit { .. } => "projectile_hit",
+        }
+    }
+
+    /// 技能开始时的目标，伤害来源或者命中的实体。
+    pub fn target(&self) -> AbilityTargetData {
+        match self {
+            AbilityTriggerPayload::DamageTaken { instigator, .. } => {
+                AbilityTargetData::Entity(*instigator)
+            }
+            AbilityTriggerPayload::CriticalHitDealt { target, .. }
+            | AbilityTriggerPayload::ProjectileHit { target, .. } => {
+                AbilityTargetData::Entity(*target)
+            }
+            AbilityTriggerPayload::LayerTagAdded(_) | AbilityTriggerPayload::HpChanged { .. } => {
+                AbilityTargetData::None
+            }
+        }
+    }
+
+    pub fn write_to_blackboard(&self, blackboard: &mut EffectBlackboard) {
+        for key in [
+            ABILITY_TRIGGER_INSTIGATOR_KEY,
+            ABILITY_TRIGGER_TARGET_KEY,
+            ABILITY_TRIGGER_MAGNITUDE_KEY,
+            ABILITY_TRIGGER_LAYERTAG_KEY,
+            ABILITY_TRIGGER_POINT_KEY,
+            ABILITY_TRIGGER_PROJECTILE_CLASS_KEY,
+        ] {
+            blackboard.remove_value(key);
+        }
+
+        blackboard.set_value(
+            ABILITY_TRIGGER_TYPE_KEY,
+            EffectValue::String(self.type_name().into()),
+        );
+        match self {
+            AbilityTriggerPayload::DamageTaken { instigator, damage } => {
+                blackboard.set_value(
+                    ABILITY_TRIGGER_INSTIGATOR_KEY,
+                    EffectValue::Entity(*instigator),
+                );
+                blackboard.set_value(ABILITY_TRIGGER_MAGNITUDE_KEY, EffectValue::F32(*damage));
+            }
+            AbilityTriggerPayload::CriticalHitDealt { target, damage } => {
+                blackboard.set_value(ABILITY_TRIGGER_TARGET_KEY, EffectValue::Entity(*target));
+                blackboard.set_value(ABILITY_TRIGGER_MAGNITUDE_KEY, EffectValue::F32(*damage));
+            }
+            AbilityTriggerPayload::LayerTagAdded(layertag) => {
+                blackboard.set_value(
+                    ABILITY_TRIGGER_LAYERTAG_KEY,
+                    EffectValue::LayerTag(layertag.clone()),
+                );
+            }
+            AbilityTriggerPayload::HpChanged { ratio, .. } => {
+                blackboard.set_value(ABILITY_TRIGGER_MAGNITUDE_KEY, EffectValue::F32(*ratio));
+            }
+            AbilityTriggerPayload::ProjectileHit {
+                projectile_class,
+                target,
+                point,
+            } => {
+                blackboard.set_value(ABILITY_TRIGGER_TARGET_KEY, EffectValue::Entity(*target));
+                blackboard.set_value(ABILITY_TRIGGER_POINT_KEY, EffectValue::Vec3(*point));
+                blackboard.set_value(
+                    ABILITY_TRIGGER_PROJECTILE_CLASS_KEY,
+                    EffectValue::String(projectile_class.clone()),
+                );
+            }
+        }
+    }
+}
+
+pub fn trigger_passive_ability_add(
+    trigger: Trigger<OnAdd, Ability>,
+    mut commands: Commands,
+    query: Query<&TbAbilityRow, With<Ability>>,
+) {
+    let ability_entity = trigger.entity();
+    let Ok(ability_row) = query.get(ability_entity) else {
+        return;
+    };
+
+    if ability_row
+        .data
+        .as_ref()
+        .is_some_and(|data| data.activation_type == AbilityType::Passive)
+    {
+        commands
+            .entity(ability_entity)
+            .insert(AbilityPassivePending);
+    }
+}
+
+/// 被动技能在技能图创建后开始，owner不满足开始条件时等待。
+#[allow(clippy::type_complexity)]
+pub fn activate_passive_abilities(
+    mut commands: Commands,
+    ability_query: Query<
+        (Entity, &Parent, &Children),
+        (With<Ability>, With<AbilityPassivePending>),
+    >,
+    owner_query: Query<&StateLayerTagContainer>,
+    start_condition_query: Query<AbilityStartCondition>,
+    graph_query: Query<&EffectGraphState>,
+) {
+    for (ability_entity, parent, children) in ability_query.iter() {
+        let graph_ready = children.iter().any(|child| {
+            graph_query
+                .get(*child)
+                .is_ok_and(|state| *state == EffectGraphState::Inactive)
+        });
+        if graph_ready
+            && can_start_ability_entity(
+                &start_condition_query,
+                ability_entity,
+                owner_query.get(parent.get()).ok(),
+            )
+        {
+            info!("activate passive ability: {:?}", ability_entity);
+            commands
+                .entity(ability_entity)
+                .remove::<AbilityPassivePending>();
+            commands.trigger_targets(AbilityStartEvent::default(), ability_entity);
+        }
+    }
+}
+
+/// 检查owner下所有的技能，满足触发条件和开始条件的未激活技能开始执行。
+#[allow(clippy::type_complexity)]
+pub fn trigger_ability_trigger(
+    trigger: Trigger<AbilityTriggerEvent>,
+    mut commands: Commands,
+    owner_query: Query<(&Children, Option<&StateLayerTagContainer>)>,
+    ability_query: Query<
+        (&AbilityTriggers, &AbilityExecuteState, Option<&Children>),
+        With<Ability>,
+    >,
+    start_condition_query: Query<AbilityStartCondition>,
+    mut graph_query: Query<(&mut EffectGraphContext, &EffectGraphState)>,
+) {
+    let payload = &trigger.event().0;
+    let Ok((children, state_layer_tag_container)) = owner_query.get(trigger.entity()) else {
+        return;
+    };
+
+    for ability_entity in children.iter() {
+        let Ok((triggers, state, graphs)) = ability_query.get(*ability_entity) else {
+            continue;
+        };
+        if *state != AbilityExecuteState::Inactive
+            || !triggers.0.iter().any(|trigger| trigger.matches(payload))
+            || !can_start_ability_entity(
+                &start_condition_query,
+                *ability_entity,
+                state_layer_tag_container,
+            )
+        {
+            continue;
+        }
+
+        for graph in graphs.into_iter().flatten() {
+            if let Ok((mut context, graph_state)) = graph_query.get_mut(*graph) {
+                if *graph_state == EffectGraphState::Inactive {
+                    payload.write_to_blackboard(&mut context.blackboard);
+                }
+            }
+        }
+
+        let target = payload.target();
+        let targets = match target {
+            AbilityTargetData::Entity(entity) => vec![entity],
+            _ => Vec::new(),
+        };
+        info!(
+            "ability {:?} triggered by {}",
+            ability_entity,
+            payload.type_name()
+        );
+        commands.trigger_targets(
+            AbilityStartEvent {
+                target,
+                targets,
+                ..default()
+            },
+            *ability_entity,
+        );
+    }
+}
+
+pub fn trigger_ability_trigger_layertag_added(
+    trigger: Trigger<LayerTagAdded>,
+    mut commands: Commands,
+) {
+    commands.trigger_targets(
+        AbilityTriggerEvent(AbilityTriggerPayload::LayerTagAdded(
+            trigger.event().layertag.clone(),
+        )),
+        trigger.entity(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use layertag::{
+        container_op::LayerTagContainer, count_container::CountLayerTagContainer,
+        registry::LayerTagRegistry,
+    };
+
+    use crate::{
+        ability::{
+            comp::{Ability, AbilityExecuteState},
+            event::AbilityStartEvent,
+            layertag::tag::{
+                AbilityStartDisableLayerTagContainer, AbilityStartLayerTagQuery,
+                AbilityStartRequiredLayerTagContainer,
+            },
+        },
+        graph::blackboard::{EffectBlackboard, EffectValue},
+        stateset::StateLayerTagContainer,
+    };
+
+    use super::{
+        trigger_ability_trigger, AbilityTrigger, AbilityTriggerEvent, AbilityTriggerParseError,
+        AbilityTriggerPayload, AbilityTriggers, ABILITY_TRIGGER_INSTIGATOR_KEY,
+        ABILITY_TRIGGER_LAYERTAG_KEY, ABILITY_TRIGGER_MAGNITUDE_KEY, ABILITY_TRIGGER_TYPE_KEY,
+    };
+
+    #[test]
+    fn trigger_matches() {
+        let damage = AbilityTriggerPayload::DamageTaken {
+            instigator: Entity::from_raw(1),
+            damage: 10.0,
+        };
+        assert!(AbilityTrigger::DamageTaken { min_damage: 5.0 }.matches(&damage));
+        assert!(!AbilityTrigger::DamageTaken { min_damage: 20.0 }.matches(&damage));
+        assert!(!AbilityTrigger::CriticalHitDealt.matches(&damage));
+
+        let hp_below = AbilityTrigger::HpBelow { ratio: 0.3 };
+        assert!(hp_below.matches(&AbilityTriggerPayload::HpChanged {
+            previous_ratio: 0.5,
+            ratio: 0.2,
+        }));
+        assert!(!hp_below.matches(&AbilityTriggerPayload::HpChanged {
+            previous_ratio: 0.2,
+            ratio: 0.1,
+        }));
+
+        let hit = AbilityTriggerPayload::ProjectileHit {
+            projectile_class: "arrow".into(),
+            target: Entity::from_raw(2),
+            point: Vec3::ZERO,
+        };
+        assert!(AbilityTrigger::ProjectileHit {
+            projectile_class: None
+        }
+        .matches(&hit));
+        assert!(!AbilityTrigger::ProjectileHit {
+            projectile_class: Some("fireball".into())
+        }
+        .matches(&hit));
+
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("trigger.wet");
+        registry.register_raw("trigger.burn");
+        let wet = registry.request_from_raw("trigger.wet").unwrap();
+        let burn = registry.request_from_raw("trigger.burn").unwrap();
+        let trigger = AbilityTrigger::LayerTagAdded(wet.clone());
+        assert!(trigger.matches(&AbilityTriggerPayload::LayerTagAdded(wet)));
+        assert!(!trigger.matches(&AbilityTriggerPayload::LayerTagAdded(burn)));
+    }
+
+    #[test]
+    fn parse_trigger() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("trigger.wet");
+
+        assert_eq!(
+            AbilityTrigger::parse("damage_taken: 10", &registry),
+            Ok(AbilityTrigger::DamageTaken { min_damage: 10.0 })
+        );
+        assert_eq!(
+            AbilityTrigger::parse("damage_taken", &registry),
+            Ok(AbilityTrigger::DamageTaken { min_damage: 0.0 })
+        );
+        assert_eq!(
+            AbilityTrigger::parse("layertag_added:trigger.wet", &registry),
+            Ok(AbilityTrigger::LayerTagAdded(
+                registry.request_from_raw("trigger.wet").unwrap()
+            ))
+        );
+        assert_eq!(
+            AbilityTrigger::parse("projectile_hit", &registry),
+            Ok(AbilityTrigger::ProjectileHit {
+                projectile_class: None
+            })
+        );
+        assert_eq!(
+            AbilityTrigger::parse("projectile_hit:arrow", &registry),
+            Ok(AbilityTrigger::ProjectileHit {
+                projectile_class: Some("arrow".into())
+            })
+        );
+
+        assert_eq!(
+            AbilityTrigger::parse("hp_below", &registry),
+            Err(AbilityTriggerParseError::MissingParam("hp_below".into()))
+        );
+        assert_eq!(
+            AbilityTrigger::parse("hp_below:low", &registry),
+            Err(AbilityTriggerParseError::InvalidParam {
+                trigger_type: "hp_below".into(),
+                param: "low".into(),
+            })
+        );
+        assert_eq!(
+            AbilityTrigger::parse("layertag_added:trigger.burn", &registry),
+            Err(AbilityTriggerParseError::InvalidParam {
+                trigger_type: "layertag_added".into(),
+                param: "trigger.burn".into(),
+            })
+        );
+        assert_eq!(
+            AbilityTrigger::parse("jump", &registry),
+            Err(AbilityTriggerParseError::UnknownType("jump".into()))
+        );
+    }
+
+    #[test]
+    fn skip_trigger_blocked_by_start_condition() {
+        #[derive(Resource, Default)]
+        struct Started(Vec<Entity>);
+
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("trigger.stunned");
+        let stunned = registry.request_from_raw("trigger.stunned").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Started>()
+            .add_observer(trigger_ability_trigger)
+            .add_observer(
+                |trigger: Trigger<AbilityStartEvent>, mut started: ResMut<Started>| {
+                    started.0.push(trigger.entity());
+                },
+            );
+
+        let owner = app
+            .world_mut()
+            .spawn(StateLayerTagContainer::default())
+            .id();
+        let mut disable = CountLayerTagContainer::default();
+        disable.add_layertag(stunned.clone());
+        let ability = app
+            .world_mut()
+            .spawn((
+                Ability,
+                AbilityExecuteState::Inactive,
+                AbilityTriggers(vec![AbilityTrigger::CriticalHitDealt]),
+                AbilityStartRequiredLayerTagContainer::default(),
+                AbilityStartDisableLayerTagContainer(disable),
+                AbilityStartLayerTagQuery::default(),
+            ))
+            .set_parent(owner)
+            .id();
+        let critical_hit = AbilityTriggerEvent(AbilityTriggerPayload::CriticalHitDealt {
+            target: Entity::PLACEHOLDER,
+            damage: 1.0,
+        });
+
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(stunned.clone());
+        app.world_mut().trigger_targets(critical_hit.clone(), owner);
+        app.update();
+        assert!(app.world().resource::<Started>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .remove_layertag(&stunned);
+        app.world_mut().trigger_targets(critical_hit, owner);
+        app.update();
+        assert_eq!(app.world().resource::<Started>().0, vec![ability]);
+    }
+
+    #[test]
+    fn write_payload_to_blackboard() {
+        let mut blackboard = EffectBlackboard::default();
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("trigger.wet");
+
+        AbilityTriggerPayload::DamageTaken {
+            instigator: Entity::from_raw(3),
+            damage: 12.0,
+        }
+        .write_to_blackboard(&mut blackboard);
+        assert_eq!(
+            blackboard.get_value(ABILITY_TRIGGER_INSTIGATOR_KEY),
+            Some(&EffectValue::Entity(Entity::from_raw(3)))
+        );
+        assert_eq!(
+            blackboard.get_value(ABILITY_TRIGGER_MAGNITUDE_KEY),
+            Some(&EffectValue::F32(12.0))
+        );
+
+        let wet = registry.request_from_raw("trigger.wet").unwrap();
+        AbilityTriggerPayload::LayerTagAdded(wet.clone()).write_to_blackboard(&mut blackboard);
+        assert_eq!(
+            blackboard.get_value(ABILITY_TRIGGER_TYPE_KEY),
+            Some(&EffectValue::String("layertag_added".into()))
+        );
+        assert_eq!(
+            blackboard.get_value(ABILITY_TRIGGER_LAYERTAG_KEY),
+            Some(&EffectValue::LayerTag(wet))
+        );
+        assert!(blackboard
+            .get_value(ABILITY_TRIGGER_INSTIGATOR_KEY)
+            .is_none());
+    }
+}
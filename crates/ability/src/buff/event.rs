@@ -3,9 +3,12 @@ use datatables::{
     effect::{TbBuff, TbBuffKey, TbBuffRow},
     tables_system_param::TableReader,
 };
-use layertag::container_op::{
-    LayerTagContainerConditionRequired, LayerTagContainerConditionWithout, LayerTagContainerOpAdd,
-    LayerTagContainerOpRemove,
+use layertag::{
+    container_op::{
+        LayerTagContainerConditionRequired, LayerTagContainerConditionWithout,
+        LayerTagContainerOpAdd, LayerTagContainerOpRemove,
+    },
+    query::LayerTagQuery,
 };
 
 use crate::{
@@ -19,7 +22,9 @@ use crate::{
         node::pin::EffectNodeSlot,
         state::EffectGraphState,
     },
-    stateset::{StateLayerTagContainer, StateLayerTagRegistry},
+    stateset::{
+        LayerTagQueryChanged, StateLayerTagContainer, StateLayerTagRegistry, StateLayerTagWatcher,
+    },
 };
 
 use super::{
@@ -32,6 +37,9 @@ use super::{
     state::{Buff, BuffExecuteState},
 };
 
+/// buff订阅owner的中断条件，条件满足时自动中断激活的buff。
+pub const BUFF_ABORT_WATCH: &str = "buff_abort";
+
 #[derive(Debug, Event)]
 pub struct BuffAddEvent {
     pub owner_entity: Entity,
@@ -51,18 +59,55 @@ pub struct BuffAbortEvent;
 #[derive(Debug, Event)]
 pub struct BuffRemoveEvent;
 
+/// 根据buff id移除owner下的buff。
+#[derive(Debug, Event)]
+pub struct BuffRemoveFromOwnerEvent {
+    pub owner_entity: Entity,
+    pub buff_id: TbBuffKey,
+}
+
 #[derive(Debug, Event)]
 pub struct BuffTickableEvent {
     pub tickable: bool,
 }
 
+#[allow(clippy::type_complexity)]
+/// 中断条件的订阅查询，没有中断条件时返回None。
+pub fn buff_abort_watch_query(
+    required_tag: &BuffAbortRequiredLayerTagContainer,
+    disable_tag: &BuffAbortDisableLayerTagContainer,
+) -> Option<LayerTagQuery> {
+    if required_tag.0.is_empty() && disable_tag.0.is_empty() {
+        return None;
+    }
+
+    Some(LayerTagQuery::from_condition(
+        &required_tag.0,
+        &disable_tag.0,
+    ))
+}
+
 pub fn trigger_buff_on_add(
     trigger: Trigger<OnAdd, Buff>,
     mut commands: Commands,
-    query: Query<&TbBuffRow, With<Buff>>,
+    query: Query<
+        (
+            &TbBuffRow,
+            &BuffAbortRequiredLayerTagContainer,
+            &BuffAbortDisableLayerTagContainer,
+        ),
+        With<Buff>,
+    >,
 ) {
     let buff_entity = trigger.entity();
-    let buff_row = query.get(buff_entity).unwrap();
+    let (buff_row, required_tag, disable_tag) = query.get(buff_entity).unwrap();
+
+    // buff在spawn之后才设置Parent，订阅使用Parent作为owner。
+    if let Some(abort_query) = buff_abort_watch_query(required_tag, disable_tag) {
+        let mut watcher = StateLayerTagWatcher::default();
+        watcher.watch(BUFF_ABORT_WATCH, None, abort_query);
+        commands.entity(buff_entity).insert(watcher);
+    }
 
     if let Some(data) = buff_row.data.clone() {
         commands.trigger_targets(
@@ -175,6 +220,26 @@ pub fn trigger_buff_remove(
     }
 }
 
+pub fn trigger_buff_remove_from_owner(
+    trigger: Trigger<BuffRemoveFromOwnerEvent>,
+    mut commands: Commands,
+    owner_query: Query<&Children>,
+    buff_query: Query<&TbBuffRow, With<Buff>>,
+) {
+    let event = trigger.event();
+    let Ok(children) = owner_query.get(event.owner_entity) else {
+        return;
+    };
+
+    for child in children {
+        if let Ok(buff_row) = buff_query.get(*child) {
+            if buff_row.key() == &event.buff_id {
+                commands.trigger_targets(BuffRemoveEvent, *child);
+            }
+        }
+    }
+}
+
 pub fn trigger_buff_abort(
     triger: Trigger<BuffAbortEvent>,
     mut commands: Commands,
@@ -217,6 +282,23 @@ pub fn trigger_buff_abort(
     }
 }
 
+/// 中断条件满足时，中断激活的buff。
+pub fn trigger_buff_abort_watch(
+    trigger: Trigger<LayerTagQueryChanged>,
+    mut commands: Commands,
+    buff_query: Query<&BuffExecuteState, With<Buff>>,
+) {
+    let event = trigger.event();
+    if event.name != BUFF_ABORT_WATCH || !event.matched {
+        return;
+    }
+
+    let buff_entity = trigger.entity();
+    if let Ok(BuffExecuteState::Active) = buff_query.get(buff_entity) {
+        commands.trigger_targets(BuffAbortEvent, buff_entity);
+    }
+}
+
 pub fn trigger_buff_tickable(
     triger: Trigger<BuffTickableEvent>,
     mut commands: Commands,
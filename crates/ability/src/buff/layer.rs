@@ -17,6 +17,14 @@ impl BuffLayer {
 }
 
 impl BuffLayer {
+    pub fn layer(&self) -> i32 {
+        self.layer
+    }
+
+    pub fn max_layer(&self) -> i32 {
+        self.max_layer
+    }
+
     pub fn add_layer(&mut self, layer: i32) {
         assert!(layer > 0, "layer must be greater than 0");
         self.layer += layer;
@@ -1,8 +1,10 @@
 use bevy::app::{App, Plugin};
 use bevy::prelude::*;
 
+use crate::graph::node::pin::ReflectEffectNodePinGroup;
 use crate::graph::node::plugin::TypedComponentIds;
 use crate::graph::node::EffectNode;
+use crate::graph::node::ReflectStateEffectNodeInstance;
 use crate::{graph::node::StateEffectNode, impl_effect_node_pin_group};
 
 ///////////////////////// Plugin /////////////////////////
@@ -25,8 +27,8 @@ impl Plugin for EffectNodeBuffEntryPlugin {
 
 ///////////////////////// Node Component /////////////////////////
 
-#[derive(Debug, Default, Component, Reflect)]
-#[reflect(Component)]
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component, Default, EffectNodePinGroup, StateEffectNodeInstance)]
 pub struct EffectNodeBuffEntry;
 
 impl_effect_node_pin_group!(EffectNodeBuffEntry,
@@ -1,16 +1,24 @@
 use crate::{
     buff::{
         event::{
-            trigger_buff_abort, trigger_buff_on_add, trigger_buff_remove, trigger_buff_start,
-            trigger_buff_tickable, BuffAbortEvent, BuffReadyEvent, BuffRemoveEvent, BuffStartEvent,
-            BuffTickableEvent,
+            buff_abort_watch_query, trigger_buff_abort, trigger_buff_abort_watch,
+            trigger_buff_add_event, trigger_buff_on_add, trigger_buff_remove,
+            trigger_buff_remove_from_owner, trigger_buff_start, trigger_buff_tickable,
+            BuffAbortEvent, BuffAddEvent, BuffReadyEvent, BuffRemoveEvent,
+            BuffRemoveFromOwnerEvent, BuffStartEvent, BuffTickableEvent, BUFF_ABORT_WATCH,
         },
+        layertag::bundle::{BuffAbortTagBundle, BuffStartTagBundle},
         state::{update_buff_state, update_buff_tick_state, Buff, BuffExecuteState},
         timer::update_buff_time_system,
     },
     graph::{state::update_to_despawn_effect_graph, EffectGraphUpdateSystemSet},
+    stateset::{StateLayerTagRegistry, StateLayerTagWatcher},
 };
 use bevy::prelude::*;
+use datatables::{
+    effect::{TbBuff, TbBuffRow},
+    TableRowChangeKind, TableRowChanged,
+};
 
 #[derive(Debug, Default)]
 pub struct BuffPlugin;
@@ -30,10 +38,15 @@ impl Plugin for BuffPlugin {
         .add_event::<BuffRemoveEvent>()
         .add_event::<BuffAbortEvent>()
         .add_event::<BuffTickableEvent>()
+        .add_event::<BuffAddEvent>()
+        .add_event::<BuffRemoveFromOwnerEvent>()
+        .add_observer(trigger_buff_add_event)
+        .add_observer(trigger_buff_remove_from_owner)
         .add_observer(trigger_buff_on_add)
         .add_observer(trigger_buff_remove)
         .add_observer(trigger_buff_start)
         .add_observer(trigger_buff_abort)
+        .add_observer(trigger_buff_abort_watch)
         .add_observer(trigger_buff_tickable)
         .add_systems(
             Update,
@@ -45,6 +58,7 @@ impl Plugin for BuffPlugin {
             Update,
             update_buff_time_system.in_set(BuffUpdateSystemSet::UpdateTime),
         )
+        .add_systems(Update, update_buff_table_rows)
         .add_systems(
             Last,
             update_to_despawn_buff.after(update_to_despawn_effect_graph),
@@ -68,3 +82,52 @@ pub fn update_to_despawn_buff(
         }
     }
 }
+
+/// 热加载修改了buff行时，重新构建已经存在的buff的LayerTag和中断条件的订阅，时间和层数不变。
+pub fn update_buff_table_rows(
+    mut commands: Commands,
+    mut event_reader: EventReader<TableRowChanged<TbBuff>>,
+    query: Query<(Entity, &TbBuffRow, Option<&StateLayerTagWatcher>), With<Buff>>,
+    state_registry: Res<StateLayerTagRegistry>,
+) {
+    for event in event_reader.read() {
+        for (entity, buff_row, watcher) in query.iter().filter(|(_, row, _)| row.key == event.key) {
+            let Some(data) = buff_row.get_data() else {
+                warn!("buff {:?} row {} is removed", entity, event.key);
+                continue;
+            };
+            if event.kind != TableRowChangeKind::Modified {
+                continue;
+            }
+
+            let abort_tag_bundle = BuffAbortTagBundle::new(
+                &data.abort_required_layertags,
+                &data.abort_disabled_layertags,
+                &state_registry,
+            );
+            let abort_query = buff_abort_watch_query(
+                &abort_tag_bundle.required_layer_tag,
+                &abort_tag_bundle.disable_layer_tag,
+            );
+
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.insert((
+                BuffStartTagBundle::new(
+                    &data.start_required_layertags,
+                    &data.start_disabled_layertags,
+                    &data.start_added_layertags,
+                    &data.start_removed_layertags,
+                    &state_registry,
+                ),
+                abort_tag_bundle,
+            ));
+            StateLayerTagWatcher::replace_watch(
+                &mut entity_commands,
+                watcher,
+                BUFF_ABORT_WATCH,
+                abort_query,
+            );
+            info!("buff {:?} row {} reloaded", entity, event.key);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::{borrow::Cow, sync::Arc};
+
+use bevy::{prelude::*, utils::HashMap};
+use layertag::layertag::LayerTag;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    graph::node::implement::cue::EffectPlayCueEvent,
+    stateset::{LayerTagAdded, LayerTagRemoved, StateLayerTagRegistry},
+};
+
+/// 表现LayerTag的根，状态中添加和移除这个根下的LayerTag时触发持续的表现。
+pub const GAMEPLAY_CUE_ROOT: &str = "Cue";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum GameplayCueKind {
+    /// 一次性的表现，例如命中特效和飘字。
+    OnExecute,
+    /// 持续的表现开始，例如燃烧特效。
+    WhileActive,
+    /// 持续的表现结束。
+    OnRemove,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct GameplayCueParams {
+    pub instigator: Option<Entity>,
+    pub magnitude: f32,
+}
+
+/// 触发在表现的目标上，只用于表现，逻辑不应该监听。
+#[derive(Debug, Event, Clone, PartialEq)]
+pub struct GameplayCueEvent {
+    pub cue: LayerTag,
+    pub kind: GameplayCueKind,
+    pub params: GameplayCueParams,
+}
+
+pub trait GameplayCueHandler: Send + Sync + 'static {
+    fn handle(&self, commands: &mut Commands, target: Entity, event: &GameplayCueEvent);
+}
+
+impl<F> GameplayCueHandler for F
+where
+    F: Fn(&mut Commands, Entity, &GameplayCueEvent) + Send + Sync + 'static,
+{
+    fn handle(&self, commands: &mut Commands, target: Entity, event: &GameplayCueEvent) {
+        self(commands, target, event);
+    }
+}
+
+/// 按照LayerTag注册的表现处理，只在客户端注册，专用服务器没有处理时跳过所有表现。
+#[derive(Resource, Default)]
+pub struct GameplayCueRegistry {
+    handlers: HashMap<Cow<'static, str>, Arc<dyn GameplayCueHandler>>,
+}
+
+impl GameplayCueRegistry {
+    pub fn register(
+        &mut self,
+        raw_cue: impl Into<Cow<'static, str>>,
+        handler: impl GameplayCueHandler,
+    ) {
+        self.handlers.insert(raw_cue.into(), Arc::new(handler));
+    }
+
+    /// 没有注册的LayerTag使用最近的父LayerTag的处理。
+    pub fn find_handler(&self, raw_cue: &str) -> Option<Arc<dyn GameplayCueHandler>> {
+        let mut raw_cue = raw_cue;
+        loop {
+            if let Some(handler) = self.handlers.get(raw_cue) {
+                return Some(handler.clone());
+            }
+            raw_cue = &raw_cue[..raw_cue.rfind(LayerTag::DELIMITER)?];
+        }
+    }
+}
+
+pub trait GameplayCueAppExt {
+    fn add_gameplay_cue_handler(
+        &mut self,
+        raw_cue: impl Into<Cow<'static, str>>,
+        handler: impl GameplayCueHandler,
+    ) -> &mut Self;
+}
+
+impl GameplayCueAppExt for App {
+    fn add_gameplay_cue_handler(
+        &mut self,
+        raw_cue: impl Into<Cow<'static, str>>,
+        handler: impl GameplayCueHandler,
+    ) -> &mut Self {
+        self.world_mut()
+            .get_resource_or_insert_with(GameplayCueRegistry::default)
+            .register(raw_cue, handler);
+        self
+    }
+}
+
+pub fn is_gameplay_cue(layertag: &LayerTag) -> bool {
+    layertag
+        .raw_layertag()
+        .split(LayerTag::DELIMITER)
+        .next()
+        .is_some_and(|root| root == GAMEPLAY_CUE_ROOT)
+}
+
+#[derive(Debug, Default)]
+pub struct GameplayCuePlugin;
+
+impl Plugin for GameplayCuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameplayCueRegistry>()
+            .add_event::<GameplayCueEvent>()
+            .add_observer(trigger_effect_play_cue)
+            .add_observer(trigger_layertag_added_cue)
+            .add_observer(trigger_layertag_removed_cue)
+            .add_observer(dispatch_gameplay_cue);
+    }
+}
+
+/// effect graph中的表现节点。
+fn trigger_effect_play_cue(
+    trigger: Trigger<EffectPlayCueEvent>,
+    mut commands: Commands,
+    registry: Res<StateLayerTagRegistry>,
+) {
+    let event = trigger.event();
+    let Some(cue) = registry.0.request_from_raw(&event.cue) else {
+        warn!("gameplay cue {} is not registered", event.cue);
+        return;
+    };
+
+    commands.trigger_targets(
+        GameplayCueEvent {
+            cue,
+            kind: GameplayCueKind::OnExecute,
+            params: GameplayCueParams {
+                instigator: None,
+                magnitude: event.magnitude,
+            },
+        },
+        trigger.entity(),
+    );
+}
+
+/// buff和技能通过添加Cue下的LayerTag触发持续的表现。
+fn trigger_layertag_added_cue(trigger: Trigger<LayerTagAdded>, mut commands: Commands) {
+    let layertag = &trigger.event().layertag;
+    if is_gameplay_cue(layertag) {
+        commands.trigger_targets(
+            GameplayCueEvent {
+                cue: layertag.clone(),
+                kind: GameplayCueKind::WhileActive,
+                params: GameplayCueParams::default(),
+            },
+            trigger.entity(),
+        );
+    }
+}
+
+fn trigger_layertag_removed_cue(trigger: Trigger<LayerTagRemoved>, mut commands: Commands) {
+    let layertag = &trigger.event().layertag;
+    if is_gameplay_cue(layertag) {
+        commands.trigger_targets(
+            GameplayCueEvent {
+                cue: layertag.clone(),
+                kind: GameplayCueKind::OnRemove,
+                params: GameplayCueParams::default(),
+            },
+            trigger.entity(),
+        );
+    }
+}
+
+fn dispatch_gameplay_cue(
+    trigger: Trigger<GameplayCueEvent>,
+    mut commands: Commands,
+    registry: Res<GameplayCueRegistry>,
+) {
+    let event = trigger.event();
+    if let Some(handler) = registry.find_handler(&event.cue.raw_layertag()) {
+        handler.handle(&mut commands, trigger.entity(), event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use layertag::{container_op::LayerTagContainer, registry::LayerTagRegistry};
+
+    use crate::{
+        graph::node::implement::cue::EffectPlayCueEvent,
+        stateset::{update_state_layertag_changes, StateLayerTagContainer, StateLayerTagRegistry},
+    };
+
+    use super::{GameplayCueAppExt, GameplayCueEvent, GameplayCueKind, GameplayCuePlugin};
+
+    #[derive(Resource, Default)]
+    struct Received(Vec<String>);
+
+    fn record(name: &'static str) -> impl Fn(&mut Commands, Entity, &GameplayCueEvent) {
+        move |commands: &mut Commands, _target: Entity, event: &GameplayCueEvent| {
+            let message = format!("{} {} {:?}", name, event.cue, event.kind);
+            commands.queue(move |world: &mut World| {
+                world.resource_mut::<Received>().0.push(message);
+            });
+        }
+    }
+
+    #[test]
+    fn dispatch_cues() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("Cue.Hit.Fire");
+        registry.register_raw("Cue.Burning");
+        registry.register_raw("State.Wet");
+        let burning = registry.request_from_raw("Cue.Burning").unwrap();
+        let wet = registry.request_from_raw("State.Wet").unwrap();
+
+        let mut app = App::new();
+        app.add_plugins(GameplayCuePlugin)
+            .insert_resource(StateLayerTagRegistry(registry))
+            .init_resource::<Received>()
+            .add_systems(Update, update_state_layertag_changes)
+            .add_gameplay_cue_handler("Cue.Hit", record("hit"))
+            .add_gameplay_cue_handler("Cue.Burning", record("burning"));
+
+        let target = app
+            .world_mut()
+            .spawn(StateLayerTagContainer::default())
+            .id();
+        app.world_mut().trigger_targets(
+            EffectPlayCueEvent {
+                cue: "Cue.Hit.Fire".into(),
+                magnitude: 10.0,
+            },
+            target,
+        );
+
+        let mut set_layertags = |add: bool| {
+            let mut entity = app.world_mut().entity_mut(target);
+            let mut container = entity.get_mut::<StateLayerTagContainer>().unwrap();
+            for layertag in [&burning, &wet] {
+                if add {
+                    container.0.add_layertag(layertag.clone());
+                } else {
+                    container.0.remove_layertag(layertag);
+                }
+            }
+            app.update();
+        };
+        set_layertags(true);
+        set_layertags(false);
+
+        assert_eq!(
+            app.world().resource::<Received>().0,
+            vec![
+                format!("hit Cue.Hit.Fire {:?}", GameplayCueKind::OnExecute),
+                format!("burning Cue.Burning {:?}", GameplayCueKind::WhileActive),
+                format!("burning Cue.Burning {:?}", GameplayCueKind::OnRemove),
+            ]
+        );
+    }
+}
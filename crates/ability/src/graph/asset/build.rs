@@ -0,0 +1,540 @@
+use std::sync::Arc;
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, GetPath, ReflectKind, TypeRegistration, TypeRegistry},
+};
+use uuid::Uuid;
+
+use crate::graph::{
+    blackboard::EffectValue,
+    context::{EffectGraphContext, GraphRef, InstantEffectNodeMap},
+    node::{
+        pin::{EffectNodeExec, EffectNodePinGroup, EffectNodeSlot, ReflectEffectNodePinGroup},
+        EffectNodeExecuteState, EffectNodeId, ReflectInstantEffectNode,
+    },
+    pin::{EffectNodeExecPin, EffectNodeSlotPin},
+    state::{EffectGraphState, EffectGraphTickState},
+};
+
+use super::{EffectGraphAsset, EffectGraphAssetError, EffectGraphAssetNode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EffectGraphAssetNodeKind {
+    Instant,
+    State,
+}
+
+/// 通过反射创建的节点，还没有分配节点id。
+struct ReflectedNode<'a> {
+    asset_node: &'a EffectGraphAssetNode,
+    registration: &'a TypeRegistration,
+    node: Box<dyn Reflect>,
+    kind: EffectGraphAssetNodeKind,
+}
+
+impl<'a> ReflectedNode<'a> {
+    fn new(
+        asset_node: &'a EffectGraphAssetNode,
+        type_registry: &'a TypeRegistry,
+    ) -> Result<Self, EffectGraphAssetError> {
+        let registration = type_registry
+            .get_with_short_type_path(&asset_node.node_type)
+            .or_else(|| type_registry.get_with_type_path(&asset_node.node_type))
+            .ok_or_else(|| EffectGraphAssetError::UnknownNodeType {
+                node: asset_node.id.clone(),
+                node_type: asset_node.node_type.clone(),
+            })?;
+
+        let not_effect_node = |data| EffectGraphAssetError::NotEffectNode {
+            node: asset_node.id.clone(),
+            node_type: asset_node.node_type.clone(),
+            data,
+        };
+
+        if registration.data::<ReflectEffectNodePinGroup>().is_none() {
+            return Err(not_effect_node("EffectNodePinGroup"));
+        }
+        let kind = if registration.data::<ReflectInstantEffectNode>().is_some() {
+            EffectGraphAssetNodeKind::Instant
+        } else if registration.data::<ReflectComponent>().is_some() {
+            EffectGraphAssetNodeKind::State
+        } else {
+            return Err(not_effect_node("InstantEffectNode or Component"));
+        };
+        let mut node = registration
+            .data::<ReflectDefault>()
+            .ok_or_else(|| not_effect_node("Default"))?
+            .default();
+
+        for (field, value) in asset_node.fields.iter() {
+            apply_field(node.as_mut(), field, value).map_err(|reason| {
+                EffectGraphAssetError::InvalidField {
+                    node: asset_node.id.clone(),
+                    field: field.clone(),
+                    reason,
+                }
+            })?;
+        }
+
+        if kind == EffectGraphAssetNodeKind::Instant {
+            *node
+                .path_mut::<Uuid>("base.node_id")
+                .map_err(|_| not_effect_node("base.node_id"))? = Uuid::new_v4();
+        }
+
+        Ok(Self {
+            asset_node,
+            registration,
+            node,
+            kind,
+        })
+    }
+
+    fn pin_group(&self) -> &dyn EffectNodePinGroup {
+        self.registration
+            .data::<ReflectEffectNodePinGroup>()
+            .and_then(|pin_group| pin_group.get(self.node.as_ref()))
+            .expect("checked in ReflectedNode::new")
+    }
+
+    fn input_exec(&self, pin: &str) -> Result<EffectNodeExec, EffectGraphAssetError> {
+        self.pin_group()
+            .get_input_exec_pin_by_name(pin)
+            .copied()
+            .ok_or_else(|| EffectGraphAssetError::InputExecPinNotFound {
+                node: self.asset_node.id.clone(),
+                pin: pin.to_string(),
+            })
+    }
+
+    fn output_exec(&self, pin: &str) -> Result<EffectNodeExec, EffectGraphAssetError> {
+        self.pin_group()
+            .get_output_exec_pin_by_name(pin)
+            .copied()
+            .ok_or_else(|| EffectGraphAssetError::OutputExecPinNotFound {
+                node: self.asset_node.id.clone(),
+                pin: pin.to_string(),
+            })
+    }
+
+    fn input_slot(&self, pin: &str) -> Result<EffectNodeSlot, EffectGraphAssetError> {
+        self.pin_group()
+            .get_input_slot_pin_by_name(pin)
+            .copied()
+            .ok_or_else(|| EffectGraphAssetError::InputSlotPinNotFound {
+                node: self.asset_node.id.clone(),
+                pin: pin.to_string(),
+            })
+    }
+
+    fn output_slot(&self, pin: &str) -> Result<EffectNodeSlot, EffectGraphAssetError> {
+        self.pin_group()
+            .get_output_slot_pin_by_name(pin)
+            .copied()
+            .ok_or_else(|| EffectGraphAssetError::OutputSlotPinNotFound {
+                node: self.asset_node.id.clone(),
+                pin: pin.to_string(),
+            })
+    }
+}
+
+/// 设置节点字段，字符串可以设置到无数据的枚举变体上。
+fn apply_field(node: &mut dyn Reflect, field: &str, value: &EffectValue) -> Result<(), String> {
+    let target = node.reflect_path_mut(field).map_err(|e| e.to_string())?;
+    let result = match value {
+        EffectValue::I8(v) => target.try_apply(v),
+        EffectValue::I16(v) => target.try_apply(v),
+        EffectValue::I32(v) => target.try_apply(v),
+        EffectValue::I64(v) => target.try_apply(v),
+        EffectValue::U8(v) => target.try_apply(v),
+        EffectValue::U16(v) => target.try_apply(v),
+        EffectValue::U32(v) => target.try_apply(v),
+        EffectValue::U64(v) => target.try_apply(v),
+        EffectValue::F32(v) => target.try_apply(v),
+        EffectValue::F64(v) => target.try_apply(v),
+        EffectValue::Bool(v) => target.try_apply(v),
+        EffectValue::Entity(v) => target.try_apply(v),
+        EffectValue::VecEntity(v) => target.try_apply(v),
+        EffectValue::Vec2(v) => target.try_apply(v),
+        EffectValue::Vec3(v) => target.try_apply(v),
+        EffectValue::Quat(v) => target.try_apply(v),
+        EffectValue::LayerTag(v) => target.try_apply(v),
+        EffectValue::Attribute(v) => target.try_apply(v),
+        EffectValue::Vec(v) => target.try_apply(v),
+        EffectValue::String(v) => match target.reflect_kind() {
+            ReflectKind::Enum => {
+                target.try_apply(&DynamicEnum::new(v.to_string(), DynamicVariant::Unit))
+            }
+            _ => target.try_apply(&v.to_string()),
+        },
+    };
+    result
+        .or_else(|_| target.try_apply(value))
+        .map_err(|e| e.to_string())
+}
+
+impl EffectGraphAsset {
+    /// 构建Effect Graph模板，返回graph实体。
+    /// 所有节点和pin检查通过之后才会生成实体，失败时不会留下任何实体。
+    pub fn build(
+        &self,
+        commands: &mut Commands,
+        instant_map: &mut InstantEffectNodeMap,
+        type_registry: &TypeRegistry,
+    ) -> Result<Entity, EffectGraphAssetError> {
+        self.validate()?;
+
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|asset_node| ReflectedNode::new(asset_node, type_registry))
+            .collect::<Result<Vec<_>, _>>()?;
+        let node_index = |id: &str| {
+            nodes
+                .iter()
+                .position(|node| node.asset_node.id == id)
+                .expect("checked in validate")
+        };
+
+        let entry = node_index(&self.entry);
+        if nodes[entry].kind != EffectGraphAssetNodeKind::State {
+            return Err(EffectGraphAssetError::EntryNotStateNode {
+                node: self.entry.clone(),
+            });
+        }
+
+        let mut inputs = vec![];
+        for (index, node) in nodes.iter().enumerate() {
+            for (pin, value) in node.asset_node.inputs.iter() {
+                let slot = node.input_slot(pin)?;
+                let value = value.widen_to(slot.pin_type).ok_or_else(|| {
+                    EffectGraphAssetError::InputValueTypeMismatch {
+                        node: node.asset_node.id.clone(),
+                        pin: pin.clone(),
+                        pin_type: slot.pin_type_name,
+                    }
+                })?;
+                inputs.push((index, slot, value));
+            }
+        }
+
+        let mut exec_connections = vec![];
+        for connection in self.exec_connections.iter() {
+            let from = node_index(&connection.from.node);
+            let to = node_index(&connection.to.node);
+            exec_connections.push((
+                from,
+                nodes[from].output_exec(&connection.from.pin)?,
+                to,
+                nodes[to].input_exec(&connection.to.pin)?,
+            ));
+        }
+
+        let mut slot_connections = vec![];
+        for connection in self.slot_connections.iter() {
+            let from = node_index(&connection.from.node);
+            let to = node_index(&connection.to.node);
+            let from_slot = nodes[from].output_slot(&connection.from.pin)?;
+            let to_slot = nodes[to].input_slot(&connection.to.pin)?;
+            if !from_slot.can_connect_to(&to_slot) {
+                return Err(EffectGraphAssetError::SlotTypeMismatch {
+                    from: connection.from.clone(),
+                    to: connection.to.clone(),
+                });
+            }
+            slot_connections.push((from, from_slot, to, to_slot));
+        }
+
+        // 检查完成，开始生成实体。
+        let graph = commands.spawn_empty().id();
+        let mut context = EffectGraphContext::new();
+        context.set_graph_ref(GraphRef::new(graph));
+
+        let mut node_ids = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            match node.kind {
+                EffectGraphAssetNodeKind::Instant => {
+                    let instant_node = node
+                        .registration
+                        .data::<ReflectInstantEffectNode>()
+                        .expect("checked in ReflectedNode::new")
+                        .get_boxed(node.node)
+                        .map_err(|_| EffectGraphAssetError::NotEffectNode {
+                            node: node.asset_node.id.clone(),
+                            node_type: node.asset_node.node_type.clone(),
+                            data: "InstantEffectNode",
+                        })?;
+                    let uuid = instant_node.get_uuid();
+                    instant_map.insert(uuid, Arc::from(instant_node));
+                    context.insert_instant_node(uuid);
+                    node_ids.push(EffectNodeId::Uuid(uuid));
+                }
+                EffectGraphAssetNodeKind::State => {
+                    let entity = commands.spawn_empty().id();
+                    commands
+                        .entity(entity)
+                        .insert_reflect(node.node.into_partial_reflect())
+                        .insert((
+                            EffectNodeExecuteState::default(),
+                            EffectNodeId::from(entity),
+                        ))
+                        .set_parent(graph);
+                    context.insert_state_node(entity);
+                    node_ids.push(EffectNodeId::Entity(entity));
+                }
+            }
+        }
+
+        if let EffectNodeId::Entity(entity) = node_ids[entry] {
+            context.set_entry_node(entity);
+        }
+
+        for (index, slot, value) in inputs {
+            context.insert_input_value(
+                EffectNodeSlotPin {
+                    node_id: node_ids[index],
+                    slot,
+                },
+                value.into(),
+            );
+        }
+
+        for (from, from_exec, to, to_exec) in exec_connections {
+            context.add_exec_connection(
+                EffectNodeExecPin {
+                    node_id: node_ids[from],
+                    exec: from_exec,
+                },
+                &[EffectNodeExecPin {
+                    node_id: node_ids[to],
+                    exec: to_exec,
+                }],
+            );
+        }
+
+        for (from, from_slot, to, to_slot) in slot_connections {
+            context
+                .add_slot_connection(
+                    EffectNodeSlotPin {
+                        node_id: node_ids[from],
+                        slot: from_slot,
+                    },
+                    &[EffectNodeSlotPin {
+                        node_id: node_ids[to],
+                        slot: to_slot,
+                    }],
+                )
+                .expect("slot type checked before spawning");
+        }
+
+        for (key, value) in self.blackboard.iter() {
+            context.blackboard.set_value(key.clone(), value.clone());
+        }
+
+        commands.entity(graph).insert((
+            context,
+            EffectGraphState::Inactive,
+            EffectGraphTickState::Ticked,
+        ));
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::{
+        ability::node::ability_entry::{EffectNodeAbilityEntry, EffectNodeAbilityEntryPlugin},
+        graph::{
+            asset::{
+                tests::TEST_GRAPH, EffectGraphAsset, EffectGraphAssetError, EffectGraphAssetMap,
+                EffectGraphAssetPlugin,
+            },
+            blackboard::EffectValue,
+            context::{EffectGraphContext, InstantEffectNodeMap},
+            event::{EffectGraphAddEvent, EffectGraphExecEvent},
+            graph_map::EffectGraphMap,
+            node::implement::test_graph::{blackboard_value, new_test_app},
+            EffectGraphOwner,
+        },
+    };
+
+    fn build(app: &mut App, asset: &EffectGraphAsset) -> Result<Entity, EffectGraphAssetError> {
+        let type_registry = app.world().resource::<AppTypeRegistry>().clone();
+        app.world_mut()
+            .resource_scope(|world, mut instant_map: Mut<InstantEffectNodeMap>| {
+                let result = {
+                    let mut commands = world.commands();
+                    asset.build(&mut commands, &mut instant_map, &type_registry.read())
+                };
+                world.flush();
+                result
+            })
+    }
+
+    fn new_app() -> App {
+        let mut app = new_test_app();
+        app.add_plugins(EffectNodeAbilityEntryPlugin);
+        app
+    }
+
+    #[test]
+    fn build_and_run_graph_asset() {
+        let mut app = new_app();
+        let asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        let template = build(&mut app, &asset).unwrap();
+        app.world_mut()
+            .resource_mut::<EffectGraphMap>()
+            .insert_graph("test_graph".into(), super::GraphRef::new(template));
+
+        let owner = app.world_mut().spawn(EffectGraphOwner).id();
+        app.world_mut().commands().trigger_targets(
+            EffectGraphAddEvent {
+                graph_class: "test_graph".into(),
+            },
+            owner,
+        );
+        app.update();
+
+        let instance = app.world().entity(owner).get::<Children>().unwrap()[0];
+        assert!(app.world().get::<EffectGraphContext>(instance).is_some());
+
+        app.world_mut().commands().trigger_targets(
+            EffectGraphExecEvent {
+                entry_exec_pin: EffectNodeAbilityEntry::OUTPUT_EXEC_START.into(),
+                execute_in_graph_state: None,
+                slot_value_map: None,
+            },
+            owner,
+        );
+        app.update();
+
+        assert_eq!(
+            blackboard_value(&app, instance, "result"),
+            Some(EffectValue::F32(20.0))
+        );
+    }
+
+    #[test]
+    fn build_reports_node_and_pin() {
+        let mut app = new_app();
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.slot_connections[0].to.pin = "c".into();
+        assert_eq!(
+            build(&mut app, &asset),
+            Err(EffectGraphAssetError::InputSlotPinNotFound {
+                node: "mul".into(),
+                pin: "c".into()
+            })
+        );
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.nodes[1]
+            .inputs
+            .insert("key".into(), EffectValue::I32(1));
+        assert_eq!(
+            build(&mut app, &asset),
+            Err(EffectGraphAssetError::InputValueTypeMismatch {
+                node: "get".into(),
+                pin: "key".into(),
+                pin_type: std::any::type_name::<String>(),
+            })
+        );
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.nodes[2].node_type = "EffectNodeUnknown".into();
+        assert_eq!(
+            build(&mut app, &asset),
+            Err(EffectGraphAssetError::UnknownNodeType {
+                node: "mul".into(),
+                node_type: "EffectNodeUnknown".into()
+            })
+        );
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.entry = "get".into();
+        assert_eq!(
+            build(&mut app, &asset),
+            Err(EffectGraphAssetError::EntryNotStateNode { node: "get".into() })
+        );
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.nodes[2]
+            .fields
+            .insert("op".into(), EffectValue::String("Pow".into()));
+        assert!(matches!(
+            build(&mut app, &asset),
+            Err(EffectGraphAssetError::InvalidField { node, field, .. }) if node == "mul" && field == "op"
+        ));
+    }
+
+    #[test]
+    fn reload_releases_old_instant_nodes() {
+        let mut app = new_app();
+        app.add_plugins((AssetPlugin::default(), EffectGraphAssetPlugin));
+
+        let asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<EffectGraphAsset>>()
+            .add(asset.clone());
+        app.world_mut()
+            .resource_mut::<EffectGraphAssetMap>()
+            .insert("test_graph".into(), handle.clone());
+        let template = build(&mut app, &asset).unwrap();
+        app.world_mut()
+            .resource_mut::<EffectGraphMap>()
+            .insert_graph("test_graph".into(), super::GraphRef::new(template));
+        let node_count = app.world().resource::<InstantEffectNodeMap>().nodes.len();
+
+        let owner = app.world_mut().spawn(EffectGraphOwner).id();
+        app.world_mut().commands().trigger_targets(
+            EffectGraphAddEvent {
+                graph_class: "test_graph".into(),
+            },
+            owner,
+        );
+        app.update();
+        let instance = app.world().entity(owner).get::<Children>().unwrap()[0];
+
+        app.world_mut()
+            .resource_mut::<Assets<EffectGraphAsset>>()
+            .get_mut(&handle)
+            .unwrap()
+            .blackboard
+            .insert("damage".into(), EffectValue::F32(20.0));
+        app.update();
+        app.update();
+
+        let graph_ref = app
+            .world()
+            .resource::<EffectGraphMap>()
+            .get_graph("test_graph".into())
+            .unwrap();
+        assert_ne!(graph_ref.get_entity(), template);
+        assert!(!app.world().entities().contains(template));
+        // 运行中的实例继续使用旧模板的节点
+        assert_eq!(
+            app.world()
+                .get::<EffectGraphContext>(instance)
+                .unwrap()
+                .get_graph_ref(),
+            Some(super::GraphRef::new(template))
+        );
+        assert_eq!(
+            app.world().resource::<InstantEffectNodeMap>().nodes.len(),
+            node_count * 2
+        );
+
+        app.world_mut().entity_mut(instance).despawn_recursive();
+        app.update();
+        assert_eq!(
+            app.world().resource::<InstantEffectNodeMap>().nodes.len(),
+            node_count
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use thiserror::Error;
+
+use super::{EffectGraphAsset, EffectGraphAssetError};
+
+#[derive(Debug, Error)]
+pub enum EffectGraphAssetLoaderError {
+    #[error("could not read effect graph asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse ron effect graph asset: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("could not parse json effect graph asset: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid effect graph asset: {0}")]
+    Invalid(#[from] EffectGraphAssetError),
+}
+
+/// 加载`*.graph.ron`和`*.graph.json`。
+#[derive(Debug, Default)]
+pub struct EffectGraphAssetLoader;
+
+impl AssetLoader for EffectGraphAssetLoader {
+    type Asset = EffectGraphAsset;
+
+    type Settings = ();
+
+    type Error = EffectGraphAssetLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        use bevy::asset::AsyncReadExt;
+        reader.read_to_end(&mut bytes).await?;
+
+        let is_json = load_context
+            .path()
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        let asset = if is_json {
+            EffectGraphAsset::from_json(&bytes)?
+        } else {
+            EffectGraphAsset::from_ron(&bytes)?
+        };
+        asset.validate()?;
+        Ok(asset)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["graph.ron", "graph.json"]
+    }
+}
@@ -0,0 +1,373 @@
+use std::collections::BTreeMap;
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+use super::{
+    blackboard::EffectValue,
+    context::{EffectGraphContext, GraphRef, InstantEffectNodeMap},
+    graph_map::{EffectGraphMap, GraphClass},
+    pool::EffectGraphPool,
+    template::EffectGraphTemplates,
+};
+
+pub mod build;
+pub mod loader;
+
+#[derive(Debug, Default)]
+pub struct EffectGraphAssetPlugin;
+
+impl Plugin for EffectGraphAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EffectGraphAsset>()
+            .init_asset_loader::<loader::EffectGraphAssetLoader>()
+            .init_resource::<EffectGraphAssetMap>()
+            .init_resource::<RetiredEffectGraphTemplates>()
+            .add_systems(
+                PreUpdate,
+                (
+                    reload_effect_graph_asset,
+                    release_retired_effect_graph_templates,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// 序列化的Effect Graph，和EffectGraphBuilder一样，构建为EffectGraphMap中的模板。
+///
+/// 节点通过反射创建，节点类型需要注册，并且反射`Default`和`EffectNodePinGroup`，
+/// 瞬时节点还需要反射`InstantEffectNode`，状态节点需要反射`Component`。
+#[derive(Debug, Clone, PartialEq, Asset, TypePath, Serialize, Deserialize)]
+pub struct EffectGraphAsset {
+    /// entry节点的id，必须是状态节点。
+    pub entry: String,
+    pub nodes: Vec<EffectGraphAssetNode>,
+    #[serde(default)]
+    pub exec_connections: Vec<EffectGraphAssetConnection>,
+    #[serde(default)]
+    pub slot_connections: Vec<EffectGraphAssetConnection>,
+    /// 黑板的初始值。
+    #[serde(default)]
+    pub blackboard: BTreeMap<String, EffectValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectGraphAssetNode {
+    /// 图内唯一的节点id，连接使用这个id。
+    pub id: String,
+    /// 注册的类型名，可以是短类型名，也可以是完整的类型路径。
+    pub node_type: String,
+    /// 节点自身的字段，key为反射路径，比如compare节点的`op`。
+    #[serde(default)]
+    pub fields: BTreeMap<String, EffectValue>,
+    /// 输入slot的默认值。
+    #[serde(default)]
+    pub inputs: BTreeMap<String, EffectValue>,
+    /// 编辑器中节点的位置，运行时不使用。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<Vec2>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectGraphAssetPin {
+    pub node: String,
+    pub pin: String,
+}
+
+/// 从输出pin连接到输入pin。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EffectGraphAssetConnection {
+    pub from: EffectGraphAssetPin,
+    pub to: EffectGraphAssetPin,
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EffectGraphAssetError {
+    #[error("duplicate node id: {node}")]
+    DuplicateNode { node: String },
+    #[error("node {node} not found")]
+    NodeNotFound { node: String },
+    #[error("entry node {node} is not a state node")]
+    EntryNotStateNode { node: String },
+    #[error("node {node}: type {node_type} is not registered")]
+    UnknownNodeType { node: String, node_type: String },
+    #[error("node {node}: type {node_type} is not an effect node, missing {data}")]
+    NotEffectNode {
+        node: String,
+        node_type: String,
+        data: &'static str,
+    },
+    #[error("node {node}: field {field} is invalid: {reason}")]
+    InvalidField {
+        node: String,
+        field: String,
+        reason: String,
+    },
+    #[error("node {node}: input exec pin {pin} not found")]
+    InputExecPinNotFound { node: String, pin: String },
+    #[error("node {node}: output exec pin {pin} not found")]
+    OutputExecPinNotFound { node: String, pin: String },
+    #[error("node {node}: input slot pin {pin} not found")]
+    InputSlotPinNotFound { node: String, pin: String },
+    #[error("node {node}: output slot pin {pin} not found")]
+    OutputSlotPinNotFound { node: String, pin: String },
+    #[error("node {node}: input value of slot pin {pin} is not {pin_type}")]
+    InputValueTypeMismatch {
+        node: String,
+        pin: String,
+        pin_type: &'static str,
+    },
+    #[error(
+        "slot {}.{} can not connect to {}.{}, pin type mismatch",
+        .from.node,
+        .from.pin,
+        .to.node,
+        .to.pin
+    )]
+    SlotTypeMismatch {
+        from: EffectGraphAssetPin,
+        to: EffectGraphAssetPin,
+    },
+}
+
+impl EffectGraphAsset {
+    pub fn from_ron(bytes: &[u8]) -> Result<Self, ron::error::SpannedError> {
+        ron::de::from_bytes(bytes)
+    }
+
+    pub fn from_json(bytes: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(bytes)
+    }
+
+    pub fn get_node(&self, id: &str) -> Option<&EffectGraphAssetNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// 检查节点id和连接，不需要类型注册信息。pin的检查在构建时进行。
+    pub fn validate(&self) -> Result<(), EffectGraphAssetError> {
+        for (index, node) in self.nodes.iter().enumerate() {
+            if self.nodes[..index].iter().any(|other| other.id == node.id) {
+                return Err(EffectGraphAssetError::DuplicateNode {
+                    node: node.id.clone(),
+                });
+            }
+        }
+
+        let check_node = |id: &str| match self.get_node(id) {
+            Some(_) => Ok(()),
+            None => Err(EffectGraphAssetError::NodeNotFound {
+                node: id.to_string(),
+            }),
+        };
+
+        check_node(&self.entry)?;
+        for connection in self
+            .exec_connections
+            .iter()
+            .chain(self.slot_connections.iter())
+        {
+            check_node(&connection.from.node)?;
+            check_node(&connection.to.node)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 资产路径作为GraphClass，可以直接在TbAbility和TbBuff的graph_class中使用。
+#[derive(Debug, Resource, Default)]
+pub struct EffectGraphAssetMap {
+    pub map: HashMap<GraphClass, Handle<EffectGraphAsset>>,
+}
+
+impl EffectGraphAssetMap {
+    pub fn get(&self, graph_class: &str) -> Option<&Handle<EffectGraphAsset>> {
+        self.map.get(graph_class)
+    }
+
+    pub fn insert(&mut self, graph_class: GraphClass, handle: Handle<EffectGraphAsset>) {
+        self.map.insert(graph_class, handle);
+    }
+}
+
+pub trait EffectGraphAssetAppExt {
+    fn load_effect_graph_asset(&mut self, path: impl Into<String>) -> &mut Self;
+}
+
+impl EffectGraphAssetAppExt for App {
+    fn load_effect_graph_asset(&mut self, path: impl Into<String>) -> &mut Self {
+        let path = path.into();
+        let handle = self
+            .world()
+            .get_resource::<AssetServer>()
+            .expect("AssetPlugin must be added before load_effect_graph_asset!")
+            .load(path.clone());
+        self.world_mut()
+            .get_resource_mut::<EffectGraphAssetMap>()
+            .expect("EffectGraphAssetPlugin must be added before load_effect_graph_asset!")
+            .insert(path, handle);
+        self
+    }
+}
+
+/// 热加载替换的旧模板和它的瞬时节点，使用旧模板的实例全部销毁后移除。
+#[derive(Debug, Resource, Default)]
+pub struct RetiredEffectGraphTemplates {
+    pub templates: HashMap<GraphRef, Vec<Uuid>>,
+}
+
+/// 资产修改后重新构建模板，之后创建的实例使用新的模板，还没有构建的模板在第一次使用时读取最新的资产。
+/// 已经运行的实例继续使用旧模板的节点，结束后不再回收，池中旧模板的实例直接销毁。
+#[allow(clippy::too_many_arguments)]
+pub fn reload_effect_graph_asset(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<EffectGraphAsset>>,
+    assets: Res<Assets<EffectGraphAsset>>,
+    asset_map: Res<EffectGraphAssetMap>,
+    mut graph_map: ResMut<EffectGraphMap>,
+    mut instant_map: ResMut<InstantEffectNodeMap>,
+    mut pool: ResMut<EffectGraphPool>,
+    mut retired: ResMut<RetiredEffectGraphTemplates>,
+    mut templates: ResMut<EffectGraphTemplates>,
+    type_registry: Res<AppTypeRegistry>,
+    context_query: Query<&EffectGraphContext>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        for (graph_class, handle) in asset_map.map.iter() {
+            if handle.id() != *id {
+                continue;
+            }
+
+            let Some(old_graph_ref) = graph_map.get_graph(graph_class.clone()) else {
+                continue;
+            };
+
+            let Some(asset) = assets.get(*id) else {
+                continue;
+            };
+
+            match asset.build(&mut commands, &mut instant_map, &type_registry.read()) {
+                Ok(graph) => {
+                    graph_map.insert_graph(graph_class.clone(), GraphRef::new(graph));
+
+                    let old_entity = old_graph_ref.get_entity();
+                    let instant_nodes = context_query
+                        .get(old_entity)
+                        .map(|context| context.instant_nodes.clone())
+                        .unwrap_or_default();
+                    retired.templates.insert(old_graph_ref, instant_nodes);
+                    templates.remove(old_graph_ref);
+                    for instance in pool.retire(old_graph_ref) {
+                        commands.entity(instance).despawn_recursive();
+                    }
+                    commands.entity(old_entity).despawn_recursive();
+                    info!(
+                        "reload effect graph template: {} {:?} => {:?}",
+                        graph_class, old_entity, graph
+                    );
+                }
+                Err(e) => {
+                    error!(
+                        "reload effect graph {} failed, keep the old template: {}",
+                        graph_class, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// 没有context再使用旧模板时，从InstantEffectNodeMap移除它的瞬时节点。
+pub fn release_retired_effect_graph_templates(
+    mut retired: ResMut<RetiredEffectGraphTemplates>,
+    mut instant_map: ResMut<InstantEffectNodeMap>,
+    mut pool: ResMut<EffectGraphPool>,
+    context_query: Query<&EffectGraphContext>,
+) {
+    if retired.templates.is_empty() {
+        return;
+    }
+
+    retired.templates.retain(|graph_ref, instant_nodes| {
+        if context_query
+            .iter()
+            .any(|context| context.get_graph_ref() == Some(*graph_ref))
+        {
+            return true;
+        }
+
+        for uuid in instant_nodes.iter() {
+            instant_map.remove(*uuid);
+        }
+        pool.release(*graph_ref);
+        debug!("release retired effect graph template: {:?}", graph_ref);
+        false
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EffectGraphAsset, EffectGraphAssetError};
+
+    pub(crate) const TEST_GRAPH: &str = r#"(
+        entry: "entry",
+        nodes: [
+            (id: "entry", node_type: "EffectNodeAbilityEntry"),
+            (id: "get", node_type: "EffectNodeGetBlackboard", inputs: { "key": String("damage") }),
+            (
+                id: "mul",
+                node_type: "EffectNodeMath",
+                fields: { "op": String("Mul") },
+                inputs: { "b": F32(2.0) },
+            ),
+            (id: "set", node_type: "EffectNodeSetBlackboard", inputs: { "key": String("result") }),
+        ],
+        exec_connections: [
+            (from: (node: "entry", pin: "start"), to: (node: "get", pin: "start")),
+            (from: (node: "get", pin: "finish"), to: (node: "mul", pin: "start")),
+            (from: (node: "mul", pin: "finish"), to: (node: "set", pin: "start")),
+        ],
+        slot_connections: [
+            (from: (node: "get", pin: "value"), to: (node: "mul", pin: "a")),
+            (from: (node: "mul", pin: "result"), to: (node: "set", pin: "value")),
+        ],
+        blackboard: { "damage": F32(10.0) },
+    )"#;
+
+    #[test]
+    fn parse_ron_and_json() {
+        let asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        assert_eq!(asset.nodes.len(), 4);
+        assert_eq!(asset.validate(), Ok(()));
+
+        let json = serde_json::to_vec(&asset).unwrap();
+        assert_eq!(EffectGraphAsset::from_json(&json).unwrap(), asset);
+    }
+
+    #[test]
+    fn validate_node_ids() {
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        asset.exec_connections[0].to.node = "missing".into();
+        assert_eq!(
+            asset.validate(),
+            Err(EffectGraphAssetError::NodeNotFound {
+                node: "missing".into()
+            })
+        );
+
+        let mut asset = EffectGraphAsset::from_ron(TEST_GRAPH.as_bytes()).unwrap();
+        let duplicate = asset.nodes[1].clone();
+        asset.nodes.push(duplicate);
+        assert_eq!(
+            asset.validate(),
+            Err(EffectGraphAssetError::DuplicateNode { node: "get".into() })
+        );
+    }
+}
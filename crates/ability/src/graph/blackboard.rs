@@ -1,15 +1,42 @@
 use bevy::{prelude::*, utils::HashMap};
-use std::borrow::Cow;
+use layertag::layertag::LayerTag;
+use serde::{Deserialize, Serialize};
+use std::{any::TypeId, borrow::Cow};
 
 use bevy::{prelude::Entity, reflect::Reflect};
 
-#[derive(Debug, Component, Default)]
+/// 图实例的黑板，存储在EffectGraphContext中，随图一起克隆。
+#[derive(Debug, Component, Default, Clone, Reflect)]
 pub struct EffectBlackboard {
     pub blackboard: HashMap<Name, EffectValue>,
 }
 
+impl EffectBlackboard {
+    pub fn get_value(&self, key: impl Into<Cow<'static, str>>) -> Option<&EffectValue> {
+        self.blackboard.get(&Name::new(key))
+    }
+
+    pub fn set_value(&mut self, key: impl Into<Cow<'static, str>>, value: EffectValue) {
+        self.blackboard.insert(Name::new(key), value);
+    }
+
+    pub fn remove_value(&mut self, key: impl Into<Cow<'static, str>>) -> Option<EffectValue> {
+        self.blackboard.remove(&Name::new(key))
+    }
+}
+
+/// 属性引用，通过反射找到实体上属性集组件中的属性。
+#[derive(Debug, Reflect, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+pub struct EffectAttributeRef {
+    /// 属性集组件的类型名。
+    pub attribute_set: Cow<'static, str>,
+    /// 属性集组件中的属性字段名。
+    pub attribute: Cow<'static, str>,
+}
+
 #[allow(unused)]
-#[derive(Debug, Reflect, PartialEq, Clone)]
+#[derive(Debug, Reflect, PartialEq, Clone, Serialize, Deserialize)]
+#[reflect(no_field_bounds)]
 pub enum EffectValue {
     I8(i8),
     I16(i16),
@@ -24,15 +51,168 @@ pub enum EffectValue {
     F32(f32),
     F64(f64),
 
+    Bool(bool),
+
     Entity(Entity),
     VecEntity(Vec<Entity>),
 
     String(Cow<'static, str>),
-    // Vec(Vec<EffectValue>),
+
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Quat(Quat),
+
+    LayerTag(LayerTag),
+    Attribute(EffectAttributeRef),
+
+    Vec(Vec<EffectValue>),
     // TODO: add when bevy support
     // BoxReflect(Box<dyn Reflect>),
 }
 
+impl EffectValue {
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            EffectValue::I8(v) => Some(*v as i64),
+            EffectValue::I16(v) => Some(*v as i64),
+            EffectValue::I32(v) => Some(*v as i64),
+            EffectValue::I64(v) => Some(*v),
+            EffectValue::U8(v) => Some(*v as i64),
+            EffectValue::U16(v) => Some(*v as i64),
+            EffectValue::U32(v) => Some(*v as i64),
+            EffectValue::U64(v) => Some(*v as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            EffectValue::F32(v) => Some(*v as f64),
+            EffectValue::F64(v) => Some(*v),
+            _ => self.as_i64().map(|v| v as f64),
+        }
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.as_f64().is_some()
+    }
+
+    /// 值对应的pin类型。
+    pub fn value_type_id(&self) -> TypeId {
+        match self {
+            EffectValue::I8(_) => TypeId::of::<i8>(),
+            EffectValue::I16(_) => TypeId::of::<i16>(),
+            EffectValue::I32(_) => TypeId::of::<i32>(),
+            EffectValue::I64(_) => TypeId::of::<i64>(),
+            EffectValue::U8(_) => TypeId::of::<u8>(),
+            EffectValue::U16(_) => TypeId::of::<u16>(),
+            EffectValue::U32(_) => TypeId::of::<u32>(),
+            EffectValue::U64(_) => TypeId::of::<u64>(),
+            EffectValue::F32(_) => TypeId::of::<f32>(),
+            EffectValue::F64(_) => TypeId::of::<f64>(),
+            EffectValue::Bool(_) => TypeId::of::<bool>(),
+            EffectValue::Entity(_) => TypeId::of::<Entity>(),
+            EffectValue::VecEntity(_) => TypeId::of::<Vec<Entity>>(),
+            EffectValue::String(_) => TypeId::of::<String>(),
+            EffectValue::Vec2(_) => TypeId::of::<Vec2>(),
+            EffectValue::Vec3(_) => TypeId::of::<Vec3>(),
+            EffectValue::Quat(_) => TypeId::of::<Quat>(),
+            EffectValue::LayerTag(_) => TypeId::of::<LayerTag>(),
+            EffectValue::Attribute(_) => TypeId::of::<EffectAttributeRef>(),
+            EffectValue::Vec(_) => TypeId::of::<Vec<EffectValue>>(),
+        }
+    }
+
+    /// 输出pin能否连接到输入pin。
+    /// EffectValue类型的pin可以连接任意类型，数值类型可以无损地隐式扩展，比如i32到i64，i16到f32。
+    pub fn is_pin_type_compatible(output: TypeId, input: TypeId) -> bool {
+        output == input
+            || output == TypeId::of::<EffectValue>()
+            || input == TypeId::of::<EffectValue>()
+            || (Self::is_string_type(output) && Self::is_string_type(input))
+            || Self::is_numeric_widening(output, input)
+    }
+
+    fn is_string_type(type_id: TypeId) -> bool {
+        type_id == TypeId::of::<String>() || type_id == TypeId::of::<Cow<'static, str>>()
+    }
+
+    fn is_numeric_widening(from: TypeId, to: TypeId) -> bool {
+        #[derive(PartialEq)]
+        enum NumericKind {
+            Signed,
+            Unsigned,
+            Float,
+        }
+
+        let numeric = |type_id| {
+            [
+                (TypeId::of::<i8>(), NumericKind::Signed, 8),
+                (TypeId::of::<i16>(), NumericKind::Signed, 16),
+                (TypeId::of::<i32>(), NumericKind::Signed, 32),
+                (TypeId::of::<i64>(), NumericKind::Signed, 64),
+                (TypeId::of::<u8>(), NumericKind::Unsigned, 8),
+                (TypeId::of::<u16>(), NumericKind::Unsigned, 16),
+                (TypeId::of::<u32>(), NumericKind::Unsigned, 32),
+                (TypeId::of::<u64>(), NumericKind::Unsigned, 64),
+                (TypeId::of::<f32>(), NumericKind::Float, 32),
+                (TypeId::of::<f64>(), NumericKind::Float, 64),
+            ]
+            .into_iter()
+            .find(|(id, _, _)| *id == type_id)
+            .map(|(_, kind, bits)| (kind, bits))
+        };
+
+        let (Some((from_kind, from_bits)), Some((to_kind, to_bits))) = (numeric(from), numeric(to))
+        else {
+            return false;
+        };
+
+        // 位数更多的类型才能无损表示，无符号数可以扩展为有符号数，整数可以扩展为浮点数。
+        let kind_compatible = match from_kind {
+            NumericKind::Signed => to_kind != NumericKind::Unsigned,
+            NumericKind::Unsigned => true,
+            NumericKind::Float => to_kind == NumericKind::Float,
+        };
+        kind_compatible && to_bits > from_bits
+    }
+
+    /// 转换为pin类型的值，只允许相同类型或者隐式扩展。
+    pub fn widen_to(&self, pin_type: TypeId) -> Option<EffectValue> {
+        let value_type = self.value_type_id();
+        if value_type == pin_type
+            || pin_type == TypeId::of::<EffectValue>()
+            || (Self::is_string_type(value_type) && Self::is_string_type(pin_type))
+        {
+            return Some(self.clone());
+        }
+        if !Self::is_numeric_widening(value_type, pin_type) {
+            return None;
+        }
+
+        let value = if pin_type == TypeId::of::<i16>() {
+            EffectValue::I16(i16::try_from(self.as_i64()?).ok()?)
+        } else if pin_type == TypeId::of::<i32>() {
+            EffectValue::I32(i32::try_from(self.as_i64()?).ok()?)
+        } else if pin_type == TypeId::of::<i64>() {
+            EffectValue::I64(self.as_i64()?)
+        } else if pin_type == TypeId::of::<u16>() {
+            EffectValue::U16(u16::try_from(self.as_i64()?).ok()?)
+        } else if pin_type == TypeId::of::<u32>() {
+            EffectValue::U32(u32::try_from(self.as_i64()?).ok()?)
+        } else if pin_type == TypeId::of::<u64>() {
+            EffectValue::U64(u64::try_from(self.as_i64()?).ok()?)
+        } else if pin_type == TypeId::of::<f32>() {
+            EffectValue::F32(self.as_f64()? as f32)
+        } else if pin_type == TypeId::of::<f64>() {
+            EffectValue::F64(self.as_f64()?)
+        } else {
+            return None;
+        };
+        Some(value)
+    }
+}
+
 pub trait BlackBoardValue {
     fn get<'a, T>(&'a self) -> Result<T, T::Error>
     where
@@ -295,6 +475,50 @@ impl<'a> TryFrom<&'a mut EffectValue> for &'a mut f64 {
     }
 }
 
+impl<'a> TryFrom<&'a EffectValue> for &'a bool {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Bool(v) => Ok(v),
+            _ => Err("not bool"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut bool {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Bool(v) => Ok(v),
+            _ => Err("not bool"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a Entity {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Entity(v) => Ok(v),
+            _ => Err("not Entity"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Entity {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Entity(v) => Ok(v),
+            _ => Err("not Entity"),
+        }
+    }
+}
+
 impl<'a> TryFrom<&'a EffectValue> for String {
     type Error = &'static str;
 
@@ -350,19 +574,137 @@ impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Vec<Entity> {
     }
 }
 
-// impl<'a, T> TryFrom<&'a EffectValue> for &'a Vec<T> {
-//     type Error = &'static str;
-//     fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
-//         match value {
-//             EffectValue::Vec(v) => {
-//                 match v {
-//                    v.into() as T => Ok(v)
-//                 }
-//             },
-//             _ => Err("not Vec"),
-//         }
-//     }
-// }
+impl<'a> TryFrom<&'a EffectValue> for &'a Vec2 {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec2(v) => Ok(v),
+            _ => Err("not Vec2"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Vec2 {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec2(v) => Ok(v),
+            _ => Err("not Vec2"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a Vec3 {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec3(v) => Ok(v),
+            _ => Err("not Vec3"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Vec3 {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec3(v) => Ok(v),
+            _ => Err("not Vec3"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a Quat {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Quat(v) => Ok(v),
+            _ => Err("not Quat"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Quat {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Quat(v) => Ok(v),
+            _ => Err("not Quat"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a LayerTag {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::LayerTag(v) => Ok(v),
+            _ => Err("not LayerTag"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut LayerTag {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::LayerTag(v) => Ok(v),
+            _ => Err("not LayerTag"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a EffectAttributeRef {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Attribute(v) => Ok(v),
+            _ => Err("not Attribute"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut EffectAttributeRef {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Attribute(v) => Ok(v),
+            _ => Err("not Attribute"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a EffectValue> for &'a Vec<EffectValue> {
+    type Error = &'static str;
+
+    fn try_from(value: &'a EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec(v) => Ok(v),
+            _ => Err("not Vec"),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Vec<EffectValue> {
+    type Error = &'static str;
+
+    fn try_from(value: &'a mut EffectValue) -> Result<Self, Self::Error> {
+        match value {
+            EffectValue::Vec(v) => Ok(v),
+            _ => Err("not Vec"),
+        }
+    }
+}
 
 // impl<'a> TryFrom<&'a EffectValue> for &'a Box<dyn Reflect> {
 //     type Error = &'static str;
@@ -507,3 +849,101 @@ impl<'a> TryFrom<&'a mut EffectValue> for &'a mut Vec<Entity> {
 //     }
 // }
 // }
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use bevy::prelude::*;
+    use layertag::{builder::LayerTagBuilder, tag::Tag};
+
+    use super::{EffectAttributeRef, EffectValue};
+
+    #[test]
+    fn try_from_new_values() {
+        let value = EffectValue::Vec3(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!((&value).try_into(), Ok(&Vec3::new(1.0, 2.0, 3.0)));
+        assert_eq!(TryInto::<&Vec2>::try_into(&value), Err("not Vec2"));
+
+        let layertag = LayerTagBuilder::new()
+            .add_tag(Tag::new("state"))
+            .add_tag(Tag::new("stun"))
+            .build_single();
+        let value = EffectValue::LayerTag(layertag.clone());
+        assert_eq!((&value).try_into(), Ok(&layertag));
+
+        let mut value = EffectValue::Vec(vec![EffectValue::I32(1), EffectValue::Bool(true)]);
+        TryInto::<&mut Vec<EffectValue>>::try_into(&mut value)
+            .unwrap()
+            .push(EffectValue::F32(2.0));
+        assert_eq!(
+            TryInto::<&Vec<EffectValue>>::try_into(&value).map(|v| v.len()),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn pin_type_compatible() {
+        let compatible = EffectValue::is_pin_type_compatible;
+        assert!(compatible(TypeId::of::<i32>(), TypeId::of::<i32>()));
+        assert!(compatible(TypeId::of::<i32>(), TypeId::of::<i64>()));
+        assert!(compatible(TypeId::of::<i16>(), TypeId::of::<f32>()));
+        assert!(compatible(TypeId::of::<u8>(), TypeId::of::<i16>()));
+        assert!(compatible(TypeId::of::<f32>(), TypeId::of::<f64>()));
+        assert!(compatible(
+            TypeId::of::<Entity>(),
+            TypeId::of::<EffectValue>()
+        ));
+        assert!(compatible(
+            TypeId::of::<EffectValue>(),
+            TypeId::of::<Vec3>()
+        ));
+
+        assert!(!compatible(TypeId::of::<i64>(), TypeId::of::<i32>()));
+        assert!(!compatible(TypeId::of::<i32>(), TypeId::of::<f32>()));
+        assert!(!compatible(TypeId::of::<i8>(), TypeId::of::<u16>()));
+        assert!(!compatible(TypeId::of::<f32>(), TypeId::of::<i64>()));
+        assert!(!compatible(TypeId::of::<Vec3>(), TypeId::of::<Vec2>()));
+    }
+
+    #[test]
+    fn widen_value() {
+        assert_eq!(
+            EffectValue::I32(-3).widen_to(TypeId::of::<i64>()),
+            Some(EffectValue::I64(-3))
+        );
+        assert_eq!(
+            EffectValue::U16(7).widen_to(TypeId::of::<f32>()),
+            Some(EffectValue::F32(7.0))
+        );
+        assert_eq!(
+            EffectValue::F32(0.5).widen_to(TypeId::of::<f64>()),
+            Some(EffectValue::F64(0.5))
+        );
+        assert_eq!(EffectValue::I64(1).widen_to(TypeId::of::<i32>()), None);
+        assert_eq!(
+            EffectValue::Bool(true).widen_to(TypeId::of::<EffectValue>()),
+            Some(EffectValue::Bool(true))
+        );
+    }
+
+    #[test]
+    fn serialize_values() {
+        let values = EffectValue::Vec(vec![
+            EffectValue::Vec2(Vec2::new(1.0, 2.0)),
+            EffectValue::LayerTag(
+                LayerTagBuilder::new()
+                    .add_tag(Tag::new("ability"))
+                    .add_tag(Tag::new("fire"))
+                    .build_single(),
+            ),
+            EffectValue::Attribute(EffectAttributeRef {
+                attribute_set: "HealthAttributeSet".into(),
+                attribute: "health".into(),
+            }),
+        ]);
+        let ron = ron::to_string(&values).unwrap();
+        assert!(ron.contains("\"ability.fire\""));
+        assert_eq!(ron::from_str::<EffectValue>(&ron).unwrap(), values);
+    }
+}
@@ -1,16 +1,19 @@
+use std::any::TypeId;
+use std::borrow::Cow;
 use std::ops::Not;
 use std::{fmt::Debug, sync::Arc};
 
-use bevy::ecs::entity::EntityHashMap;
 use bevy::utils::hashbrown::hash_map::EntryRef;
 use bevy::{prelude::*, utils::HashMap};
+use thiserror::Error;
 use uuid::Uuid;
 
-use super::blackboard::EffectValue;
+use super::blackboard::{EffectBlackboard, EffectValue};
 use super::node::pin::EffectNodePinGroup;
 use super::node::EffectNodeId;
 use super::node::InstantEffectNode;
 use super::pin::{EffectNodeExecPin, EffectNodeSlotPin, EffectNodeSlotValue};
+use super::template::EffectGraphTemplate;
 
 #[derive(Component, Debug, PartialEq, Eq, Clone, Copy, Hash, Reflect)]
 #[reflect(Component)]
@@ -39,14 +42,35 @@ impl InstantEffectNodeMap {
     pub fn get(&self, uuid: Uuid) -> Option<Arc<dyn InstantEffectNode>> {
         self.nodes.get(&uuid).cloned()
     }
+
+    pub fn remove(&mut self, uuid: Uuid) -> Option<Arc<dyn InstantEffectNode>> {
+        self.nodes.remove(&uuid)
+    }
 }
 
 pub trait EffectGraphExternalContext: Debug + Sync + Send {}
 
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum EffectGraphContextError {
+    #[error(
+        "output slot {:?}.{}({}) can not connect to input slot {:?}.{}({})",
+        .output.node_id,
+        .output.slot.name,
+        .output.slot.pin_type_name,
+        .input.node_id,
+        .input.slot.name,
+        .input.slot.pin_type_name
+    )]
+    SlotTypeMismatch {
+        output: EffectNodeSlotPin,
+        input: EffectNodeSlotPin,
+    },
+}
+
 #[derive(Debug, Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct EffectGraphContext {
-    // output to input connections
+    // output to input connections, 模板的实例为空，使用template中的连接
     pub exec_connections: HashMap<EffectNodeExecPin, Vec<EffectNodeExecPin>>,
     pub slot_connections: HashMap<EffectNodeSlotPin, Vec<EffectNodeSlotPin>>,
 
@@ -60,6 +84,20 @@ pub struct EffectGraphContext {
     pub instant_nodes: Vec<Uuid>,
     pub state_nodes: Vec<Entity>,
 
+    /// 实例共享的模板，模板自己和直接构建的图为None。
+    #[reflect(ignore)]
+    pub template: Option<Arc<EffectGraphTemplate>>,
+
+    /// 图实例自己的黑板，回收时从模板重置，不同实例之间不共享。
+    pub blackboard: EffectBlackboard,
+
+    /// 客户端预测执行时为true，伤害、buff和投射物等玩法节点由服务器执行，这里只执行表现相关的节点。
+    pub predicted: bool,
+
+    /// 调试时记录写入的输出值，由EffectGraphTrace收集，为None时不记录。
+    #[reflect(ignore)]
+    pub output_write_log: Option<Vec<(EffectNodeSlotPin, EffectValue)>>,
+
     // 方式1，是将external context存入Res中，使用id去获取，但删除比较麻烦
     // 方式2，将external context定义为一个trait，然后在context中存储一个Option<Box<dyn Trait>>，这样可以直接存储
     // 方式3，将external context定义为一个trait，且实现为一个component, 插入这个component到Effeect Graph, 通过trait查询这个组件。
@@ -78,95 +116,86 @@ impl EffectGraphContext {
             entry_node: None,
             instant_nodes: vec![],
             state_nodes: vec![],
+            template: None,
+            blackboard: EffectBlackboard::default(),
+            predicted: false,
+            output_write_log: None,
             external_context: None,
             graph_ref: None,
         }
     }
 
-    pub fn replace_state_entities(&mut self, new_entities: EntityHashMap<Entity>) {
-        self.state_nodes.clear();
-
-        for (old_entity, new_entity) in new_entities {
-            let keys = self
-                .outputs
-                .keys()
-                .filter(|key| match key.node_id {
-                    EffectNodeId::Uuid(_) => false,
-                    EffectNodeId::Entity(entity) => entity == old_entity,
-                })
-                .cloned()
-                .collect::<Vec<_>>();
-            for mut key in keys {
-                let value = self.outputs.remove(&key).unwrap();
-                key.node_id = new_entity.into();
-                self.outputs.insert(key, value);
-            }
-
-            let keys = self
-                .inputs
-                .keys()
-                .filter(|key| match key.node_id {
-                    EffectNodeId::Uuid(_) => false,
-                    EffectNodeId::Entity(entity) => entity == old_entity,
-                })
-                .cloned()
-                .collect::<Vec<_>>();
-            for mut key in keys {
-                let value = self.inputs.remove(&key).unwrap();
-                key.node_id = EffectNodeId::Entity(new_entity);
-                self.inputs.insert(key, value);
-            }
-
-            let keys = self
-                .exec_connections
-                .keys()
-                .filter(|key| match key.node_id {
-                    EffectNodeId::Uuid(_) => false,
-                    EffectNodeId::Entity(entity) => entity == old_entity,
-                })
-                .cloned()
-                .collect::<Vec<_>>();
-            for mut key in keys {
-                let value = self.exec_connections.remove(&key).unwrap();
-                key.node_id = EffectNodeId::Entity(new_entity);
-                self.exec_connections.insert(key, value);
-            }
-            self.exec_connections.values_mut().for_each(|value| {
-                for v in value {
-                    if v.node_id == EffectNodeId::Entity(old_entity) {
-                        v.node_id = EffectNodeId::Entity(new_entity);
-                    }
-                }
-            });
+    /// 模板的实例，连接和瞬时节点引用模板，state_nodes和模板的state_nodes一一对应。
+    pub fn from_template(template: Arc<EffectGraphTemplate>, state_nodes: Vec<Entity>) -> Self {
+        let mut context = Self::new();
+        context.graph_ref = Some(template.graph_ref);
+        context.state_nodes = state_nodes;
+        context.entry_node = template
+            .entry_node
+            .and_then(|entry_node| template.template_node_index(entry_node))
+            .map(|index| context.state_nodes[index]);
+        context.template = Some(template);
+        context.reset_from_template();
+        context
+    }
 
-            let keys = self
-                .slot_connections
-                .keys()
-                .filter(|key| match key.node_id {
-                    EffectNodeId::Uuid(_) => false,
-                    EffectNodeId::Entity(entity) => entity == old_entity,
-                })
-                .cloned()
-                .collect::<Vec<_>>();
-            for mut key in keys {
-                let value = self.slot_connections.remove(&key).unwrap();
-                key.node_id = EffectNodeId::Entity(new_entity);
-                self.slot_connections.insert(key, value);
-            }
-            self.slot_connections.values_mut().for_each(|value| {
-                for v in value {
-                    if v.node_id == EffectNodeId::Entity(old_entity) {
-                        v.node_id = EffectNodeId::Entity(new_entity);
+    /// 回收实例时从模板重置slot的值和黑板，节点的运行时数据由节点自己重置。
+    pub fn reset_from_template(&mut self) {
+        let Some(template) = self.template.clone() else {
+            return;
+        };
+
+        let to_instance = |pin: &EffectNodeSlotPin| EffectNodeSlotPin {
+            node_id: self.instance_node_id(pin.node_id),
+            slot: pin.slot,
+        };
+        let inputs = template
+            .inputs
+            .iter()
+            .map(|(key, value)| {
+                let value = match value {
+                    EffectNodeSlotValue::Ref(slot_pin) => {
+                        EffectNodeSlotValue::Ref(to_instance(slot_pin))
                     }
-                }
-            });
+                    value => value.clone(),
+                };
+                (to_instance(key), value)
+            })
+            .collect();
+        let outputs = template
+            .outputs
+            .iter()
+            .map(|(key, value)| (to_instance(key), value.clone()))
+            .collect();
+        self.inputs = inputs;
+        self.outputs = outputs;
+        self.blackboard = template.blackboard.clone();
+        self.predicted = false;
+    }
 
-            if self.entry_node == Some(old_entity) {
-                self.entry_node = Some(new_entity);
-            }
+    /// 实例节点对应的模板节点，没有模板时不变。
+    fn template_node_id(&self, node_id: EffectNodeId) -> EffectNodeId {
+        let (EffectNodeId::Entity(entity), Some(template)) = (node_id, self.template.as_ref())
+        else {
+            return node_id;
+        };
+        self.state_nodes
+            .iter()
+            .position(|node| *node == entity)
+            .and_then(|index| template.template_node(index))
+            .map_or(node_id, EffectNodeId::from)
+    }
 
-            self.state_nodes.push(new_entity);
-        }
+    /// 模板节点对应的实例节点，没有模板时不变。
+    fn instance_node_id(&self, node_id: EffectNodeId) -> EffectNodeId {
+        let (EffectNodeId::Entity(entity), Some(template)) = (node_id, self.template.as_ref())
+        else {
+            return node_id;
+        };
+        template
+            .template_node_index(entity)
+            .and_then(|index| self.state_nodes.get(index))
+            .map_or(node_id, |node| EffectNodeId::from(*node))
     }
 }
 
@@ -184,17 +213,18 @@ impl EffectGraphContext {
 
     pub fn get_input_value_type_from_node<'a, T: TryFrom<&'a EffectValue>>(
         &'a self,
-        entity: Entity,
+        node_id: impl Into<EffectNodeId>,
         node: &impl EffectNodePinGroup,
         pin_name: &'static str,
     ) -> Option<T>
     where
         <T as TryFrom<&'a EffectValue>>::Error: Debug,
     {
+        let node_id = node_id.into();
         node.get_input_slot_pin_by_name(pin_name)
             .and_then(|slot_pin| {
                 self.get_input_value_type::<T>(&EffectNodeSlotPin {
-                    node_id: EffectNodeId::Entity(entity),
+                    node_id,
                     slot: *slot_pin,
                 })
             })
@@ -219,8 +249,9 @@ impl EffectGraphContext {
                     }
                 },
 
+                // Ref指向其他节点的输出slot，值在outputs中
                 EffectNodeSlotValue::Ref(slot_pin) => {
-                    let slot_value = self.get_input_value(slot_pin);
+                    let slot_value = self.get_output_value(slot_pin);
                     match slot_value {
                         Some(EffectNodeSlotValue::Value(value)) => match value.try_into() {
                             Ok(v) => Some(v),
@@ -271,7 +302,28 @@ impl EffectGraphContext {
         }
     }
 
-    pub fn add_slot_connection(&mut self, key: EffectNodeSlotPin, value: &[EffectNodeSlotPin]) {
+    /// 连接输出slot到输入slot，输入slot的值引用输出slot的值。
+    /// pin类型不兼容时不添加任何连接。
+    pub fn add_slot_connection(
+        &mut self,
+        key: EffectNodeSlotPin,
+        value: &[EffectNodeSlotPin],
+    ) -> Result<(), EffectGraphContextError> {
+        if let Some(input_slot_pin) = value
+            .iter()
+            .find(|input_slot_pin| !key.slot.can_connect_to(&input_slot_pin.slot))
+        {
+            return Err(EffectGraphContextError::SlotTypeMismatch {
+                output: key,
+                input: *input_slot_pin,
+            });
+        }
+
+        for input_slot_pin in value {
+            self.inputs
+                .insert(*input_slot_pin, EffectNodeSlotValue::Ref(key));
+        }
+
         match self.slot_connections.entry_ref(&key) {
             EntryRef::Occupied(entry) => {
                 entry.into_mut().extend(value);
@@ -280,13 +332,55 @@ impl EffectGraphContext {
                 self.slot_connections.insert(key, value.to_vec());
             }
         }
+        Ok(())
     }
 
     pub fn get_connected_output_exec_pins(
         &self,
         key: &EffectNodeExecPin,
-    ) -> Option<&Vec<EffectNodeExecPin>> {
-        self.exec_connections.get(key)
+    ) -> Option<Cow<'_, [EffectNodeExecPin]>> {
+        let Some(template) = self.template.as_ref() else {
+            return self
+                .exec_connections
+                .get(key)
+                .map(|pins| pins.as_slice().into());
+        };
+        let key = EffectNodeExecPin {
+            node_id: self.template_node_id(key.node_id),
+            exec: key.exec,
+        };
+        template.exec_connections.get(&key).map(|pins| {
+            pins.iter()
+                .map(|pin| EffectNodeExecPin {
+                    node_id: self.instance_node_id(pin.node_id),
+                    exec: pin.exec,
+                })
+                .collect()
+        })
+    }
+
+    pub fn get_connected_input_slot_pins(
+        &self,
+        key: &EffectNodeSlotPin,
+    ) -> Option<Cow<'_, [EffectNodeSlotPin]>> {
+        let Some(template) = self.template.as_ref() else {
+            return self
+                .slot_connections
+                .get(key)
+                .map(|pins| pins.as_slice().into());
+        };
+        let key = EffectNodeSlotPin {
+            node_id: self.template_node_id(key.node_id),
+            slot: key.slot,
+        };
+        template.slot_connections.get(&key).map(|pins| {
+            pins.iter()
+                .map(|pin| EffectNodeSlotPin {
+                    node_id: self.instance_node_id(pin.node_id),
+                    slot: pin.slot,
+                })
+                .collect()
+        })
     }
 }
 
@@ -295,9 +389,53 @@ impl EffectGraphContext {
         self.inputs.insert(key, value);
     }
 
+    /// 连接的输入slot类型不同时，输入slot保存隐式扩展之后的值，比如i32输出到f64输入。
     pub fn insert_output_value(&mut self, key: EffectNodeSlotPin, value: EffectNodeSlotValue) {
+        if let EffectNodeSlotValue::Value(value) = &value {
+            let widen_values: Vec<_> = self
+                .get_connected_input_slot_pins(&key)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|input_slot_pin| {
+                    let pin_type = input_slot_pin.slot.pin_type;
+                    if pin_type == value.value_type_id() || pin_type == TypeId::of::<EffectValue>()
+                    {
+                        return None;
+                    }
+                    value
+                        .widen_to(pin_type)
+                        .map(|widen_value| (*input_slot_pin, widen_value))
+                })
+                .collect();
+            for (input_slot_pin, widen_value) in widen_values {
+                self.inputs.insert(input_slot_pin, widen_value.into());
+            }
+        }
+        if let (EffectNodeSlotValue::Value(value), Some(output_write_log)) =
+            (&value, self.output_write_log.as_mut())
+        {
+            output_write_log.push((key, value.clone()));
+        }
         self.outputs.insert(key, value);
     }
+
+    pub fn insert_output_value_from_node(
+        &mut self,
+        node_id: impl Into<EffectNodeId>,
+        node: &impl EffectNodePinGroup,
+        pin_name: &'static str,
+        value: EffectValue,
+    ) {
+        if let Some(slot_pin) = node.get_output_slot_pin_by_name(pin_name) {
+            self.insert_output_value(
+                EffectNodeSlotPin {
+                    node_id: node_id.into(),
+                    slot: *slot_pin,
+                },
+                value.into(),
+            );
+        }
+    }
 }
 
 impl EffectGraphContext {
@@ -329,3 +467,83 @@ impl EffectGraphContext {
         self.graph_ref
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use uuid::Uuid;
+
+    use crate::{
+        graph::{
+            blackboard::EffectValue,
+            node::{
+                pin::{EffectNodePinGroup, EffectNodeSlot},
+                EffectNodeId,
+            },
+            pin::{EffectNodeSlotPin, EffectNodeSlotValue},
+        },
+        impl_effect_node_pin_group,
+    };
+
+    use super::{EffectGraphContext, EffectGraphContextError};
+
+    struct EffectNodeTestOutput;
+
+    impl_effect_node_pin_group!(EffectNodeTestOutput,
+        output => (
+            finish => (count: i32)
+        )
+    );
+
+    struct EffectNodeTestInput;
+
+    impl_effect_node_pin_group!(EffectNodeTestInput,
+        input => (
+            start => (scale: f64, target: Entity)
+        )
+    );
+
+    fn slot_pin(node_id: EffectNodeId, slot: Option<&EffectNodeSlot>) -> EffectNodeSlotPin {
+        EffectNodeSlotPin {
+            node_id,
+            slot: *slot.unwrap(),
+        }
+    }
+
+    #[test]
+    fn slot_connection_type_validate_and_widen() {
+        let output_id = EffectNodeId::Uuid(Uuid::new_v4());
+        let input_id = EffectNodeId::Uuid(Uuid::new_v4());
+        let count = slot_pin(
+            output_id,
+            EffectNodeTestOutput
+                .get_output_slot_pin_by_name(EffectNodeTestOutput::OUTPUT_SLOT_COUNT),
+        );
+        let scale = slot_pin(
+            input_id,
+            EffectNodeTestInput.get_input_slot_pin_by_name(EffectNodeTestInput::INPUT_SLOT_SCALE),
+        );
+        let target = slot_pin(
+            input_id,
+            EffectNodeTestInput.get_input_slot_pin_by_name(EffectNodeTestInput::INPUT_SLOT_TARGET),
+        );
+
+        let mut context = EffectGraphContext::new();
+        assert_eq!(
+            context.add_slot_connection(count, &[scale, target]),
+            Err(EffectGraphContextError::SlotTypeMismatch {
+                output: count,
+                input: target,
+            })
+        );
+        assert!(context.get_input_value(&scale).is_none());
+
+        assert_eq!(context.add_slot_connection(count, &[scale]), Ok(()));
+        context.insert_output_value(count, EffectValue::I32(3).into());
+        assert_eq!(
+            context.get_input_value(&scale),
+            Some(&EffectNodeSlotValue::Value(EffectValue::F64(3.0)))
+        );
+        assert_eq!(context.get_input_value_type::<&f64>(&scale), Some(&3.0));
+    }
+}
@@ -0,0 +1,512 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+};
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::Serialize;
+
+use super::{
+    blackboard::EffectValue,
+    context::EffectGraphContext,
+    event::EffectNodeExecEvent,
+    executor::EffectGraphExecutor,
+    node::{EffectNodeExecuteState, EffectNodeId},
+    pin::{EffectNodeExecPin, EffectNodeSlotPin},
+    state::EffectGraphState,
+};
+
+/// 可选的调试插件，记录图实例的执行过程。
+/// 只有带有EffectGraphTrace的图实例会被记录，trace_all为true时所有新的图实例都会添加。
+#[derive(Debug, Default)]
+pub struct EffectGraphDebuggerPlugin;
+
+impl Plugin for EffectGraphDebuggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectGraphTraceSettings>()
+            .add_event::<EffectGraphDebugEvent>()
+            .add_systems(
+                PostUpdate,
+                (attach_effect_graph_trace, record_effect_graph_trace).chain(),
+            )
+            .add_observer(trigger_add_effect_graph_trace)
+            .add_observer(trigger_remove_effect_graph_trace)
+            .add_observer(trigger_record_effect_node_exec)
+            .add_observer(trigger_effect_graph_debug);
+    }
+}
+
+#[derive(Debug, Resource, Clone)]
+pub struct EffectGraphTraceSettings {
+    /// 给所有新的图实例添加EffectGraphTrace。
+    pub trace_all: bool,
+    /// 每个图实例最多保存的记录数量。
+    pub capacity: usize,
+}
+
+impl Default for EffectGraphTraceSettings {
+    fn default() -> Self {
+        Self {
+            trace_all: false,
+            capacity: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EffectGraphTraceEvent {
+    /// 执行器执行的输出exec pin，以及连接的输入exec pin。
+    ExecPin {
+        output: EffectNodeExecPin,
+        inputs: Vec<EffectNodeExecPin>,
+    },
+    InstantNodeExecute {
+        input: EffectNodeExecPin,
+    },
+    StateNodeExec {
+        input: EffectNodeExecPin,
+    },
+    SlotValue {
+        slot: EffectNodeSlotPin,
+        value: EffectValue,
+    },
+    GraphState {
+        state: EffectGraphState,
+    },
+    NodeState {
+        node: Entity,
+        state: EffectNodeExecuteState,
+    },
+    BreakpointHit {
+        output: EffectNodeExecPin,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectGraphTraceRecord {
+    /// 记录时的Time::elapsed_secs。
+    pub time: f32,
+    pub event: EffectGraphTraceEvent,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum EffectGraphDebugControl {
+    #[default]
+    Running,
+    Paused,
+    /// 执行一个输出exec pin之后暂停。
+    Step,
+    /// 忽略当前断点继续执行。
+    Continue,
+}
+
+/// 图实例的执行记录，环形缓冲区，超出容量时丢弃最早的记录。
+#[derive(Debug, Component)]
+pub struct EffectGraphTrace {
+    records: VecDeque<EffectGraphTraceRecord>,
+    capacity: usize,
+    breakpoints: HashSet<EffectNodeId>,
+    control: EffectGraphDebugControl,
+}
+
+impl Default for EffectGraphTrace {
+    fn default() -> Self {
+        Self::new(EffectGraphTraceSettings::default().capacity)
+    }
+}
+
+impl EffectGraphTrace {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            records: VecDeque::with_capacity(capacity),
+            capacity,
+            breakpoints: HashSet::default(),
+            control: EffectGraphDebugControl::Running,
+        }
+    }
+
+    pub fn records(&self) -> impl Iterator<Item = &EffectGraphTraceRecord> {
+        self.records.iter()
+    }
+
+    pub fn record(&mut self, time: f32, event: EffectGraphTraceEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.records.len() == self.capacity {
+            self.records.pop_front();
+        }
+        self.records
+            .push_back(EffectGraphTraceRecord { time, event });
+    }
+
+    pub fn clear(&mut self) {
+        self.records.clear();
+    }
+
+    /// 执行到连接了断点节点的输出exec pin之前暂停。
+    pub fn add_breakpoint(&mut self, node_id: EffectNodeId) {
+        self.breakpoints.insert(node_id);
+    }
+
+    pub fn remove_breakpoint(&mut self, node_id: EffectNodeId) {
+        self.breakpoints.remove(&node_id);
+    }
+
+    pub fn control(&self) -> EffectGraphDebugControl {
+        self.control
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.control == EffectGraphDebugControl::Paused
+    }
+
+    pub fn step(&mut self) {
+        self.control = EffectGraphDebugControl::Step;
+    }
+
+    pub fn resume(&mut self) {
+        if self.control == EffectGraphDebugControl::Paused {
+            self.control = EffectGraphDebugControl::Continue;
+        }
+    }
+
+    /// 执行器在执行输出exec pin之前调用，返回false时暂停执行，输出exec pin保留在执行器中。
+    pub(crate) fn can_execute(
+        &mut self,
+        time: f32,
+        output: EffectNodeExecPin,
+        inputs: &[EffectNodeExecPin],
+    ) -> bool {
+        match self.control {
+            EffectGraphDebugControl::Paused => false,
+            EffectGraphDebugControl::Step => {
+                self.control = EffectGraphDebugControl::Paused;
+                true
+            }
+            EffectGraphDebugControl::Continue => {
+                self.control = EffectGraphDebugControl::Running;
+                true
+            }
+            EffectGraphDebugControl::Running => {
+                if inputs
+                    .iter()
+                    .any(|input| self.breakpoints.contains(&input.node_id))
+                {
+                    self.control = EffectGraphDebugControl::Paused;
+                    self.record(time, EffectGraphTraceEvent::BreakpointHit { output });
+                    return false;
+                }
+                true
+            }
+        }
+    }
+
+    /// 收集context中记录的输出值。
+    pub(crate) fn record_output_writes(&mut self, time: f32, context: &mut EffectGraphContext) {
+        let Some(output_write_log) = context.output_write_log.as_mut() else {
+            return;
+        };
+        for (slot, value) in std::mem::take(output_write_log) {
+            self.record(time, EffectGraphTraceEvent::SlotValue { slot, value });
+        }
+    }
+
+    pub fn to_json(&self, graph: Entity) -> serde_json::Value {
+        serde_json::json!({
+            "graph": graph,
+            "breakpoints": self.breakpoints.iter().collect::<Vec<_>>(),
+            "control": self.control,
+            "records": self.records,
+        })
+    }
+
+    /// 导出为json时间线，用于离线查看。
+    pub fn export_json(&self, graph: Entity, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, &self.to_json(graph))?;
+        Ok(())
+    }
+}
+
+/// 对图实例的调试操作，target为图实例。
+#[derive(Debug, Event, Clone, PartialEq)]
+pub enum EffectGraphDebugEvent {
+    AddBreakpoint(EffectNodeId),
+    RemoveBreakpoint(EffectNodeId),
+    Step,
+    Continue,
+    ExportJson(PathBuf),
+}
+
+pub fn trigger_effect_graph_debug(
+    trigger: Trigger<EffectGraphDebugEvent>,
+    mut query: Query<&mut EffectGraphTrace>,
+) {
+    let graph = trigger.entity();
+    let Ok(mut trace) = query.get_mut(graph) else {
+        warn!("effect graph {:?} has no trace", graph);
+        return;
+    };
+
+    match trigger.event() {
+        EffectGraphDebugEvent::AddBreakpoint(node_id) => trace.add_breakpoint(*node_id),
+        EffectGraphDebugEvent::RemoveBreakpoint(node_id) => trace.remove_breakpoint(*node_id),
+        EffectGraphDebugEvent::Step => trace.step(),
+        EffectGraphDebugEvent::Continue => trace.resume(),
+        EffectGraphDebugEvent::ExportJson(path) => match trace.export_json(graph, path) {
+            Ok(()) => info!("export effect graph {:?} trace to {:?}", graph, path),
+            Err(e) => error!("export effect graph {:?} trace failed: {}", graph, e),
+        },
+    }
+}
+
+/// 模板没有执行器，只给图实例添加。
+pub fn attach_effect_graph_trace(
+    mut commands: Commands,
+    settings: Res<EffectGraphTraceSettings>,
+    query: Query<Entity, (Added<EffectGraphExecutor>, Without<EffectGraphTrace>)>,
+) {
+    if !settings.trace_all {
+        return;
+    }
+    for graph in query.iter() {
+        commands
+            .entity(graph)
+            .insert(EffectGraphTrace::new(settings.capacity));
+    }
+}
+
+pub fn trigger_add_effect_graph_trace(
+    trigger: Trigger<OnAdd, EffectGraphTrace>,
+    mut query: Query<&mut EffectGraphContext>,
+) {
+    if let Ok(mut context) = query.get_mut(trigger.entity()) {
+        context.output_write_log = Some(vec![]);
+    }
+}
+
+pub fn trigger_remove_effect_graph_trace(
+    trigger: Trigger<OnRemove, EffectGraphTrace>,
+    mut query: Query<&mut EffectGraphContext>,
+) {
+    if let Ok(mut context) = query.get_mut(trigger.entity()) {
+        context.output_write_log = None;
+    }
+}
+
+/// 状态节点的父实体是图实例。
+pub fn trigger_record_effect_node_exec(
+    trigger: Trigger<EffectNodeExecEvent>,
+    time: Res<Time>,
+    node_query: Query<&Parent>,
+    mut trace_query: Query<&mut EffectGraphTrace>,
+) {
+    let input = trigger.event().input_exec_pin;
+    let EffectNodeId::Entity(node) = input.node_id else {
+        return;
+    };
+    let Ok(parent) = node_query.get(node) else {
+        return;
+    };
+    if let Ok(mut trace) = trace_query.get_mut(parent.get()) {
+        trace.record(
+            time.elapsed_secs(),
+            EffectGraphTraceEvent::StateNodeExec { input },
+        );
+    }
+}
+
+/// 记录状态变化，以及状态节点在执行器之外写入的输出值。
+pub fn record_effect_graph_trace(
+    time: Res<Time>,
+    mut trace_query: Query<(&mut EffectGraphTrace, &mut EffectGraphContext)>,
+    graph_state_query: Query<(Entity, &EffectGraphState), Changed<EffectGraphState>>,
+    node_state_query: Query<
+        (Entity, &EffectNodeExecuteState, &Parent),
+        Changed<EffectNodeExecuteState>,
+    >,
+) {
+    let time = time.elapsed_secs();
+
+    for (graph, state) in graph_state_query.iter() {
+        if let Ok((mut trace, _)) = trace_query.get_mut(graph) {
+            trace.record(time, EffectGraphTraceEvent::GraphState { state: *state });
+        }
+    }
+
+    for (node, state, parent) in node_state_query.iter() {
+        if let Ok((mut trace, _)) = trace_query.get_mut(parent.get()) {
+            trace.record(
+                time,
+                EffectGraphTraceEvent::NodeState {
+                    node,
+                    state: *state,
+                },
+            );
+        }
+    }
+
+    for (mut trace, mut context) in trace_query.iter_mut() {
+        if context
+            .output_write_log
+            .as_ref()
+            .is_some_and(|output_write_log| !output_write_log.is_empty())
+        {
+            trace.record_output_writes(time, &mut context);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use uuid::Uuid;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::{EffectNodeGetBlackboard, EffectNodeSetBlackboard},
+                test_graph::{blackboard_value, new_test_app, start_entry, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::{
+        EffectGraphDebugEvent, EffectGraphDebuggerPlugin, EffectGraphTrace, EffectGraphTraceEvent,
+    };
+
+    /// entry => get(damage) => set(result)
+    fn spawn_traced_graph(app: &mut App) -> (Entity, Entity, Uuid) {
+        let mut graph = TestGraph::new(app);
+        graph
+            .context
+            .blackboard
+            .set_value("damage", EffectValue::F32(10.0));
+
+        let get = EffectNodeGetBlackboard::new();
+        let set = EffectNodeSetBlackboard::new();
+        let set_uuid = set.get_uuid();
+        graph.set_input(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("damage".into()),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("result".into()),
+        );
+        graph.connect_entry(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::OUTPUT_EXEC_FINISH,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::OUTPUT_SLOT_VALUE,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.add_instant_node(app, get);
+        graph.add_instant_node(app, set);
+
+        let owner = graph.owner;
+        let graph_entity = graph.spawn(app);
+        app.world_mut()
+            .entity_mut(graph_entity)
+            .insert(EffectGraphTrace::new(64));
+        (owner, graph_entity, set_uuid)
+    }
+
+    fn new_app() -> App {
+        let mut app = new_test_app();
+        app.add_plugins(EffectGraphDebuggerPlugin);
+        app
+    }
+
+    #[test]
+    fn record_exec_and_slot_values() {
+        let mut app = new_app();
+        let (owner, graph, _) = spawn_traced_graph(&mut app);
+        start_entry(&mut app, owner);
+
+        assert_eq!(
+            blackboard_value(&app, graph, "result"),
+            Some(EffectValue::F32(10.0))
+        );
+        let trace = app.world().get::<EffectGraphTrace>(graph).unwrap();
+        let events = trace
+            .records()
+            .map(|record| &record.event)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, EffectGraphTraceEvent::ExecPin { .. }))
+                .count(),
+            2
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, EffectGraphTraceEvent::InstantNodeExecute { .. }))
+                .count(),
+            2
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            EffectGraphTraceEvent::SlotValue {
+                value: EffectValue::F32(10.0),
+                ..
+            }
+        )));
+
+        let json = trace.to_json(graph);
+        assert_eq!(json["records"][0]["event"]["type"], "exec_pin");
+    }
+
+    #[test]
+    fn breakpoint_and_step() {
+        let mut app = new_app();
+        let (owner, graph, set_uuid) = spawn_traced_graph(&mut app);
+        app.world_mut()
+            .trigger_targets(EffectGraphDebugEvent::AddBreakpoint(set_uuid.into()), graph);
+        start_entry(&mut app, owner);
+
+        assert_eq!(blackboard_value(&app, graph, "result"), None);
+        let trace = app.world().get::<EffectGraphTrace>(graph).unwrap();
+        assert!(trace.is_paused());
+        assert!(trace
+            .records()
+            .any(|record| matches!(record.event, EffectGraphTraceEvent::BreakpointHit { .. })));
+
+        app.update();
+        assert_eq!(blackboard_value(&app, graph, "result"), None);
+
+        app.world_mut()
+            .trigger_targets(EffectGraphDebugEvent::Step, graph);
+        app.update();
+        assert_eq!(
+            blackboard_value(&app, graph, "result"),
+            Some(EffectValue::F32(10.0))
+        );
+    }
+}
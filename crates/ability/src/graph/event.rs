@@ -1,17 +1,16 @@
-use std::sync::Arc;
-
-use atom_utils::clone_entities::{CloneEntityTreeCommand, EntityTreeNode};
-use bevy::{ecs::entity::EntityHashMap, prelude::*, utils::HashMap};
+use bevy::{prelude::*, utils::HashMap};
 
 use crate::graph::pin::EffectNodeSlotPin;
 
 use super::{
+    asset::{EffectGraphAsset, EffectGraphAssetMap},
     blackboard::EffectValue,
     context::{EffectGraphContext, GraphRef, InstantEffectNodeMap},
     executor::EffectGraphExecutor,
     graph_map::{EffectGraphBuilderMap, EffectGraphMap, GraphClass},
     node::pin::{EffectNodeExec, EffectNodeSlot},
     pin::EffectNodeExecPin,
+    pool::{spawn_effect_graph_instance, EffectGraphPool},
     state::{EffectGraphState, EffectGraphTickState},
     EffectGraphOwner,
 };
@@ -22,27 +21,6 @@ pub struct EffectNodeExecEvent {
     pub input_exec_pin: EffectNodeExecPin,
 }
 
-#[derive(Debug, Event, Clone, Copy)]
-pub struct CloneEffectGraphStartEvent {
-    pub graph_ref: GraphRef,
-    pub destination_entity: Entity,
-}
-
-impl Default for CloneEffectGraphStartEvent {
-    fn default() -> Self {
-        Self {
-            graph_ref: GraphRef::new(Entity::PLACEHOLDER),
-            destination_entity: Entity::PLACEHOLDER,
-        }
-    }
-}
-
-#[derive(Debug, Event)]
-pub struct CloneEffectGraphEndEvent {
-    pub destination_root_entity: Entity,
-    pub old_new_entities: EntityHashMap<Entity>,
-}
-
 // 添加一个EffectGraph
 #[derive(Debug, Event, Clone)]
 pub struct EffectGraphAddEvent {
@@ -116,13 +94,13 @@ pub fn trigger_effect_graph_to_remove(
 pub fn trigger_effect_graph_exec(
     trigger: Trigger<EffectGraphExecEvent>,
     mut commands: Commands,
+    mut pool: ResMut<EffectGraphPool>,
     graph_owner_query: Query<&Children, With<EffectGraphOwner>>,
     mut graph_query: Query<(
         &mut EffectGraphContext,
         &mut EffectGraphExecutor,
         &EffectGraphState,
     )>,
-    instant_map: Res<InstantEffectNodeMap>,
 ) {
     let graph_owner_entity = trigger.entity();
     let Ok(children) = graph_owner_query.get(graph_owner_entity) else {
@@ -140,7 +118,7 @@ pub fn trigger_effect_graph_exec(
     // 以上有问题，如果技能需要还原，结束，中断等，多次技能使用同一个EffectGraph instance，
     // 会有问题。因为每个节点存储了多个状态，但是不知道应该还原，结束，中断哪些状态。
     if trigger.event().entry_exec_pin == "ready".into() {
-        let mut clone_event = CloneEffectGraphStartEvent::default();
+        let mut graph_ref = None;
         let mut to_clone_graph = false;
         for child in children {
             if let Ok((context, mut _executor, state)) = graph_query.get(*child) {
@@ -150,7 +128,7 @@ pub fn trigger_effect_graph_exec(
                         break;
                     }
                     EffectGraphState::Active => {
-                        clone_event.graph_ref = context.get_graph_ref().unwrap();
+                        graph_ref = context.get_graph_ref();
                         to_clone_graph = true;
                     }
                     EffectGraphState::ToRemove => {}
@@ -158,15 +136,18 @@ pub fn trigger_effect_graph_exec(
             }
         }
 
-        if to_clone_graph {
-            let new_graph_instance = commands.spawn_empty().set_parent(graph_owner_entity).id();
-            clone_event.destination_entity = new_graph_instance;
-            commands.trigger_targets(clone_event, new_graph_instance);
+        if let (true, Some(graph_ref)) = (to_clone_graph, graph_ref) {
+            let new_graph_instance = spawn_effect_graph_instance(
+                &mut commands,
+                &mut pool,
+                graph_ref,
+                graph_owner_entity,
+            );
             info!(
                 "trigger effect graph exec to clone graph: {:?} => {:?} => {:?} ",
                 graph_owner_entity,
                 trigger.event(),
-                clone_event
+                new_graph_instance
             );
 
             commands.trigger_targets(trigger.event().clone(), graph_owner_entity);
@@ -196,14 +177,10 @@ pub fn trigger_effect_graph_exec(
                         );
                     }
                 }
-                executor.start_push_output_pin(
-                    EffectNodeExecPin {
-                        node_id: context.get_entry_node().unwrap().into(),
-                        exec: trigger.event().entry_exec_pin,
-                    },
-                    &context,
-                    &instant_map,
-                );
+                executor.start_push_output_pin(EffectNodeExecPin {
+                    node_id: context.get_entry_node().unwrap().into(),
+                    exec: trigger.event().entry_exec_pin,
+                });
             };
 
             match trigger.event().execute_in_graph_state {
@@ -220,12 +197,17 @@ pub fn trigger_effect_graph_exec(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn trigger_effect_graph_add(
     trigger: Trigger<EffectGraphAddEvent>,
     mut commands: Commands,
+    mut pool: ResMut<EffectGraphPool>,
     mut graph_map: ResMut<EffectGraphMap>,
     graph_builder_map: Res<EffectGraphBuilderMap>,
     mut instant_map: ResMut<InstantEffectNodeMap>,
+    graph_asset_map: Option<Res<EffectGraphAssetMap>>,
+    graph_assets: Option<Res<Assets<EffectGraphAsset>>>,
+    type_registry: Res<AppTypeRegistry>,
 ) {
     let graph_owner_entity = trigger.entity();
     let event = trigger.event();
@@ -241,7 +223,36 @@ pub fn trigger_effect_graph_add(
                 graph_map.insert_graph(event.graph_class.clone(), graph_ref.unwrap());
             }
             None => {
-                error!("graph builder not found: {}", event.graph_class);
+                // 没有builder时，从资产构建，graph_class为资产路径。
+                let graph_asset = graph_asset_map
+                    .as_ref()
+                    .and_then(|asset_map| asset_map.get(&event.graph_class))
+                    .and_then(|handle| graph_assets.as_ref()?.get(handle));
+                match graph_asset {
+                    Some(graph_asset) => {
+                        match graph_asset.build(
+                            &mut commands,
+                            &mut instant_map,
+                            &type_registry.read(),
+                        ) {
+                            Ok(graph) => {
+                                graph_ref = Some(GraphRef::new(graph));
+                                info!("build graph template from asset: {:?}", graph);
+                                graph_map
+                                    .insert_graph(event.graph_class.clone(), graph_ref.unwrap());
+                            }
+                            Err(e) => {
+                                error!(
+                                    "build graph {} from asset failed: {}",
+                                    event.graph_class, e
+                                );
+                            }
+                        }
+                    }
+                    None => {
+                        error!("graph builder not found: {}", event.graph_class);
+                    }
+                }
             }
         }
     }
@@ -250,72 +261,5 @@ pub fn trigger_effect_graph_add(
         return;
     };
 
-    let new_graph_entity = commands.spawn_empty().set_parent(graph_owner_entity).id();
-    commands.trigger_targets(
-        CloneEffectGraphStartEvent {
-            graph_ref,
-            destination_entity: new_graph_entity,
-        },
-        new_graph_entity,
-    );
-}
-
-pub fn trigger_clone_effect_graph_start(
-    trigger: Trigger<CloneEffectGraphStartEvent>,
-    mut commands: Commands,
-    query_children: Query<&Children>,
-) {
-    let event = trigger.event();
-    assert_ne!(trigger.entity(), Entity::PLACEHOLDER);
-
-    let graph_ref = event.graph_ref;
-    let new_graph_entity = event.destination_entity;
-
-    // clone all entity and component to effect graph.
-    let entity_tree_node = EntityTreeNode::from_entity_recursive(
-        &mut commands,
-        graph_ref.get_entity(),
-        Some(new_graph_entity),
-        &query_children,
-    );
-    let mut old_new_entities = entity_tree_node.recursive_get_entities_map();
-    old_new_entities.remove(&graph_ref.get_entity());
-
-    let clone_entities = CloneEntityTreeCommand(Arc::new(entity_tree_node));
-    commands.queue(clone_entities);
-    commands.trigger_targets(
-        CloneEffectGraphEndEvent {
-            destination_root_entity: new_graph_entity,
-            old_new_entities,
-        },
-        new_graph_entity,
-    );
-
-    info!("clone_effect_graph_start");
-}
-
-/// add to effect entity observer
-/// 替换所有graph context中的entities，替换为新的entities
-pub fn trigger_clone_effect_graph_end(
-    trigger: Trigger<CloneEffectGraphEndEvent>,
-    mut commands: Commands,
-    mut query: Query<&mut EffectGraphContext>,
-) {
-    let event = trigger.event();
-    assert_ne!(trigger.entity(), Entity::PLACEHOLDER);
-
-    commands
-        .entity(trigger.entity())
-        .insert(EffectGraphExecutor::default());
-
-    info!("clone entities: {:?}", event.old_new_entities);
-
-    let mut context = query.get_mut(trigger.entity()).unwrap();
-    // info!("context: before");
-    // dbg!(&context);
-    context.replace_state_entities(event.old_new_entities.clone());
-    // info!("context: after");
-    // dbg!(&context);
-
-    info!("clone_effect_graph_end");
+    spawn_effect_graph_instance(&mut commands, &mut pool, graph_ref, graph_owner_entity);
 }
@@ -1,12 +1,17 @@
-use std::ops::Not;
+use std::borrow::Cow;
 
 use bevy::prelude::*;
 
 use super::{
+    blackboard::EffectValue,
     context::{EffectGraphContext, InstantEffectNodeMap},
+    debugger::{EffectGraphTrace, EffectGraphTraceEvent},
     event::EffectNodeExecEvent,
-    node::{pin::EffectNodePinGroup, EffectNodeId},
-    pin::EffectNodeExecPin,
+    node::{
+        pin::{EffectNodePinGroup, EffectNodeSlot},
+        EffectNodeId,
+    },
+    pin::{EffectNodeExecPin, EffectNodeSlotPin},
     EffectGraphUpdateSystemSet,
 };
 
@@ -25,109 +30,186 @@ impl Plugin for EffectGraphExecutorPlugin {
     }
 }
 
+/// 待执行的输出exec pin，以及执行前写入该节点输出slot的值。
+/// 用于同一个输出pin需要以不同的输出值多次执行的情况，比如循环节点。
+#[derive(Debug, Clone, PartialEq, Reflect)]
+pub struct EffectNodeExecOutput {
+    pub exec_pin: EffectNodeExecPin,
+    pub slot_values: Vec<(EffectNodeSlot, EffectValue)>,
+}
+
+impl From<EffectNodeExecPin> for EffectNodeExecOutput {
+    fn from(exec_pin: EffectNodeExecPin) -> Self {
+        Self {
+            exec_pin,
+            slot_values: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Default, Component, Reflect)]
 #[reflect(Component)]
 pub struct EffectGraphExecutor {
-    current_node_outputs: Vec<EffectNodeExecPin>,
+    current_node_outputs: Vec<EffectNodeExecOutput>,
 }
 
 impl EffectGraphExecutor {
-    fn push_node_output_pin(&mut self, output_exec: EffectNodeExecPin) {
-        self.current_node_outputs.push(output_exec);
+    fn push_node_output_pin(&mut self, output: EffectNodeExecOutput) {
+        self.current_node_outputs.push(output);
     }
 
-    // use to start execute next nodes of exec pin
-    // push still to next node is state node.
-    pub fn start_push_output_pin(
-        &mut self,
-        output_exec_pin: EffectNodeExecPin,
-        context: &EffectGraphContext,
-        instant_nodes: &Res<InstantEffectNodeMap>,
-    ) {
-        self.push_node_output_pin(output_exec_pin);
-        self.continue_push_next_node_output_pin(output_exec_pin, context, instant_nodes);
+    /// 状态节点从这个输出pin继续执行。
+    /// 后续瞬时节点的push_execute_chain在它们execute之后由execute_graph调用，
+    /// 这里不再提前展开，否则分支节点会在条件计算之前选择输出pin。
+    pub fn start_push_output_pin(&mut self, output_exec_pin: EffectNodeExecPin) {
+        self.push_node_output_pin(output_exec_pin.into());
     }
 
-    // only use in push_execute_chain method  of instant node
-    pub fn continue_push_next_node_output_pin(
+    // only use in push_execute_chain method of instant node
+    pub fn continue_push_next_node_output_pin_from_node_name(
         &mut self,
-        output_exec_pin: EffectNodeExecPin,
-        context: &EffectGraphContext,
-        instant_nodes: &Res<InstantEffectNodeMap>,
+        node_id: EffectNodeId,
+        node: &impl EffectNodePinGroup,
+        output_exec_pin_name: &str,
     ) {
-        if let Some(next_input_exec_pins) = context.get_connected_output_exec_pins(&output_exec_pin)
-        {
-            for next_input_exec_pin in next_input_exec_pins {
-                if let EffectNodeId::Uuid(uuid) = next_input_exec_pin.node_id {
-                    if let Some(node) = instant_nodes.get(uuid) {
-                        node.push_execute_chain(
-                            context,
-                            self,
-                            next_input_exec_pin.exec,
-                            instant_nodes,
-                        );
-                    }
-                }
-            }
-        }
+        self.continue_push_next_node_output_pin_with_values(
+            node_id,
+            node,
+            output_exec_pin_name,
+            vec![],
+        );
     }
 
-    // only use in push_execute_chain method  of instant node
-    pub fn continue_push_next_node_output_pin_from_node_name(
+    // only use in push_execute_chain method of instant node
+    // output slot values will be written when the exec pin is executed.
+    pub fn continue_push_next_node_output_pin_with_values(
         &mut self,
         node_id: EffectNodeId,
         node: &impl EffectNodePinGroup,
         output_exec_pin_name: &str,
-        context: &EffectGraphContext,
-        instant_nodes: &Res<InstantEffectNodeMap>,
+        slot_values: Vec<(&'static str, EffectValue)>,
     ) {
-        if let Some(pin) = node.get_output_exec_pin_by_name(output_exec_pin_name) {
-            self.continue_push_next_node_output_pin(
-                EffectNodeExecPin {
-                    node_id,
-                    exec: *pin,
-                },
-                context,
-                instant_nodes,
-            );
-        }
+        let Some(pin) = node.get_output_exec_pin_by_name(output_exec_pin_name) else {
+            error!("output exec pin {} not found", output_exec_pin_name);
+            return;
+        };
+
+        let slot_values = slot_values
+            .into_iter()
+            .filter_map(|(name, value)| match node.get_output_slot_pin_by_name(name) {
+                Some(slot) => Some((*slot, value)),
+                None => {
+                    error!("output slot pin {} not found", name);
+                    None
+                }
+            })
+            .collect();
+
+        self.push_node_output_pin(EffectNodeExecOutput {
+            exec_pin: EffectNodeExecPin {
+                node_id,
+                exec: *pin,
+            },
+            slot_values,
+        });
     }
 }
 
+/// 深度优先执行，一个输出pin后续的瞬时节点全部执行完毕后，才会执行下一个输出pin。
+/// 循环节点对同一个输出pin多次写入输出值，广度优先时后续节点只能读到最后一次的值。
+/// 带有EffectGraphTrace的图实例会记录执行过程，并在断点处暂停。
 fn execute_graph(
     mut commands: Commands,
-    mut query: Query<(&mut EffectGraphContext, &mut EffectGraphExecutor)>,
+    mut query: Query<(
+        &mut EffectGraphContext,
+        &mut EffectGraphExecutor,
+        Option<&mut EffectGraphTrace>,
+    )>,
     instant_nodes: Res<InstantEffectNodeMap>,
+    time: Res<Time>,
 ) {
-    for (mut context, mut executor) in query.iter_mut() {
-        while executor.current_node_outputs.is_empty().not() {
-            // info!(
-            //     "executor.current_node_outputs: {:?}",
-            //     executor.current_node_outputs
-            // );
-            // dbg!(&context);
+    let time = time.elapsed_secs();
+    for (mut context, mut executor, mut trace) in query.iter_mut() {
+        while let Some(current_exec_pin) = executor
+            .current_node_outputs
+            .first()
+            .map(|current| current.exec_pin)
+        {
+            let next_input_exec_pins = context
+                .get_connected_output_exec_pins(&current_exec_pin)
+                .map(Cow::into_owned);
+
+            if let Some(trace) = trace.as_mut() {
+                if !trace.can_execute(
+                    time,
+                    current_exec_pin,
+                    next_input_exec_pins.as_deref().unwrap_or_default(),
+                ) {
+                    break;
+                }
+            }
+
             let current = executor.current_node_outputs.remove(0);
-            if let Some(next_input_exec_pins) =
-                context.get_connected_output_exec_pins(&current).cloned()
-            {
-                info!("next_input_exec_pins: {:?}", next_input_exec_pins);
-                for next_input_exec_pin in next_input_exec_pins {
-                    match next_input_exec_pin.node_id {
-                        EffectNodeId::Uuid(uuid) => {
-                            if let Some(node) = instant_nodes.get(uuid) {
-                                node.execute(&mut context);
+            for (slot, value) in current.slot_values {
+                context.insert_output_value(
+                    EffectNodeSlotPin {
+                        node_id: current.exec_pin.node_id,
+                        slot,
+                    },
+                    value.into(),
+                );
+            }
+
+            let Some(next_input_exec_pins) = next_input_exec_pins else {
+                continue;
+            };
+
+            if let Some(trace) = trace.as_mut() {
+                trace.record_output_writes(time, &mut context);
+                trace.record(
+                    time,
+                    EffectGraphTraceEvent::ExecPin {
+                        output: current.exec_pin,
+                        inputs: next_input_exec_pins.clone(),
+                    },
+                );
+            }
+
+            trace!("next_input_exec_pins: {:?}", next_input_exec_pins);
+            let mut next_executor = EffectGraphExecutor::default();
+            for next_input_exec_pin in next_input_exec_pins {
+                match next_input_exec_pin.node_id {
+                    EffectNodeId::Uuid(uuid) => {
+                        if let Some(node) = instant_nodes.get(uuid) {
+                            node.execute(&mut context, &mut commands);
+                            if let Some(trace) = trace.as_mut() {
+                                trace.record(
+                                    time,
+                                    EffectGraphTraceEvent::InstantNodeExecute {
+                                        input: next_input_exec_pin,
+                                    },
+                                );
+                                trace.record_output_writes(time, &mut context);
                             }
+                            node.push_execute_chain(
+                                &context,
+                                &mut next_executor,
+                                next_input_exec_pin.exec,
+                            );
                         }
-                        EffectNodeId::Entity(entity) => {
-                            assert_ne!(entity, Entity::PLACEHOLDER);
-                            let event = EffectNodeExecEvent {
-                                input_exec_pin: next_input_exec_pin,
-                            };
-                            commands.trigger(event);
-                        }
+                    }
+                    EffectNodeId::Entity(entity) => {
+                        assert_ne!(entity, Entity::PLACEHOLDER);
+                        let event = EffectNodeExecEvent {
+                            input_exec_pin: next_input_exec_pin,
+                        };
+                        commands.trigger(event);
                     }
                 }
             }
+            executor
+                .current_node_outputs
+                .splice(0..0, next_executor.current_node_outputs);
         }
     }
 }
@@ -1,27 +1,33 @@
 use bevy::{app::App, prelude::*, reflect::Reflect};
 use context::{EffectGraphContext, GraphRef, InstantEffectNodeMap};
 use event::{
-    trigger_clone_effect_graph_end, trigger_clone_effect_graph_start, trigger_effect_graph_add,
-    trigger_effect_graph_exec, trigger_effect_graph_tickable, trigger_effect_graph_to_remove,
-    CloneEffectGraphEndEvent, CloneEffectGraphStartEvent, EffectGraphAddEvent,
-    EffectGraphExecEvent, EffectGraphRemoveEvent, EffectGraphTickableEvent,
+    trigger_effect_graph_add, trigger_effect_graph_exec, trigger_effect_graph_tickable,
+    trigger_effect_graph_to_remove, EffectGraphAddEvent, EffectGraphExecEvent,
+    EffectGraphRemoveEvent, EffectGraphTickableEvent,
 };
 use executor::EffectGraphExecutorPlugin;
 use graph_map::{EffectGraphBuilderMap, EffectGraphMap};
+use pool::EffectGraphPool;
 use state::{update_to_despawn_effect_graph, EffectGraphState, EffectGraphTickState};
+use template::EffectGraphTemplates;
 
 use self::state::reset_effect_graph_state;
 
+pub mod asset;
 pub mod blackboard;
 pub mod builder;
 pub mod bundle;
 pub mod context;
+pub mod debugger;
 pub mod event;
 pub mod executor;
 pub mod graph_map;
 pub mod pin;
 pub mod node;
+pub mod pool;
 pub mod state;
+pub mod template;
+pub mod validate;
 
 #[derive(Debug, Default)]
 pub struct EffectGraphPlugin;
@@ -45,8 +51,8 @@ impl Plugin for EffectGraphPlugin {
             .init_resource::<InstantEffectNodeMap>()
             .init_resource::<EffectGraphMap>()
             .init_resource::<EffectGraphBuilderMap>()
-            .add_event::<CloneEffectGraphStartEvent>()
-            .add_event::<CloneEffectGraphEndEvent>()
+            .init_resource::<EffectGraphPool>()
+            .init_resource::<EffectGraphTemplates>()
             .add_event::<EffectGraphAddEvent>()
             .add_event::<EffectGraphExecEvent>()
             .add_event::<EffectGraphRemoveEvent>()
@@ -56,8 +62,6 @@ impl Plugin for EffectGraphPlugin {
                 reset_effect_graph_state.in_set(EffectGraphUpdateSystemSet::UpdateState),
             )
             .add_systems(Last, update_to_despawn_effect_graph)
-            .add_observer(trigger_clone_effect_graph_start)
-            .add_observer(trigger_clone_effect_graph_end)
             .add_observer(trigger_effect_graph_add)
             .add_observer(trigger_effect_graph_exec)
             .add_observer(trigger_effect_graph_tickable)
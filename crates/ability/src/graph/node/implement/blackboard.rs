@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        blackboard::EffectValue,
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeBlackboardPlugin;
+
+impl Plugin for EffectNodeBlackboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeGetBlackboard>()
+            .register_type::<EffectNodeSetBlackboard>();
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 读取图黑板中的值。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeGetBlackboard {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeGetBlackboard {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeGetBlackboard,
+    input => (
+        start => (key: String)
+    )
+    output => (
+        finish => (value: EffectValue)
+    )
+);
+
+impl EffectNode for EffectNodeGetBlackboard {}
+
+impl InstantEffectNode for EffectNodeGetBlackboard {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, _commands: &mut Commands) {
+        let Some(key) = context.get_input_value_type_from_node::<String>(
+            self.get_uuid(),
+            self,
+            EffectNodeGetBlackboard::INPUT_SLOT_KEY,
+        ) else {
+            return;
+        };
+
+        match context.blackboard.get_value(key.clone()).cloned() {
+            Some(value) => {
+                context.insert_output_value_from_node(
+                    self.get_uuid(),
+                    self,
+                    EffectNodeGetBlackboard::OUTPUT_SLOT_VALUE,
+                    value,
+                );
+            }
+            None => {
+                warn!("blackboard key not found: {}", key);
+            }
+        }
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeGetBlackboard::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+/// 写入值到图黑板中。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeSetBlackboard {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeSetBlackboard {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeSetBlackboard,
+    input => (
+        start => (key: String, value: EffectValue)
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodeSetBlackboard {}
+
+impl InstantEffectNode for EffectNodeSetBlackboard {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, _commands: &mut Commands) {
+        let key = context.get_input_value_type_from_node::<String>(
+            self.get_uuid(),
+            self,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+        );
+        let value = context
+            .get_input_value_type_from_node::<&EffectValue>(
+                self.get_uuid(),
+                self,
+                EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+            )
+            .cloned();
+
+        if let (Some(key), Some(value)) = (key, value) {
+            context.blackboard.set_value(key, value);
+        }
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeSetBlackboard::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::test_graph::{blackboard_value, new_test_app, TestGraph},
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectNodeGetBlackboard, EffectNodeSetBlackboard};
+
+    #[test]
+    fn get_and_set_blackboard() {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+        graph
+            .context
+            .blackboard
+            .set_value("hp", EffectValue::F32(30.0));
+
+        let get = EffectNodeGetBlackboard::new();
+        let set = EffectNodeSetBlackboard::new();
+        graph.set_input(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("hp".into()),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("hp_copy".into()),
+        );
+        graph.connect_entry(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::OUTPUT_EXEC_FINISH,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            get.get_uuid(),
+            &get,
+            EffectNodeGetBlackboard::OUTPUT_SLOT_VALUE,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.add_instant_node(&mut app, get);
+        graph.add_instant_node(&mut app, set);
+
+        let graph_entity = graph.run(&mut app);
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "hp_copy"),
+            Some(EffectValue::F32(30.0))
+        );
+    }
+}
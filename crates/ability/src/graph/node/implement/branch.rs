@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeBranchPlugin;
+
+impl Plugin for EffectNodeBranchPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeBranch>();
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 根据条件选择执行的输出pin，条件不存在时视为false。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeBranch {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeBranch {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeBranch,
+    input => (
+        start => (condition: bool)
+    )
+    output => (
+        on_true => (),
+        on_false => ()
+    )
+);
+
+impl EffectNode for EffectNodeBranch {}
+
+impl InstantEffectNode for EffectNodeBranch {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, _context: &mut EffectGraphContext, _commands: &mut Commands) {
+        trace!(
+            "node {} execute: {:?}",
+            std::any::type_name::<EffectNodeBranch>(),
+            self.get_uuid(),
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        let condition = context
+            .get_input_value_type_from_node::<&bool>(
+                self.get_uuid(),
+                self,
+                EffectNodeBranch::INPUT_SLOT_CONDITION,
+            )
+            .copied()
+            .unwrap_or_default();
+
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            if condition {
+                EffectNodeBranch::OUTPUT_EXEC_ON_TRUE
+            } else {
+                EffectNodeBranch::OUTPUT_EXEC_ON_FALSE
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::EffectNodeSetBlackboard,
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::EffectNodeBranch;
+
+    fn run_branch(condition: bool) -> (App, Entity) {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let branch = EffectNodeBranch::new();
+        let on_true = EffectNodeSetBlackboard::new();
+        let on_false = EffectNodeSetBlackboard::new();
+
+        graph.set_input(
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::INPUT_SLOT_CONDITION,
+            EffectValue::Bool(condition),
+        );
+        for (node, key) in [(&on_true, "on_true"), (&on_false, "on_false")] {
+            graph.set_input(
+                node.get_uuid(),
+                node,
+                EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+                EffectValue::String(key.into()),
+            );
+            graph.set_input(
+                node.get_uuid(),
+                node,
+                EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+                EffectValue::Bool(true),
+            );
+        }
+
+        graph.connect_entry(
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::OUTPUT_EXEC_ON_TRUE,
+            on_true.get_uuid(),
+            &on_true,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::OUTPUT_EXEC_ON_FALSE,
+            on_false.get_uuid(),
+            &on_false,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, branch);
+        graph.add_instant_node(&mut app, on_true);
+        graph.add_instant_node(&mut app, on_false);
+
+        let graph_entity = graph.run(&mut app);
+        (app, graph_entity)
+    }
+
+    #[test]
+    fn branch_on_true() {
+        let (app, graph) = run_branch(true);
+        assert_eq!(
+            blackboard_value(&app, graph, "on_true"),
+            Some(EffectValue::Bool(true))
+        );
+        assert_eq!(blackboard_value(&app, graph, "on_false"), None);
+    }
+
+    #[test]
+    fn branch_on_false() {
+        let (app, graph) = run_branch(false);
+        assert_eq!(blackboard_value(&app, graph, "on_true"), None);
+        assert_eq!(
+            blackboard_value(&app, graph, "on_false"),
+            Some(EffectValue::Bool(true))
+        );
+    }
+}
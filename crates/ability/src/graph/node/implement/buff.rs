@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    ability::target::blackboard_target,
+    buff::event::{BuffAddEvent, BuffRemoveFromOwnerEvent},
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeBuffPlugin;
+
+impl Plugin for EffectNodeBuffPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeAddBuff>()
+            .register_type::<EffectNodeRemoveBuff>();
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 给target添加buff，已经存在时增加层数。target没有输入时，使用黑板中的技能目标。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeAddBuff {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeAddBuff {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeAddBuff,
+    input => (
+        start => (target: Entity, buff_id: i32)
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodeAddBuff {}
+
+impl InstantEffectNode for EffectNodeAddBuff {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands) {
+        // 客户端预测时由服务器执行
+        if context.predicted {
+            return;
+        }
+
+        let target = context
+            .get_input_value_type_from_node::<&Entity>(
+                self.get_uuid(),
+                self,
+                EffectNodeAddBuff::INPUT_SLOT_TARGET,
+            )
+            .copied()
+            .or_else(|| blackboard_target(context));
+        let buff_id = context.get_input_value_type_from_node::<&i32>(
+            self.get_uuid(),
+            self,
+            EffectNodeAddBuff::INPUT_SLOT_BUFF_ID,
+        );
+
+        let (Some(target), Some(buff_id)) = (target, buff_id) else {
+            warn!("add buff node {:?} missing input", self.get_uuid());
+            return;
+        };
+
+        commands.trigger(BuffAddEvent {
+            owner_entity: target,
+            buff_id: *buff_id,
+        });
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeAddBuff::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+/// 移除target上的buff。target没有输入时，使用黑板中的技能目标。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeRemoveBuff {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeRemoveBuff {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeRemoveBuff,
+    input => (
+        start => (target: Entity, buff_id: i32)
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodeRemoveBuff {}
+
+impl InstantEffectNode for EffectNodeRemoveBuff {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands) {
+        // 客户端预测时由服务器执行
+        if context.predicted {
+            return;
+        }
+
+        let target = context
+            .get_input_value_type_from_node::<&Entity>(
+                self.get_uuid(),
+                self,
+                EffectNodeRemoveBuff::INPUT_SLOT_TARGET,
+            )
+            .copied()
+            .or_else(|| blackboard_target(context));
+        let buff_id = context.get_input_value_type_from_node::<&i32>(
+            self.get_uuid(),
+            self,
+            EffectNodeRemoveBuff::INPUT_SLOT_BUFF_ID,
+        );
+
+        let (Some(target), Some(buff_id)) = (target, buff_id) else {
+            warn!("remove buff node {:?} missing input", self.get_uuid());
+            return;
+        };
+
+        commands.trigger(BuffRemoveFromOwnerEvent {
+            owner_entity: target,
+            buff_id: *buff_id,
+        });
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeRemoveBuff::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::{
+        buff::event::{BuffAddEvent, BuffRemoveFromOwnerEvent},
+        graph::{
+            blackboard::EffectValue,
+            node::{
+                implement::test_graph::{new_test_app, TestGraph},
+                InstantEffectNode,
+            },
+        },
+    };
+
+    use super::{EffectNodeAddBuff, EffectNodeRemoveBuff};
+
+    #[derive(Debug, Default, Resource)]
+    struct ReceivedBuffEvents {
+        added: Vec<(Entity, i32)>,
+        removed: Vec<(Entity, i32)>,
+    }
+
+    #[test]
+    fn add_then_remove_buff() {
+        let mut app = new_test_app();
+        app.init_resource::<ReceivedBuffEvents>()
+            .add_observer(
+                |trigger: Trigger<BuffAddEvent>, mut received: ResMut<ReceivedBuffEvents>| {
+                    let event = trigger.event();
+                    received.added.push((event.owner_entity, event.buff_id));
+                },
+            )
+            .add_observer(
+                |trigger: Trigger<BuffRemoveFromOwnerEvent>,
+                 mut received: ResMut<ReceivedBuffEvents>| {
+                    let event = trigger.event();
+                    received.removed.push((event.owner_entity, event.buff_id));
+                },
+            );
+        let mut graph = TestGraph::new(&mut app);
+
+        let target = app.world_mut().spawn_empty().id();
+        let add = EffectNodeAddBuff::new();
+        let remove = EffectNodeRemoveBuff::new();
+        graph.set_input(
+            add.get_uuid(),
+            &add,
+            EffectNodeAddBuff::INPUT_SLOT_TARGET,
+            EffectValue::Entity(target),
+        );
+        graph.set_input(
+            add.get_uuid(),
+            &add,
+            EffectNodeAddBuff::INPUT_SLOT_BUFF_ID,
+            EffectValue::I32(1001),
+        );
+        graph.set_input(
+            remove.get_uuid(),
+            &remove,
+            EffectNodeRemoveBuff::INPUT_SLOT_TARGET,
+            EffectValue::Entity(target),
+        );
+        graph.set_input(
+            remove.get_uuid(),
+            &remove,
+            EffectNodeRemoveBuff::INPUT_SLOT_BUFF_ID,
+            EffectValue::I32(1002),
+        );
+        graph.connect_entry(add.get_uuid(), &add, EffectNodeAddBuff::INPUT_EXEC_START);
+        graph.connect_exec(
+            add.get_uuid(),
+            &add,
+            EffectNodeAddBuff::OUTPUT_EXEC_FINISH,
+            remove.get_uuid(),
+            &remove,
+            EffectNodeRemoveBuff::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, add);
+        graph.add_instant_node(&mut app, remove);
+
+        graph.run(&mut app);
+        let received = app.world().resource::<ReceivedBuffEvents>();
+        assert_eq!(received.added, vec![(target, 1001)]);
+        assert_eq!(received.removed, vec![(target, 1002)]);
+    }
+}
@@ -0,0 +1,261 @@
+use std::cmp::Ordering;
+
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        blackboard::EffectValue,
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeComparePlugin;
+
+impl Plugin for EffectNodeComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeCompare>()
+            .register_type::<EffectNodeCompareOp>();
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum EffectNodeCompareOp {
+    #[default]
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+impl EffectNodeCompareOp {
+    /// 数值之间按照f64比较，其他类型只支持相等和不相等。
+    pub fn compare(&self, lhs: &EffectValue, rhs: &EffectValue) -> Option<bool> {
+        let ordering = match (lhs.as_f64(), rhs.as_f64()) {
+            (Some(a), Some(b)) => a.partial_cmp(&b),
+            _ => match self {
+                EffectNodeCompareOp::Equal => return Some(lhs == rhs),
+                EffectNodeCompareOp::NotEqual => return Some(lhs != rhs),
+                _ => None,
+            },
+        }?;
+
+        Some(match self {
+            EffectNodeCompareOp::Equal => ordering == Ordering::Equal,
+            EffectNodeCompareOp::NotEqual => ordering != Ordering::Equal,
+            EffectNodeCompareOp::Less => ordering == Ordering::Less,
+            EffectNodeCompareOp::LessEqual => ordering != Ordering::Greater,
+            EffectNodeCompareOp::Greater => ordering == Ordering::Greater,
+            EffectNodeCompareOp::GreaterEqual => ordering != Ordering::Less,
+        })
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeCompare {
+    pub base: InstantEffectNodeBase,
+    pub op: EffectNodeCompareOp,
+}
+
+impl EffectNodeCompare {
+    pub fn new(op: EffectNodeCompareOp) -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+            op,
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeCompare,
+    input => (
+        start => (a: EffectValue, b: EffectValue)
+    )
+    output => (
+        finish => (result: bool)
+    )
+);
+
+impl EffectNode for EffectNodeCompare {}
+
+impl InstantEffectNode for EffectNodeCompare {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, _commands: &mut Commands) {
+        let a = context.get_input_value_type_from_node::<&EffectValue>(
+            self.get_uuid(),
+            self,
+            EffectNodeCompare::INPUT_SLOT_A,
+        );
+        let b = context.get_input_value_type_from_node::<&EffectValue>(
+            self.get_uuid(),
+            self,
+            EffectNodeCompare::INPUT_SLOT_B,
+        );
+
+        let result = match (a, b) {
+            (Some(a), Some(b)) => match self.op.compare(a, b) {
+                Some(result) => result,
+                None => {
+                    error!("can not compare {:?} {:?} {:?}", a, self.op, b);
+                    false
+                }
+            },
+            _ => false,
+        };
+
+        context.insert_output_value_from_node(
+            self.get_uuid(),
+            self,
+            EffectNodeCompare::OUTPUT_SLOT_RESULT,
+            EffectValue::Bool(result),
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeCompare::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::EffectNodeSetBlackboard,
+                branch::EffectNodeBranch,
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectNodeCompare, EffectNodeCompareOp};
+
+    #[test]
+    fn compare_op() {
+        let op = EffectNodeCompareOp::Less;
+        assert_eq!(
+            op.compare(&EffectValue::I32(1), &EffectValue::F32(1.5)),
+            Some(true)
+        );
+        assert_eq!(
+            op.compare(&EffectValue::F64(2.0), &EffectValue::U8(2)),
+            Some(false)
+        );
+        assert_eq!(
+            op.compare(&EffectValue::String("a".into()), &EffectValue::I32(1)),
+            None
+        );
+
+        let op = EffectNodeCompareOp::Equal;
+        assert_eq!(
+            op.compare(
+                &EffectValue::String("a".into()),
+                &EffectValue::String("a".into())
+            ),
+            Some(true)
+        );
+    }
+
+    /// if target hp < 30% do X
+    #[test]
+    fn compare_then_branch() {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let compare = EffectNodeCompare::new(EffectNodeCompareOp::Less);
+        let branch = EffectNodeBranch::new();
+        let set = EffectNodeSetBlackboard::new();
+
+        graph.set_input(
+            compare.get_uuid(),
+            &compare,
+            EffectNodeCompare::INPUT_SLOT_A,
+            EffectValue::F32(0.2),
+        );
+        graph.set_input(
+            compare.get_uuid(),
+            &compare,
+            EffectNodeCompare::INPUT_SLOT_B,
+            EffectValue::F32(0.3),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("low_hp".into()),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+            EffectValue::Bool(true),
+        );
+
+        graph.connect_entry(
+            compare.get_uuid(),
+            &compare,
+            EffectNodeCompare::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            compare.get_uuid(),
+            &compare,
+            EffectNodeCompare::OUTPUT_EXEC_FINISH,
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            compare.get_uuid(),
+            &compare,
+            EffectNodeCompare::OUTPUT_SLOT_RESULT,
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::INPUT_SLOT_CONDITION,
+        );
+        graph.connect_exec(
+            branch.get_uuid(),
+            &branch,
+            EffectNodeBranch::OUTPUT_EXEC_ON_TRUE,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, compare);
+        graph.add_instant_node(&mut app, branch);
+        graph.add_instant_node(&mut app, set);
+
+        let graph_entity = graph.run(&mut app);
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "low_hp"),
+            Some(EffectValue::Bool(true))
+        );
+    }
+}
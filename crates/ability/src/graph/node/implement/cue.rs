@@ -0,0 +1,176 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeCuePlugin;
+
+impl Plugin for EffectNodeCuePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodePlayCue>()
+            .add_event::<EffectPlayCueEvent>();
+    }
+}
+
+/// 在target上播放表现(特效，音效等)，不影响逻辑，由GameplayCuePlugin转换为GameplayCueEvent。
+#[derive(Debug, Event, Clone, PartialEq)]
+pub struct EffectPlayCueEvent {
+    /// 表现的LayerTag，例如Cue.Hit.Fire。
+    pub cue: Cow<'static, str>,
+    pub magnitude: f32,
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// magnitude没有输入时为0。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodePlayCue {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodePlayCue {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodePlayCue,
+    input => (
+        start => (target: Entity, cue: String, magnitude: f32)
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodePlayCue {}
+
+impl InstantEffectNode for EffectNodePlayCue {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands) {
+        let target = context.get_input_value_type_from_node::<&Entity>(
+            self.get_uuid(),
+            self,
+            EffectNodePlayCue::INPUT_SLOT_TARGET,
+        );
+        let cue = context.get_input_value_type_from_node::<String>(
+            self.get_uuid(),
+            self,
+            EffectNodePlayCue::INPUT_SLOT_CUE,
+        );
+        let magnitude = context
+            .get_input_value_type_from_node::<&f32>(
+                self.get_uuid(),
+                self,
+                EffectNodePlayCue::INPUT_SLOT_MAGNITUDE,
+            )
+            .copied()
+            .unwrap_or_default();
+
+        let (Some(target), Some(cue)) = (target, cue) else {
+            warn!("play cue node {:?} missing input", self.get_uuid());
+            return;
+        };
+
+        commands.trigger_targets(
+            EffectPlayCueEvent {
+                cue: cue.into(),
+                magnitude,
+            },
+            *target,
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodePlayCue::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::test_graph::{new_test_app, TestGraph},
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectNodePlayCue, EffectPlayCueEvent};
+
+    #[derive(Debug, Default, Resource)]
+    struct ReceivedCues(Vec<(Entity, EffectPlayCueEvent)>);
+
+    #[test]
+    fn play_cue() {
+        let mut app = new_test_app();
+        app.init_resource::<ReceivedCues>().add_observer(
+            |trigger: Trigger<EffectPlayCueEvent>, mut received: ResMut<ReceivedCues>| {
+                received.0.push((trigger.entity(), trigger.event().clone()));
+            },
+        );
+        let mut graph = TestGraph::new(&mut app);
+
+        let target = app.world_mut().spawn_empty().id();
+        let cue = EffectNodePlayCue::new();
+        graph.set_input(
+            cue.get_uuid(),
+            &cue,
+            EffectNodePlayCue::INPUT_SLOT_TARGET,
+            EffectValue::Entity(target),
+        );
+        graph.set_input(
+            cue.get_uuid(),
+            &cue,
+            EffectNodePlayCue::INPUT_SLOT_CUE,
+            EffectValue::String("Cue.Hit".into()),
+        );
+        graph.connect_entry(cue.get_uuid(), &cue, EffectNodePlayCue::INPUT_EXEC_START);
+        graph.add_instant_node(&mut app, cue);
+
+        graph.run(&mut app);
+        assert_eq!(
+            app.world().resource::<ReceivedCues>().0,
+            vec![(
+                target,
+                EffectPlayCueEvent {
+                    cue: "Cue.Hit".into(),
+                    magnitude: 0.0,
+                }
+            )]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+use datatables::damage::TbDamageTypeKey;
+use uuid::Uuid;
+
+use crate::{
+    ability::target::blackboard_target,
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeDamagePlugin;
+
+impl Plugin for EffectNodeDamagePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeApplyDamage>()
+            .add_event::<EffectApplyDamageEvent>();
+    }
+}
+
+/// 对target造成伤害，由游戏层监听并修改属性。
+#[derive(Debug, Event, Clone, Copy, PartialEq)]
+pub struct EffectApplyDamageEvent {
+    pub instigator: Entity,
+    pub damage: f32,
+    /// 伤害类型，None时不计算减免和暴击。
+    pub damage_type: Option<TbDamageTypeKey>,
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// target没有输入时，使用黑板中的技能目标，damage_type没有输入时没有伤害类型。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeApplyDamage {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeApplyDamage {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeApplyDamage,
+    input => (
+        start => (instigator: Entity, target: Entity, damage: f32, damage_type: i32)
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodeApplyDamage {}
+
+impl InstantEffectNode for EffectNodeApplyDamage {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands) {
+        // 客户端预测时由服务器执行
+        if context.predicted {
+            return;
+        }
+
+        let instigator = context.get_input_value_type_from_node::<&Entity>(
+            self.get_uuid(),
+            self,
+            EffectNodeApplyDamage::INPUT_SLOT_INSTIGATOR,
+        );
+        let target = context
+            .get_input_value_type_from_node::<&Entity>(
+                self.get_uuid(),
+                self,
+                EffectNodeApplyDamage::INPUT_SLOT_TARGET,
+            )
+            .copied()
+            .or_else(|| blackboard_target(context));
+        let damage = context.get_input_value_type_from_node::<&f32>(
+            self.get_uuid(),
+            self,
+            EffectNodeApplyDamage::INPUT_SLOT_DAMAGE,
+        );
+        let damage_type = context
+            .get_input_value_type_from_node::<&TbDamageTypeKey>(
+                self.get_uuid(),
+                self,
+                EffectNodeApplyDamage::INPUT_SLOT_DAMAGE_TYPE,
+            )
+            .copied();
+
+        let (Some(instigator), Some(target), Some(damage)) = (instigator, target, damage) else {
+            warn!("apply damage node {:?} missing input", self.get_uuid());
+            return;
+        };
+
+        commands.trigger_targets(
+            EffectApplyDamageEvent {
+                instigator: *instigator,
+                damage: *damage,
+                damage_type,
+            },
+            target,
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeApplyDamage::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::test_graph::{new_test_app, TestGraph},
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectApplyDamageEvent, EffectNodeApplyDamage};
+
+    #[derive(Debug, Default, Resource)]
+    struct ReceivedDamage(Vec<(Entity, EffectApplyDamageEvent)>);
+
+    fn run_damage_graph(predicted: bool) -> (App, Entity, Entity) {
+        let mut app = new_test_app();
+        app.init_resource::<ReceivedDamage>().add_observer(
+            |trigger: Trigger<EffectApplyDamageEvent>, mut received: ResMut<ReceivedDamage>| {
+                received.0.push((trigger.entity(), *trigger.event()));
+            },
+        );
+        let mut graph = TestGraph::new(&mut app);
+
+        let target = app.world_mut().spawn_empty().id();
+        let damage = EffectNodeApplyDamage::new();
+        graph.set_input(
+            damage.get_uuid(),
+            &damage,
+            EffectNodeApplyDamage::INPUT_SLOT_INSTIGATOR,
+            EffectValue::Entity(graph.owner),
+        );
+        graph.set_input(
+            damage.get_uuid(),
+            &damage,
+            EffectNodeApplyDamage::INPUT_SLOT_TARGET,
+            EffectValue::Entity(target),
+        );
+        graph.set_input(
+            damage.get_uuid(),
+            &damage,
+            EffectNodeApplyDamage::INPUT_SLOT_DAMAGE,
+            EffectValue::F32(12.0),
+        );
+        graph.set_input(
+            damage.get_uuid(),
+            &damage,
+            EffectNodeApplyDamage::INPUT_SLOT_DAMAGE_TYPE,
+            EffectValue::I32(2),
+        );
+        graph.connect_entry(
+            damage.get_uuid(),
+            &damage,
+            EffectNodeApplyDamage::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, damage);
+        graph.context.predicted = predicted;
+
+        let owner = graph.owner;
+        graph.run(&mut app);
+        (app, owner, target)
+    }
+
+    #[test]
+    fn apply_damage() {
+        let (app, owner, target) = run_damage_graph(false);
+        assert_eq!(
+            app.world().resource::<ReceivedDamage>().0,
+            vec![(
+                target,
+                EffectApplyDamageEvent {
+                    instigator: owner,
+                    damage: 12.0,
+                    damage_type: Some(2),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn skip_damage_when_predicted() {
+        let (app, _, _) = run_damage_graph(true);
+        assert!(app.world().resource::<ReceivedDamage>().0.is_empty());
+    }
+}
@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        blackboard::EffectValue,
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeForEachPlugin;
+
+impl Plugin for EffectNodeForEachPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeForEach>();
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 遍历实体列表，每个元素执行一次loop_body，全部执行完毕后执行completed。
+///
+/// NOTE: loop_body后续的瞬时节点会在下一个元素之前执行完毕，
+/// 但是状态节点是通过事件触发的，读取到的element是最后一个元素的值。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeForEach {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeForEach {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeForEach,
+    input => (
+        start => (entities: Vec<Entity>)
+    )
+    output => (
+        loop_body => (element: Entity, index: i32),
+        completed => ()
+    )
+);
+
+impl EffectNode for EffectNodeForEach {}
+
+impl InstantEffectNode for EffectNodeForEach {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, _context: &mut EffectGraphContext, _commands: &mut Commands) {
+        trace!(
+            "node {} execute: {:?}",
+            std::any::type_name::<EffectNodeForEach>(),
+            self.get_uuid(),
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        let entities = context
+            .get_input_value_type_from_node::<&Vec<Entity>>(
+                self.get_uuid(),
+                self,
+                EffectNodeForEach::INPUT_SLOT_ENTITIES,
+            )
+            .cloned()
+            .unwrap_or_default();
+
+        for (index, entity) in entities.into_iter().enumerate() {
+            executor.continue_push_next_node_output_pin_with_values(
+                self.get_uuid().into(),
+                self,
+                EffectNodeForEach::OUTPUT_EXEC_LOOP_BODY,
+                vec![
+                    (
+                        EffectNodeForEach::OUTPUT_SLOT_ELEMENT,
+                        EffectValue::Entity(entity),
+                    ),
+                    (
+                        EffectNodeForEach::OUTPUT_SLOT_INDEX,
+                        EffectValue::I32(index as i32),
+                    ),
+                ],
+            );
+        }
+
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeForEach::OUTPUT_EXEC_COMPLETED,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::{EffectNodeGetBlackboard, EffectNodeSetBlackboard},
+                math::{EffectNodeMath, EffectNodeMathOp},
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::EffectNodeForEach;
+
+    /// sum = sum + index for every element, then copy the last element.
+    #[test]
+    fn for_each_entities() {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let entities = vec![
+            app.world_mut().spawn_empty().id(),
+            app.world_mut().spawn_empty().id(),
+            app.world_mut().spawn_empty().id(),
+        ];
+        graph
+            .context
+            .blackboard
+            .set_value("sum", EffectValue::I32(0));
+
+        let for_each = EffectNodeForEach::new();
+        let get_sum = EffectNodeGetBlackboard::new();
+        let add = EffectNodeMath::new(EffectNodeMathOp::Add);
+        let set_sum = EffectNodeSetBlackboard::new();
+        let set_last = EffectNodeSetBlackboard::new();
+
+        graph.set_input(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::INPUT_SLOT_ENTITIES,
+            EffectValue::VecEntity(entities.clone()),
+        );
+        graph.set_input(
+            get_sum.get_uuid(),
+            &get_sum,
+            EffectNodeGetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("sum".into()),
+        );
+        graph.set_input(
+            set_sum.get_uuid(),
+            &set_sum,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("sum".into()),
+        );
+        graph.set_input(
+            set_last.get_uuid(),
+            &set_last,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("last".into()),
+        );
+
+        graph.connect_entry(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::OUTPUT_EXEC_LOOP_BODY,
+            get_sum.get_uuid(),
+            &get_sum,
+            EffectNodeGetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            get_sum.get_uuid(),
+            &get_sum,
+            EffectNodeGetBlackboard::OUTPUT_EXEC_FINISH,
+            add.get_uuid(),
+            &add,
+            EffectNodeMath::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            add.get_uuid(),
+            &add,
+            EffectNodeMath::OUTPUT_EXEC_FINISH,
+            set_sum.get_uuid(),
+            &set_sum,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::OUTPUT_EXEC_COMPLETED,
+            set_last.get_uuid(),
+            &set_last,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            get_sum.get_uuid(),
+            &get_sum,
+            EffectNodeGetBlackboard::OUTPUT_SLOT_VALUE,
+            add.get_uuid(),
+            &add,
+            EffectNodeMath::INPUT_SLOT_A,
+        );
+        graph.connect_slot(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::OUTPUT_SLOT_INDEX,
+            add.get_uuid(),
+            &add,
+            EffectNodeMath::INPUT_SLOT_B,
+        );
+        graph.connect_slot(
+            add.get_uuid(),
+            &add,
+            EffectNodeMath::OUTPUT_SLOT_RESULT,
+            set_sum.get_uuid(),
+            &set_sum,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.connect_slot(
+            for_each.get_uuid(),
+            &for_each,
+            EffectNodeForEach::OUTPUT_SLOT_ELEMENT,
+            set_last.get_uuid(),
+            &set_last,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.add_instant_node(&mut app, for_each);
+        graph.add_instant_node(&mut app, get_sum);
+        graph.add_instant_node(&mut app, add);
+        graph.add_instant_node(&mut app, set_sum);
+        graph.add_instant_node(&mut app, set_last);
+
+        let graph_entity: Entity = graph.run(&mut app);
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "sum"),
+            Some(EffectValue::I32(3))
+        );
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "last"),
+            Some(EffectValue::Entity(entities[2]))
+        );
+    }
+}
@@ -8,8 +8,8 @@ use crate::{
         context::EffectGraphContext,
         node::{
             bundle::InstantEffectNodeBase,
-            pin::{EffectNodeExec, EffectNodePinGroup},
-            EffectNode, EffectNodeId, InstantEffectNode,
+            pin::{EffectNodeExec, EffectNodePinGroup, ReflectEffectNodePinGroup},
+            EffectNode, EffectNodeId, InstantEffectNode, ReflectInstantEffectNode,
         },
         pin::EffectNodeSlotPin,
     },
@@ -28,6 +28,7 @@ impl Plugin for EffectNodeLogPlugin {
 ///////////////////////// Node Component /////////////////////////
 
 #[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
 pub struct EffectNodeLog {
     pub base: InstantEffectNodeBase,
 }
@@ -58,7 +59,7 @@ impl InstantEffectNode for EffectNodeLog {
 
     fn collect(&self, _context: &mut EffectGraphContext) {}
 
-    fn execute(&self, context: &mut EffectGraphContext) {
+    fn execute(&self, context: &mut EffectGraphContext, _commands: &mut Commands) {
         info!(
             "node {} execute: {:?}",
             std::any::type_name::<EffectNodeLog>(),
@@ -84,17 +85,14 @@ impl InstantEffectNode for EffectNodeLog {
 
     fn push_execute_chain(
         &self,
-        context: &EffectGraphContext,
+        _context: &EffectGraphContext,
         executor: &mut crate::graph::executor::EffectGraphExecutor,
         _input_exec_pin: EffectNodeExec,
-        instant_nodes: &Res<crate::graph::context::InstantEffectNodeMap>,
     ) {
         executor.continue_push_next_node_output_pin_from_node_name(
             self.get_uuid().into(),
             self,
             EffectNodeLog::OUTPUT_EXEC_FINISH,
-            context,
-            instant_nodes,
         );
     }
 }
@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        blackboard::EffectValue,
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeMathPlugin;
+
+impl Plugin for EffectNodeMathPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeMath>()
+            .register_type::<EffectNodeMathOp>();
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Reflect)]
+pub enum EffectNodeMathOp {
+    #[default]
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+}
+
+impl EffectNodeMathOp {
+    /// 相同类型的值计算结果保持原类型，溢出时返回None。不同的数值类型按照f64计算。
+    pub fn apply(&self, lhs: &EffectValue, rhs: &EffectValue) -> Option<EffectValue> {
+        macro_rules! same_int_variant {
+            ($($variant:ident: $ty:ty),*) => {
+                match (lhs, rhs) {
+                    $(
+                        (EffectValue::$variant(a), EffectValue::$variant(b)) => {
+                            return self
+                                .apply_i64(i64::from(*a), i64::from(*b))
+                                .and_then(|v| <$ty>::try_from(v).ok())
+                                .map(EffectValue::$variant);
+                        }
+                    )*
+                    (EffectValue::I64(a), EffectValue::I64(b)) => {
+                        return self.apply_i64(*a, *b).map(EffectValue::I64);
+                    }
+                    _ => {}
+                }
+            };
+        }
+
+        same_int_variant!(I8: i8, I16: i16, I32: i32, U8: u8, U16: u16, U32: u32);
+
+        if let (EffectValue::F32(a), EffectValue::F32(b)) = (lhs, rhs) {
+            return Some(EffectValue::F32(
+                self.apply_f64(f64::from(*a), f64::from(*b)) as f32,
+            ));
+        }
+
+        Some(EffectValue::F64(
+            self.apply_f64(lhs.as_f64()?, rhs.as_f64()?),
+        ))
+    }
+
+    fn apply_i64(&self, a: i64, b: i64) -> Option<i64> {
+        match self {
+            EffectNodeMathOp::Add => a.checked_add(b),
+            EffectNodeMathOp::Sub => a.checked_sub(b),
+            EffectNodeMathOp::Mul => a.checked_mul(b),
+            EffectNodeMathOp::Div => a.checked_div(b),
+            EffectNodeMathOp::Min => Some(a.min(b)),
+            EffectNodeMathOp::Max => Some(a.max(b)),
+        }
+    }
+
+    fn apply_f64(&self, a: f64, b: f64) -> f64 {
+        match self {
+            EffectNodeMathOp::Add => a + b,
+            EffectNodeMathOp::Sub => a - b,
+            EffectNodeMathOp::Mul => a * b,
+            EffectNodeMathOp::Div => a / b,
+            EffectNodeMathOp::Min => a.min(b),
+            EffectNodeMathOp::Max => a.max(b),
+        }
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeMath {
+    pub base: InstantEffectNodeBase,
+    pub op: EffectNodeMathOp,
+}
+
+impl EffectNodeMath {
+    pub fn new(op: EffectNodeMathOp) -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+            op,
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeMath,
+    input => (
+        start => (a: EffectValue, b: EffectValue)
+    )
+    output => (
+        finish => (result: EffectValue)
+    )
+);
+
+impl EffectNode for EffectNodeMath {}
+
+impl InstantEffectNode for EffectNodeMath {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, _commands: &mut Commands) {
+        let a = context.get_input_value_type_from_node::<&EffectValue>(
+            self.get_uuid(),
+            self,
+            EffectNodeMath::INPUT_SLOT_A,
+        );
+        let b = context.get_input_value_type_from_node::<&EffectValue>(
+            self.get_uuid(),
+            self,
+            EffectNodeMath::INPUT_SLOT_B,
+        );
+
+        let (Some(a), Some(b)) = (a, b) else {
+            return;
+        };
+
+        match self.op.apply(a, b) {
+            Some(result) => {
+                context.insert_output_value_from_node(
+                    self.get_uuid(),
+                    self,
+                    EffectNodeMath::OUTPUT_SLOT_RESULT,
+                    result,
+                );
+            }
+            None => {
+                error!("can not apply {:?} to {:?} and {:?}", self.op, a, b);
+            }
+        }
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeMath::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::EffectNodeSetBlackboard,
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectNodeMath, EffectNodeMathOp};
+
+    #[test]
+    fn math_op() {
+        assert_eq!(
+            EffectNodeMathOp::Add.apply(&EffectValue::I32(1), &EffectValue::I32(2)),
+            Some(EffectValue::I32(3))
+        );
+        assert_eq!(
+            EffectNodeMathOp::Mul.apply(&EffectValue::F32(1.5), &EffectValue::F32(2.0)),
+            Some(EffectValue::F32(3.0))
+        );
+        assert_eq!(
+            EffectNodeMathOp::Sub.apply(&EffectValue::I32(1), &EffectValue::F64(0.5)),
+            Some(EffectValue::F64(0.5))
+        );
+        assert_eq!(
+            EffectNodeMathOp::Div.apply(&EffectValue::I32(1), &EffectValue::I32(0)),
+            None
+        );
+        assert_eq!(
+            EffectNodeMathOp::Max.apply(&EffectValue::Bool(true), &EffectValue::I32(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn math_node() {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let math = EffectNodeMath::new(EffectNodeMathOp::Mul);
+        let set = EffectNodeSetBlackboard::new();
+
+        graph.set_input(
+            math.get_uuid(),
+            &math,
+            EffectNodeMath::INPUT_SLOT_A,
+            EffectValue::F32(100.0),
+        );
+        graph.set_input(
+            math.get_uuid(),
+            &math,
+            EffectNodeMath::INPUT_SLOT_B,
+            EffectValue::F32(0.5),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("damage".into()),
+        );
+
+        graph.connect_entry(math.get_uuid(), &math, EffectNodeMath::INPUT_EXEC_START);
+        graph.connect_exec(
+            math.get_uuid(),
+            &math,
+            EffectNodeMath::OUTPUT_EXEC_FINISH,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            math.get_uuid(),
+            &math,
+            EffectNodeMath::OUTPUT_SLOT_RESULT,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.add_instant_node(&mut app, math);
+        graph.add_instant_node(&mut app, set);
+
+        let graph_entity = graph.run(&mut app);
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "damage"),
+            Some(EffectValue::F32(50.0))
+        );
+    }
+}
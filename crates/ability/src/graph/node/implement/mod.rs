@@ -1,3 +1,250 @@
+pub mod blackboard;
+pub mod branch;
+pub mod buff;
+pub mod compare;
+pub mod cue;
+pub mod damage;
+pub mod for_each;
 pub mod log;
+pub mod math;
+pub mod projectile;
+pub mod random;
 pub mod seq;
 pub mod timer;
+pub mod wait_event;
+
+/// 在无窗口的App中运行effect graph。
+#[cfg(test)]
+pub(crate) mod test_graph {
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+
+    use super::{
+        blackboard::EffectNodeBlackboardPlugin, branch::EffectNodeBranchPlugin,
+        buff::EffectNodeBuffPlugin, compare::EffectNodeComparePlugin, cue::EffectNodeCuePlugin,
+        damage::EffectNodeDamagePlugin, for_each::EffectNodeForEachPlugin,
+        log::EffectNodeLogPlugin, math::EffectNodeMathPlugin,
+        projectile::EffectNodeProjectilePlugin, random::EffectNodeRandomPlugin,
+        seq::EffectNodeSeqPlugin, timer::EffectNodeTimerPlugin,
+        wait_event::EffectNodeWaitEventPlugin,
+    };
+    use crate::graph::{
+        blackboard::EffectValue,
+        context::{EffectGraphContext, InstantEffectNodeMap},
+        event::EffectGraphExecEvent,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::StateEffectNodeBundle, pin::EffectNodePinGroup, plugin::EffectNodePlugin,
+            EffectNodeId, InstantEffectNode, StateEffectNode,
+        },
+        pin::{EffectNodeExecPin, EffectNodeSlotPin},
+        state::{EffectGraphState, EffectGraphTickState},
+        EffectGraphOwner, EffectGraphPlugin,
+    };
+
+    pub(crate) const ENTRY_EXEC_START: &str = "start";
+
+    pub(crate) fn new_test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(EffectGraphPlugin)
+            .add_plugins(EffectNodePlugin)
+            .add_plugins((
+                EffectNodeLogPlugin,
+                EffectNodeSeqPlugin,
+                EffectNodeTimerPlugin,
+                EffectNodeBranchPlugin,
+                EffectNodeForEachPlugin,
+                EffectNodeWaitEventPlugin,
+                EffectNodeRandomPlugin,
+                EffectNodeComparePlugin,
+                EffectNodeMathPlugin,
+                EffectNodeBlackboardPlugin,
+            ))
+            .add_plugins((
+                EffectNodeDamagePlugin,
+                EffectNodeBuffPlugin,
+                EffectNodeProjectilePlugin,
+                EffectNodeCuePlugin,
+            ));
+        app
+    }
+
+    /// 手动构建的图实例，entry节点只是一个空的entity，只使用它的输出pin。
+    pub(crate) struct TestGraph {
+        pub owner: Entity,
+        pub entry: Entity,
+        pub state_nodes: Vec<Entity>,
+        pub context: EffectGraphContext,
+    }
+
+    impl TestGraph {
+        pub fn new(app: &mut App) -> Self {
+            let owner = app.world_mut().spawn(EffectGraphOwner).id();
+            let entry = app.world_mut().spawn_empty().id();
+            let mut context = EffectGraphContext::new();
+            context.set_entry_node(entry);
+            context.insert_state_node(entry);
+            Self {
+                owner,
+                entry,
+                state_nodes: vec![],
+                context,
+            }
+        }
+
+        pub fn add_instant_node<T: InstantEffectNode + 'static>(&mut self, app: &mut App, node: T) {
+            self.context.insert_instant_node(node.get_uuid());
+            app.world_mut()
+                .resource_mut::<InstantEffectNodeMap>()
+                .insert(node.get_uuid(), Arc::new(node));
+        }
+
+        pub fn add_state_node<T: Component + StateEffectNode>(
+            &mut self,
+            app: &mut App,
+            node: T,
+        ) -> Entity {
+            let entity = app.world_mut().spawn_empty().id();
+            app.world_mut()
+                .entity_mut(entity)
+                .insert(StateEffectNodeBundle {
+                    state_node: node,
+                    execute_state: default(),
+                    node_id: entity.into(),
+                });
+            self.context.insert_state_node(entity);
+            self.state_nodes.push(entity);
+            entity
+        }
+
+        pub fn connect_entry(
+            &mut self,
+            to_id: impl Into<EffectNodeId>,
+            to_node: &impl EffectNodePinGroup,
+            to_exec: &str,
+        ) {
+            self.context.add_exec_connection(
+                EffectNodeExecPin {
+                    node_id: self.entry.into(),
+                    exec: ENTRY_EXEC_START.into(),
+                },
+                &[EffectNodeExecPin {
+                    node_id: to_id.into(),
+                    exec: *to_node.get_input_exec_pin_by_name(to_exec).unwrap(),
+                }],
+            );
+        }
+
+        pub fn connect_exec(
+            &mut self,
+            from_id: impl Into<EffectNodeId>,
+            from_node: &impl EffectNodePinGroup,
+            from_exec: &str,
+            to_id: impl Into<EffectNodeId>,
+            to_node: &impl EffectNodePinGroup,
+            to_exec: &str,
+        ) {
+            self.context.add_exec_connection(
+                EffectNodeExecPin {
+                    node_id: from_id.into(),
+                    exec: *from_node.get_output_exec_pin_by_name(from_exec).unwrap(),
+                },
+                &[EffectNodeExecPin {
+                    node_id: to_id.into(),
+                    exec: *to_node.get_input_exec_pin_by_name(to_exec).unwrap(),
+                }],
+            );
+        }
+
+        pub fn connect_slot(
+            &mut self,
+            from_id: impl Into<EffectNodeId>,
+            from_node: &impl EffectNodePinGroup,
+            from_slot: &str,
+            to_id: impl Into<EffectNodeId>,
+            to_node: &impl EffectNodePinGroup,
+            to_slot: &str,
+        ) {
+            self.context
+                .add_slot_connection(
+                    EffectNodeSlotPin {
+                        node_id: from_id.into(),
+                        slot: *from_node.get_output_slot_pin_by_name(from_slot).unwrap(),
+                    },
+                    &[EffectNodeSlotPin {
+                        node_id: to_id.into(),
+                        slot: *to_node.get_input_slot_pin_by_name(to_slot).unwrap(),
+                    }],
+                )
+                .unwrap();
+        }
+
+        pub fn set_input(
+            &mut self,
+            node_id: impl Into<EffectNodeId>,
+            node: &impl EffectNodePinGroup,
+            slot: &str,
+            value: EffectValue,
+        ) {
+            self.context.insert_input_value(
+                EffectNodeSlotPin {
+                    node_id: node_id.into(),
+                    slot: *node.get_input_slot_pin_by_name(slot).unwrap(),
+                },
+                value.into(),
+            );
+        }
+
+        /// spawn graph instance under the owner, and execute the entry start pin.
+        pub fn run(self, app: &mut App) -> Entity {
+            let owner = self.owner;
+            let graph = self.spawn(app);
+            start_entry(app, owner);
+            graph
+        }
+
+        /// spawn graph instance under the owner without executing.
+        pub fn spawn(self, app: &mut App) -> Entity {
+            let graph = app
+                .world_mut()
+                .spawn((
+                    self.context,
+                    EffectGraphExecutor::default(),
+                    EffectGraphState::Inactive,
+                    EffectGraphTickState::Ticked,
+                ))
+                .set_parent(self.owner)
+                .id();
+            app.world_mut().entity_mut(self.entry).set_parent(graph);
+            for node in self.state_nodes {
+                app.world_mut().entity_mut(node).set_parent(graph);
+            }
+            graph
+        }
+    }
+
+    /// execute the entry start pin of all graphs under the owner.
+    pub(crate) fn start_entry(app: &mut App, owner: Entity) {
+        app.world_mut().trigger_targets(
+            EffectGraphExecEvent {
+                entry_exec_pin: ENTRY_EXEC_START.into(),
+                execute_in_graph_state: None,
+                slot_value_map: None,
+            },
+            owner,
+        );
+        app.update();
+    }
+
+    pub(crate) fn blackboard_value(
+        app: &App,
+        graph: Entity,
+        key: &'static str,
+    ) -> Option<EffectValue> {
+        app.world()
+            .get::<EffectGraphContext>(graph)
+            .and_then(|context| context.blackboard.get_value(key).cloned())
+    }
+}
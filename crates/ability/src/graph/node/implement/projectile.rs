@@ -0,0 +1,213 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeProjectilePlugin;
+
+impl Plugin for EffectNodeProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeSpawnProjectile>()
+            .add_event::<EffectSpawnProjectileEvent>();
+    }
+}
+
+/// 请求生成投射物，投射物的实现在游戏层，按照projectile_class创建。
+#[derive(Debug, Event, Clone, PartialEq)]
+pub struct EffectSpawnProjectileEvent {
+    pub instigator: Entity,
+    pub target: Option<Entity>,
+    pub projectile_class: Cow<'static, str>,
+    /// None时沿instigator的朝向发射。
+    pub direction: Option<Vec3>,
+    pub speed: f32,
+}
+
+///////////////////////// Node Component /////////////////////////
+
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeSpawnProjectile {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeSpawnProjectile {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeSpawnProjectile,
+    input => (
+        start => (
+            instigator: Entity,
+            target: Entity,
+            projectile_class: String,
+            direction: Vec3,
+            speed: f32
+        )
+    )
+    output => (
+        finish => ()
+    )
+);
+
+impl EffectNode for EffectNodeSpawnProjectile {}
+
+impl InstantEffectNode for EffectNodeSpawnProjectile {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands) {
+        // 客户端预测时由服务器执行
+        if context.predicted {
+            return;
+        }
+
+        let instigator = context.get_input_value_type_from_node::<&Entity>(
+            self.get_uuid(),
+            self,
+            EffectNodeSpawnProjectile::INPUT_SLOT_INSTIGATOR,
+        );
+        let target = context
+            .get_input_value_type_from_node::<&Entity>(
+                self.get_uuid(),
+                self,
+                EffectNodeSpawnProjectile::INPUT_SLOT_TARGET,
+            )
+            .copied();
+        let projectile_class = context.get_input_value_type_from_node::<String>(
+            self.get_uuid(),
+            self,
+            EffectNodeSpawnProjectile::INPUT_SLOT_PROJECTILE_CLASS,
+        );
+        let direction = context
+            .get_input_value_type_from_node::<&Vec3>(
+                self.get_uuid(),
+                self,
+                EffectNodeSpawnProjectile::INPUT_SLOT_DIRECTION,
+            )
+            .copied();
+        let speed = context.get_input_value_type_from_node::<&f32>(
+            self.get_uuid(),
+            self,
+            EffectNodeSpawnProjectile::INPUT_SLOT_SPEED,
+        );
+
+        let (Some(instigator), Some(projectile_class), Some(speed)) =
+            (instigator, projectile_class, speed)
+        else {
+            warn!("spawn projectile node {:?} missing input", self.get_uuid());
+            return;
+        };
+
+        commands.trigger(EffectSpawnProjectileEvent {
+            instigator: *instigator,
+            target,
+            projectile_class: projectile_class.into(),
+            direction,
+            speed: *speed,
+        });
+    }
+
+    fn push_execute_chain(
+        &self,
+        _context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            EffectNodeSpawnProjectile::OUTPUT_EXEC_FINISH,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::test_graph::{new_test_app, TestGraph},
+            InstantEffectNode,
+        },
+    };
+
+    use super::{EffectNodeSpawnProjectile, EffectSpawnProjectileEvent};
+
+    #[derive(Debug, Default, Resource)]
+    struct ReceivedProjectiles(Vec<EffectSpawnProjectileEvent>);
+
+    #[test]
+    fn spawn_projectile_without_target() {
+        let mut app = new_test_app();
+        app.init_resource::<ReceivedProjectiles>().add_observer(
+            |trigger: Trigger<EffectSpawnProjectileEvent>,
+             mut received: ResMut<ReceivedProjectiles>| {
+                received.0.push(trigger.event().clone());
+            },
+        );
+        let mut graph = TestGraph::new(&mut app);
+
+        let spawn = EffectNodeSpawnProjectile::new();
+        graph.set_input(
+            spawn.get_uuid(),
+            &spawn,
+            EffectNodeSpawnProjectile::INPUT_SLOT_INSTIGATOR,
+            EffectValue::Entity(graph.owner),
+        );
+        graph.set_input(
+            spawn.get_uuid(),
+            &spawn,
+            EffectNodeSpawnProjectile::INPUT_SLOT_PROJECTILE_CLASS,
+            EffectValue::String("fire_ball".into()),
+        );
+        graph.set_input(
+            spawn.get_uuid(),
+            &spawn,
+            EffectNodeSpawnProjectile::INPUT_SLOT_SPEED,
+            EffectValue::F32(12.0),
+        );
+        graph.connect_entry(
+            spawn.get_uuid(),
+            &spawn,
+            EffectNodeSpawnProjectile::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, spawn);
+
+        let owner = graph.owner;
+        graph.run(&mut app);
+        assert_eq!(
+            app.world().resource::<ReceivedProjectiles>().0,
+            vec![EffectSpawnProjectileEvent {
+                instigator: owner,
+                target: None,
+                projectile_class: "fire_ball".into(),
+                direction: None,
+                speed: 12.0,
+            }]
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+use uuid::Uuid;
+
+use crate::{
+    graph::{
+        context::EffectGraphContext,
+        executor::EffectGraphExecutor,
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeRandomPlugin;
+
+impl Plugin for EffectNodeRandomPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeRandom>();
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 按照概率随机选择执行的输出pin，probability不存在时视为0.5。
+#[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
+pub struct EffectNodeRandom {
+    pub base: InstantEffectNodeBase,
+}
+
+impl EffectNodeRandom {
+    pub fn new() -> Self {
+        Self {
+            base: InstantEffectNodeBase::new(),
+        }
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeRandom,
+    input => (
+        start => (probability: f32)
+    )
+    output => (
+        on_success => (),
+        on_failure => ()
+    )
+);
+
+impl EffectNode for EffectNodeRandom {}
+
+impl InstantEffectNode for EffectNodeRandom {
+    fn get_uuid(&self) -> Uuid {
+        self.base.node_id
+    }
+
+    fn collect(&self, _context: &mut EffectGraphContext) {}
+
+    fn execute(&self, _context: &mut EffectGraphContext, _commands: &mut Commands) {
+        trace!(
+            "node {} execute: {:?}",
+            std::any::type_name::<EffectNodeRandom>(),
+            self.get_uuid(),
+        );
+    }
+
+    fn push_execute_chain(
+        &self,
+        context: &EffectGraphContext,
+        executor: &mut EffectGraphExecutor,
+        _input_exec_pin: EffectNodeExec,
+    ) {
+        let probability = context
+            .get_input_value_type_from_node::<&f32>(
+                self.get_uuid(),
+                self,
+                EffectNodeRandom::INPUT_SLOT_PROBABILITY,
+            )
+            .copied()
+            .unwrap_or(0.5);
+
+        let success = rand::random::<f32>() < probability;
+        executor.continue_push_next_node_output_pin_from_node_name(
+            self.get_uuid().into(),
+            self,
+            if success {
+                EffectNodeRandom::OUTPUT_EXEC_ON_SUCCESS
+            } else {
+                EffectNodeRandom::OUTPUT_EXEC_ON_FAILURE
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::EffectNodeSetBlackboard,
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            InstantEffectNode,
+        },
+    };
+
+    use super::EffectNodeRandom;
+
+    fn run_random(probability: f32) -> Option<EffectValue> {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let random = EffectNodeRandom::new();
+        let on_success = EffectNodeSetBlackboard::new();
+        let on_failure = EffectNodeSetBlackboard::new();
+
+        graph.set_input(
+            random.get_uuid(),
+            &random,
+            EffectNodeRandom::INPUT_SLOT_PROBABILITY,
+            EffectValue::F32(probability),
+        );
+        for (node, value) in [(&on_success, true), (&on_failure, false)] {
+            graph.set_input(
+                node.get_uuid(),
+                node,
+                EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+                EffectValue::String("success".into()),
+            );
+            graph.set_input(
+                node.get_uuid(),
+                node,
+                EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+                EffectValue::Bool(value),
+            );
+        }
+
+        graph.connect_entry(
+            random.get_uuid(),
+            &random,
+            EffectNodeRandom::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            random.get_uuid(),
+            &random,
+            EffectNodeRandom::OUTPUT_EXEC_ON_SUCCESS,
+            on_success.get_uuid(),
+            &on_success,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_exec(
+            random.get_uuid(),
+            &random,
+            EffectNodeRandom::OUTPUT_EXEC_ON_FAILURE,
+            on_failure.get_uuid(),
+            &on_failure,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.add_instant_node(&mut app, random);
+        graph.add_instant_node(&mut app, on_success);
+        graph.add_instant_node(&mut app, on_failure);
+
+        let graph_entity = graph.run(&mut app);
+        blackboard_value(&app, graph_entity, "success")
+    }
+
+    #[test]
+    fn random_always_success() {
+        assert_eq!(run_random(1.0), Some(EffectValue::Bool(true)));
+    }
+
+    #[test]
+    fn random_always_failure() {
+        assert_eq!(run_random(0.0), Some(EffectValue::Bool(false)));
+    }
+}
@@ -5,9 +5,13 @@ use uuid::Uuid;
 
 use crate::{
     graph::{
-        context::{EffectGraphContext, InstantEffectNodeMap},
+        context::EffectGraphContext,
         executor::EffectGraphExecutor,
-        node::{bundle::InstantEffectNodeBase, pin::EffectNodeExec, EffectNode, InstantEffectNode},
+        node::{
+            bundle::InstantEffectNodeBase,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, InstantEffectNode, ReflectInstantEffectNode,
+        },
     },
     impl_effect_node_pin_group,
 };
@@ -24,6 +28,7 @@ impl Plugin for EffectNodeSeqPlugin {
 ///////////////////////// Node Component /////////////////////////
 
 #[derive(Debug, Default, Reflect)]
+#[reflect(Default, InstantEffectNode, EffectNodePinGroup)]
 pub struct EffectNodeSeq {
     pub base: InstantEffectNodeBase,
 }
@@ -59,42 +64,33 @@ impl InstantEffectNode for EffectNodeSeq {
 
     fn push_execute_chain(
         &self,
-        context: &EffectGraphContext,
+        _context: &EffectGraphContext,
         executor: &mut EffectGraphExecutor,
         _input_exec_pin: EffectNodeExec,
-        instant_nodes: &Res<InstantEffectNodeMap>,
     ) {
         executor.continue_push_next_node_output_pin_from_node_name(
             self.get_uuid().into(),
             self,
             EffectNodeSeq::OUTPUT_EXEC_FINISH_1,
-            context,
-            instant_nodes,
         );
         executor.continue_push_next_node_output_pin_from_node_name(
             self.get_uuid().into(),
             self,
             EffectNodeSeq::OUTPUT_EXEC_FINISH_2,
-            context,
-            instant_nodes,
         );
         executor.continue_push_next_node_output_pin_from_node_name(
             self.get_uuid().into(),
             self,
             EffectNodeSeq::OUTPUT_EXEC_FINISH_3,
-            context,
-            instant_nodes,
         );
         executor.continue_push_next_node_output_pin_from_node_name(
             self.get_uuid().into(),
             self,
             EffectNodeSeq::OUTPUT_EXEC_FINISH_4,
-            context,
-            instant_nodes,
         );
     }
 
-    fn execute(&self, _context: &mut EffectGraphContext) {
+    fn execute(&self, _context: &mut EffectGraphContext, _commands: &mut Commands) {
         info!(
             "node {} execute: {:?}",
             std::any::type_name::<EffectNodeSeq>(),
@@ -2,11 +2,13 @@ use bevy::{prelude::*, time::Time};
 
 use crate::{
     graph::{
-        context::{EffectGraphContext, InstantEffectNodeMap},
+        context::EffectGraphContext,
         event::EffectNodeExecEvent,
         executor::EffectGraphExecutor,
         node::{
-            pin::EffectNodeExec, EffectNode, EffectNodeExecuteState, EffectNodeId, StateEffectNode,
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, EffectNodeExecuteState, EffectNodeId, ReflectStateEffectNodeInstance,
+            StateEffectNode,
         },
         pin::EffectNodeExecPin,
         state::EffectGraphTickState,
@@ -35,14 +37,18 @@ pub struct EffectNodeTimerState {
 }
 
 #[derive(Clone, Debug, Default, Component, Reflect)]
-#[reflect(Component)]
+#[reflect(Component, Default, EffectNodePinGroup, StateEffectNodeInstance)]
 pub struct EffectNodeTimer {
     pub states: Vec<EffectNodeTimerState>,
 }
 
 impl EffectNode for EffectNodeTimer {}
 
-impl StateEffectNode for EffectNodeTimer {}
+impl StateEffectNode for EffectNodeTimer {
+    fn reset(&mut self) {
+        self.states.clear();
+    }
+}
 
 impl_effect_node_pin_group!(EffectNodeTimer,
     input => (
@@ -58,7 +64,6 @@ fn trigger_effect_node_event(
     trigger: Trigger<EffectNodeExecEvent>,
     mut query: Query<(&mut EffectNodeTimer, &mut EffectNodeExecuteState, &Parent)>,
     mut graph_query: Query<(&EffectGraphContext, &mut EffectGraphExecutor)>,
-    instant_nodes: Res<InstantEffectNodeMap>,
 ) {
     let pin = trigger.event().input_exec_pin;
     let EffectNodeId::Entity(entity) = pin.node_id else {
@@ -84,32 +89,23 @@ fn trigger_effect_node_event(
                     *state = EffectNodeExecuteState::Active;
                 }
 
-                executor.start_push_output_pin(
-                    EffectNodeExecPin {
-                        node_id: entity.into(),
-                        exec: EffectNodeTimer::OUTPUT_EXEC_START.into(),
-                    },
-                    context,
-                    &instant_nodes,
-                );
+                executor.start_push_output_pin(EffectNodeExecPin {
+                    node_id: entity.into(),
+                    exec: EffectNodeTimer::OUTPUT_EXEC_START.into(),
+                });
             }
         }
     }
 }
 
 fn update_timer(
-    mut graph_query: Query<(
-        &EffectGraphContext,
-        &mut EffectGraphExecutor,
-        &EffectGraphTickState,
-    )>,
+    mut graph_query: Query<(&mut EffectGraphExecutor, &EffectGraphTickState)>,
     mut query: Query<(
         Entity,
         &mut EffectNodeTimer,
         &mut EffectNodeExecuteState,
         &Parent,
     )>,
-    instant_map: Res<InstantEffectNodeMap>,
     time: Res<Time>,
 ) {
     for (entity, mut node, mut node_state, parent) in query.iter_mut() {
@@ -117,7 +113,7 @@ fn update_timer(
             continue;
         }
 
-        let (context, mut executor, tick_state) = graph_query.get_mut(parent.get()).unwrap();
+        let (mut executor, tick_state) = graph_query.get_mut(parent.get()).unwrap();
         if *tick_state != EffectGraphTickState::Ticked {
             continue;
         }
@@ -125,16 +121,12 @@ fn update_timer(
         for state in node.states.iter_mut() {
             state.elapse -= time.delta_secs();
             if state.elapse <= 0.0 {
-                executor.start_push_output_pin(
-                    EffectNodeExecPin {
-                        node_id: EffectNodeId::Entity(entity),
-                        exec: EffectNodeExec {
-                            name: EffectNodeTimer::OUTPUT_EXEC_FINISH,
-                        },
+                executor.start_push_output_pin(EffectNodeExecPin {
+                    node_id: EffectNodeId::Entity(entity),
+                    exec: EffectNodeExec {
+                        name: EffectNodeTimer::OUTPUT_EXEC_FINISH,
                     },
-                    context,
-                    &instant_map,
-                );
+                });
             }
         }
 
@@ -0,0 +1,293 @@
+use std::borrow::Cow;
+
+use bevy::prelude::*;
+
+use crate::{
+    graph::{
+        blackboard::EffectValue,
+        context::EffectGraphContext,
+        event::EffectNodeExecEvent,
+        executor::EffectGraphExecutor,
+        node::{
+            pin::{EffectNodeExec, ReflectEffectNodePinGroup},
+            EffectNode, EffectNodeExecuteState, EffectNodeId, ReflectStateEffectNodeInstance,
+            StateEffectNode,
+        },
+        pin::EffectNodeExecPin,
+        state::EffectGraphTickState,
+        EffectGraphOwner,
+    },
+    impl_effect_node_pin_group,
+};
+
+#[derive(Debug, Default)]
+pub struct EffectNodeWaitEventPlugin;
+
+impl Plugin for EffectNodeWaitEventPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EffectNodeWaitEvent>()
+            .add_event::<EffectGraphNotifyEvent>()
+            .add_observer(trigger_effect_node_event)
+            .add_observer(trigger_effect_graph_notify);
+    }
+}
+
+/// 通知graph owner(技能或者buff)下所有等待该事件的节点，比如动画通知，命中通知等。
+#[derive(Debug, Event, Clone)]
+pub struct EffectGraphNotifyEvent {
+    pub name: Cow<'static, str>,
+    pub payload: Option<EffectValue>,
+}
+
+impl EffectGraphNotifyEvent {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            name: name.into(),
+            payload: None,
+        }
+    }
+
+    pub fn with_payload(mut self, payload: EffectValue) -> Self {
+        self.payload = Some(payload);
+        self
+    }
+}
+
+///////////////////////// Node Component /////////////////////////
+
+/// 等待graph owner收到指定名字的EffectGraphNotifyEvent。
+/// 多次start会等待多次，一次通知会唤醒所有的等待。
+#[derive(Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component, Default, EffectNodePinGroup, StateEffectNodeInstance)]
+pub struct EffectNodeWaitEvent {
+    pub waiting_count: usize,
+}
+
+impl EffectNode for EffectNodeWaitEvent {}
+
+impl StateEffectNode for EffectNodeWaitEvent {
+    fn reset(&mut self) {
+        self.waiting_count = 0;
+    }
+}
+
+impl_effect_node_pin_group!(EffectNodeWaitEvent,
+    input => (
+        start => (event_name: String),
+        cancel => ()
+    )
+    output => (
+        start => (),
+        received => (payload: EffectValue),
+        cancel => ()
+    )
+);
+
+fn trigger_effect_node_event(
+    trigger: Trigger<EffectNodeExecEvent>,
+    mut query: Query<(
+        &mut EffectNodeWaitEvent,
+        &mut EffectNodeExecuteState,
+        &Parent,
+    )>,
+    mut graph_query: Query<&mut EffectGraphExecutor>,
+) {
+    let pin = trigger.event().input_exec_pin;
+    let EffectNodeId::Entity(entity) = pin.node_id else {
+        return;
+    };
+
+    let Ok((mut node, mut state, parent)) = query.get_mut(entity) else {
+        return;
+    };
+
+    trace!("trigger_node_event: wait event {:?}", pin);
+
+    let Ok(mut executor) = graph_query.get_mut(parent.get()) else {
+        return;
+    };
+
+    match pin.exec.name {
+        EffectNodeWaitEvent::INPUT_EXEC_START => {
+            node.waiting_count += 1;
+            *state = EffectNodeExecuteState::Active;
+
+            executor.start_push_output_pin(EffectNodeExecPin {
+                node_id: entity.into(),
+                exec: EffectNodeWaitEvent::OUTPUT_EXEC_START.into(),
+            });
+        }
+        EffectNodeWaitEvent::INPUT_EXEC_CANCEL => {
+            if *state == EffectNodeExecuteState::Idle {
+                return;
+            }
+
+            node.waiting_count = 0;
+            *state = EffectNodeExecuteState::Idle;
+
+            executor.start_push_output_pin(EffectNodeExecPin {
+                node_id: entity.into(),
+                exec: EffectNodeWaitEvent::OUTPUT_EXEC_CANCEL.into(),
+            });
+        }
+        _ => {}
+    }
+}
+
+fn trigger_effect_graph_notify(
+    trigger: Trigger<EffectGraphNotifyEvent>,
+    owner_query: Query<&Children, With<EffectGraphOwner>>,
+    mut graph_query: Query<(
+        &mut EffectGraphContext,
+        &mut EffectGraphExecutor,
+        &EffectGraphTickState,
+        &Children,
+    )>,
+    mut node_query: Query<(&mut EffectNodeWaitEvent, &mut EffectNodeExecuteState)>,
+) {
+    let owner_entity = trigger.entity();
+    let Ok(graphs) = owner_query.get(owner_entity) else {
+        return;
+    };
+
+    let event = trigger.event();
+    for graph in graphs {
+        let Ok((mut context, mut executor, tick_state, nodes)) = graph_query.get_mut(*graph) else {
+            continue;
+        };
+
+        if *tick_state != EffectGraphTickState::Ticked {
+            continue;
+        }
+
+        for node_entity in nodes {
+            let Ok((mut node, mut state)) = node_query.get_mut(*node_entity) else {
+                continue;
+            };
+
+            if *state == EffectNodeExecuteState::Idle {
+                continue;
+            }
+
+            let event_name = context.get_input_value_type_from_node::<String>(
+                *node_entity,
+                &*node,
+                EffectNodeWaitEvent::INPUT_SLOT_EVENT_NAME,
+            );
+            if event_name.as_deref() != Some(event.name.as_ref()) {
+                continue;
+            }
+
+            trace!(
+                "wait event node {:?} received event: {:?}",
+                node_entity, event
+            );
+
+            if let Some(payload) = event.payload.clone() {
+                context.insert_output_value_from_node(
+                    *node_entity,
+                    &*node,
+                    EffectNodeWaitEvent::OUTPUT_SLOT_PAYLOAD,
+                    payload,
+                );
+            }
+
+            for _ in 0..node.waiting_count {
+                executor.start_push_output_pin(EffectNodeExecPin {
+                    node_id: (*node_entity).into(),
+                    exec: EffectNodeWaitEvent::OUTPUT_EXEC_RECEIVED.into(),
+                });
+            }
+
+            node.waiting_count = 0;
+            *state = EffectNodeExecuteState::Idle;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::{
+        blackboard::EffectValue,
+        node::{
+            implement::{
+                blackboard::EffectNodeSetBlackboard,
+                test_graph::{blackboard_value, new_test_app, TestGraph},
+            },
+            EffectNodeExecuteState, InstantEffectNode,
+        },
+    };
+
+    use super::{EffectGraphNotifyEvent, EffectNodeWaitEvent};
+
+    #[test]
+    fn wait_until_event() {
+        let mut app = new_test_app();
+        let mut graph = TestGraph::new(&mut app);
+
+        let wait = graph.add_state_node(&mut app, EffectNodeWaitEvent::default());
+        let set = EffectNodeSetBlackboard::new();
+        let wait_node = EffectNodeWaitEvent::default();
+
+        graph.set_input(
+            wait,
+            &wait_node,
+            EffectNodeWaitEvent::INPUT_SLOT_EVENT_NAME,
+            EffectValue::String("hit".into()),
+        );
+        graph.set_input(
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_KEY,
+            EffectValue::String("hit_target".into()),
+        );
+
+        graph.connect_entry(wait, &wait_node, EffectNodeWaitEvent::INPUT_EXEC_START);
+        graph.connect_exec(
+            wait,
+            &wait_node,
+            EffectNodeWaitEvent::OUTPUT_EXEC_RECEIVED,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_EXEC_START,
+        );
+        graph.connect_slot(
+            wait,
+            &wait_node,
+            EffectNodeWaitEvent::OUTPUT_SLOT_PAYLOAD,
+            set.get_uuid(),
+            &set,
+            EffectNodeSetBlackboard::INPUT_SLOT_VALUE,
+        );
+        graph.add_instant_node(&mut app, set);
+
+        let owner = graph.owner;
+        let graph_entity = graph.run(&mut app);
+        assert_eq!(
+            app.world().get::<EffectNodeExecuteState>(wait),
+            Some(&EffectNodeExecuteState::Active)
+        );
+        assert_eq!(blackboard_value(&app, graph_entity, "hit_target"), None);
+
+        // other events do not wake up the node.
+        app.world_mut()
+            .trigger_targets(EffectGraphNotifyEvent::new("miss"), owner);
+        app.update();
+        assert_eq!(blackboard_value(&app, graph_entity, "hit_target"), None);
+
+        let target = app.world_mut().spawn_empty().id();
+        app.world_mut().trigger_targets(
+            EffectGraphNotifyEvent::new("hit").with_payload(EffectValue::Entity(target)),
+            owner,
+        );
+        app.update();
+        assert_eq!(
+            blackboard_value(&app, graph_entity, "hit_target"),
+            Some(EffectValue::Entity(target))
+        );
+        assert_eq!(
+            app.world().get::<EffectNodeExecuteState>(wait),
+            Some(&EffectNodeExecuteState::Idle)
+        );
+    }
+}
@@ -5,36 +5,65 @@ pub mod plugin;
 
 use bevy::prelude::*;
 use pin::EffectNodeExec;
+use serde::{Serialize, Serializer};
 use uuid::Uuid;
 
-use super::{
-    context::{EffectGraphContext, InstantEffectNodeMap},
-    executor::EffectGraphExecutor,
-};
+use super::{context::EffectGraphContext, executor::EffectGraphExecutor};
 
 pub trait EffectNode {}
 
 /// all non-system effect node
+#[reflect_trait]
 pub trait InstantEffectNode: Sync + Send {
     fn get_uuid(&self) -> Uuid;
 
-    /// 设置后续执行的输出pin。
+    /// 设置后续执行的输出pin。在execute之后调用，可以根据执行结果选择输出pin。
     fn push_execute_chain(
         &self,
         context: &EffectGraphContext,
         executor: &mut EffectGraphExecutor,
         input_exec_pin: EffectNodeExec,
-        instant_nodes: &Res<InstantEffectNodeMap>,
     );
 
     fn collect(&self, context: &mut EffectGraphContext);
-    fn execute(&self, context: &mut EffectGraphContext);
+
+    /// 瞬时节点不持有运行时数据，需要影响world的节点通过commands触发事件。
+    fn execute(&self, context: &mut EffectGraphContext, commands: &mut Commands);
 }
 
 /// all system effect node
-pub trait StateEffectNode {}
+/// 节点组件保存在模板上作为配置，每个实例插入一份副本，回收实例时调用reset清除运行时数据。
+pub trait StateEffectNode: Component + Clone + std::fmt::Debug {
+    fn reset(&mut self) {}
+}
+
+/// 类型擦除的StateEffectNode，模板通过反射找到节点组件后保存，见`graph::template`。
+#[reflect_trait]
+pub trait StateEffectNodeInstance: Sync + Send + std::fmt::Debug {
+    fn clone_instance(&self) -> Box<dyn StateEffectNodeInstance>;
+
+    fn insert_instance(&self, entity: &mut EntityWorldMut);
+
+    fn reset_instance(&self, entity: &mut EntityWorldMut);
+}
+
+impl<T: StateEffectNode> StateEffectNodeInstance for T {
+    fn clone_instance(&self) -> Box<dyn StateEffectNodeInstance> {
+        Box::new(self.clone())
+    }
+
+    fn insert_instance(&self, entity: &mut EntityWorldMut) {
+        entity.insert(self.clone());
+    }
+
+    fn reset_instance(&self, entity: &mut EntityWorldMut) {
+        if let Some(mut node) = entity.get_mut::<T>() {
+            node.reset();
+        }
+    }
+}
 
-#[derive(Debug, Component, Default, Copy, Clone, PartialEq, Eq, Reflect)]
+#[derive(Debug, Component, Default, Copy, Clone, PartialEq, Eq, Reflect, Serialize)]
 #[reflect(Component)]
 pub enum EffectNodeExecuteState {
     #[default]
@@ -66,6 +95,19 @@ impl EffectNodeId {
     }
 }
 
+impl Serialize for EffectNodeId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            EffectNodeId::Uuid(uuid) => {
+                serializer.serialize_newtype_variant("EffectNodeId", 0, "Uuid", &uuid.to_string())
+            }
+            EffectNodeId::Entity(entity) => {
+                serializer.serialize_newtype_variant("EffectNodeId", 1, "Entity", entity)
+            }
+        }
+    }
+}
+
 impl From<Entity> for EffectNodeId {
     fn from(entity: Entity) -> Self {
         Self::Entity(entity)
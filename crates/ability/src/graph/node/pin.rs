@@ -1,6 +1,9 @@
 use std::any::TypeId;
 
 use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::graph::blackboard::EffectValue;
 
 #[derive(Debug)]
 pub struct EffectNodeExecGroup {
@@ -8,7 +11,7 @@ pub struct EffectNodeExecGroup {
     pub slots: Vec<EffectNodeSlot>,
 }
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Reflect, Serialize)]
 pub struct EffectNodeExec {
     pub name: &'static str,
 }
@@ -19,10 +22,12 @@ impl From<&'static str> for EffectNodeExec {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize)]
 pub struct EffectNodeSlot {
     pub name: &'static str,
+    #[serde(skip)]
     pub pin_type: TypeId,
+    pub pin_type_name: &'static str,
 }
 
 impl EffectNodeSlot {
@@ -30,8 +35,14 @@ impl EffectNodeSlot {
         Self {
             name,
             pin_type: TypeId::of::<T>(),
+            pin_type_name: std::any::type_name::<T>(),
         }
     }
+
+    /// 作为输出slot，能否连接到输入slot。
+    pub fn can_connect_to(&self, input: &EffectNodeSlot) -> bool {
+        EffectValue::is_pin_type_compatible(self.pin_type, input.pin_type)
+    }
 }
 
 #[reflect_trait]
@@ -145,10 +156,9 @@ macro_rules! impl_effect_node_pin_group {
                                 exec: $crate::graph::node::pin::EffectNodeExec { name: $node::[<INPUT_EXEC_ $in_exec:snake:upper>] },
                                 slots: vec![
                                 $(
-                                    $crate::graph::node::pin::EffectNodeSlot {
-                                        name: $node::[<INPUT_SLOT_ $in_pin:snake:upper>],
-                                        pin_type: std::any::TypeId::of::<$in_type>(),
-                                    },
+                                    $crate::graph::node::pin::EffectNodeSlot::new::<$in_type>(
+                                        $node::[<INPUT_SLOT_ $in_pin:snake:upper>],
+                                    ),
                                 )*
                                 ],
                             }
@@ -168,10 +178,9 @@ macro_rules! impl_effect_node_pin_group {
                                 exec: $crate::graph::node::pin::EffectNodeExec { name: $node::[<OUTPUT_EXEC_ $out_exec:snake:upper>] },
                                 slots: vec![
                                     $(
-                                        $crate::graph::node::pin::EffectNodeSlot {
-                                            name: $node::[<OUTPUT_SLOT_ $out_pin:snake:upper>],
-                                            pin_type: std::any::TypeId::of::<$out_type>(),
-                                        },
+                                        $crate::graph::node::pin::EffectNodeSlot::new::<$out_type>(
+                                            $node::[<OUTPUT_SLOT_ $out_pin:snake:upper>],
+                                        ),
                                     )*
                                 ],
                             }
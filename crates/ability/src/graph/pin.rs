@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::Serialize;
 
 use super::{
     blackboard::EffectValue,
@@ -8,13 +9,13 @@ use super::{
     },
 };
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect, Hash, Serialize)]
 pub struct EffectNodeExecPin {
     pub node_id: EffectNodeId,
     pub exec: EffectNodeExec,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Reflect, Hash, Serialize)]
 pub struct EffectNodeSlotPin {
     pub node_id: EffectNodeId,
     pub slot: EffectNodeSlot,
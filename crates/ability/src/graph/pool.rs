@@ -0,0 +1,279 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    context::{EffectGraphContext, GraphRef},
+    executor::EffectGraphExecutor,
+    state::{EffectGraphState, EffectGraphTickState},
+    template::instantiate_effect_graph,
+};
+
+/// 每个图模板最多回收的实例数量，为0时不回收。
+pub const EFFECT_GRAPH_POOL_CAPACITY: usize = 64;
+
+/// 池中的实例没有Parent，不属于任何owner。
+#[derive(Debug, Component, Default)]
+pub struct EffectGraphPooled;
+
+/// 回收的图实例，按照模板分类，添加图时优先复用。
+#[derive(Debug, Resource)]
+pub struct EffectGraphPool {
+    pub capacity: usize,
+    instances: HashMap<GraphRef, Vec<Entity>>,
+    /// 热加载替换的旧模板，实例不再放回池中。
+    retired: HashSet<GraphRef>,
+}
+
+impl Default for EffectGraphPool {
+    fn default() -> Self {
+        Self {
+            capacity: EFFECT_GRAPH_POOL_CAPACITY,
+            instances: HashMap::default(),
+            retired: HashSet::default(),
+        }
+    }
+}
+
+impl EffectGraphPool {
+    pub fn len(&self, graph_ref: GraphRef) -> usize {
+        self.instances.get(&graph_ref).map_or(0, Vec::len)
+    }
+
+    pub fn take(&mut self, graph_ref: GraphRef) -> Option<Entity> {
+        self.instances.get_mut(&graph_ref)?.pop()
+    }
+
+    /// 模板被替换后不再回收它的实例，返回池中已有的实例，由调用者销毁。
+    pub fn retire(&mut self, graph_ref: GraphRef) -> Vec<Entity> {
+        self.retired.insert(graph_ref);
+        self.instances.remove(&graph_ref).unwrap_or_default()
+    }
+
+    /// 旧模板的实例全部销毁后调用。
+    pub fn release(&mut self, graph_ref: GraphRef) {
+        self.retired.remove(&graph_ref);
+    }
+
+    fn try_put(&mut self, graph_ref: GraphRef, instance: Entity) -> bool {
+        if self.retired.contains(&graph_ref) {
+            return false;
+        }
+        let instances = self.instances.entry(graph_ref).or_default();
+        if instances.len() >= self.capacity {
+            return false;
+        }
+        instances.push(instance);
+        true
+    }
+}
+
+/// 复用池中的实例，没有时从共享的模板实例化。
+pub fn spawn_effect_graph_instance(
+    commands: &mut Commands,
+    pool: &mut EffectGraphPool,
+    graph_ref: GraphRef,
+    owner: Entity,
+) -> Entity {
+    if let Some(instance) = pool.take(graph_ref) {
+        commands
+            .entity(instance)
+            .remove::<EffectGraphPooled>()
+            .insert(EffectGraphTickState::Ticked)
+            .set_parent(owner);
+        trace!("reuse graph instance: {:?} => {:?}", owner, instance);
+        return instance;
+    }
+
+    let instance = commands.spawn_empty().set_parent(owner).id();
+    commands.queue(move |world: &mut World| {
+        instantiate_effect_graph(world, graph_ref, instance);
+    });
+    instance
+}
+
+/// 重置实例并放入池中，直接重置slot的值、黑板和节点的运行时数据，
+/// 不是模板的实例或者池满时返回false。
+pub fn recycle_effect_graph_instance(
+    commands: &mut Commands,
+    pool: &mut EffectGraphPool,
+    instance: Entity,
+    context: &mut EffectGraphContext,
+) -> bool {
+    let Some(template) = context.template.clone() else {
+        return false;
+    };
+    if !pool.try_put(template.graph_ref, instance) {
+        return false;
+    }
+
+    context.reset_from_template();
+    for (index, node) in context.state_nodes.iter().enumerate() {
+        let template = template.clone();
+        commands
+            .entity(*node)
+            .queue(move |mut entity: EntityWorldMut| {
+                template.state_nodes[index].reset_instance(&mut entity);
+            });
+    }
+    commands.entity(instance).remove_parent().insert((
+        EffectGraphPooled,
+        EffectGraphState::Inactive,
+        EffectGraphTickState::Paused,
+        EffectGraphExecutor::default(),
+    ));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+
+    use crate::graph::{
+        context::{EffectGraphContext, GraphRef},
+        event::{EffectGraphAddEvent, EffectGraphRemoveEvent},
+        graph_map::EffectGraphMap,
+        node::{
+            implement::{test_graph::new_test_app, wait_event::EffectNodeWaitEvent},
+            EffectNodeExecuteState,
+        },
+        state::{EffectGraphState, EffectGraphTickState},
+        EffectGraphOwner,
+    };
+
+    use super::{EffectGraphPool, EffectGraphPooled};
+
+    const GRAPH_CLASS: &str = "pooled_graph";
+
+    fn spawn_template(app: &mut App) -> GraphRef {
+        let template = app.world_mut().spawn_empty().id();
+        let node = app
+            .world_mut()
+            .spawn((
+                EffectNodeExecuteState::default(),
+                EffectNodeWaitEvent::default(),
+            ))
+            .set_parent(template)
+            .id();
+        let mut context = EffectGraphContext::new();
+        context.set_entry_node(node);
+        context.insert_state_node(node);
+        context.set_graph_ref(GraphRef::new(template));
+        app.world_mut().entity_mut(template).insert((
+            context,
+            EffectGraphState::Inactive,
+            EffectGraphTickState::Ticked,
+        ));
+
+        let graph_ref = GraphRef::new(template);
+        app.world_mut()
+            .resource_mut::<EffectGraphMap>()
+            .insert_graph(GRAPH_CLASS.into(), graph_ref);
+        graph_ref
+    }
+
+    fn add_graph(app: &mut App) -> (Entity, Entity) {
+        let owner = app.world_mut().spawn(EffectGraphOwner).id();
+        app.world_mut().trigger_targets(
+            EffectGraphAddEvent {
+                graph_class: GRAPH_CLASS.into(),
+            },
+            owner,
+        );
+        app.update();
+        let instance = app.world().get::<Children>(owner).unwrap()[0];
+        (owner, instance)
+    }
+
+    #[test]
+    fn recycle_graph_instance() {
+        let mut app = new_test_app();
+        let graph_ref = spawn_template(&mut app);
+
+        let (owner, instance) = add_graph(&mut app);
+        let node = app.world().get::<Children>(instance).unwrap()[0];
+        assert_ne!(node, graph_ref.get_entity());
+        let context = app.world().get::<EffectGraphContext>(instance).unwrap();
+        assert!(context.template.is_some());
+        assert!(context.exec_connections.is_empty());
+        assert_eq!(context.get_entry_node(), Some(node));
+
+        app.world_mut()
+            .entity_mut(node)
+            .insert(EffectNodeExecuteState::Active)
+            .get_mut::<EffectNodeWaitEvent>()
+            .unwrap()
+            .waiting_count = 2;
+        app.world_mut()
+            .trigger_targets(EffectGraphRemoveEvent, owner);
+        app.update();
+        assert!(app.world().get::<EffectGraphPooled>(instance).is_none());
+
+        app.world_mut()
+            .entity_mut(node)
+            .insert(EffectNodeExecuteState::Idle);
+        app.update();
+        assert!(app
+            .world()
+            .get::<Children>(owner)
+            .is_none_or(|c| c.is_empty()));
+        assert!(app.world().get::<EffectGraphPooled>(instance).is_some());
+        assert_eq!(app.world().resource::<EffectGraphPool>().len(graph_ref), 1);
+        // 节点直接重置，不重新创建
+        assert_eq!(app.world().get::<Children>(instance).unwrap()[0], node);
+        assert_eq!(
+            app.world()
+                .get::<EffectNodeWaitEvent>(node)
+                .unwrap()
+                .waiting_count,
+            0
+        );
+
+        let (_, reused) = add_graph(&mut app);
+        assert_eq!(reused, instance);
+        assert!(app.world().get::<EffectGraphPooled>(reused).is_none());
+        assert_eq!(
+            app.world().get::<EffectGraphState>(reused),
+            Some(&EffectGraphState::Inactive)
+        );
+        assert_eq!(app.world().resource::<EffectGraphPool>().len(graph_ref), 0);
+    }
+
+    #[test]
+    fn instances_share_template() {
+        let mut app = new_test_app();
+        spawn_template(&mut app);
+
+        let (_, first) = add_graph(&mut app);
+        let (_, second) = add_graph(&mut app);
+        let template = |instance: Entity| {
+            app.world()
+                .get::<EffectGraphContext>(instance)
+                .unwrap()
+                .template
+                .clone()
+                .unwrap()
+        };
+        assert!(Arc::ptr_eq(&template(first), &template(second)));
+        assert_ne!(
+            app.world().get::<Children>(first).unwrap()[0],
+            app.world().get::<Children>(second).unwrap()[0]
+        );
+    }
+
+    #[test]
+    fn despawn_when_pool_full() {
+        let mut app = new_test_app();
+        app.world_mut().resource_mut::<EffectGraphPool>().capacity = 0;
+        spawn_template(&mut app);
+
+        let (owner, instance) = add_graph(&mut app);
+        app.world_mut()
+            .trigger_targets(EffectGraphRemoveEvent, owner);
+        app.update();
+        assert!(app.world().get::<EffectGraphContext>(instance).is_none());
+    }
+}
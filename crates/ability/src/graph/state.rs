@@ -1,7 +1,12 @@
 use bevy::log::info;
 use bevy::prelude::*;
+use serde::Serialize;
 
-use super::{context::EffectGraphContext, node::EffectNodeExecuteState};
+use super::{
+    context::EffectGraphContext,
+    node::EffectNodeExecuteState,
+    pool::{recycle_effect_graph_instance, EffectGraphPool, EffectGraphPooled},
+};
 
 #[derive(Debug, Component, Default, Copy, Clone, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
@@ -11,7 +16,7 @@ pub enum EffectGraphTickState {
     Paused,
 }
 
-#[derive(Debug, Component, Default, PartialEq, Eq, Hash, Reflect, Clone, Copy)]
+#[derive(Debug, Component, Default, PartialEq, Eq, Hash, Reflect, Clone, Copy, Serialize)]
 #[reflect(Component)]
 pub enum EffectGraphState {
     #[default]
@@ -44,12 +49,18 @@ pub fn reset_effect_graph_state(
     }
 }
 
+/// 模板的实例优先放回池中，池满时销毁。
+#[allow(clippy::type_complexity)]
 pub fn update_to_despawn_effect_graph(
     mut commands: Commands,
-    query: Query<(Entity, &EffectGraphState, &EffectGraphContext)>,
+    mut pool: ResMut<EffectGraphPool>,
+    mut query: Query<
+        (Entity, &EffectGraphState, &mut EffectGraphContext),
+        Without<EffectGraphPooled>,
+    >,
     node_state_query: Query<&EffectNodeExecuteState>,
 ) {
-    for (graph_entity, state, context) in query.iter() {
+    for (graph_entity, state, mut context) in query.iter_mut() {
         if *state == EffectGraphState::ToRemove
             && context.state_nodes.iter().all(|node| {
                 if let Ok(node_state) = node_state_query.get(*node) {
@@ -60,6 +71,10 @@ pub fn update_to_despawn_effect_graph(
                 false
             })
         {
+            if recycle_effect_graph_instance(&mut commands, &mut pool, graph_entity, &mut context) {
+                info!("recycle graph: {:?}", graph_entity);
+                continue;
+            }
             commands.entity(graph_entity).despawn_recursive();
             info!("despawn graph: {:?}", graph_entity);
         }
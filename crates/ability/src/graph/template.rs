@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use bevy::{prelude::*, reflect::TypeRegistry, utils::HashMap};
+use uuid::Uuid;
+
+use super::{
+    blackboard::EffectBlackboard,
+    context::{EffectGraphContext, GraphRef},
+    executor::EffectGraphExecutor,
+    node::{
+        EffectNodeExecuteState, EffectNodeId, ReflectStateEffectNodeInstance,
+        StateEffectNodeInstance,
+    },
+    pin::{EffectNodeExecPin, EffectNodeSlotPin, EffectNodeSlotValue},
+    state::{EffectGraphState, EffectGraphTickState},
+};
+
+/// 模板的状态节点，节点组件作为配置保存，实例化时插入副本。
+#[derive(Debug)]
+pub struct EffectGraphTemplateNode {
+    pub entity: Entity,
+    /// 没有StateEffectNode组件的节点为None，比如只有执行状态的entry节点。
+    pub node: Option<Box<dyn StateEffectNodeInstance>>,
+}
+
+impl EffectGraphTemplateNode {
+    fn spawn_instance(&self, world: &mut World, graph: Entity) -> Entity {
+        let mut entity = world.spawn(EffectNodeExecuteState::Idle);
+        let node_id = entity.id();
+        entity.insert(EffectNodeId::from(node_id));
+        if let Some(node) = self.node.as_ref() {
+            node.insert_instance(&mut entity);
+        }
+        entity.set_parent(graph);
+        node_id
+    }
+
+    /// 清除实例节点的运行时数据，不重新复制节点组件。
+    pub fn reset_instance(&self, entity: &mut EntityWorldMut) {
+        entity.insert(EffectNodeExecuteState::Idle);
+        if let Some(node) = self.node.as_ref() {
+            node.reset_instance(entity);
+        }
+    }
+}
+
+/// 同一个图模板的实例共享的拓扑和节点配置，节点id为模板的节点entity。
+/// 实例只保存slot的值、黑板和节点的执行状态，见`EffectGraphContext::from_template`。
+#[derive(Debug)]
+pub struct EffectGraphTemplate {
+    pub graph_ref: GraphRef,
+    pub exec_connections: HashMap<EffectNodeExecPin, Vec<EffectNodeExecPin>>,
+    pub slot_connections: HashMap<EffectNodeSlotPin, Vec<EffectNodeSlotPin>>,
+    pub inputs: HashMap<EffectNodeSlotPin, EffectNodeSlotValue>,
+    pub outputs: HashMap<EffectNodeSlotPin, EffectNodeSlotValue>,
+    pub blackboard: EffectBlackboard,
+    pub entry_node: Option<Entity>,
+    pub instant_nodes: Vec<Uuid>,
+    /// 实例的`state_nodes`按照相同顺序保存对应的实例节点。
+    pub state_nodes: Vec<EffectGraphTemplateNode>,
+}
+
+impl EffectGraphTemplate {
+    fn from_world(world: &World, graph_ref: GraphRef) -> Option<Self> {
+        let context = world.get::<EffectGraphContext>(graph_ref.get_entity())?;
+        let registry = world.resource::<AppTypeRegistry>().read();
+
+        let mut node_entities = context.state_nodes.clone();
+        if let Some(entry_node) = context.entry_node {
+            if !node_entities.contains(&entry_node) {
+                node_entities.push(entry_node);
+            }
+        }
+        let state_nodes = node_entities
+            .into_iter()
+            .map(|entity| EffectGraphTemplateNode {
+                entity,
+                node: get_state_effect_node(world, &registry, entity),
+            })
+            .collect();
+
+        Some(Self {
+            graph_ref,
+            exec_connections: context.exec_connections.clone(),
+            slot_connections: context.slot_connections.clone(),
+            inputs: context.inputs.clone(),
+            outputs: context.outputs.clone(),
+            blackboard: context.blackboard.clone(),
+            entry_node: context.entry_node,
+            instant_nodes: context.instant_nodes.clone(),
+            state_nodes,
+        })
+    }
+
+    pub fn template_node(&self, index: usize) -> Option<Entity> {
+        self.state_nodes.get(index).map(|node| node.entity)
+    }
+
+    pub fn template_node_index(&self, entity: Entity) -> Option<usize> {
+        self.state_nodes
+            .iter()
+            .position(|node| node.entity == entity)
+    }
+}
+
+/// 通过反射找到节点上实现了StateEffectNodeInstance的组件，每个模板只在第一次实例化时查找。
+fn get_state_effect_node(
+    world: &World,
+    registry: &TypeRegistry,
+    entity: Entity,
+) -> Option<Box<dyn StateEffectNodeInstance>> {
+    let entity_ref = world.get_entity(entity).ok()?;
+    entity_ref
+        .archetype()
+        .components()
+        .find_map(|component_id| {
+            let type_id = world.components().get_info(component_id)?.type_id()?;
+            let registration = registry.get(type_id)?;
+            let reflect_node = registration.data::<ReflectStateEffectNodeInstance>()?;
+            let component = registration
+                .data::<ReflectComponent>()?
+                .reflect(entity_ref)?;
+            Some(reflect_node.get(component)?.clone_instance())
+        })
+}
+
+/// 按照GraphRef共享的模板，第一次实例化时从模板entity创建，模板被替换或销毁时移除。
+#[derive(Debug, Default, Resource)]
+pub struct EffectGraphTemplates {
+    templates: HashMap<GraphRef, Arc<EffectGraphTemplate>>,
+}
+
+impl EffectGraphTemplates {
+    pub fn get(&self, graph_ref: GraphRef) -> Option<Arc<EffectGraphTemplate>> {
+        self.templates.get(&graph_ref).cloned()
+    }
+
+    /// 已经创建的实例继续持有旧模板，直到销毁。
+    pub fn remove(&mut self, graph_ref: GraphRef) -> Option<Arc<EffectGraphTemplate>> {
+        self.templates.remove(&graph_ref)
+    }
+
+    fn get_or_create(world: &mut World, graph_ref: GraphRef) -> Option<Arc<EffectGraphTemplate>> {
+        if let Some(template) = world.resource::<Self>().get(graph_ref) {
+            return Some(template);
+        }
+        let template = Arc::new(EffectGraphTemplate::from_world(world, graph_ref)?);
+        world
+            .resource_mut::<Self>()
+            .templates
+            .insert(graph_ref, template.clone());
+        Some(template)
+    }
+}
+
+/// 在instance上创建模板的实例，只创建状态节点和保存slot值的context，拓扑和瞬时节点引用模板。
+pub fn instantiate_effect_graph(world: &mut World, graph_ref: GraphRef, instance: Entity) {
+    if world.get_entity(instance).is_err() {
+        return;
+    }
+    let Some(template) = EffectGraphTemplates::get_or_create(world, graph_ref) else {
+        error!("effect graph template not found: {:?}", graph_ref);
+        return;
+    };
+
+    let state_nodes = template
+        .state_nodes
+        .iter()
+        .map(|node| node.spawn_instance(world, instance))
+        .collect();
+    world.entity_mut(instance).insert((
+        EffectGraphContext::from_template(template, state_nodes),
+        EffectGraphExecutor::default(),
+        EffectGraphState::Inactive,
+        EffectGraphTickState::Ticked,
+    ));
+}
@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use datatables::{
+    effect::{TbAbility, TbBuff},
+    validate::TableValidationReport,
+    Tables,
+};
+
+use super::{
+    asset::{EffectGraphAsset, EffectGraphAssetMap},
+    blackboard::EffectValue,
+    graph_map::EffectGraphBuilderMap,
+    node::implement::buff::{EffectNodeAddBuff, EffectNodeRemoveBuff},
+};
+
+/// 技能和buff引用的graph_class，以及graph资产中buff节点的buff_id。
+/// builder构建的graph在代码中设置buff_id，只能检查graph_class。
+pub struct EffectGraphRefs<'a> {
+    pub ability: &'a TbAbility,
+    pub buff: &'a TbBuff,
+    pub builder_map: &'a EffectGraphBuilderMap,
+    pub asset_map: &'a EffectGraphAssetMap,
+    pub assets: &'a Assets<EffectGraphAsset>,
+}
+
+impl EffectGraphRefs<'_> {
+    pub fn validate(&self, report: &mut TableValidationReport) {
+        macro_rules! validate_graph_class {
+            ($table:expr, $name:literal) => {
+                for row in $table
+                    .data_list
+                    .iter()
+                    .filter(|row| !row.graph_class.is_empty())
+                {
+                    self.validate_graph_class(report, $name, row.id, &row.graph_class);
+                }
+            };
+        }
+        validate_graph_class!(self.ability, "TbAbility");
+        validate_graph_class!(self.buff, "TbBuff");
+    }
+
+    fn validate_graph_class(
+        &self,
+        report: &mut TableValidationReport,
+        table: &'static str,
+        row: i32,
+        graph_class: &str,
+    ) {
+        if self
+            .builder_map
+            .get_effect_graph_builder(graph_class)
+            .is_some()
+        {
+            return;
+        }
+
+        let Some(handle) = self.asset_map.get(graph_class) else {
+            report.check(
+                false,
+                table,
+                row,
+                "graph_class",
+                graph_class,
+                "EffectGraphBuilderMap or EffectGraphAssetMap",
+            );
+            return;
+        };
+
+        let Some(asset) = self.assets.get(handle) else {
+            debug!(
+                "effect graph {} is not loaded, skip buff check",
+                graph_class
+            );
+            return;
+        };
+
+        let buff_nodes = [
+            EffectNodeAddBuff::short_type_path(),
+            EffectNodeAddBuff::type_path(),
+            EffectNodeRemoveBuff::short_type_path(),
+            EffectNodeRemoveBuff::type_path(),
+        ];
+        for node in asset
+            .nodes
+            .iter()
+            .filter(|node| buff_nodes.contains(&node.node_type.as_str()))
+        {
+            if let Some(EffectValue::I32(buff_id)) =
+                node.inputs.get(EffectNodeAddBuff::INPUT_SLOT_BUFF_ID)
+            {
+                report.check(
+                    self.buff.data_map.contains_key(buff_id),
+                    table,
+                    row,
+                    format!("graph_class({}).{}.buff_id", graph_class, node.id),
+                    buff_id,
+                    "TbBuff",
+                );
+            }
+        }
+    }
+}
+
+pub fn validate_effect_graph_refs(
+    tables: Res<Tables>,
+    ability: Res<Assets<TbAbility>>,
+    buff: Res<Assets<TbBuff>>,
+    builder_map: Res<EffectGraphBuilderMap>,
+    asset_map: Res<EffectGraphAssetMap>,
+    assets: Res<Assets<EffectGraphAsset>>,
+    mut report: ResMut<TableValidationReport>,
+) {
+    let (Some(ability), Some(buff)) = (ability.get(&tables.tb_ability), buff.get(&tables.tb_buff))
+    else {
+        return;
+    };
+
+    EffectGraphRefs {
+        ability,
+        buff,
+        builder_map: &builder_map,
+        asset_map: &asset_map,
+        assets: &assets,
+    }
+    .validate(&mut report);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use datatables::{
+        effect::{TbAbility, TbBuff},
+        validate::TableValidationReport,
+    };
+    use serde_json::json;
+
+    use super::EffectGraphRefs;
+    use crate::graph::{
+        asset::{EffectGraphAsset, EffectGraphAssetMap},
+        builder::EffectGraphBuilder,
+        context::InstantEffectNodeMap,
+        graph_map::EffectGraphBuilderMap,
+    };
+
+    /// 和ability example中注册的builder同名。
+    #[derive(Debug)]
+    struct EffectNodeGraphBaseAttack;
+
+    impl EffectGraphBuilder for EffectNodeGraphBaseAttack {
+        fn get_effect_graph_name(&self) -> &'static str {
+            "EffectNodeGraphBaseAttack"
+        }
+
+        fn build(&self, _: &mut Commands, _: &mut ResMut<InstantEffectNodeMap>) -> Entity {
+            unreachable!()
+        }
+    }
+
+    const BUFF_GRAPH: &str = r#"(
+        entry: "entry",
+        nodes: [
+            (id: "entry", node_type: "EffectNodeAbilityEntry"),
+            (id: "add", node_type: "EffectNodeAddBuff", inputs: { "buff_id": I32(1) }),
+            (id: "remove", node_type: "EffectNodeRemoveBuff", inputs: { "buff_id": I32(99) }),
+        ],
+    )"#;
+
+    fn read_table(file: &str) -> serde_json::Value {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/datatables")
+            .join(file);
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn validate_graph_class_and_buff_id() {
+        let mut abilities = read_table("effect_tbability.json");
+        let buff = TbBuff::new(&read_table("effect_tbbuff.json")).unwrap();
+
+        let mut builder_map = EffectGraphBuilderMap::default();
+        builder_map.map.insert(
+            "EffectNodeGraphBaseAttack".into(),
+            Box::new(EffectNodeGraphBaseAttack),
+        );
+        let mut assets = Assets::<EffectGraphAsset>::default();
+        let mut asset_map = EffectGraphAssetMap::default();
+        asset_map.insert(
+            "graphs/buff.ron".into(),
+            assets.add(EffectGraphAsset::from_ron(BUFF_GRAPH.as_bytes()).unwrap()),
+        );
+
+        let ability = TbAbility::new(&abilities).unwrap();
+        let refs = EffectGraphRefs {
+            ability: &ability,
+            buff: &buff,
+            builder_map: &builder_map,
+            asset_map: &asset_map,
+            assets: &assets,
+        };
+        let mut report = TableValidationReport::default();
+        refs.validate(&mut report);
+        assert!(report.is_empty(), "{}", report);
+
+        abilities[0]["graph_class"] = json!("graphs/buff.ron");
+        abilities[1]["graph_class"] = json!("EffectNodeGraphMissing");
+        let (id0, id1) = (
+            abilities[0]["id"].to_string(),
+            abilities[1]["id"].to_string(),
+        );
+        let ability = TbAbility::new(&abilities).unwrap();
+        let refs = EffectGraphRefs {
+            ability: &ability,
+            ..refs
+        };
+        let mut report = TableValidationReport::default();
+        refs.validate(&mut report);
+        let errors: Vec<_> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                format!(
+                    "TbAbility row {} field graph_class(graphs/buff.ron).remove.buff_id: 99 not found in TbBuff",
+                    id0
+                ),
+                format!(
+                    "TbAbility row {} field graph_class: EffectNodeGraphMissing not found in EffectGraphBuilderMap or EffectGraphAssetMap",
+                    id1
+                ),
+            ]
+        );
+    }
+}
@@ -1,13 +1,29 @@
-use ability::{node::ability_entry::EffectNodeAbilityEntryPlugin, plugin::AbilityPlugin};
-use bevy::app::{First, Plugin};
+use ability::{
+    node::ability_entry::EffectNodeAbilityEntryPlugin, plugin::AbilityPlugin,
+    target::AbilityTargetPlugin, trigger::AbilityTriggerPlugin,
+};
+use bevy::{
+    app::{First, Plugin, PostUpdate},
+    prelude::{IntoSystemConfigs, OnEnter},
+};
 use buff::plugin::BuffPlugin;
-use graph::{node::plugin::EffectNodePlugin, EffectGraphPlugin};
-use stateset::{init_state_layertag_registry, StateLayerTagRegistry};
+use cue::GameplayCuePlugin;
+use datatables::{validate::TableValidationSet, TableLoadingState};
+use graph::{
+    asset::EffectGraphAssetPlugin, node::plugin::EffectNodePlugin,
+    validate::validate_effect_graph_refs, EffectGraphPlugin,
+};
+use stateset::{
+    init_state_layertag_registry, init_state_layertag_watches, update_state_layertag_changes,
+    validate_state_layertags, LayerTagAdded, LayerTagQueryChanged, LayerTagRemoved,
+    StateLayerTagContainer, StateLayerTagRegistry, StateLayerTagWatcher,
+};
 
 pub mod ability;
 pub mod attribute;
 pub mod buff;
 pub mod bundle;
+pub mod cue;
 pub mod graph;
 pub mod stateset;
 
@@ -22,11 +38,29 @@ pub struct AbilitySubsystemPlugin;
 impl Plugin for AbilitySubsystemPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugins(AbilityPlugin)
+            .add_plugins(AbilityTargetPlugin)
+            .add_plugins(AbilityTriggerPlugin)
             .add_plugins(BuffPlugin)
+            .add_plugins(GameplayCuePlugin)
             .add_plugins(EffectGraphPlugin)
+            .add_plugins(EffectGraphAssetPlugin)
             .add_plugins(EffectNodePlugin)
             .add_plugins(EffectNodeAbilityEntryPlugin)
             .init_resource::<StateLayerTagRegistry>()
-            .add_systems(First, init_state_layertag_registry);
+            .register_type::<StateLayerTagContainer>()
+            .register_type::<StateLayerTagWatcher>()
+            .add_event::<LayerTagAdded>()
+            .add_event::<LayerTagRemoved>()
+            .add_event::<LayerTagQueryChanged>()
+            .add_observer(init_state_layertag_watches)
+            .add_systems(
+                First,
+                (init_state_layertag_registry, validate_state_layertags).chain(),
+            )
+            .add_systems(PostUpdate, update_state_layertag_changes)
+            .add_systems(
+                OnEnter(TableLoadingState::Loaded),
+                validate_effect_graph_refs.in_set(TableValidationSet::Extension),
+            );
     }
 }
@@ -0,0 +1,3 @@
+//! LayerTag表生成的常量，表中的LayerTag修改后，引用不存在的常量会编译失败。
+
+layertag_derive::layertag_table!("../../assets/datatables/layertag_tblayertag.json");
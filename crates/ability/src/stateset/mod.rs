@@ -1,28 +1,472 @@
+pub mod layertags;
+
+use std::borrow::Cow;
+
 use bevy::{
-    prelude::{Component, EventReader, ResMut, Resource},
+    ecs::system::EntityCommands,
+    log::{error, warn},
+    prelude::{
+        Commands, Component, Entity, Event, EventReader, Local, OnInsert, Parent, Query, Res,
+        ResMut, Resource, Trigger,
+    },
     reflect::Reflect,
+    utils::HashSet,
+};
+use datatables::{
+    damage::TbDamageType,
+    effect::{TbAbility, TbBuff},
+    layertag::TbLayerTag,
+    tables_system_param::TableReader,
+    TableRowChangeKind, TableRowChanged, TablesLoadedEvent,
+};
+use layertag::{
+    count_container::{CountLayerTagContainer, LayerTagChange},
+    layertag::LayerTag,
+    query::{validate_raw_layertags, LayerTagQuery},
+    registry::LayerTagRegistry,
 };
-use datatables::{layertag::TbLayerTag, tables_system_param::TableReader, TablesLoadedEvent};
-use layertag::{count_container::CountLayerTagContainer, registry::LayerTagRegistry};
 
-#[derive(Component, Default, Debug, Reflect)]
+/// 记录LayerTag的变化，由`update_state_layertag_changes`触发LayerTagAdded和LayerTagRemoved。
+#[derive(Component, Debug, Reflect)]
 pub struct StateLayerTagContainer(pub CountLayerTagContainer);
 
+impl Default for StateLayerTagContainer {
+    fn default() -> Self {
+        Self(CountLayerTagContainer::with_change_log())
+    }
+}
+
 #[derive(Resource, Default, Debug, Reflect)]
 pub struct StateLayerTagRegistry(pub LayerTagRegistry);
 
+/// LayerTag计数从0变为1，触发在StateLayerTagContainer的实体上。
+#[derive(Debug, Event, Clone)]
+pub struct LayerTagAdded {
+    pub layertag: LayerTag,
+}
+
+/// LayerTag计数从1变为0，触发在StateLayerTagContainer的实体上。
+#[derive(Debug, Event, Clone)]
+pub struct LayerTagRemoved {
+    pub layertag: LayerTag,
+}
+
+/// 查询结果变化，触发在订阅的实体上。
+#[derive(Debug, Event, Clone)]
+pub struct LayerTagQueryChanged {
+    pub name: Cow<'static, str>,
+    pub owner: Entity,
+    pub matched: bool,
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub struct StateLayerTagWatch {
+    pub name: Cow<'static, str>,
+    /// 订阅的StateLayerTagContainer所在的实体，None时使用Parent，用于技能和buff。
+    pub owner: Option<Entity>,
+    pub query: LayerTagQuery,
+    /// None表示还没有计算过，owner还没有StateLayerTagContainer时保持None。
+    matched: Option<bool>,
+}
+
+impl StateLayerTagWatch {
+    fn owner(&self, parent: Option<&Parent>) -> Option<Entity> {
+        self.owner.or(parent.map(|parent| parent.get()))
+    }
+}
+
+/// 订阅StateLayerTagContainer的查询，结果变化时触发LayerTagQueryChanged。
+/// 第一次计算的结果作为初始值，不会触发，插入组件时和owner添加容器后计算。
+#[derive(Component, Debug, Default, Clone, Reflect)]
+pub struct StateLayerTagWatcher {
+    watches: Vec<StateLayerTagWatch>,
+}
+
+impl StateLayerTagWatcher {
+    pub fn watch(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        owner: Option<Entity>,
+        query: LayerTagQuery,
+    ) -> &mut Self {
+        let name = name.into();
+        self.unwatch(&name);
+        self.watches.push(StateLayerTagWatch {
+            name,
+            owner,
+            query,
+            matched: None,
+        });
+        self
+    }
+
+    pub fn unwatch(&mut self, name: &str) {
+        self.watches.retain(|watch| watch.name != name);
+    }
+
+    pub fn get_watch(&self, name: &str) -> Option<&StateLayerTagWatch> {
+        self.watches.iter().find(|watch| watch.name == name)
+    }
+
+    pub fn is_matched(&self, name: &str) -> bool {
+        self.get_watch(name)
+            .is_some_and(|watch| watch.matched == Some(true))
+    }
+
+    /// 替换实体上name的订阅，query为None时取消订阅，用于热加载后重新构建条件。
+    /// 重新插入组件，替换后的结果和添加时一样作为初始值，不会触发。
+    pub fn replace_watch(
+        entity_commands: &mut EntityCommands,
+        current: Option<&StateLayerTagWatcher>,
+        name: &'static str,
+        query: Option<LayerTagQuery>,
+    ) {
+        let mut watcher = current.cloned().unwrap_or_default();
+        watcher.unwatch(name);
+        if let Some(query) = query {
+            watcher.watch(name, None, query);
+        }
+
+        entity_commands.remove::<StateLayerTagWatcher>();
+        if !watcher.watches.is_empty() {
+            entity_commands.insert(watcher);
+        }
+    }
+}
+
 // TODO: 设置整个游戏的State，保证执行顺序。
+/// 第一次加载时注册所有LayerTag，热加载时只处理变化的行，已经注册的LayerTag的id不变。
 pub fn init_state_layertag_registry(
     mut event_reader: EventReader<TablesLoadedEvent>,
+    mut row_changed_reader: EventReader<TableRowChanged<TbLayerTag>>,
     table: TableReader<TbLayerTag>,
     mut registry: ResMut<StateLayerTagRegistry>,
+    mut initialized: Local<bool>,
 ) {
-    if event_reader.read().len() > 0 {
+    let loaded = event_reader.read().len() > 0;
+    if !*initialized && loaded {
         if let Some(list) = table.get_data_list_in_map_table() {
             registry.0.clear();
             list.iter().for_each(|value| {
                 registry.0.register_raw(&value.raw_layertag);
             });
+            *initialized = true;
+        }
+    }
+
+    for event in row_changed_reader.read() {
+        match event.kind {
+            TableRowChangeKind::Added => registry.0.register_raw(&event.key),
+            TableRowChangeKind::Removed => {
+                if !registry.0.unregister_raw(&event.key) {
+                    warn!("layertag {} is still a parent, keep registered", event.key);
+                }
+            }
+            TableRowChangeKind::Modified => {}
         }
     }
 }
+
+/// 表加载后检查技能、buff和伤害类型中的LayerTag都已注册，以及生成的常量和表一致，只报告错误。
+pub fn validate_state_layertags(
+    mut event_reader: EventReader<TablesLoadedEvent>,
+    ability_table: TableReader<TbAbility>,
+    buff_table: TableReader<TbBuff>,
+    damage_type_table: TableReader<TbDamageType>,
+    registry: Res<StateLayerTagRegistry>,
+) {
+    if event_reader.read().len() == 0 {
+        return;
+    }
+
+    for layertag in layertags::ALL {
+        if registry.0.request_static(*layertag).is_none() {
+            error!(
+                "layertag const {} is not registered in TbLayerTag",
+                layertag
+            );
+        }
+    }
+
+    macro_rules! validate_rows {
+        ($table:expr, $name:literal) => {
+            for row in $table.get_data_list_in_map_table().into_iter().flatten() {
+                let raw_layertags = row
+                    .start_required_layertags
+                    .iter()
+                    .chain(row.start_disabled_layertags.iter())
+                    .chain(row.abort_required_layertags.iter())
+                    .chain(row.abort_disabled_layertags.iter())
+                    .chain(
+                        row.start_added_layertags
+                            .iter()
+                            .chain(row.start_removed_layertags.iter())
+                            .map(|revertable| &revertable.raw_layertag),
+                    )
+                    .map(|raw_layertag| raw_layertag.as_str());
+                for (raw_layertag, e) in validate_raw_layertags(&registry.0, raw_layertags) {
+                    error!("{} {} layertag {}: {}", $name, row.id, raw_layertag, e);
+                }
+            }
+        };
+    }
+    validate_rows!(ability_table, "ability");
+    validate_rows!(buff_table, "buff");
+
+    for row in damage_type_table
+        .get_data_list_in_map_table()
+        .into_iter()
+        .flatten()
+        .filter(|row| !row.immune_layertag.is_empty())
+    {
+        for (raw_layertag, e) in
+            validate_raw_layertags(&registry.0, std::iter::once(row.immune_layertag.as_str()))
+        {
+            error!("damage type {} layertag {}: {}", row.id, raw_layertag, e);
+        }
+    }
+}
+
+/// 插入订阅时用owner当前的容器计算初始值，Parent之后才设置时留给`update_state_layertag_changes`。
+pub fn init_state_layertag_watches(
+    trigger: Trigger<OnInsert, StateLayerTagWatcher>,
+    mut watcher_query: Query<(&mut StateLayerTagWatcher, Option<&Parent>)>,
+    state_query: Query<&StateLayerTagContainer>,
+) {
+    let Ok((mut watcher, parent)) = watcher_query.get_mut(trigger.entity()) else {
+        return;
+    };
+    for watch in watcher.watches.iter_mut() {
+        if watch.matched.is_some() {
+            continue;
+        }
+        let Some(container) = watch
+            .owner(parent)
+            .and_then(|owner| state_query.get(owner).ok())
+        else {
+            continue;
+        };
+        watch.matched = Some(watch.query.matches(&container.0));
+    }
+}
+
+/// 取出StateLayerTagContainer的变化并触发事件，然后重新计算变化的容器上的订阅。
+pub fn update_state_layertag_changes(
+    mut commands: Commands,
+    mut state_query: Query<(Entity, &mut StateLayerTagContainer)>,
+    mut watcher_query: Query<(Entity, &mut StateLayerTagWatcher, Option<&Parent>)>,
+) {
+    let mut changed_owners = HashSet::default();
+    for (entity, mut container) in state_query.iter_mut() {
+        if !container.0.has_changes() {
+            continue;
+        }
+
+        changed_owners.insert(entity);
+        for change in container.0.take_changes() {
+            match change {
+                LayerTagChange::Added(layertag) => {
+                    commands.trigger_targets(LayerTagAdded { layertag }, entity);
+                }
+                LayerTagChange::Removed(layertag) => {
+                    commands.trigger_targets(LayerTagRemoved { layertag }, entity);
+                }
+            }
+        }
+    }
+
+    for (entity, mut watcher, parent) in watcher_query.iter_mut() {
+        for watch in watcher.watches.iter_mut() {
+            let Some(owner) = watch.owner(parent) else {
+                continue;
+            };
+            if watch.matched.is_some() && !changed_owners.contains(&owner) {
+                continue;
+            }
+            let Ok((_, container)) = state_query.get(owner) else {
+                continue;
+            };
+
+            let matched = watch.query.matches(&container.0);
+            let previous = watch.matched.replace(matched);
+            if previous.is_some_and(|previous| previous != matched) {
+                commands.trigger_targets(
+                    LayerTagQueryChanged {
+                        name: watch.name.clone(),
+                        owner,
+                        matched,
+                    },
+                    entity,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use layertag::{
+        container_op::LayerTagContainer, query::LayerTagQuery, registry::LayerTagRegistry,
+    };
+
+    use super::{
+        init_state_layertag_watches, layertags, update_state_layertag_changes,
+        validate_raw_layertags, LayerTagAdded, LayerTagQueryChanged, LayerTagRemoved,
+        StateLayerTagContainer, StateLayerTagWatcher,
+    };
+
+    #[derive(Resource, Default)]
+    struct Received(Vec<String>);
+
+    #[test]
+    fn watch_layertag_changes() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("stateset.wet");
+        let wet = registry.request_from_raw("stateset.wet").unwrap();
+        let query = LayerTagQuery::parse("none(stateset.*)", &registry).unwrap();
+
+        let mut app = App::new();
+        app.init_resource::<Received>()
+            .add_systems(Update, update_state_layertag_changes)
+            .add_observer(
+                |trigger: Trigger<LayerTagAdded>, mut received: ResMut<Received>| {
+                    received
+                        .0
+                        .push(format!("added {}", trigger.event().layertag));
+                },
+            )
+            .add_observer(
+                |trigger: Trigger<LayerTagRemoved>, mut received: ResMut<Received>| {
+                    received
+                        .0
+                        .push(format!("removed {}", trigger.event().layertag));
+                },
+            )
+            .add_observer(
+                |trigger: Trigger<LayerTagQueryChanged>, mut received: ResMut<Received>| {
+                    let event = trigger.event();
+                    received.0.push(format!("{} {}", event.name, event.matched));
+                },
+            );
+
+        let owner = app
+            .world_mut()
+            .spawn(StateLayerTagContainer::default())
+            .id();
+        let mut watcher = StateLayerTagWatcher::default();
+        watcher.watch("dry", None, query);
+        app.world_mut().spawn(watcher).set_parent(owner);
+        app.update();
+        assert!(app.world().resource::<Received>().0.is_empty());
+
+        let set_wet = |app: &mut App, add: bool| {
+            let mut entity = app.world_mut().entity_mut(owner);
+            let mut container = entity.get_mut::<StateLayerTagContainer>().unwrap();
+            if add {
+                container.0.add_layertag(wet.clone());
+            } else {
+                container.0.remove_layertag(&wet);
+            }
+            app.update();
+        };
+        set_wet(&mut app, true);
+        set_wet(&mut app, true);
+        set_wet(&mut app, false);
+        set_wet(&mut app, false);
+
+        assert_eq!(
+            app.world().resource::<Received>().0,
+            vec![
+                "added stateset.wet",
+                "dry false",
+                "removed stateset.wet",
+                "dry true"
+            ]
+        );
+    }
+
+    /// owner的容器在订阅之后添加时，第一次计算的结果作为初始值。
+    #[test]
+    fn watch_container_added_after_watcher() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("stateset.late.wet");
+        let wet = registry.request_from_raw("stateset.late.wet").unwrap();
+        let query = LayerTagQuery::parse("none(stateset.late.*)", &registry).unwrap();
+
+        let mut app = App::new();
+        app.init_resource::<Received>()
+            .add_systems(Update, update_state_layertag_changes)
+            .add_observer(init_state_layertag_watches)
+            .add_observer(
+                |trigger: Trigger<LayerTagQueryChanged>, mut received: ResMut<Received>| {
+                    let event = trigger.event();
+                    received.0.push(format!("{} {}", event.name, event.matched));
+                },
+            );
+
+        let owner = app.world_mut().spawn_empty().id();
+        let mut watcher = StateLayerTagWatcher::default();
+        watcher.watch("dry", Some(owner), query.clone());
+        let watcher_entity = app.world_mut().spawn(watcher).id();
+        app.update();
+        assert!(!app
+            .world()
+            .get::<StateLayerTagWatcher>(watcher_entity)
+            .unwrap()
+            .is_matched("dry"));
+
+        app.world_mut()
+            .entity_mut(owner)
+            .insert(StateLayerTagContainer::default());
+        app.update();
+        assert!(app.world().resource::<Received>().0.is_empty());
+        assert!(app
+            .world()
+            .get::<StateLayerTagWatcher>(watcher_entity)
+            .unwrap()
+            .is_matched("dry"));
+
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(wet.clone());
+        app.update();
+        assert_eq!(app.world().resource::<Received>().0, vec!["dry false"]);
+
+        // 容器已经存在时，插入订阅就计算初始值
+        let mut watcher = StateLayerTagWatcher::default();
+        watcher.watch("dry", Some(owner), query);
+        let watcher_entity = app.world_mut().spawn(watcher).id();
+        assert_eq!(
+            app.world()
+                .get::<StateLayerTagWatcher>(watcher_entity)
+                .unwrap()
+                .get_watch("dry")
+                .unwrap()
+                .matched,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn validate_layertags() {
+        let mut registry = LayerTagRegistry::default();
+        for layertag in layertags::ALL {
+            registry.register_raw(layertag.raw_layertag());
+        }
+        assert_eq!(
+            registry.request_static(layertags::A_B_C),
+            registry.request_from_raw("A.B.C")
+        );
+        assert!(registry.request_static(layertags::A_B).is_some());
+
+        let raw_layertags = ["A.D", "A.B.*", "A.X", "any(A.D, A.Y)"];
+        let errors = validate_raw_layertags(&registry, raw_layertags.into_iter());
+        assert_eq!(
+            errors.iter().map(|(raw, _)| *raw).collect::<Vec<_>>(),
+            vec!["A.X", "any(A.D, A.Y)"]
+        );
+    }
+}
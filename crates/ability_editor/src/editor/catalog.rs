@@ -0,0 +1,144 @@
+use ability::graph::node::{
+    pin::{EffectNodeExec, EffectNodeExecGroup, EffectNodeSlot, ReflectEffectNodePinGroup},
+    ReflectInstantEffectNode,
+};
+use bevy::{
+    prelude::*,
+    reflect::{ReflectRef, TypeRegistration, TypeRegistry},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditorNodeKind {
+    Instant,
+    State,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorPinGroup {
+    pub exec: EffectNodeExec,
+    pub slots: Vec<EffectNodeSlot>,
+}
+
+/// 节点自身可以编辑的字段，写入资产的fields。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorNodeField {
+    pub name: String,
+    pub type_path: String,
+}
+
+/// 通过反射注册的节点类型，和资产构建时的要求一致。
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorNodeType {
+    /// 资产中使用的短类型名。
+    pub name: String,
+    pub type_path: String,
+    pub kind: EditorNodeKind,
+    pub inputs: Vec<EditorPinGroup>,
+    pub outputs: Vec<EditorPinGroup>,
+    pub fields: Vec<EditorNodeField>,
+}
+
+impl EditorNodeType {
+    pub fn input_exec(&self, pin: &str) -> Option<&EffectNodeExec> {
+        self.inputs
+            .iter()
+            .map(|group| &group.exec)
+            .find(|exec| exec.name == pin)
+    }
+
+    pub fn output_exec(&self, pin: &str) -> Option<&EffectNodeExec> {
+        self.outputs
+            .iter()
+            .map(|group| &group.exec)
+            .find(|exec| exec.name == pin)
+    }
+
+    pub fn input_slot(&self, pin: &str) -> Option<&EffectNodeSlot> {
+        self.inputs
+            .iter()
+            .flat_map(|group| group.slots.iter())
+            .find(|slot| slot.name == pin)
+    }
+
+    pub fn output_slot(&self, pin: &str) -> Option<&EffectNodeSlot> {
+        self.outputs
+            .iter()
+            .flat_map(|group| group.slots.iter())
+            .find(|slot| slot.name == pin)
+    }
+
+    pub fn field(&self, name: &str) -> Option<&EditorNodeField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// 编辑器可以放置的节点类型，按名字排序。
+#[derive(Debug, Default, Clone, Resource)]
+pub struct EditorNodeCatalog {
+    pub node_types: Vec<EditorNodeType>,
+}
+
+impl EditorNodeCatalog {
+    pub fn from_type_registry(type_registry: &TypeRegistry) -> Self {
+        let mut node_types = type_registry
+            .iter()
+            .filter_map(reflect_node_type)
+            .collect::<Vec<_>>();
+        node_types.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { node_types }
+    }
+
+    /// 和资产构建一样，可以是短类型名，也可以是完整的类型路径。
+    pub fn get(&self, node_type: &str) -> Option<&EditorNodeType> {
+        self.node_types
+            .iter()
+            .find(|x| x.name == node_type || x.type_path == node_type)
+    }
+}
+
+fn to_editor_pin_groups(groups: &[EffectNodeExecGroup]) -> Vec<EditorPinGroup> {
+    groups
+        .iter()
+        .map(|group| EditorPinGroup {
+            exec: group.exec,
+            slots: group.slots.clone(),
+        })
+        .collect()
+}
+
+fn reflect_node_type(registration: &TypeRegistration) -> Option<EditorNodeType> {
+    let pin_group = registration.data::<ReflectEffectNodePinGroup>()?;
+    let kind = if registration.data::<ReflectInstantEffectNode>().is_some() {
+        EditorNodeKind::Instant
+    } else if registration.data::<ReflectComponent>().is_some() {
+        EditorNodeKind::State
+    } else {
+        return None;
+    };
+    let node = registration.data::<ReflectDefault>()?.default();
+    let pin_group = pin_group.get(node.as_ref())?;
+
+    // 瞬时节点的base只保存节点id，不能编辑。
+    let fields = match node.reflect_ref() {
+        ReflectRef::Struct(node) => (0..node.field_len())
+            .filter_map(|index| {
+                let name = node.name_at(index).filter(|name| *name != "base")?;
+                Some(EditorNodeField {
+                    name: name.to_string(),
+                    type_path: node.field_at(index)?.reflect_type_path().to_string(),
+                })
+            })
+            .collect(),
+        _ => vec![],
+    };
+
+    let type_info = registration.type_info();
+    Some(EditorNodeType {
+        name: type_info.type_path_table().short_path().to_string(),
+        type_path: type_info.type_path().to_string(),
+        kind,
+        inputs: to_editor_pin_groups(pin_group.get_input_pin_group()),
+        outputs: to_editor_pin_groups(pin_group.get_output_pin_group()),
+        fields,
+    })
+}
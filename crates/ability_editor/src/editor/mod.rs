@@ -0,0 +1,102 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_inspector_egui::bevy_egui::EguiPlugin;
+
+use self::{
+    catalog::EditorNodeCatalog,
+    model::EditorGraph,
+    preview::EffectGraphPreviewPlugin,
+    ui::{
+        effect_graph_editor_ui, init_editor_node_catalog, toggle_effect_graph_editor,
+        PendingConnection,
+    },
+};
+
+pub mod catalog;
+pub mod model;
+pub mod preview;
+mod ui;
+
+/// 游戏内的Effect Graph编辑器，节点类型来自反射注册，需要在ability的插件之后添加。
+#[derive(Debug, Default)]
+pub struct EffectGraphEditorPlugin;
+
+impl Plugin for EffectGraphEditorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.add_plugins(EffectGraphPreviewPlugin)
+            .init_resource::<EffectGraphEditor>()
+            .init_resource::<EditorNodeCatalog>()
+            .add_systems(PostStartup, init_editor_node_catalog)
+            .add_systems(
+                Update,
+                (toggle_effect_graph_editor, effect_graph_editor_ui).chain(),
+            );
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct EffectGraphEditor {
+    pub open: bool,
+    pub toggle_key: KeyCode,
+    pub graph: EditorGraph,
+    /// 保存和加载的文件路径，ron或者json。
+    pub path: String,
+    pub selected_node: Option<String>,
+    pub preview_owner: Option<Entity>,
+    pub preview_entry_exec: Option<&'static str>,
+    /// 最近一次操作的结果或者错误。
+    pub message: String,
+    pending_connection: Option<PendingConnection>,
+    /// 正在编辑的值的文本。
+    value_buffers: HashMap<String, String>,
+    filter: String,
+    new_blackboard_key: String,
+}
+
+impl Default for EffectGraphEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            toggle_key: KeyCode::F9,
+            graph: EditorGraph::default(),
+            path: "assets/graphs/new.graph.ron".to_string(),
+            selected_node: None,
+            preview_owner: None,
+            preview_entry_exec: None,
+            message: String::new(),
+            pending_connection: None,
+            value_buffers: HashMap::default(),
+            filter: String::new(),
+            new_blackboard_key: String::new(),
+        }
+    }
+}
+
+impl EffectGraphEditor {
+    pub fn reset(&mut self, graph: EditorGraph) {
+        self.graph = graph;
+        self.preview_entry_exec = None;
+        self.pending_connection = None;
+        self.select(None);
+    }
+
+    pub fn select(&mut self, node: Option<String>) {
+        self.selected_node = node;
+        self.value_buffers.clear();
+    }
+
+    /// 新节点放在最右侧节点的右边。
+    fn next_node_position(&self) -> Vec2 {
+        self.graph
+            .asset
+            .nodes
+            .iter()
+            .filter_map(|node| node.position)
+            .max_by(|a, b| a.x.total_cmp(&b.x))
+            .map_or(Vec2::new(20.0, 20.0), |position| {
+                position + Vec2::new(220.0, 0.0)
+            })
+    }
+}
@@ -0,0 +1,636 @@
+use std::path::Path;
+
+use ability::graph::{
+    asset::{
+        EffectGraphAsset, EffectGraphAssetConnection, EffectGraphAssetError, EffectGraphAssetNode,
+        EffectGraphAssetPin,
+    },
+    blackboard::EffectValue,
+};
+use bevy::prelude::*;
+use thiserror::Error;
+
+use super::catalog::{EditorNodeCatalog, EditorNodeKind, EditorNodeType};
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum EditorGraphError {
+    #[error("node type {0} is not registered")]
+    UnknownNodeType(String),
+    #[error("graph has no entry node")]
+    MissingEntry,
+    #[error("node {0} can not connect to itself")]
+    SelfConnection(String),
+    #[error("node {node}: field {field} not found")]
+    FieldNotFound { node: String, field: String },
+    #[error(transparent)]
+    Asset(#[from] EffectGraphAssetError),
+}
+
+#[derive(Debug, Error)]
+pub enum EditorGraphIoError {
+    #[error("could not access effect graph file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse ron effect graph: {0}")]
+    RonParse(#[from] ron::error::SpannedError),
+    #[error("could not write ron effect graph: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("could not parse json effect graph: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid effect graph: {0}")]
+    Invalid(#[from] EditorGraphError),
+}
+
+/// 编辑中的图，所有修改都通过节点类型表检查pin和类型，不依赖窗口。
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditorGraph {
+    pub asset: EffectGraphAsset,
+}
+
+impl Default for EditorGraph {
+    fn default() -> Self {
+        Self::new(EffectGraphAsset {
+            entry: String::new(),
+            nodes: vec![],
+            exec_connections: vec![],
+            slot_connections: vec![],
+            blackboard: Default::default(),
+        })
+    }
+}
+
+impl EditorGraph {
+    pub fn new(asset: EffectGraphAsset) -> Self {
+        Self { asset }
+    }
+
+    pub fn get_node(&self, id: &str) -> Option<&EffectGraphAssetNode> {
+        self.asset.get_node(id)
+    }
+
+    fn get_node_mut(&mut self, id: &str) -> Result<&mut EffectGraphAssetNode, EditorGraphError> {
+        self.asset
+            .nodes
+            .iter_mut()
+            .find(|node| node.id == id)
+            .ok_or_else(|| node_not_found(id))
+    }
+
+    pub fn node_type<'a>(
+        &self,
+        catalog: &'a EditorNodeCatalog,
+        id: &str,
+    ) -> Result<&'a EditorNodeType, EditorGraphError> {
+        let node = self.get_node(id).ok_or_else(|| node_not_found(id))?;
+        catalog.get(&node.node_type).ok_or_else(|| {
+            EffectGraphAssetError::UnknownNodeType {
+                node: node.id.clone(),
+                node_type: node.node_type.clone(),
+            }
+            .into()
+        })
+    }
+
+    /// 添加节点并返回节点id，没有entry时第一个状态节点作为entry。
+    pub fn add_node(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        node_type: &str,
+        position: Vec2,
+    ) -> Result<String, EditorGraphError> {
+        let editor_node_type = catalog
+            .get(node_type)
+            .ok_or_else(|| EditorGraphError::UnknownNodeType(node_type.to_string()))?;
+
+        let prefix = node_id_prefix(&editor_node_type.name);
+        let id = (1..)
+            .map(|index| format!("{}_{}", prefix, index))
+            .find(|id| self.get_node(id).is_none())
+            .expect("node id index is unbounded");
+
+        self.asset.nodes.push(EffectGraphAssetNode {
+            id: id.clone(),
+            node_type: editor_node_type.name.clone(),
+            fields: Default::default(),
+            inputs: Default::default(),
+            position: Some(position),
+        });
+        if self.asset.entry.is_empty() && editor_node_type.kind == EditorNodeKind::State {
+            self.asset.entry = id.clone();
+        }
+        Ok(id)
+    }
+
+    /// 同时移除节点的所有连接。
+    pub fn remove_node(&mut self, id: &str) -> Result<EffectGraphAssetNode, EditorGraphError> {
+        let index = self
+            .asset
+            .nodes
+            .iter()
+            .position(|node| node.id == id)
+            .ok_or_else(|| node_not_found(id))?;
+        let is_connected = |connection: &EffectGraphAssetConnection| {
+            connection.from.node == id || connection.to.node == id
+        };
+        self.asset.exec_connections.retain(|x| !is_connected(x));
+        self.asset.slot_connections.retain(|x| !is_connected(x));
+        if self.asset.entry == id {
+            self.asset.entry.clear();
+        }
+        Ok(self.asset.nodes.remove(index))
+    }
+
+    pub fn move_node(&mut self, id: &str, position: Vec2) -> Result<(), EditorGraphError> {
+        self.get_node_mut(id)?.position = Some(position);
+        Ok(())
+    }
+
+    pub fn set_entry(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        id: &str,
+    ) -> Result<(), EditorGraphError> {
+        if self.node_type(catalog, id)?.kind != EditorNodeKind::State {
+            return Err(EffectGraphAssetError::EntryNotStateNode {
+                node: id.to_string(),
+            }
+            .into());
+        }
+        self.asset.entry = id.to_string();
+        Ok(())
+    }
+
+    fn check_exec_connection(
+        &self,
+        catalog: &EditorNodeCatalog,
+        connection: &EffectGraphAssetConnection,
+    ) -> Result<(), EditorGraphError> {
+        let (from, to) = (&connection.from, &connection.to);
+        if self
+            .node_type(catalog, &from.node)?
+            .output_exec(&from.pin)
+            .is_none()
+        {
+            return Err(EffectGraphAssetError::OutputExecPinNotFound {
+                node: from.node.clone(),
+                pin: from.pin.clone(),
+            }
+            .into());
+        }
+        if self
+            .node_type(catalog, &to.node)?
+            .input_exec(&to.pin)
+            .is_none()
+        {
+            return Err(EffectGraphAssetError::InputExecPinNotFound {
+                node: to.node.clone(),
+                pin: to.pin.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_slot_connection(
+        &self,
+        catalog: &EditorNodeCatalog,
+        connection: &EffectGraphAssetConnection,
+    ) -> Result<(), EditorGraphError> {
+        let (from, to) = (&connection.from, &connection.to);
+        let from_slot = self
+            .node_type(catalog, &from.node)?
+            .output_slot(&from.pin)
+            .ok_or_else(|| EffectGraphAssetError::OutputSlotPinNotFound {
+                node: from.node.clone(),
+                pin: from.pin.clone(),
+            })?;
+        let to_slot = self
+            .node_type(catalog, &to.node)?
+            .input_slot(&to.pin)
+            .ok_or_else(|| EffectGraphAssetError::InputSlotPinNotFound {
+                node: to.node.clone(),
+                pin: to.pin.clone(),
+            })?;
+        if !from_slot.can_connect_to(to_slot) {
+            return Err(EffectGraphAssetError::SlotTypeMismatch {
+                from: from.clone(),
+                to: to.clone(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    fn check_input_value(
+        &self,
+        catalog: &EditorNodeCatalog,
+        id: &str,
+        pin: &str,
+        value: &EffectValue,
+    ) -> Result<(), EditorGraphError> {
+        let slot = self
+            .node_type(catalog, id)?
+            .input_slot(pin)
+            .ok_or_else(|| EffectGraphAssetError::InputSlotPinNotFound {
+                node: id.to_string(),
+                pin: pin.to_string(),
+            })?;
+        if value.widen_to(slot.pin_type).is_none() {
+            return Err(EffectGraphAssetError::InputValueTypeMismatch {
+                node: id.to_string(),
+                pin: pin.to_string(),
+                pin_type: slot.pin_type_name,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// 一个输出exec pin可以连接多个输入exec pin。
+    pub fn connect_exec(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        from: EffectGraphAssetPin,
+        to: EffectGraphAssetPin,
+    ) -> Result<(), EditorGraphError> {
+        if from.node == to.node {
+            return Err(EditorGraphError::SelfConnection(from.node));
+        }
+        let connection = EffectGraphAssetConnection { from, to };
+        self.check_exec_connection(catalog, &connection)?;
+        if !self.asset.exec_connections.contains(&connection) {
+            self.asset.exec_connections.push(connection);
+        }
+        Ok(())
+    }
+
+    /// 一个输入slot只有一个来源，替换已有的连接。
+    pub fn connect_slot(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        from: EffectGraphAssetPin,
+        to: EffectGraphAssetPin,
+    ) -> Result<(), EditorGraphError> {
+        if from.node == to.node {
+            return Err(EditorGraphError::SelfConnection(from.node));
+        }
+        let connection = EffectGraphAssetConnection { from, to };
+        self.check_slot_connection(catalog, &connection)?;
+        self.asset
+            .slot_connections
+            .retain(|x| x.to != connection.to);
+        self.asset.slot_connections.push(connection);
+        Ok(())
+    }
+
+    pub fn disconnect_exec(
+        &mut self,
+        from: &EffectGraphAssetPin,
+        to: &EffectGraphAssetPin,
+    ) -> bool {
+        let len = self.asset.exec_connections.len();
+        self.asset
+            .exec_connections
+            .retain(|x| x.from != *from || x.to != *to);
+        len != self.asset.exec_connections.len()
+    }
+
+    pub fn disconnect_slot(
+        &mut self,
+        from: &EffectGraphAssetPin,
+        to: &EffectGraphAssetPin,
+    ) -> bool {
+        let len = self.asset.slot_connections.len();
+        self.asset
+            .slot_connections
+            .retain(|x| x.from != *from || x.to != *to);
+        len != self.asset.slot_connections.len()
+    }
+
+    /// 设置输入slot的默认值，None时移除。
+    pub fn set_input_value(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        id: &str,
+        pin: &str,
+        value: Option<EffectValue>,
+    ) -> Result<(), EditorGraphError> {
+        if let Some(value) = value.as_ref() {
+            self.check_input_value(catalog, id, pin, value)?;
+        }
+        let node = self.get_node_mut(id)?;
+        match value {
+            Some(value) => node.inputs.insert(pin.to_string(), value),
+            None => node.inputs.remove(pin),
+        };
+        Ok(())
+    }
+
+    /// 字段的值在构建时通过反射检查。
+    pub fn set_field(
+        &mut self,
+        catalog: &EditorNodeCatalog,
+        id: &str,
+        field: &str,
+        value: Option<EffectValue>,
+    ) -> Result<(), EditorGraphError> {
+        if self.node_type(catalog, id)?.field(field).is_none() {
+            return Err(EditorGraphError::FieldNotFound {
+                node: id.to_string(),
+                field: field.to_string(),
+            });
+        }
+        let node = self.get_node_mut(id)?;
+        match value {
+            Some(value) => node.fields.insert(field.to_string(), value),
+            None => node.fields.remove(field),
+        };
+        Ok(())
+    }
+
+    pub fn set_blackboard_value(&mut self, key: impl Into<String>, value: Option<EffectValue>) {
+        let key = key.into();
+        match value {
+            Some(value) => self.asset.blackboard.insert(key, value),
+            None => self.asset.blackboard.remove(&key),
+        };
+    }
+
+    /// 检查所有节点、pin和类型，除了字段的值，通过时资产可以构建。
+    pub fn validate(&self, catalog: &EditorNodeCatalog) -> Result<(), EditorGraphError> {
+        if self.asset.entry.is_empty() {
+            return Err(EditorGraphError::MissingEntry);
+        }
+        self.asset.validate()?;
+
+        for node in self.asset.nodes.iter() {
+            let node_type = self.node_type(catalog, &node.id)?;
+            for field in node.fields.keys() {
+                if node_type.field(field).is_none() {
+                    return Err(EditorGraphError::FieldNotFound {
+                        node: node.id.clone(),
+                        field: field.clone(),
+                    });
+                }
+            }
+            for (pin, value) in node.inputs.iter() {
+                self.check_input_value(catalog, &node.id, pin, value)?;
+            }
+        }
+        if self.node_type(catalog, &self.asset.entry)?.kind != EditorNodeKind::State {
+            return Err(EffectGraphAssetError::EntryNotStateNode {
+                node: self.asset.entry.clone(),
+            }
+            .into());
+        }
+
+        for connection in self.asset.exec_connections.iter() {
+            self.check_exec_connection(catalog, connection)?;
+        }
+        for connection in self.asset.slot_connections.iter() {
+            self.check_slot_connection(catalog, connection)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(&self.asset, ron::ser::PrettyConfig::default())
+    }
+
+    /// 和资产加载一样，根据扩展名读取ron或者json。
+    pub fn load(path: &Path) -> Result<Self, EditorGraphIoError> {
+        let bytes = std::fs::read(path)?;
+        let asset = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            EffectGraphAsset::from_json(&bytes)?
+        } else {
+            EffectGraphAsset::from_ron(&bytes)?
+        };
+        Ok(Self::new(asset))
+    }
+
+    /// 检查通过才会保存，避免保存无法加载的资产。
+    pub fn save(&self, catalog: &EditorNodeCatalog, path: &Path) -> Result<(), EditorGraphIoError> {
+        self.validate(catalog)?;
+        let content = if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            serde_json::to_string_pretty(&self.asset)?
+        } else {
+            self.to_ron()?
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+}
+
+fn node_not_found(id: &str) -> EditorGraphError {
+    EffectGraphAssetError::NodeNotFound {
+        node: id.to_string(),
+    }
+    .into()
+}
+
+/// EffectNodeMath => math。
+fn node_id_prefix(node_type: &str) -> String {
+    let name = node_type.strip_prefix("EffectNode").unwrap_or(node_type);
+    let mut prefix = String::with_capacity(name.len() + 4);
+    for (index, c) in name.chars().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            prefix.push('_');
+        }
+        prefix.extend(c.to_lowercase());
+    }
+    if prefix.is_empty() {
+        "node".to_string()
+    } else {
+        prefix
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use ability::{
+        ability::node::ability_entry::EffectNodeAbilityEntry,
+        graph::{
+            asset::{EffectGraphAsset, EffectGraphAssetError, EffectGraphAssetPin},
+            blackboard::EffectValue,
+            node::implement::{
+                blackboard::{EffectNodeGetBlackboard, EffectNodeSetBlackboard},
+                math::EffectNodeMath,
+            },
+        },
+    };
+    use bevy::{prelude::*, reflect::TypeRegistry};
+
+    use crate::editor::catalog::{EditorNodeCatalog, EditorNodeKind};
+
+    use super::{EditorGraph, EditorGraphError};
+
+    pub(crate) fn new_catalog() -> EditorNodeCatalog {
+        let mut type_registry = TypeRegistry::default();
+        type_registry.register::<EffectNodeAbilityEntry>();
+        type_registry.register::<EffectNodeGetBlackboard>();
+        type_registry.register::<EffectNodeSetBlackboard>();
+        type_registry.register::<EffectNodeMath>();
+        type_registry.register::<EffectValue>();
+        EditorNodeCatalog::from_type_registry(&type_registry)
+    }
+
+    fn pin(node: &str, pin: &str) -> EffectGraphAssetPin {
+        EffectGraphAssetPin {
+            node: node.into(),
+            pin: pin.into(),
+        }
+    }
+
+    /// 和资产测试中的图相同：result = damage * 2。
+    pub(crate) fn new_graph(catalog: &EditorNodeCatalog) -> EditorGraph {
+        let mut graph = EditorGraph::default();
+        for node_type in [
+            "EffectNodeAbilityEntry",
+            "EffectNodeGetBlackboard",
+            "EffectNodeMath",
+            "EffectNodeSetBlackboard",
+        ] {
+            graph.add_node(catalog, node_type, Vec2::ZERO).unwrap();
+        }
+        for (from, to) in [
+            (
+                pin("ability_entry_1", "start"),
+                pin("get_blackboard_1", "start"),
+            ),
+            (pin("get_blackboard_1", "finish"), pin("math_1", "start")),
+            (pin("math_1", "finish"), pin("set_blackboard_1", "start")),
+        ] {
+            graph.connect_exec(catalog, from, to).unwrap();
+        }
+        graph
+            .connect_slot(
+                catalog,
+                pin("get_blackboard_1", "value"),
+                pin("math_1", "a"),
+            )
+            .unwrap();
+        graph
+            .connect_slot(
+                catalog,
+                pin("math_1", "result"),
+                pin("set_blackboard_1", "value"),
+            )
+            .unwrap();
+        graph
+            .set_input_value(
+                catalog,
+                "get_blackboard_1",
+                "key",
+                Some(EffectValue::String("damage".into())),
+            )
+            .unwrap();
+        graph
+            .set_input_value(
+                catalog,
+                "set_blackboard_1",
+                "key",
+                Some(EffectValue::String("result".into())),
+            )
+            .unwrap();
+        graph
+            .set_input_value(catalog, "math_1", "b", Some(EffectValue::F32(2.0)))
+            .unwrap();
+        graph
+            .set_field(
+                catalog,
+                "math_1",
+                "op",
+                Some(EffectValue::String("Mul".into())),
+            )
+            .unwrap();
+        graph.set_blackboard_value("damage", Some(EffectValue::F32(10.0)));
+        graph
+    }
+
+    #[test]
+    fn catalog_from_reflection() {
+        let catalog = new_catalog();
+        assert_eq!(catalog.node_types.len(), 4);
+
+        let entry = catalog.get("EffectNodeAbilityEntry").unwrap();
+        assert_eq!(entry.kind, EditorNodeKind::State);
+        assert!(entry.output_exec("ready").is_some());
+
+        let math = catalog.get("EffectNodeMath").unwrap();
+        assert_eq!(math.kind, EditorNodeKind::Instant);
+        assert!(math.input_slot("a").is_some());
+        assert!(math.field("op").is_some());
+        assert!(math.field("base").is_none());
+        assert_eq!(catalog.get(&math.type_path), Some(math));
+    }
+
+    #[test]
+    fn edit_and_save_graph() {
+        let catalog = new_catalog();
+        let mut graph = new_graph(&catalog);
+        assert_eq!(graph.asset.entry, "ability_entry_1");
+        assert_eq!(graph.validate(&catalog), Ok(()));
+
+        assert_eq!(
+            graph.connect_slot(
+                &catalog,
+                pin("ability_entry_1", "target"),
+                pin("set_blackboard_1", "key")
+            ),
+            Err(EditorGraphError::Asset(
+                EffectGraphAssetError::SlotTypeMismatch {
+                    from: pin("ability_entry_1", "target"),
+                    to: pin("set_blackboard_1", "key"),
+                }
+            ))
+        );
+        assert_eq!(
+            graph.connect_exec(
+                &catalog,
+                pin("math_1", "start"),
+                pin("set_blackboard_1", "start")
+            ),
+            Err(EditorGraphError::Asset(
+                EffectGraphAssetError::OutputExecPinNotFound {
+                    node: "math_1".into(),
+                    pin: "start".into(),
+                }
+            ))
+        );
+        assert!(matches!(
+            graph.set_input_value(
+                &catalog,
+                "get_blackboard_1",
+                "key",
+                Some(EffectValue::I32(1))
+            ),
+            Err(EditorGraphError::Asset(
+                EffectGraphAssetError::InputValueTypeMismatch { .. }
+            ))
+        ));
+        assert!(matches!(
+            graph.set_entry(&catalog, "math_1"),
+            Err(EditorGraphError::Asset(
+                EffectGraphAssetError::EntryNotStateNode { .. }
+            ))
+        ));
+
+        let ron = graph.to_ron().unwrap();
+        assert_eq!(
+            EditorGraph::new(EffectGraphAsset::from_ron(ron.as_bytes()).unwrap()),
+            graph
+        );
+
+        graph.remove_node("ability_entry_1").unwrap();
+        assert_eq!(graph.asset.exec_connections.len(), 2);
+        assert_eq!(
+            graph.validate(&catalog),
+            Err(EditorGraphError::MissingEntry)
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use ability::graph::{
+    asset::{EffectGraphAsset, EffectGraphAssetError},
+    context::{EffectGraphContext, GraphRef, InstantEffectNodeMap},
+    executor::EffectGraphExecutor,
+    node::pin::EffectNodeExec,
+    pin::EffectNodeExecPin,
+    pool::{spawn_effect_graph_instance, EffectGraphPool},
+    template::EffectGraphTemplates,
+    EffectGraphOwner, EffectGraphUpdateSystemSet,
+};
+use bevy::prelude::*;
+
+/// 在owner上运行编辑中的图，每次预览重新构建模板，不影响EffectGraphMap中的图。
+#[derive(Debug, Event, Clone)]
+pub struct EffectGraphPreviewEvent {
+    pub owner: Entity,
+    /// entry节点开始执行的输出exec pin。
+    pub entry_exec: EffectNodeExec,
+    pub graph: EffectGraphAsset,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct EffectGraphPreview {
+    pub template: Option<GraphRef>,
+    pub instance: Option<Entity>,
+    pub error: Option<EffectGraphAssetError>,
+    /// 等待实例clone完成后执行。
+    pending_exec: Option<EffectNodeExec>,
+}
+
+#[derive(Debug, Default)]
+pub struct EffectGraphPreviewPlugin;
+
+impl Plugin for EffectGraphPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectGraphPreview>()
+            .add_event::<EffectGraphPreviewEvent>()
+            .add_observer(trigger_effect_graph_preview)
+            .add_systems(
+                Update,
+                start_effect_graph_preview.before(EffectGraphUpdateSystemSet::Execute),
+            );
+    }
+}
+
+/// 上一次预览的实例和模板直接销毁，不放回池中。
+fn trigger_effect_graph_preview(
+    trigger: Trigger<EffectGraphPreviewEvent>,
+    mut commands: Commands,
+    mut preview: ResMut<EffectGraphPreview>,
+    mut pool: ResMut<EffectGraphPool>,
+    mut templates: ResMut<EffectGraphTemplates>,
+    mut instant_map: ResMut<InstantEffectNodeMap>,
+    type_registry: Res<AppTypeRegistry>,
+) {
+    let event = trigger.event();
+    let graph = match event
+        .graph
+        .build(&mut commands, &mut instant_map, &type_registry.read())
+    {
+        Ok(graph) => graph,
+        Err(e) => {
+            error!("preview effect graph failed: {}", e);
+            preview.error = Some(e);
+            return;
+        }
+    };
+
+    if let Some(instance) = preview.instance.take() {
+        if let Some(mut entity) = commands.get_entity(instance) {
+            entity.despawn_recursive();
+        }
+    }
+    if let Some(template) = preview.template.take() {
+        templates.remove(template);
+        commands.entity(template.get_entity()).despawn_recursive();
+    }
+
+    let graph_ref = GraphRef::new(graph);
+    commands.entity(event.owner).insert(EffectGraphOwner);
+    let instance = spawn_effect_graph_instance(&mut commands, &mut pool, graph_ref, event.owner);
+    info!(
+        "preview effect graph: {:?} => {:?} => {:?}",
+        event.owner, graph, instance
+    );
+
+    preview.template = Some(graph_ref);
+    preview.instance = Some(instance);
+    preview.error = None;
+    preview.pending_exec = Some(event.entry_exec);
+}
+
+fn start_effect_graph_preview(
+    mut preview: ResMut<EffectGraphPreview>,
+    mut graph_query: Query<(&EffectGraphContext, &mut EffectGraphExecutor)>,
+) {
+    let (Some(instance), Some(exec)) = (preview.instance, preview.pending_exec) else {
+        return;
+    };
+    let Ok((context, mut executor)) = graph_query.get_mut(instance) else {
+        return;
+    };
+    let Some(entry_node) = context.get_entry_node() else {
+        return;
+    };
+
+    executor.start_push_output_pin(EffectNodeExecPin {
+        node_id: entry_node.into(),
+        exec,
+    });
+    preview.pending_exec = None;
+}
+
+#[cfg(test)]
+mod tests {
+    use ability::{
+        ability::node::ability_entry::{EffectNodeAbilityEntry, EffectNodeAbilityEntryPlugin},
+        graph::{
+            blackboard::EffectValue,
+            context::EffectGraphContext,
+            node::{
+                implement::{blackboard::EffectNodeBlackboardPlugin, math::EffectNodeMathPlugin},
+                plugin::EffectNodePlugin,
+            },
+            EffectGraphPlugin,
+        },
+    };
+    use bevy::prelude::*;
+
+    use crate::editor::{catalog::EditorNodeCatalog, model::tests::new_graph};
+
+    use super::{EffectGraphPreview, EffectGraphPreviewEvent, EffectGraphPreviewPlugin};
+
+    #[test]
+    fn preview_graph_on_owner() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugins(EffectGraphPlugin)
+            .add_plugins(EffectNodePlugin)
+            .add_plugins((
+                EffectNodeAbilityEntryPlugin,
+                EffectNodeBlackboardPlugin,
+                EffectNodeMathPlugin,
+                EffectGraphPreviewPlugin,
+            ));
+
+        let catalog = EditorNodeCatalog::from_type_registry(
+            &app.world().resource::<AppTypeRegistry>().read(),
+        );
+        let graph = new_graph(&catalog);
+        let owner = app.world_mut().spawn_empty().id();
+
+        for _ in 0..2 {
+            app.world_mut().trigger(EffectGraphPreviewEvent {
+                owner,
+                entry_exec: EffectNodeAbilityEntry::OUTPUT_EXEC_START.into(),
+                graph: graph.asset.clone(),
+            });
+            app.update();
+            app.update();
+
+            let instance = app
+                .world()
+                .resource::<EffectGraphPreview>()
+                .instance
+                .unwrap();
+            assert_eq!(
+                app.world()
+                    .get::<EffectGraphContext>(instance)
+                    .and_then(|context| context.blackboard.get_value("result").cloned()),
+                Some(EffectValue::F32(20.0))
+            );
+        }
+        assert_eq!(app.world().get::<Children>(owner).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,539 @@
+use std::path::Path;
+
+use ability::graph::{asset::EffectGraphAssetPin, blackboard::EffectValue, EffectGraphOwner};
+use bevy::{prelude::*, utils::HashMap};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts};
+
+use super::{
+    catalog::{EditorNodeCatalog, EditorNodeKind},
+    model::EditorGraph,
+    preview::{EffectGraphPreview, EffectGraphPreviewEvent},
+    EffectGraphEditor,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum EditorPinKind {
+    Exec,
+    Slot,
+}
+
+/// 点击输出pin之后等待点击输入pin。
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PendingConnection {
+    pub kind: EditorPinKind,
+    pub from: EffectGraphAssetPin,
+}
+
+const NODE_WIDTH: f32 = 180.0;
+const EXEC_COLOR: egui::Color32 = egui::Color32::WHITE;
+const SLOT_COLOR: egui::Color32 = egui::Color32::LIGHT_BLUE;
+
+pub(crate) fn toggle_effect_graph_editor(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut editor: ResMut<EffectGraphEditor>,
+) {
+    if keyboard_input.just_pressed(editor.toggle_key) {
+        editor.open = !editor.open;
+    }
+}
+
+pub(crate) fn init_editor_node_catalog(
+    mut commands: Commands,
+    type_registry: Res<AppTypeRegistry>,
+) {
+    commands.insert_resource(EditorNodeCatalog::from_type_registry(&type_registry.read()));
+}
+
+pub(crate) fn effect_graph_editor_ui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut editor: ResMut<EffectGraphEditor>,
+    catalog: Res<EditorNodeCatalog>,
+    preview: Res<EffectGraphPreview>,
+    owner_query: Query<(Entity, Option<&Name>), With<EffectGraphOwner>>,
+) {
+    if !editor.open {
+        return;
+    }
+    let ctx = contexts.ctx_mut();
+    let editor = editor.as_mut();
+
+    egui::TopBottomPanel::top("effect_graph_editor_toolbar").show(ctx, |ui| {
+        toolbar_ui(ui, &mut commands, editor, &catalog, &preview, &owner_query);
+    });
+    egui::SidePanel::left("effect_graph_editor_palette")
+        .default_width(200.0)
+        .show(ctx, |ui| palette_ui(ui, editor, &catalog));
+    egui::SidePanel::right("effect_graph_editor_inspector")
+        .default_width(260.0)
+        .show(ctx, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| inspector_ui(ui, editor, &catalog));
+        });
+    egui::CentralPanel::default().show(ctx, |ui| canvas_ui(ui, editor, &catalog));
+}
+
+fn toolbar_ui(
+    ui: &mut egui::Ui,
+    commands: &mut Commands,
+    editor: &mut EffectGraphEditor,
+    catalog: &EditorNodeCatalog,
+    preview: &EffectGraphPreview,
+    owner_query: &Query<(Entity, Option<&Name>), With<EffectGraphOwner>>,
+) {
+    ui.horizontal(|ui| {
+        ui.label("path");
+        ui.text_edit_singleline(&mut editor.path);
+        if ui.button("New").clicked() {
+            editor.reset(EditorGraph::default());
+        }
+        if ui.button("Load").clicked() {
+            match EditorGraph::load(Path::new(&editor.path)) {
+                Ok(graph) => {
+                    editor.reset(graph);
+                    editor.message = format!("loaded {}", editor.path);
+                }
+                Err(e) => editor.message = e.to_string(),
+            }
+        }
+        if ui.button("Save").clicked() {
+            editor.message = match editor.graph.save(catalog, Path::new(&editor.path)) {
+                Ok(()) => format!("saved {}", editor.path),
+                Err(e) => e.to_string(),
+            };
+        }
+        if ui.button("Validate").clicked() {
+            editor.message = match editor.graph.validate(catalog) {
+                Ok(()) => "graph is valid".to_string(),
+                Err(e) => e.to_string(),
+            };
+        }
+    });
+
+    ui.horizontal(|ui| {
+        let owner_name = |entity: Entity| {
+            owner_query
+                .get(entity)
+                .ok()
+                .and_then(|(_, name)| name.map(|name| format!("{} {}", name, entity)))
+                .unwrap_or_else(|| entity.to_string())
+        };
+        egui::ComboBox::from_label("owner")
+            .selected_text(editor.preview_owner.map(owner_name).unwrap_or_default())
+            .show_ui(ui, |ui| {
+                for (entity, _) in owner_query.iter() {
+                    ui.selectable_value(
+                        &mut editor.preview_owner,
+                        Some(entity),
+                        owner_name(entity),
+                    );
+                }
+            });
+
+        let entry_execs = editor
+            .graph
+            .node_type(catalog, &editor.graph.asset.entry)
+            .map(|node_type| {
+                node_type
+                    .outputs
+                    .iter()
+                    .map(|group| group.exec.name)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        egui::ComboBox::from_label("entry exec")
+            .selected_text(editor.preview_entry_exec.unwrap_or_default())
+            .show_ui(ui, |ui| {
+                for exec in entry_execs {
+                    ui.selectable_value(&mut editor.preview_entry_exec, Some(exec), exec);
+                }
+            });
+
+        let can_preview = editor.preview_owner.is_some() && editor.preview_entry_exec.is_some();
+        if ui
+            .add_enabled(can_preview, egui::Button::new("Preview"))
+            .clicked()
+        {
+            match editor.graph.validate(catalog) {
+                Ok(()) => commands.trigger(EffectGraphPreviewEvent {
+                    owner: editor.preview_owner.unwrap(),
+                    entry_exec: editor.preview_entry_exec.unwrap().into(),
+                    graph: editor.graph.asset.clone(),
+                }),
+                Err(e) => editor.message = e.to_string(),
+            }
+        }
+        if let Some(error) = preview.error.as_ref() {
+            ui.colored_label(egui::Color32::RED, error.to_string());
+        }
+    });
+
+    if !editor.message.is_empty() {
+        ui.label(&editor.message);
+    }
+}
+
+fn palette_ui(ui: &mut egui::Ui, editor: &mut EffectGraphEditor, catalog: &EditorNodeCatalog) {
+    ui.heading("Nodes");
+    ui.text_edit_singleline(&mut editor.filter);
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        for node_type in catalog.node_types.iter() {
+            if !editor.filter.is_empty()
+                && !node_type
+                    .name
+                    .to_lowercase()
+                    .contains(&editor.filter.to_lowercase())
+            {
+                continue;
+            }
+            let label = match node_type.kind {
+                EditorNodeKind::Instant => node_type.name.clone(),
+                EditorNodeKind::State => format!("{} (state)", node_type.name),
+            };
+            if ui
+                .button(label)
+                .on_hover_text(&node_type.type_path)
+                .clicked()
+            {
+                let position = editor.next_node_position();
+                match editor.graph.add_node(catalog, &node_type.name, position) {
+                    Ok(id) => editor.select(Some(id)),
+                    Err(e) => editor.message = e.to_string(),
+                }
+            }
+        }
+    });
+}
+
+/// 值使用ron格式编辑，例如`F32(1.0)`和`String("key")`，输入为空时移除。
+fn value_ui(
+    ui: &mut egui::Ui,
+    buffers: &mut HashMap<String, String>,
+    key: String,
+    value: Option<&EffectValue>,
+) -> Option<Option<EffectValue>> {
+    let buffer = buffers.entry(key).or_insert_with(|| {
+        value
+            .and_then(|value| ron::to_string(value).ok())
+            .unwrap_or_default()
+    });
+    let response = ui.text_edit_singleline(buffer);
+    if !response.lost_focus() {
+        return None;
+    }
+    if buffer.trim().is_empty() {
+        return Some(None);
+    }
+    ron::from_str::<EffectValue>(buffer).ok().map(Some)
+}
+
+fn inspector_ui(ui: &mut egui::Ui, editor: &mut EffectGraphEditor, catalog: &EditorNodeCatalog) {
+    if let Some(id) = editor.selected_node.clone() {
+        ui.heading(&id);
+        let Ok(node_type) = editor.graph.node_type(catalog, &id) else {
+            ui.label("unknown node type");
+            return;
+        };
+        ui.label(&node_type.type_path);
+
+        ui.horizontal(|ui| {
+            if node_type.kind == EditorNodeKind::State
+                && editor.graph.asset.entry != id
+                && ui.button("Set Entry").clicked()
+            {
+                if let Err(e) = editor.graph.set_entry(catalog, &id) {
+                    editor.message = e.to_string();
+                }
+            }
+            if ui.button("Remove").clicked() {
+                let _ = editor.graph.remove_node(&id);
+                editor.select(None);
+            }
+        });
+        let Some(node) = editor.graph.get_node(&id).cloned() else {
+            return;
+        };
+
+        ui.separator();
+        ui.label("fields");
+        for field in node_type.fields.iter() {
+            ui.horizontal(|ui| {
+                ui.label(&field.name).on_hover_text(&field.type_path);
+                let key = format!("field.{}", field.name);
+                if let Some(value) = value_ui(
+                    ui,
+                    &mut editor.value_buffers,
+                    key,
+                    node.fields.get(&field.name),
+                ) {
+                    if let Err(e) = editor.graph.set_field(catalog, &id, &field.name, value) {
+                        editor.message = e.to_string();
+                    }
+                }
+            });
+        }
+
+        ui.separator();
+        ui.label("inputs");
+        for slot in node_type.inputs.iter().flat_map(|group| group.slots.iter()) {
+            let connected = editor
+                .graph
+                .asset
+                .slot_connections
+                .iter()
+                .any(|x| x.to.node == id && x.to.pin == slot.name);
+            ui.horizontal(|ui| {
+                ui.label(slot.name).on_hover_text(slot.pin_type_name);
+                if connected {
+                    ui.label("connected");
+                    return;
+                }
+                let key = format!("input.{}", slot.name);
+                if let Some(value) = value_ui(
+                    ui,
+                    &mut editor.value_buffers,
+                    key,
+                    node.inputs.get(slot.name),
+                ) {
+                    if let Err(e) = editor.graph.set_input_value(catalog, &id, slot.name, value) {
+                        editor.message = e.to_string();
+                    }
+                }
+            });
+        }
+        ui.separator();
+    }
+
+    ui.heading("Blackboard");
+    let blackboard = editor.graph.asset.blackboard.clone();
+    for (key, value) in blackboard.iter() {
+        ui.horizontal(|ui| {
+            ui.label(key);
+            let buffer_key = format!("blackboard.{}", key);
+            if let Some(value) = value_ui(ui, &mut editor.value_buffers, buffer_key, Some(value)) {
+                editor.graph.set_blackboard_value(key.clone(), value);
+            }
+        });
+    }
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut editor.new_blackboard_key);
+        if ui.button("Add").clicked() && !editor.new_blackboard_key.is_empty() {
+            let key = std::mem::take(&mut editor.new_blackboard_key);
+            editor
+                .graph
+                .set_blackboard_value(key, Some(EffectValue::F32(0.0)));
+        }
+    });
+}
+
+/// 点击输出pin开始连接，再点击输入pin完成连接，右键点击输入pin断开连接。
+fn canvas_ui(ui: &mut egui::Ui, editor: &mut EffectGraphEditor, catalog: &EditorNodeCatalog) {
+    let canvas = ui.max_rect();
+    let mut pin_positions: HashMap<(String, String, bool), egui::Pos2> = HashMap::default();
+
+    let nodes = editor.graph.asset.nodes.clone();
+    for node in nodes.iter() {
+        let Some(node_type) = catalog.get(&node.node_type) else {
+            continue;
+        };
+        let position = node.position.unwrap_or_default();
+        let area = egui::Area::new(egui::Id::new(("effect_graph_node", &node.id)))
+            .current_pos(canvas.min + egui::vec2(position.x, position.y))
+            .movable(true)
+            .show(ui.ctx(), |ui| {
+                let selected = editor.selected_node.as_ref() == Some(&node.id);
+                let stroke = if selected {
+                    egui::Stroke::new(2.0, egui::Color32::YELLOW)
+                } else {
+                    ui.visuals().window_stroke()
+                };
+                egui::Frame::window(ui.style())
+                    .stroke(stroke)
+                    .show(ui, |ui| {
+                        ui.set_width(NODE_WIDTH);
+                        let title = if editor.graph.asset.entry == node.id {
+                            format!("{} [entry]", node.id)
+                        } else {
+                            node.id.clone()
+                        };
+                        if ui.selectable_label(selected, title).clicked() {
+                            editor.select(Some(node.id.clone()));
+                        }
+                        ui.small(&node_type.name);
+                        ui.separator();
+
+                        ui.columns(2, |columns| {
+                            for group in node_type.inputs.iter() {
+                                pin_ui(
+                                    &mut columns[0],
+                                    editor,
+                                    catalog,
+                                    &mut pin_positions,
+                                    &node.id,
+                                    EditorPinKind::Exec,
+                                    group.exec.name,
+                                    false,
+                                );
+                                for slot in group.slots.iter() {
+                                    pin_ui(
+                                        &mut columns[0],
+                                        editor,
+                                        catalog,
+                                        &mut pin_positions,
+                                        &node.id,
+                                        EditorPinKind::Slot,
+                                        slot.name,
+                                        false,
+                                    );
+                                }
+                            }
+                            for group in node_type.outputs.iter() {
+                                pin_ui(
+                                    &mut columns[1],
+                                    editor,
+                                    catalog,
+                                    &mut pin_positions,
+                                    &node.id,
+                                    EditorPinKind::Exec,
+                                    group.exec.name,
+                                    true,
+                                );
+                                for slot in group.slots.iter() {
+                                    pin_ui(
+                                        &mut columns[1],
+                                        editor,
+                                        catalog,
+                                        &mut pin_positions,
+                                        &node.id,
+                                        EditorPinKind::Slot,
+                                        slot.name,
+                                        true,
+                                    );
+                                }
+                            }
+                        });
+                    });
+            });
+
+        let delta = area.response.drag_delta();
+        if delta != egui::Vec2::ZERO {
+            let _ = editor
+                .graph
+                .move_node(&node.id, position + Vec2::new(delta.x, delta.y));
+        }
+    }
+
+    let painter = ui.ctx().layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("effect_graph_connections"),
+    ));
+    let connections = editor
+        .graph
+        .asset
+        .exec_connections
+        .iter()
+        .map(|x| (x, EXEC_COLOR))
+        .chain(
+            editor
+                .graph
+                .asset
+                .slot_connections
+                .iter()
+                .map(|x| (x, SLOT_COLOR)),
+        );
+    for (connection, color) in connections {
+        let from = pin_positions.get(&(
+            connection.from.node.clone(),
+            connection.from.pin.clone(),
+            true,
+        ));
+        let to = pin_positions.get(&(connection.to.node.clone(), connection.to.pin.clone(), false));
+        if let (Some(from), Some(to)) = (from, to) {
+            painter.line_segment([*from, *to], egui::Stroke::new(2.0, color));
+        }
+    }
+
+    if let Some(pending) = editor.pending_connection.as_ref() {
+        let key = (pending.from.node.clone(), pending.from.pin.clone(), true);
+        if let (Some(from), Some(pointer)) =
+            (pin_positions.get(&key), ui.ctx().pointer_latest_pos())
+        {
+            painter.line_segment(
+                [*from, pointer],
+                egui::Stroke::new(1.0, egui::Color32::GRAY),
+            );
+        }
+        if ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+            editor.pending_connection = None;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pin_ui(
+    ui: &mut egui::Ui,
+    editor: &mut EffectGraphEditor,
+    catalog: &EditorNodeCatalog,
+    pin_positions: &mut HashMap<(String, String, bool), egui::Pos2>,
+    node: &str,
+    kind: EditorPinKind,
+    pin: &'static str,
+    output: bool,
+) {
+    let (icon, color) = match kind {
+        EditorPinKind::Exec => ("▶", EXEC_COLOR),
+        EditorPinKind::Slot => ("●", SLOT_COLOR),
+    };
+    let text = if output {
+        format!("{} {}", pin, icon)
+    } else {
+        format!("{} {}", icon, pin)
+    };
+    let response = ui.add(egui::Button::new(egui::RichText::new(text).color(color)).frame(false));
+    let position = if output {
+        response.rect.right_center()
+    } else {
+        response.rect.left_center()
+    };
+    pin_positions.insert((node.to_string(), pin.to_string(), output), position);
+
+    let asset_pin = EffectGraphAssetPin {
+        node: node.to_string(),
+        pin: pin.to_string(),
+    };
+    if output && response.clicked() {
+        editor.pending_connection = Some(PendingConnection {
+            kind,
+            from: asset_pin,
+        });
+        return;
+    }
+    if output {
+        return;
+    }
+
+    if response.secondary_clicked() {
+        let graph = &mut editor.graph.asset;
+        match kind {
+            EditorPinKind::Exec => graph.exec_connections.retain(|x| x.to != asset_pin),
+            EditorPinKind::Slot => graph.slot_connections.retain(|x| x.to != asset_pin),
+        }
+        return;
+    }
+
+    if response.clicked() {
+        let Some(pending) = editor.pending_connection.take() else {
+            return;
+        };
+        if pending.kind != kind {
+            editor.message = "can not connect exec pin and slot pin".to_string();
+            return;
+        }
+        let result = match kind {
+            EditorPinKind::Exec => editor.graph.connect_exec(catalog, pending.from, asset_pin),
+            EditorPinKind::Slot => editor.graph.connect_slot(catalog, pending.from, asset_pin),
+        };
+        if let Err(e) = result {
+            editor.message = e.to_string();
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! 比较json和二进制表格数据的解析时间，数据为luban导出到`assets/datatables`的`*.json`和`*.bytes`。
+//! 缺少数据文件或者解析失败时直接失败，避免输出没有意义的时间。
+//!
+//! cargo bench -p datatables
+
+use std::{hint::black_box, path::Path, time::Instant};
+
+use cfg::{
+    LubanError,
+    damage::TbDamageType,
+    effect::{TbAbility, TbBuff},
+    global::TbGlobal,
+    item::TbItem,
+    layertag::TbLayerTag,
+    test::{TbMultiIndexList, TbMultiUnionIndexList, TbNullIndexList},
+    unit::{TbMonster, TbNpc, TbPlayer, TbRelationShip},
+};
+use luban_lib::ByteBuf;
+
+const ITERATIONS: u32 = 1_000;
+
+/// 返回每次迭代的平均时间，单位为微秒。
+fn bench(name: &str, mut f: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let elapsed = start.elapsed().as_nanos() as f64 / ITERATIONS as f64 / 1000.0;
+    println!("{:<40} {:>10.2} us/iter", name, elapsed);
+    elapsed
+}
+
+fn read(dir: &Path, file: &str) -> Vec<u8> {
+    let path = dir.join(file);
+    std::fs::read(&path).unwrap_or_else(|e| {
+        panic!(
+            "{}: {}, export the tables with datatables/gen_export_json.bat and gen_export_bin.bat",
+            path.display(),
+            e
+        )
+    })
+}
+
+fn bench_table<T>(
+    dir: &Path,
+    file: &str,
+    from_json: fn(&serde_json::Value) -> Result<T, LubanError>,
+    from_bin: fn(ByteBuf) -> Result<T, LubanError>,
+) -> (f64, f64) {
+    let json_file = format!("{}.json", file);
+    let json = read(dir, &json_file);
+    let value = serde_json::from_slice::<serde_json::Value>(&json)
+        .unwrap_or_else(|e| panic!("{}: {}", json_file, e));
+    if let Err(e) = from_json(&value) {
+        panic!("{}: {}", json_file, e);
+    }
+    let json_time = bench(&json_file, || {
+        let value = serde_json::from_slice::<serde_json::Value>(black_box(&json)).unwrap();
+        black_box(from_json(&value).unwrap());
+    });
+
+    let bin_file = format!("{}.bytes", file);
+    let bytes = read(dir, &bin_file);
+    if let Err(e) = from_bin(ByteBuf::new(bytes.clone())) {
+        panic!("{}: {}", bin_file, e);
+    }
+    // ByteBuf需要持有数据，拷贝在计时之外完成。
+    let mut inputs: Vec<_> = (0..ITERATIONS).map(|_| bytes.clone()).collect();
+    let bin_time = bench(&bin_file, || {
+        let bytes = inputs.pop().unwrap();
+        black_box(from_bin(ByteBuf::new(black_box(bytes))).unwrap());
+    });
+    (json_time, bin_time)
+}
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../assets/datatables");
+    let results = [
+        bench_table(
+            &dir,
+            "damage_tbdamagetype",
+            TbDamageType::new,
+            TbDamageType::new_bin,
+        ),
+        bench_table(&dir, "effect_tbability", TbAbility::new, TbAbility::new_bin),
+        bench_table(&dir, "effect_tbbuff", TbBuff::new, TbBuff::new_bin),
+        bench_table(
+            &dir,
+            "layertag_tblayertag",
+            TbLayerTag::new,
+            TbLayerTag::new_bin,
+        ),
+        bench_table(&dir, "unit_tbmonster", TbMonster::new, TbMonster::new_bin),
+        bench_table(&dir, "unit_tbnpc", TbNpc::new, TbNpc::new_bin),
+        bench_table(&dir, "unit_tbplayer", TbPlayer::new, TbPlayer::new_bin),
+        bench_table(
+            &dir,
+            "unit_tbrelationship",
+            TbRelationShip::new,
+            TbRelationShip::new_bin,
+        ),
+        bench_table(&dir, "item_tbitem", TbItem::new, TbItem::new_bin),
+        bench_table(&dir, "global_tbglobal", TbGlobal::new, TbGlobal::new_bin),
+        bench_table(
+            &dir,
+            "test_tbmultiindexlist",
+            TbMultiIndexList::new,
+            TbMultiIndexList::new_bin,
+        ),
+        bench_table(
+            &dir,
+            "test_tbmultiunionindexlist",
+            TbMultiUnionIndexList::new,
+            TbMultiUnionIndexList::new_bin,
+        ),
+        bench_table(
+            &dir,
+            "test_tbnullindexlist",
+            TbNullIndexList::new,
+            TbNullIndexList::new_bin,
+        ),
+    ];
+
+    let json_total: f64 = results.iter().map(|(json, _)| json).sum();
+    let bin_total: f64 = results.iter().map(|(_, bin)| bin).sum();
+    println!("{:<40} {:>10.2} us/iter", "json total", json_total);
+    println!("{:<40} {:>10.2} us/iter", "bytes total", bin_total);
+    println!("{:<40} {:>10.2}x", "json / bytes", json_total / bin_total);
+}
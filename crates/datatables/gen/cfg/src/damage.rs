@@ -0,0 +1,315 @@
+
+/*!
+<auto-generated>
+    This code was generated by a tool.
+    Changes to this file may cause incorrect behavior and will be lost if
+    the code is regenerated.
+</auto-generated>
+*/
+
+
+use super::*;
+
+#[derive(Debug, Hash, Eq, PartialEq, bevy::reflect::Reflect, macros::EnumFromNum)]
+pub enum DamageMitigation {
+    None = 0,
+    Armor = 1,
+    Resistance = 2,
+}
+
+impl From<i32> for DamageMitigation {
+    fn from(value: i32) -> Self {
+        match value { 
+            0 => DamageMitigation::None,
+            1 => DamageMitigation::Armor,
+            2 => DamageMitigation::Resistance,
+            _ => panic!("Invalid value for DamageMitigation:{}", value),
+        }
+    }
+}
+
+impl DamageMitigation {
+    pub fn try_from_value(value: i32) -> Result<Self, LubanError> {
+        match value {
+            0 => Ok(DamageMitigation::None),
+            1 => Ok(DamageMitigation::Armor),
+            2 => Ok(DamageMitigation::Resistance),
+            _ => Err(LubanError::Bean(format!("Invalid value for DamageMitigation:{}", value))),
+        }
+    }
+
+    pub fn to_value(&self) -> i32 {
+        match self {
+            DamageMitigation::None => 0,
+            DamageMitigation::Armor => 1,
+            DamageMitigation::Resistance => 2,
+        }
+    }
+}
+
+#[derive(bevy::reflect::Reflect, Debug)]
+pub struct DamageType {
+    /// 这是id
+    pub id: i32,
+    /// 名字
+    pub name: String,
+    /// 描述
+    pub desc: String,
+    /// 减免使用的属性
+    pub mitigation: crate::damage::DamageMitigation,
+    /// 是否可以暴击
+    pub can_crit: bool,
+    /// 是否无视护盾
+    pub ignore_shield: bool,
+    /// 免疫的状态，为空时不能免疫
+    pub immune_layertag: String,
+}
+
+impl DamageType{
+    pub fn new(json: &serde_json::Value) -> Result<DamageType, LubanError> {
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let mitigation = crate::damage::DamageMitigation::try_from_value(json["mitigation"].as_i64().ok_or_else(|| LubanError::field("mitigation", "enum", &json["mitigation"]))? as i32).map_err(|e| e.in_field("mitigation"))?;
+        let can_crit = json["can_crit"].as_bool().ok_or_else(|| LubanError::field("can_crit", "bool", &json["can_crit"]))?;
+        let ignore_shield = json["ignore_shield"].as_bool().ok_or_else(|| LubanError::field("ignore_shield", "bool", &json["ignore_shield"]))?;
+        let immune_layertag = json["immune_layertag"].as_str().ok_or_else(|| LubanError::field("immune_layertag", "string", &json["immune_layertag"]))?.to_string();
+        
+        Ok(DamageType { id, name, desc, mitigation, can_crit, ignore_shield, immune_layertag, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<DamageType, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let mitigation = crate::damage::DamageMitigation::try_from_value(buf.read_int().map_err(|e| LubanError::bin_field("mitigation", e))?).map_err(|e| e.in_field("mitigation"))?;
+        let can_crit = buf.read_bool().map_err(|e| LubanError::bin_field("can_crit", e))?;
+        let ignore_shield = buf.read_bool().map_err(|e| LubanError::bin_field("ignore_shield", e))?;
+        let immune_layertag = buf.read_string().map_err(|e| LubanError::bin_field("immune_layertag", e))?;
+        
+        Ok(DamageType { id, name, desc, mitigation, can_crit, ignore_shield, immune_layertag, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_int(self.mitigation.to_value());
+        buf.write_bool(self.can_crit);
+        buf.write_bool(self.ignore_shield);
+        buf.write_string(&self.immune_layertag);
+    }
+
+    pub const __ID__: i32 = -1610448056;
+}
+
+
+#[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
+pub struct TbDamageType {
+    pub data_list: Vec<std::sync::Arc<crate::damage::DamageType>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::damage::DamageType>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
+}
+
+impl TbDamageType {
+    pub fn new(json: &serde_json::Value) -> Result<TbDamageType, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::damage::DamageType>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::damage::DamageType>> = vec![];
+
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbDamageType expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::damage::DamageType::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbDamageType", row_id(x, "id", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbDamageType::canonical_source(&data_list);
+        Ok(TbDamageType { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbDamageType, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::damage::DamageType>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::damage::DamageType>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbDamageType: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::damage::DamageType::new_bin(&mut buf).map_err(|e| e.in_row("TbDamageType", format!("#{}", index)))?);
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+
+        let (source, content_hash) = TbDamageType::canonical_source(&data_list);
+        Ok(TbDamageType { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbDamageType, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbDamageType::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbDamageType::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbDamageType, TableLoaderError> {
+        Ok(TbDamageType::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::damage::DamageType>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
+    }
+
+    pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::damage::DamageType>> {
+        self.data_map.get(key).map(|x| x.clone())
+    }
+}
+
+impl std::ops::Index<i32> for TbDamageType {
+    type Output = std::sync::Arc<crate::damage::DamageType>;
+
+    fn index(&self, index: i32) -> &Self::Output {
+        &self.data_map.get(&index).unwrap()
+    }
+}
+impl luban_lib::table::Table for TbDamageType {
+    type Value = std::sync::Arc<crate::damage::DamageType>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
+}
+pub type TbDamageTypeKey = i32;
+#[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
+pub struct TbDamageTypeRow {
+    pub key: TbDamageTypeKey,
+    #[serde(skip)]
+    pub data: Option<std::sync::Arc<crate::damage::DamageType>>,
+}
+
+impl PartialEq for TbDamageTypeRow {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl TbDamageTypeRow {
+    pub fn new(key: TbDamageTypeKey, data: Option<std::sync::Arc<crate::damage::DamageType>>) -> Self {
+        Self { key, data }
+    }
+
+    pub fn key(&self) -> &TbDamageTypeKey {
+        &self.key
+    }
+
+    pub fn set_key(&mut self, key: TbDamageTypeKey) {
+        self.key = key;
+    }
+
+    pub fn set_data(&mut self, data: Option<std::sync::Arc<crate::damage::DamageType>>) {
+        self.data = data;
+    }
+
+    pub fn get_data(&self) -> Option<std::sync::Arc<crate::damage::DamageType>> {
+        self.data.clone()
+    }
+
+    pub fn data(&self) -> std::sync::Arc<crate::damage::DamageType> {
+        self.data.clone().unwrap()
+    }
+}
+
+
+impl luban_lib::table::MapTable for TbDamageType {
+    type Key = TbDamageTypeKey;
+    type List = Vec<std::sync::Arc<crate::damage::DamageType>>;
+    type Map = bevy::utils::HashMap<Self::Key, Self::Value>;
+
+    fn get_row(&self, key: &Self::Key) -> Option<Self::Value> {
+        self.data_map.get(key).map(|x| x.clone())
+    }
+
+    fn get_data_list(&self) -> &Self::List {
+        &self.data_list
+    }
+
+    fn get_data_map(&self) -> &Self::Map {
+        &self.data_map
+    }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbDamageTypeRow {
+    type Table = TbDamageType;
+
+    fn key(&self) -> &TbDamageTypeKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbDamageType as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
+}
+
+
+#[derive(Debug, Default)]
+pub struct TbDamageTypeLoader;
+
+impl bevy::asset::AssetLoader for TbDamageTypeLoader {
+    type Asset = TbDamageType;
+
+    type Settings = ();
+
+    type Error = TableLoaderError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        settings: &Self::Settings,
+        load_context: &mut bevy::asset::LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        bevy::log::info!("TbDamageTypeLoader loading start");
+        let start = std::time::Instant::now();
+        let mut bytes = Vec::new();
+        use bevy::asset::AsyncReadExt;
+        reader.read_to_end(&mut bytes).await?;
+        let tb = TbDamageType::from_bytes(bytes)?;
+        bevy::log::info!("TbDamageTypeLoader loading over, {:?}", start.elapsed());
+        Ok(tb)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[TABLE_FILE_EXTENSION]
+    }
+}
+
+
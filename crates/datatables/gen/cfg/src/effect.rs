@@ -27,6 +27,23 @@ impl From<i32> for AbilityType {
     }
 }
 
+impl AbilityType {
+    pub fn try_from_value(value: i32) -> Result<Self, LubanError> {
+        match value {
+            0 => Ok(AbilityType::Active),
+            1 => Ok(AbilityType::Passive),
+            _ => Err(LubanError::Bean(format!("Invalid value for AbilityType:{}", value))),
+        }
+    }
+
+    pub fn to_value(&self) -> i32 {
+        match self {
+            AbilityType::Active => 0,
+            AbilityType::Passive => 1,
+        }
+    }
+}
+
 #[derive(bevy::reflect::Reflect, Debug)]
 pub struct Ability {
     /// 这是id
@@ -53,25 +70,64 @@ pub struct Ability {
     pub abort_required_layertags: Vec<String>,
     /// 技能启动需要的状态
     pub abort_disabled_layertags: Vec<String>,
+    /// 技能触发条件
+    pub triggers: Vec<String>,
 }
 
 impl Ability{
     pub fn new(json: &serde_json::Value) -> Result<Ability, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let graph_class = json["graph_class"].as_str().unwrap().to_string();
-        let activation_type = json["activation_type"].as_i64().unwrap().into();
-        let cd = (json["cd"].as_f64().unwrap() as f32);
-        let start_required_layertags = json["start_required_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let start_disabled_layertags = json["start_disabled_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let start_added_layertags = json["start_added_layertags"].as_array().unwrap().iter().map(|field| crate::effect::RevertableLayerTag::new(&field).unwrap()).collect();
-        let start_removed_layertags = json["start_removed_layertags"].as_array().unwrap().iter().map(|field| crate::effect::RevertableLayerTag::new(&field).unwrap()).collect();
-        let abort_required_layertags = json["abort_required_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let abort_disabled_layertags = json["abort_disabled_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let graph_class = json["graph_class"].as_str().ok_or_else(|| LubanError::field("graph_class", "string", &json["graph_class"]))?.to_string();
+        let activation_type = crate::effect::AbilityType::try_from_value(json["activation_type"].as_i64().ok_or_else(|| LubanError::field("activation_type", "enum", &json["activation_type"]))? as i32).map_err(|e| e.in_field("activation_type"))?;
+        let cd = (json["cd"].as_f64().ok_or_else(|| LubanError::field("cd", "float", &json["cd"]))? as f32);
+        let start_required_layertags = json["start_required_layertags"].as_array().ok_or_else(|| LubanError::field("start_required_layertags", "array", &json["start_required_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("start_required_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let start_disabled_layertags = json["start_disabled_layertags"].as_array().ok_or_else(|| LubanError::field("start_disabled_layertags", "array", &json["start_disabled_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("start_disabled_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let start_added_layertags = json["start_added_layertags"].as_array().ok_or_else(|| LubanError::field("start_added_layertags", "array", &json["start_added_layertags"]))?.iter().enumerate().map(|(i, field)| crate::effect::RevertableLayerTag::new(field).map_err(|e| e.in_field(&format!("start_added_layertags[{}]", i)))).collect::<Result<_, _>>()?;
+        let start_removed_layertags = json["start_removed_layertags"].as_array().ok_or_else(|| LubanError::field("start_removed_layertags", "array", &json["start_removed_layertags"]))?.iter().enumerate().map(|(i, field)| crate::effect::RevertableLayerTag::new(field).map_err(|e| e.in_field(&format!("start_removed_layertags[{}]", i)))).collect::<Result<_, _>>()?;
+        let abort_required_layertags = json["abort_required_layertags"].as_array().ok_or_else(|| LubanError::field("abort_required_layertags", "array", &json["abort_required_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("abort_required_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let abort_disabled_layertags = json["abort_disabled_layertags"].as_array().ok_or_else(|| LubanError::field("abort_disabled_layertags", "array", &json["abort_disabled_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("abort_disabled_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let triggers = json["triggers"].as_array().ok_or_else(|| LubanError::field("triggers", "array", &json["triggers"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("triggers[{}]", i), "string", field))).collect::<Result<_, _>>()?;
         
-        Ok(Ability { id, name, desc, graph_class, activation_type, cd, start_required_layertags, start_disabled_layertags, start_added_layertags, start_removed_layertags, abort_required_layertags, abort_disabled_layertags, })
-    }
+        Ok(Ability { id, name, desc, graph_class, activation_type, cd, start_required_layertags, start_disabled_layertags, start_added_layertags, start_removed_layertags, abort_required_layertags, abort_disabled_layertags, triggers, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Ability, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let graph_class = buf.read_string().map_err(|e| LubanError::bin_field("graph_class", e))?;
+        let activation_type = crate::effect::AbilityType::try_from_value(buf.read_int().map_err(|e| LubanError::bin_field("activation_type", e))?).map_err(|e| e.in_field("activation_type"))?;
+        let cd = buf.read_float().map_err(|e| LubanError::bin_field("cd", e))?;
+        let start_required_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_required_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("start_required_layertags[{}]", i0), e))?); } _e0 };
+        let start_disabled_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_disabled_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("start_disabled_layertags[{}]", i0), e))?); } _e0 };
+        let start_added_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_added_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::effect::RevertableLayerTag::new_bin(buf).map_err(|e| e.in_field(&format!("start_added_layertags[{}]", i0)))?); } _e0 };
+        let start_removed_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_removed_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::effect::RevertableLayerTag::new_bin(buf).map_err(|e| e.in_field(&format!("start_removed_layertags[{}]", i0)))?); } _e0 };
+        let abort_required_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("abort_required_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("abort_required_layertags[{}]", i0), e))?); } _e0 };
+        let abort_disabled_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("abort_disabled_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("abort_disabled_layertags[{}]", i0), e))?); } _e0 };
+        let triggers = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("triggers", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("triggers[{}]", i0), e))?); } _e0 };
+        
+        Ok(Ability { id, name, desc, graph_class, activation_type, cd, start_required_layertags, start_disabled_layertags, start_added_layertags, start_removed_layertags, abort_required_layertags, abort_disabled_layertags, triggers, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_string(&self.graph_class);
+        buf.write_int(self.activation_type.to_value());
+        buf.write_float(self.cd);
+        buf.write_size(self.start_required_layertags.len()); for x0 in self.start_required_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.start_disabled_layertags.len()); for x0 in self.start_disabled_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.start_added_layertags.len()); for x0 in self.start_added_layertags.iter() { x0.write_bin(buf); }
+        buf.write_size(self.start_removed_layertags.len()); for x0 in self.start_removed_layertags.iter() { x0.write_bin(buf); }
+        buf.write_size(self.abort_required_layertags.len()); for x0 in self.abort_required_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.abort_disabled_layertags.len()); for x0 in self.abort_disabled_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.triggers.len()); for x0 in self.triggers.iter() { buf.write_string(x0); }
+    }
+
+    pub const __ID__: i32 = 1631647149;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -106,22 +162,58 @@ pub struct Buff {
 
 impl Buff{
     pub fn new(json: &serde_json::Value) -> Result<Buff, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let graph_class = json["graph_class"].as_str().unwrap().to_string();
-        let max_layer = (json["max_layer"].as_i64().unwrap() as i32);
-        let duration = (json["duration"].as_f64().unwrap() as f32);
-        let interval = (json["interval"].as_f64().unwrap() as f32);
-        let start_required_layertags = json["start_required_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let start_disabled_layertags = json["start_disabled_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let start_added_layertags = json["start_added_layertags"].as_array().unwrap().iter().map(|field| crate::effect::RevertableLayerTag::new(&field).unwrap()).collect();
-        let start_removed_layertags = json["start_removed_layertags"].as_array().unwrap().iter().map(|field| crate::effect::RevertableLayerTag::new(&field).unwrap()).collect();
-        let abort_required_layertags = json["abort_required_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
-        let abort_disabled_layertags = json["abort_disabled_layertags"].as_array().unwrap().iter().map(|field| field.as_str().unwrap().to_string()).collect();
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let graph_class = json["graph_class"].as_str().ok_or_else(|| LubanError::field("graph_class", "string", &json["graph_class"]))?.to_string();
+        let max_layer = (json["max_layer"].as_i64().ok_or_else(|| LubanError::field("max_layer", "int", &json["max_layer"]))? as i32);
+        let duration = (json["duration"].as_f64().ok_or_else(|| LubanError::field("duration", "float", &json["duration"]))? as f32);
+        let interval = (json["interval"].as_f64().ok_or_else(|| LubanError::field("interval", "float", &json["interval"]))? as f32);
+        let start_required_layertags = json["start_required_layertags"].as_array().ok_or_else(|| LubanError::field("start_required_layertags", "array", &json["start_required_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("start_required_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let start_disabled_layertags = json["start_disabled_layertags"].as_array().ok_or_else(|| LubanError::field("start_disabled_layertags", "array", &json["start_disabled_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("start_disabled_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let start_added_layertags = json["start_added_layertags"].as_array().ok_or_else(|| LubanError::field("start_added_layertags", "array", &json["start_added_layertags"]))?.iter().enumerate().map(|(i, field)| crate::effect::RevertableLayerTag::new(field).map_err(|e| e.in_field(&format!("start_added_layertags[{}]", i)))).collect::<Result<_, _>>()?;
+        let start_removed_layertags = json["start_removed_layertags"].as_array().ok_or_else(|| LubanError::field("start_removed_layertags", "array", &json["start_removed_layertags"]))?.iter().enumerate().map(|(i, field)| crate::effect::RevertableLayerTag::new(field).map_err(|e| e.in_field(&format!("start_removed_layertags[{}]", i)))).collect::<Result<_, _>>()?;
+        let abort_required_layertags = json["abort_required_layertags"].as_array().ok_or_else(|| LubanError::field("abort_required_layertags", "array", &json["abort_required_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("abort_required_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        let abort_disabled_layertags = json["abort_disabled_layertags"].as_array().ok_or_else(|| LubanError::field("abort_disabled_layertags", "array", &json["abort_disabled_layertags"]))?.iter().enumerate().map(|(i, field)| field.as_str().map(|x| x.to_string()).ok_or_else(|| LubanError::field(&format!("abort_disabled_layertags[{}]", i), "string", field))).collect::<Result<_, _>>()?;
+        
+        Ok(Buff { id, name, desc, graph_class, max_layer, duration, interval, start_required_layertags, start_disabled_layertags, start_added_layertags, start_removed_layertags, abort_required_layertags, abort_disabled_layertags, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Buff, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let graph_class = buf.read_string().map_err(|e| LubanError::bin_field("graph_class", e))?;
+        let max_layer = buf.read_int().map_err(|e| LubanError::bin_field("max_layer", e))?;
+        let duration = buf.read_float().map_err(|e| LubanError::bin_field("duration", e))?;
+        let interval = buf.read_float().map_err(|e| LubanError::bin_field("interval", e))?;
+        let start_required_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_required_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("start_required_layertags[{}]", i0), e))?); } _e0 };
+        let start_disabled_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_disabled_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("start_disabled_layertags[{}]", i0), e))?); } _e0 };
+        let start_added_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_added_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::effect::RevertableLayerTag::new_bin(buf).map_err(|e| e.in_field(&format!("start_added_layertags[{}]", i0)))?); } _e0 };
+        let start_removed_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("start_removed_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::effect::RevertableLayerTag::new_bin(buf).map_err(|e| e.in_field(&format!("start_removed_layertags[{}]", i0)))?); } _e0 };
+        let abort_required_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("abort_required_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("abort_required_layertags[{}]", i0), e))?); } _e0 };
+        let abort_disabled_layertags = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("abort_disabled_layertags", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_string().map_err(|e| LubanError::bin_field(&format!("abort_disabled_layertags[{}]", i0), e))?); } _e0 };
         
         Ok(Buff { id, name, desc, graph_class, max_layer, duration, interval, start_required_layertags, start_disabled_layertags, start_added_layertags, start_removed_layertags, abort_required_layertags, abort_disabled_layertags, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_string(&self.graph_class);
+        buf.write_int(self.max_layer);
+        buf.write_float(self.duration);
+        buf.write_float(self.interval);
+        buf.write_size(self.start_required_layertags.len()); for x0 in self.start_required_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.start_disabled_layertags.len()); for x0 in self.start_disabled_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.start_added_layertags.len()); for x0 in self.start_added_layertags.iter() { x0.write_bin(buf); }
+        buf.write_size(self.start_removed_layertags.len()); for x0 in self.start_removed_layertags.iter() { x0.write_bin(buf); }
+        buf.write_size(self.abort_required_layertags.len()); for x0 in self.abort_required_layertags.iter() { buf.write_string(x0); }
+        buf.write_size(self.abort_disabled_layertags.len()); for x0 in self.abort_disabled_layertags.iter() { buf.write_string(x0); }
+    }
+
+    pub const __ID__: i32 = 1614517936;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -132,18 +224,38 @@ pub struct RevertableLayerTag {
 
 impl RevertableLayerTag{
     pub fn new(json: &serde_json::Value) -> Result<RevertableLayerTag, LubanError> {
-        let raw_layertag = json["raw_layertag"].as_str().unwrap().to_string();
-        let revertable = json["revertable"].as_bool().unwrap();
+        let raw_layertag = json["raw_layertag"].as_str().ok_or_else(|| LubanError::field("raw_layertag", "string", &json["raw_layertag"]))?.to_string();
+        let revertable = json["revertable"].as_bool().ok_or_else(|| LubanError::field("revertable", "bool", &json["revertable"]))?;
         
         Ok(RevertableLayerTag { raw_layertag, revertable, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<RevertableLayerTag, LubanError> {
+        let raw_layertag = buf.read_string().map_err(|e| LubanError::bin_field("raw_layertag", e))?;
+        let revertable = buf.read_bool().map_err(|e| LubanError::bin_field("revertable", e))?;
+        
+        Ok(RevertableLayerTag { raw_layertag, revertable, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_string(&self.raw_layertag);
+        buf.write_bool(self.revertable);
+    }
+
+    pub const __ID__: i32 = 136820612;
 }
 
 
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbAbility {
     pub data_list: Vec<std::sync::Arc<crate::effect::Ability>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Ability>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Ability>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbAbility {
@@ -151,13 +263,65 @@ impl TbAbility {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Ability>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::effect::Ability>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::effect::Ability::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbAbility expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::effect::Ability::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbAbility", row_id(x, "id", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbAbility::canonical_source(&data_list);
+        Ok(TbAbility { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbAbility, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Ability>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::effect::Ability>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbAbility: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::effect::Ability::new_bin(&mut buf).map_err(|e| e.in_row("TbAbility", format!("#{}", index)))?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
 
-        Ok(TbAbility { data_map, data_list })
+        let (source, content_hash) = TbAbility::canonical_source(&data_list);
+        Ok(TbAbility { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbAbility, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbAbility::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbAbility::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbAbility, TableLoaderError> {
+        Ok(TbAbility::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::effect::Ability>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::effect::Ability>> {
@@ -174,6 +338,14 @@ impl std::ops::Index<i32> for TbAbility {
 }
 impl luban_lib::table::Table for TbAbility {
     type Value = std::sync::Arc<crate::effect::Ability>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbAbilityKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -232,6 +404,22 @@ impl luban_lib::table::MapTable for TbAbility {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbAbilityRow {
+    type Table = TbAbility;
+
+    fn key(&self) -> &TbAbilityKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbAbility as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -252,17 +440,17 @@ impl bevy::asset::AssetLoader for TbAbilityLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbAbilityLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbAbility::new(&t).unwrap();
-        bevy::log::info!("TbAbilityLoader loading over");
+        let tb = TbAbility::from_bytes(bytes)?;
+        bevy::log::info!("TbAbilityLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -270,7 +458,13 @@ impl bevy::asset::AssetLoader for TbAbilityLoader {
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbBuff {
     pub data_list: Vec<std::sync::Arc<crate::effect::Buff>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Buff>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Buff>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbBuff {
@@ -278,13 +472,65 @@ impl TbBuff {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Buff>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::effect::Buff>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::effect::Buff::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbBuff expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::effect::Buff::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbBuff", row_id(x, "id", index)));
+                    continue;
+                }
+            };
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
 
-        Ok(TbBuff { data_map, data_list })
+        let (source, content_hash) = TbBuff::canonical_source(&data_list);
+        Ok(TbBuff { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbBuff, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::effect::Buff>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::effect::Buff>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbBuff: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::effect::Buff::new_bin(&mut buf).map_err(|e| e.in_row("TbBuff", format!("#{}", index)))?);
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+
+        let (source, content_hash) = TbBuff::canonical_source(&data_list);
+        Ok(TbBuff { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbBuff, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbBuff::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbBuff::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbBuff, TableLoaderError> {
+        Ok(TbBuff::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::effect::Buff>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::effect::Buff>> {
@@ -301,6 +547,14 @@ impl std::ops::Index<i32> for TbBuff {
 }
 impl luban_lib::table::Table for TbBuff {
     type Value = std::sync::Arc<crate::effect::Buff>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbBuffKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -359,6 +613,22 @@ impl luban_lib::table::MapTable for TbBuff {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbBuffRow {
+    type Table = TbBuff;
+
+    fn key(&self) -> &TbBuffKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbBuff as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -379,17 +649,17 @@ impl bevy::asset::AssetLoader for TbBuffLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbBuffLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbBuff::new(&t).unwrap();
-        bevy::log::info!("TbBuffLoader loading over");
+        let tb = TbBuff::from_bytes(bytes)?;
+        bevy::log::info!("TbBuffLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -25,36 +25,107 @@ pub struct Global {
 
 impl Global{
     pub fn new(json: &serde_json::Value) -> Result<Global, LubanError> {
-        let x1 = (json["x1"].as_i64().unwrap() as i32);
-        let x2 = (json["x2"].as_i64().unwrap() as i32);
-        let x3 = (json["x3"].as_i64().unwrap() as i32);
-        let x4 = (json["x4"].as_i64().unwrap() as i32);
-        let x5 = (json["x5"].as_i64().unwrap() as i32);
-        let x6 = (json["x6"].as_i64().unwrap() as i32);
-        let x7 = json["x7"].as_array().unwrap().iter().map(|field| (field.as_i64().unwrap() as i32)).collect();
+        let x1 = (json["x1"].as_i64().ok_or_else(|| LubanError::field("x1", "int", &json["x1"]))? as i32);
+        let x2 = (json["x2"].as_i64().ok_or_else(|| LubanError::field("x2", "int", &json["x2"]))? as i32);
+        let x3 = (json["x3"].as_i64().ok_or_else(|| LubanError::field("x3", "int", &json["x3"]))? as i32);
+        let x4 = (json["x4"].as_i64().ok_or_else(|| LubanError::field("x4", "int", &json["x4"]))? as i32);
+        let x5 = (json["x5"].as_i64().ok_or_else(|| LubanError::field("x5", "int", &json["x5"]))? as i32);
+        let x6 = (json["x6"].as_i64().ok_or_else(|| LubanError::field("x6", "int", &json["x6"]))? as i32);
+        let x7 = json["x7"].as_array().ok_or_else(|| LubanError::field("x7", "array", &json["x7"]))?.iter().enumerate().map(|(i, field)| field.as_i64().map(|x| x as i32).ok_or_else(|| LubanError::field(&format!("x7[{}]", i), "int", field))).collect::<Result<_, _>>()?;
         
         Ok(Global { x1, x2, x3, x4, x5, x6, x7, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Global, LubanError> {
+        let x1 = buf.read_int().map_err(|e| LubanError::bin_field("x1", e))?;
+        let x2 = buf.read_int().map_err(|e| LubanError::bin_field("x2", e))?;
+        let x3 = buf.read_int().map_err(|e| LubanError::bin_field("x3", e))?;
+        let x4 = buf.read_int().map_err(|e| LubanError::bin_field("x4", e))?;
+        let x5 = buf.read_int().map_err(|e| LubanError::bin_field("x5", e))?;
+        let x6 = buf.read_int().map_err(|e| LubanError::bin_field("x6", e))?;
+        let x7 = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("x7", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(buf.read_int().map_err(|e| LubanError::bin_field(&format!("x7[{}]", i0), e))?); } _e0 };
+        
+        Ok(Global { x1, x2, x3, x4, x5, x6, x7, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.x1);
+        buf.write_int(self.x2);
+        buf.write_int(self.x3);
+        buf.write_int(self.x4);
+        buf.write_int(self.x5);
+        buf.write_int(self.x6);
+        buf.write_size(self.x7.len()); for x0 in self.x7.iter() { buf.write_int(*x0); }
+    }
+
+    pub const __ID__: i32 = 1250542702;
 }
 
 
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbGlobal {
     pub data: std::sync::Arc<crate::global::Global>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbGlobal {
     pub fn new(json: &serde_json::Value) -> Result<TbGlobal, LubanError> {
-        let json = json.as_array().unwrap();
+        let json = json.as_array().ok_or_else(|| LubanError::Table("TbGlobal expect array".to_string()))?;
         let n = json.len();
         if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
-        let data = std::sync::Arc::new(crate::global::Global::new(&json[0])?);
-        Ok(TbGlobal { data })
+        let data = std::sync::Arc::new(crate::global::Global::new(&json[0]).map_err(|e| e.in_row("TbGlobal", row_id(&json[0], "", 0)))?);
+        let (source, content_hash) = TbGlobal::canonical_source(std::slice::from_ref(&data));
+        Ok(TbGlobal { data, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbGlobal, LubanError> {
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbGlobal: {}", e)))?;
+        if n != 1 { return Err(LubanError::Table(format!("table mode=one, but size != 1"))); }
+        let data = std::sync::Arc::new(crate::global::Global::new_bin(&mut buf).map_err(|e| e.in_row("TbGlobal", "#0".to_string()))?);
+        let (source, content_hash) = TbGlobal::canonical_source(std::slice::from_ref(&data));
+        Ok(TbGlobal { data, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbGlobal, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbGlobal::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbGlobal::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbGlobal, TableLoaderError> {
+        Ok(TbGlobal::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::global::Global>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 }
 
 impl luban_lib::table::Table for TbGlobal {
     type Value = std::sync::Arc<crate::global::Global>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 
 impl luban_lib::table::OneTable for TbGlobal {
@@ -81,17 +152,17 @@ impl bevy::asset::AssetLoader for TbGlobalLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbGlobalLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbGlobal::new(&t).unwrap();
-        bevy::log::info!("TbGlobalLoader loading over");
+        let tb = TbGlobal::from_bytes(bytes)?;
+        bevy::log::info!("TbGlobalLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -34,6 +34,27 @@ impl From<i32> for EQuality {
     }
 }
 
+impl EQuality {
+    pub fn try_from_value(value: i32) -> Result<Self, LubanError> {
+        match value {
+            1 => Ok(EQuality::WHITE),
+            2 => Ok(EQuality::BLUE),
+            3 => Ok(EQuality::PURPLE),
+            4 => Ok(EQuality::RED),
+            _ => Err(LubanError::Bean(format!("Invalid value for EQuality:{}", value))),
+        }
+    }
+
+    pub fn to_value(&self) -> i32 {
+        match self {
+            EQuality::WHITE => 1,
+            EQuality::BLUE => 2,
+            EQuality::PURPLE => 3,
+            EQuality::RED => 4,
+        }
+    }
+}
+
 #[derive(bevy::reflect::Reflect, Debug)]
 pub struct Item {
     /// 这是id
@@ -61,20 +82,52 @@ pub struct Item {
 
 impl Item{
     pub fn new(json: &serde_json::Value) -> Result<Item, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let price = (json["price"].as_i64().unwrap() as i32);
-        let upgrade_to_item_id = (json["upgrade_to_item_id"].as_i64().unwrap() as i32);
-        let mut expire_time = None; if let Some(value) = json.get("expire_time") { expire_time = Some((json["expire_time"].as_i64().unwrap() as u64)); }
-        let batch_useable = json["batch_useable"].as_bool().unwrap();
-        let quality = json["quality"].as_i64().unwrap().into();
-        let exchange_stream = crate::item::ItemExchange::new(&json["exchange_stream"])?;
-        let exchange_list = json["exchange_list"].as_array().unwrap().iter().map(|field| crate::item::ItemExchange::new(&field).unwrap()).collect();
-        let exchange_column = crate::item::ItemExchange::new(&json["exchange_column"])?;
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let price = (json["price"].as_i64().ok_or_else(|| LubanError::field("price", "int", &json["price"]))? as i32);
+        let upgrade_to_item_id = (json["upgrade_to_item_id"].as_i64().ok_or_else(|| LubanError::field("upgrade_to_item_id", "int", &json["upgrade_to_item_id"]))? as i32);
+        let mut expire_time = None; if let Some(value) = json.get("expire_time") { expire_time = Some((value.as_i64().ok_or_else(|| LubanError::field("expire_time", "int", value))? as u64)); }
+        let batch_useable = json["batch_useable"].as_bool().ok_or_else(|| LubanError::field("batch_useable", "bool", &json["batch_useable"]))?;
+        let quality = crate::item::EQuality::try_from_value(json["quality"].as_i64().ok_or_else(|| LubanError::field("quality", "enum", &json["quality"]))? as i32).map_err(|e| e.in_field("quality"))?;
+        let exchange_stream = crate::item::ItemExchange::new(&json["exchange_stream"]).map_err(|e| e.in_field("exchange_stream"))?;
+        let exchange_list = json["exchange_list"].as_array().ok_or_else(|| LubanError::field("exchange_list", "array", &json["exchange_list"]))?.iter().enumerate().map(|(i, field)| crate::item::ItemExchange::new(field).map_err(|e| e.in_field(&format!("exchange_list[{}]", i)))).collect::<Result<_, _>>()?;
+        let exchange_column = crate::item::ItemExchange::new(&json["exchange_column"]).map_err(|e| e.in_field("exchange_column"))?;
         
         Ok(Item { id, name, desc, price, upgrade_to_item_id, expire_time, batch_useable, quality, exchange_stream, exchange_list, exchange_column, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Item, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let price = buf.read_int().map_err(|e| LubanError::bin_field("price", e))?;
+        let upgrade_to_item_id = buf.read_int().map_err(|e| LubanError::bin_field("upgrade_to_item_id", e))?;
+        let mut expire_time = if buf.read_bool().map_err(|e| LubanError::bin_field("expire_time", e))? { Some(buf.read_ulong().map_err(|e| LubanError::bin_field("expire_time", e))?) } else { None };
+        let batch_useable = buf.read_bool().map_err(|e| LubanError::bin_field("batch_useable", e))?;
+        let quality = crate::item::EQuality::try_from_value(buf.read_int().map_err(|e| LubanError::bin_field("quality", e))?).map_err(|e| e.in_field("quality"))?;
+        let exchange_stream = crate::item::ItemExchange::new_bin(buf).map_err(|e| e.in_field("exchange_stream"))?;
+        let exchange_list = {let n0 = std::cmp::min(buf.read_size().map_err(|e| LubanError::bin_field("exchange_list", e))?, buf.size());let mut _e0 = vec![]; for i0 in 0..n0 { _e0.push(crate::item::ItemExchange::new_bin(buf).map_err(|e| e.in_field(&format!("exchange_list[{}]", i0)))?); } _e0 };
+        let exchange_column = crate::item::ItemExchange::new_bin(buf).map_err(|e| e.in_field("exchange_column"))?;
+        
+        Ok(Item { id, name, desc, price, upgrade_to_item_id, expire_time, batch_useable, quality, exchange_stream, exchange_list, exchange_column, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_int(self.price);
+        buf.write_int(self.upgrade_to_item_id);
+        match &self.expire_time { Some(value) => { buf.write_bool(true); buf.write_ulong(*value); } None => buf.write_bool(false), }
+        buf.write_bool(self.batch_useable);
+        buf.write_int(self.quality.to_value());
+        self.exchange_stream.write_bin(buf);
+        buf.write_size(self.exchange_list.len()); for x0 in self.exchange_list.iter() { x0.write_bin(buf); }
+        self.exchange_column.write_bin(buf);
+    }
+
+    pub const __ID__: i32 = 2107285806;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -87,18 +140,38 @@ pub struct ItemExchange {
 
 impl ItemExchange{
     pub fn new(json: &serde_json::Value) -> Result<ItemExchange, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let num = (json["num"].as_i64().unwrap() as i32);
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let num = (json["num"].as_i64().ok_or_else(|| LubanError::field("num", "int", &json["num"]))? as i32);
         
         Ok(ItemExchange { id, num, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<ItemExchange, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let num = buf.read_int().map_err(|e| LubanError::bin_field("num", e))?;
+        
+        Ok(ItemExchange { id, num, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_int(self.num);
+    }
+
+    pub const __ID__: i32 = 1814660465;
 }
 
 
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbItem {
     pub data_list: Vec<std::sync::Arc<crate::item::Item>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::item::Item>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::item::Item>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbItem {
@@ -106,13 +179,65 @@ impl TbItem {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::item::Item>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::item::Item>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::item::Item::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbItem expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::item::Item::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbItem", row_id(x, "id", index)));
+                    continue;
+                }
+            };
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
 
-        Ok(TbItem { data_map, data_list })
+        let (source, content_hash) = TbItem::canonical_source(&data_list);
+        Ok(TbItem { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbItem, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::item::Item>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::item::Item>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbItem: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::item::Item::new_bin(&mut buf).map_err(|e| e.in_row("TbItem", format!("#{}", index)))?);
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+
+        let (source, content_hash) = TbItem::canonical_source(&data_list);
+        Ok(TbItem { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbItem, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbItem::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbItem::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbItem, TableLoaderError> {
+        Ok(TbItem::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::item::Item>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::item::Item>> {
@@ -129,6 +254,14 @@ impl std::ops::Index<i32> for TbItem {
 }
 impl luban_lib::table::Table for TbItem {
     type Value = std::sync::Arc<crate::item::Item>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbItemKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -187,6 +320,22 @@ impl luban_lib::table::MapTable for TbItem {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbItemRow {
+    type Table = TbItem;
+
+    fn key(&self) -> &TbItemKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbItem as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -207,17 +356,17 @@ impl bevy::asset::AssetLoader for TbItemLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbItemLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbItem::new(&t).unwrap();
-        bevy::log::info!("TbItemLoader loading over");
+        let tb = TbItem::from_bytes(bytes)?;
+        bevy::log::info!("TbItemLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -22,19 +22,41 @@ pub struct LayerTag {
 
 impl LayerTag{
     pub fn new(json: &serde_json::Value) -> Result<LayerTag, LubanError> {
-        let raw_layertag = json["raw_layertag"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let counter = json["counter"].as_bool().unwrap();
+        let raw_layertag = json["raw_layertag"].as_str().ok_or_else(|| LubanError::field("raw_layertag", "string", &json["raw_layertag"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let counter = json["counter"].as_bool().ok_or_else(|| LubanError::field("counter", "bool", &json["counter"]))?;
         
         Ok(LayerTag { raw_layertag, desc, counter, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<LayerTag, LubanError> {
+        let raw_layertag = buf.read_string().map_err(|e| LubanError::bin_field("raw_layertag", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let counter = buf.read_bool().map_err(|e| LubanError::bin_field("counter", e))?;
+        
+        Ok(LayerTag { raw_layertag, desc, counter, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_string(&self.raw_layertag);
+        buf.write_string(&self.desc);
+        buf.write_bool(self.counter);
+    }
+
+    pub const __ID__: i32 = -690942898;
 }
 
 
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbLayerTag {
     pub data_list: Vec<std::sync::Arc<crate::layertag::LayerTag>>,
-    pub data_map: bevy::utils::HashMap<String, std::sync::Arc<crate::layertag::LayerTag>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<String, std::sync::Arc<crate::layertag::LayerTag>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbLayerTag {
@@ -42,13 +64,65 @@ impl TbLayerTag {
         let mut data_map: bevy::utils::HashMap<String, std::sync::Arc<crate::layertag::LayerTag>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::layertag::LayerTag>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::layertag::LayerTag::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbLayerTag expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::layertag::LayerTag::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbLayerTag", row_id(x, "raw_layertag", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.raw_layertag.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbLayerTag::canonical_source(&data_list);
+        Ok(TbLayerTag { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbLayerTag, LubanError> {
+        let mut data_map: bevy::utils::HashMap<String, std::sync::Arc<crate::layertag::LayerTag>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::layertag::LayerTag>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbLayerTag: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::layertag::LayerTag::new_bin(&mut buf).map_err(|e| e.in_row("TbLayerTag", format!("#{}", index)))?);
             data_list.push(row.clone());
             data_map.insert(row.raw_layertag.clone(), row.clone());
         }
 
-        Ok(TbLayerTag { data_map, data_list })
+        let (source, content_hash) = TbLayerTag::canonical_source(&data_list);
+        Ok(TbLayerTag { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbLayerTag, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbLayerTag::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbLayerTag::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbLayerTag, TableLoaderError> {
+        Ok(TbLayerTag::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::layertag::LayerTag>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &String) -> Option<std::sync::Arc<crate::layertag::LayerTag>> {
@@ -65,6 +139,14 @@ impl std::ops::Index<String> for TbLayerTag {
 }
 impl luban_lib::table::Table for TbLayerTag {
     type Value = std::sync::Arc<crate::layertag::LayerTag>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbLayerTagKey = String;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -123,6 +205,22 @@ impl luban_lib::table::MapTable for TbLayerTag {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbLayerTagRow {
+    type Table = TbLayerTag;
+
+    fn key(&self) -> &TbLayerTagKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbLayerTag as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -143,17 +241,17 @@ impl bevy::asset::AssetLoader for TbLayerTagLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbLayerTagLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbLayerTag::new(&t).unwrap();
-        bevy::log::info!("TbLayerTagLoader loading over");
+        let tb = TbLayerTag::from_bytes(bytes)?;
+        bevy::log::info!("TbLayerTagLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -15,6 +15,7 @@
 
 pub mod prelude{
     pub use crate::*;
+    pub use crate::damage::*;
     pub use crate::effect::*;
     pub use crate::layertag::*;
     pub use crate::unit::*;
@@ -23,6 +24,7 @@ pub mod prelude{
     pub use crate::test::*;
 }
 
+use crate::damage::*;
 use crate::effect::*;
 use crate::layertag::*;
 use crate::unit::*;
@@ -37,26 +39,119 @@ pub trait GetBase<'a, T> {
     fn get_base(&'a self) -> Result<T, LubanError>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum LubanError {
     Loader(String),
     Table(String),
     Bean(String),
     Polymorphic(String),
     Unknown(String),
+    /// 字段的值不存在或者类型错误，field为`exchange_list[1].id`这样的路径。
+    Field {
+        table: String,
+        row: String,
+        field: String,
+        message: String,
+    },
+    /// 表格中所有出错的行。
+    Rows(Vec<LubanError>),
+}
+
+impl LubanError {
+    pub fn field(field: &str, expect: &str, value: &serde_json::Value) -> LubanError {
+        LubanError::Field {
+            table: String::new(),
+            row: String::new(),
+            field: field.to_string(),
+            message: format!("expect {}, found {}", expect, value),
+        }
+    }
+
+    /// 二进制数据读取失败，数据不足或者格式错误。
+    pub fn bin_field(field: &str, error: luban_lib::ByteBufError) -> LubanError {
+        LubanError::Field {
+            table: String::new(),
+            row: String::new(),
+            field: field.to_string(),
+            message: error.to_string(),
+        }
+    }
+
+    /// 嵌套的bean和列表元素出错时，字段路径加上父字段。
+    pub fn in_field(self, parent: &str) -> LubanError {
+        match self {
+            LubanError::Field { table, row, field, message } => LubanError::Field {
+                table,
+                row,
+                field: if field.is_empty() { parent.to_string() } else { format!("{}.{}", parent, field) },
+                message,
+            },
+            LubanError::Rows(errors) => LubanError::Rows(errors.into_iter().map(|e| e.in_field(parent)).collect()),
+            e => LubanError::Field {
+                table: String::new(),
+                row: String::new(),
+                field: parent.to_string(),
+                message: e.to_string(),
+            },
+        }
+    }
+
+    pub fn in_row(self, table: &str, row: String) -> LubanError {
+        match self {
+            LubanError::Field { field, message, .. } => LubanError::Field {
+                table: table.to_string(),
+                row,
+                field,
+                message,
+            },
+            LubanError::Rows(errors) => LubanError::Rows(errors.into_iter().map(|e| e.in_row(table, row.clone())).collect()),
+            e => LubanError::Field {
+                table: table.to_string(),
+                row,
+                field: String::new(),
+                message: e.to_string(),
+            },
+        }
+    }
+
+    /// 展开Rows，每一项为一个出错的字段。
+    pub fn flatten(self) -> Vec<LubanError> {
+        match self {
+            LubanError::Rows(errors) => errors.into_iter().flat_map(|e| e.flatten()).collect(),
+            e => vec![e],
+        }
+    }
+}
+
+/// 出错的行使用key字段的值，没有key时使用行号。
+fn row_id(json: &serde_json::Value, key: &str, index: usize) -> String {
+    match json.get(key) {
+        Some(id) if !key.is_empty() => id.to_string(),
+        _ => format!("#{}", index),
+    }
 }
 
 impl std::error::Error for LubanError {}
 
 impl std::fmt::Display for LubanError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(match self {
+        match self {
             LubanError::Loader(msg) |
             LubanError::Table(msg) |
             LubanError::Bean(msg) |
             LubanError::Polymorphic(msg) |
-            LubanError::Unknown(msg) => msg,
-        })
+            LubanError::Unknown(msg) => f.write_str(msg),
+            LubanError::Field { table, row, field, message } => write!(f, "{} row {} field {}: {}", table, row, field, message),
+            LubanError::Rows(errors) => {
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("\n")?;
+                    }
+                    write!(f, "{}", e)?;
+                }
+                Ok(())
+            }
+        }
     }
 }
     
@@ -70,8 +165,15 @@ pub enum TableLoaderError {
     LubanError(#[from] LubanError),
 }
 
+/// 表格数据文件的扩展名，开启bin feature时读取luban导出的二进制数据。
+#[cfg(not(feature = "bin"))]
+pub const TABLE_FILE_EXTENSION: &str = "json";
+#[cfg(feature = "bin")]
+pub const TABLE_FILE_EXTENSION: &str = "bytes";
+
 #[derive(Debug, bevy::prelude::Resource, Default)]
 pub struct Tables{
+    pub tb_damage_type: bevy::asset::Handle<crate::damage::TbDamageType>,
     pub tb_ability: bevy::asset::Handle<crate::effect::TbAbility>,
     pub tb_buff: bevy::asset::Handle<crate::effect::TbBuff>,
     pub tb_layer_tag: bevy::asset::Handle<crate::layertag::TbLayerTag>,
@@ -90,21 +192,23 @@ pub struct Tables{
 impl Tables {
     pub fn new<G: Clone + Send + Sync + 'static>(asset_server: bevy::prelude::Res<bevy::asset::AssetServer>, tables_path: std::path::PathBuf, guard: G) -> Tables {
         let mut tables = Tables {
-            tb_ability: asset_server.load_acquire(tables_path.join("effect_tbability.json"), guard.clone()),
-            tb_buff: asset_server.load_acquire(tables_path.join("effect_tbbuff.json"), guard.clone()),
-            tb_layer_tag: asset_server.load_acquire(tables_path.join("layertag_tblayertag.json"), guard.clone()),
-            tb_monster: asset_server.load_acquire(tables_path.join("unit_tbmonster.json"), guard.clone()),
-            tb_npc: asset_server.load_acquire(tables_path.join("unit_tbnpc.json"), guard.clone()),
-            tb_player: asset_server.load_acquire(tables_path.join("unit_tbplayer.json"), guard.clone()),
-            tb_relation_ship: asset_server.load_acquire(tables_path.join("unit_tbrelationship.json"), guard.clone()),
-            tb_item: asset_server.load_acquire(tables_path.join("item_tbitem.json"), guard.clone()),
-            tb_global: asset_server.load_acquire(tables_path.join("global_tbglobal.json"), guard.clone()),
-            tb_multi_index_list: asset_server.load_acquire(tables_path.join("test_tbmultiindexlist.json"), guard.clone()),
-            tb_multi_union_index_list: asset_server.load_acquire(tables_path.join("test_tbmultiunionindexlist.json"), guard.clone()),
-            tb_null_index_list: asset_server.load_acquire(tables_path.join("test_tbnullindexlist.json"), guard.clone()),
+            tb_damage_type: asset_server.load_acquire(tables_path.join(format!("damage_tbdamagetype.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_ability: asset_server.load_acquire(tables_path.join(format!("effect_tbability.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_buff: asset_server.load_acquire(tables_path.join(format!("effect_tbbuff.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_layer_tag: asset_server.load_acquire(tables_path.join(format!("layertag_tblayertag.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_monster: asset_server.load_acquire(tables_path.join(format!("unit_tbmonster.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_npc: asset_server.load_acquire(tables_path.join(format!("unit_tbnpc.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_player: asset_server.load_acquire(tables_path.join(format!("unit_tbplayer.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_relation_ship: asset_server.load_acquire(tables_path.join(format!("unit_tbrelationship.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_item: asset_server.load_acquire(tables_path.join(format!("item_tbitem.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_global: asset_server.load_acquire(tables_path.join(format!("global_tbglobal.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_multi_index_list: asset_server.load_acquire(tables_path.join(format!("test_tbmultiindexlist.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_multi_union_index_list: asset_server.load_acquire(tables_path.join(format!("test_tbmultiunionindexlist.{}", TABLE_FILE_EXTENSION)), guard.clone()),
+            tb_null_index_list: asset_server.load_acquire(tables_path.join(format!("test_tbnullindexlist.{}", TABLE_FILE_EXTENSION)), guard.clone()),
             table_handle_map: bevy::utils::HashMap::default(),
         };
 
+        tables.table_handle_map.insert(std::any::TypeId::of::<crate::damage::TbDamageType>(), tables.tb_damage_type.clone_weak().untyped());
         tables.table_handle_map.insert(std::any::TypeId::of::<crate::effect::TbAbility>(), tables.tb_ability.clone_weak().untyped());
         tables.table_handle_map.insert(std::any::TypeId::of::<crate::effect::TbBuff>(), tables.tb_buff.clone_weak().untyped());
         tables.table_handle_map.insert(std::any::TypeId::of::<crate::layertag::TbLayerTag>(), tables.tb_layer_tag.clone_weak().untyped());
@@ -137,42 +241,76 @@ impl bevy::app::Plugin for TableAssetsPlugin {
         use bevy::asset::AssetApp;
         app
             .add_event::<TablesLoadedEvent>()
+            .add_event::<TablesLoadFailed>()
+            .init_resource::<TableHashes>()
+            .init_asset_loader::<TbDamageTypeLoader>()
+            .init_asset::<TbDamageType>()
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbDamageType>, table_asset_load_failed::<TbDamageType>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbDamageType>)
+            .add_event::<TableRowChanged<TbDamageType>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbDamageType, crate::damage::DamageType>, table_row_components::<TbDamageTypeRow>).chain())
             .init_asset_loader::<TbAbilityLoader>()
             .init_asset::<TbAbility>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbAbility>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbAbility>, table_asset_load_failed::<TbAbility>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbAbility>)
+            .add_event::<TableRowChanged<TbAbility>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbAbility, crate::effect::Ability>, table_row_components::<TbAbilityRow>).chain())
             .init_asset_loader::<TbBuffLoader>()
             .init_asset::<TbBuff>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbBuff>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbBuff>, table_asset_load_failed::<TbBuff>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbBuff>)
+            .add_event::<TableRowChanged<TbBuff>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbBuff, crate::effect::Buff>, table_row_components::<TbBuffRow>).chain())
             .init_asset_loader::<TbLayerTagLoader>()
             .init_asset::<TbLayerTag>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbLayerTag>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbLayerTag>, table_asset_load_failed::<TbLayerTag>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbLayerTag>)
+            .add_event::<TableRowChanged<TbLayerTag>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbLayerTag, crate::layertag::LayerTag>, table_row_components::<TbLayerTagRow>).chain())
             .init_asset_loader::<TbMonsterLoader>()
             .init_asset::<TbMonster>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbMonster>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbMonster>, table_asset_load_failed::<TbMonster>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbMonster>)
+            .add_event::<TableRowChanged<TbMonster>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbMonster, crate::unit::Monster>, table_row_components::<TbMonsterRow>).chain())
             .init_asset_loader::<TbNpcLoader>()
             .init_asset::<TbNpc>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbNpc>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbNpc>, table_asset_load_failed::<TbNpc>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbNpc>)
+            .add_event::<TableRowChanged<TbNpc>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbNpc, crate::unit::Npc>, table_row_components::<TbNpcRow>).chain())
             .init_asset_loader::<TbPlayerLoader>()
             .init_asset::<TbPlayer>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbPlayer>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbPlayer>, table_asset_load_failed::<TbPlayer>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbPlayer>)
+            .add_event::<TableRowChanged<TbPlayer>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbPlayer, crate::unit::Player>, table_row_components::<TbPlayerRow>).chain())
             .init_asset_loader::<TbRelationShipLoader>()
             .init_asset::<TbRelationShip>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbRelationShip>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbRelationShip>, table_asset_load_failed::<TbRelationShip>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbRelationShip>)
             .init_asset_loader::<TbItemLoader>()
             .init_asset::<TbItem>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbItem>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbItem>, table_asset_load_failed::<TbItem>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbItem>)
+            .add_event::<TableRowChanged<TbItem>>()
+            .add_systems(bevy::app::PreUpdate, (table_row_changes::<TbItem, crate::item::Item>, table_row_components::<TbItemRow>).chain())
             .init_asset_loader::<TbGlobalLoader>()
             .init_asset::<TbGlobal>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbGlobal>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbGlobal>, table_asset_load_failed::<TbGlobal>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbGlobal>)
             .init_asset_loader::<TbMultiIndexListLoader>()
             .init_asset::<TbMultiIndexList>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbMultiIndexList>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbMultiIndexList>, table_asset_load_failed::<TbMultiIndexList>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbMultiIndexList>)
             .init_asset_loader::<TbMultiUnionIndexListLoader>()
             .init_asset::<TbMultiUnionIndexList>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbMultiUnionIndexList>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbMultiUnionIndexList>, table_asset_load_failed::<TbMultiUnionIndexList>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbMultiUnionIndexList>)
             .init_asset_loader::<TbNullIndexListLoader>()
             .init_asset::<TbNullIndexList>()
-            .add_systems(bevy::app::PreUpdate, table_asset_loadeds::<TbNullIndexList>)
+            .add_systems(bevy::app::PreUpdate, (table_asset_loadeds::<TbNullIndexList>, table_asset_load_failed::<TbNullIndexList>))
+            .add_systems(bevy::app::PreUpdate, table_content_hash::<TbNullIndexList>)
             ;
     }
 }
@@ -182,6 +320,112 @@ pub struct TablesLoadedEvent {
     pub asset_handles: smallvec::SmallVec<[bevy::asset::UntypedHandle;1]>,
 }
 
+/// 表格加载或者热加载失败，errors为表格中所有出错的字段，json格式错误等没有字段的错误为Loader。
+#[derive(Debug, Clone, bevy::ecs::event::Event)]
+pub struct TablesLoadFailed {
+    pub table: &'static str,
+    pub path: bevy::asset::AssetPath<'static>,
+    pub errors: Vec<LubanError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableRowChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// 热加载后逐行比较新旧表触发，第一次加载不触发。
+/// 只有map表会逐行触发，one表和list表的行没有key，热加载后通过AssetEvent或者TablesLoadedEvent重新读取整张表。
+#[derive(Debug, bevy::ecs::event::Event)]
+pub struct TableRowChanged<A: luban_lib::table::MapTable> {
+    pub table: &'static str,
+    pub key: A::Key,
+    pub kind: TableRowChangeKind,
+}
+
+/// 热加载的处理，保留上一次加载的表共享的data_map，新表替换后和旧表逐行比较。
+fn table_row_changes<A, R>(
+    mut event_reader: bevy::prelude::EventReader<bevy::asset::AssetEvent<A>>,
+    mut event_writer: bevy::prelude::EventWriter<TableRowChanged<A>>,
+    tables: bevy::prelude::Res<Tables>,
+    assets: bevy::prelude::Res<bevy::asset::Assets<A>>,
+    mut old_rows: bevy::prelude::Local<Option<std::sync::Arc<<A as luban_lib::table::MapTable>::Map>>>,
+) where
+    A: luban_lib::table::MapTable<Value = std::sync::Arc<R>, Map = bevy::utils::HashMap<<A as luban_lib::table::MapTable>::Key, std::sync::Arc<R>>> + bevy::reflect::TypePath,
+    A::Key: Clone + Eq + std::hash::Hash + Send + Sync,
+    R: bevy::reflect::PartialReflect,
+{
+    let Ok(handle) = tables.get_table_handle::<A>() else {
+        return;
+    };
+    if !event_reader.read().any(|event| event.is_loaded_with_dependencies(&handle) || event.is_modified(&handle)) {
+        return;
+    }
+    let Some(table) = assets.get(&handle) else {
+        return;
+    };
+    let new_rows = table.get_shared_data_map();
+    let Some(old_rows) = old_rows.replace(new_rows.clone()) else {
+        return;
+    };
+    if std::sync::Arc::ptr_eq(&old_rows, &new_rows) {
+        return;
+    }
+
+    let table_name = <A as bevy::reflect::TypePath>::short_type_path();
+    for (key, kind) in table_row_diff(&old_rows, &new_rows) {
+        event_writer.send(TableRowChanged { table: table_name, key, kind });
+    }
+}
+
+/// 逐行比较新旧表，行没有重新创建或者反射比较相等时认为没有变化。
+pub fn table_row_diff<K, R>(
+    old_rows: &bevy::utils::HashMap<K, std::sync::Arc<R>>,
+    new_rows: &bevy::utils::HashMap<K, std::sync::Arc<R>>,
+) -> Vec<(K, TableRowChangeKind)>
+where
+    K: Clone + Eq + std::hash::Hash,
+    R: bevy::reflect::PartialReflect,
+{
+    let mut changes = vec![];
+    for (key, new_row) in new_rows.iter() {
+        let kind = match old_rows.get(key) {
+            None => TableRowChangeKind::Added,
+            Some(old_row) if std::sync::Arc::ptr_eq(old_row, new_row) || old_row.reflect_partial_eq(new_row.as_ref()) == Some(true) => continue,
+            Some(_) => TableRowChangeKind::Modified,
+        };
+        changes.push((key.clone(), kind));
+    }
+    for key in old_rows.keys().filter(|key| !new_rows.contains_key(*key)) {
+        changes.push((key.clone(), TableRowChangeKind::Removed));
+    }
+    changes
+}
+
+/// 更新已经存在的行组件的data，之后可以通过Changed<TbXxxRow>响应行的变化。
+fn table_row_components<C: luban_lib::table::TableRow>(
+    mut event_reader: bevy::prelude::EventReader<TableRowChanged<C::Table>>,
+    tables: bevy::prelude::Res<Tables>,
+    assets: bevy::prelude::Res<bevy::asset::Assets<C::Table>>,
+    mut query: bevy::prelude::Query<&mut C>,
+) where
+    <C::Table as luban_lib::table::MapTable>::Key: PartialEq + Send + Sync,
+{
+    let keys: Vec<_> = event_reader.read().map(|event| &event.key).collect();
+    if keys.is_empty() {
+        return;
+    }
+    let Some(table) = tables.get_table_handle::<C::Table>().ok().and_then(|handle| assets.get(&handle)) else {
+        return;
+    };
+    for mut row in query.iter_mut() {
+        if keys.contains(&row.key()) {
+            let data = luban_lib::table::MapTable::get_row(table, row.key());
+            row.set_data(data);
+        }
+    }
+}
 
 fn table_asset_loadeds<A: bevy::asset::Asset>(
     mut event_reader: bevy::prelude::EventReader<bevy::asset::AssetEvent<A>>,
@@ -205,6 +449,146 @@ fn table_asset_loadeds<A: bevy::asset::Asset>(
     }
 }
 
+fn table_asset_load_failed<A: bevy::asset::Asset>(
+    mut event_reader: bevy::prelude::EventReader<bevy::asset::AssetLoadFailedEvent<A>>,
+    mut event_writer: bevy::prelude::EventWriter<TablesLoadFailed>,
+) {
+    for event in event_reader.read() {
+        let mut errors = vec![];
+        let mut source: Option<&(dyn std::error::Error + 'static)> = Some(&event.error);
+        while let Some(e) = source {
+            if let Some(TableLoaderError::LubanError(e)) = e.downcast_ref::<TableLoaderError>() {
+                errors = e.clone().flatten();
+                break;
+            }
+            source = e.source();
+        }
+        if errors.is_empty() {
+            errors.push(LubanError::Loader(event.error.to_string()));
+        }
+        for e in errors.iter() {
+            bevy::log::error!("table {} load failed: {}", event.path, e);
+        }
+        event_writer.send(TablesLoadFailed {
+            table: <A as bevy::reflect::TypePath>::short_type_path(),
+            path: event.path.clone(),
+            errors,
+        });
+    }
+}
+
+/// 表名(short_type_path)到内容哈希，加载和热加载后更新，用于客户端和服务器的握手。
+#[derive(Debug, Default, Clone, PartialEq, bevy::prelude::Resource, serde::Serialize, serde::Deserialize)]
+pub struct TableHashes(pub std::collections::BTreeMap<String, u64>);
+
+fn table_content_hash<A: luban_lib::table::Table>(
+    mut event_reader: bevy::prelude::EventReader<bevy::asset::AssetEvent<A>>,
+    tables: bevy::prelude::Res<Tables>,
+    assets: bevy::prelude::Res<bevy::asset::Assets<A>>,
+    mut hashes: bevy::prelude::ResMut<TableHashes>,
+) {
+    let Ok(handle) = tables.get_table_handle::<A>() else {
+        return;
+    };
+    if !event_reader.read().any(|event| event.is_loaded_with_dependencies(&handle) || event.is_modified(&handle)) {
+        return;
+    }
+    let Some(table) = assets.get(&handle) else {
+        return;
+    };
+    hashes.0.insert(<A as bevy::reflect::TypePath>::short_type_path().to_string(), table.content_hash());
+}
+
+/// 按表名取出计算哈希的二进制内容，表没有加载时为None。
+pub fn table_source(world: &bevy::prelude::World, table: &str) -> Option<Vec<u8>> {
+    fn source<A: luban_lib::table::Table>(world: &bevy::prelude::World, handle: &bevy::asset::Handle<A>) -> Option<Vec<u8>> {
+        world.get_resource::<bevy::asset::Assets<A>>()?.get(handle).map(|table| table.source().to_vec())
+    }
+    let tables = world.get_resource::<Tables>()?;
+    match table {
+        "TbDamageType" => source(world, &tables.tb_damage_type),
+        "TbAbility" => source(world, &tables.tb_ability),
+        "TbBuff" => source(world, &tables.tb_buff),
+        "TbLayerTag" => source(world, &tables.tb_layer_tag),
+        "TbMonster" => source(world, &tables.tb_monster),
+        "TbNpc" => source(world, &tables.tb_npc),
+        "TbPlayer" => source(world, &tables.tb_player),
+        "TbRelationShip" => source(world, &tables.tb_relation_ship),
+        "TbItem" => source(world, &tables.tb_item),
+        "TbGlobal" => source(world, &tables.tb_global),
+        "TbMultiIndexList" => source(world, &tables.tb_multi_index_list),
+        "TbMultiUnionIndexList" => source(world, &tables.tb_multi_union_index_list),
+        "TbNullIndexList" => source(world, &tables.tb_null_index_list),
+        _ => None,
+    }
+}
+
+/// 用其他端的table_source替换本地的表，和热加载一样触发Modified，之后更新哈希和行。
+pub fn override_table(world: &mut bevy::prelude::World, table: &str, bytes: Vec<u8>) -> Result<(), TableLoaderError> {
+    fn insert<A: bevy::asset::Asset>(world: &mut bevy::prelude::World, id: bevy::asset::AssetId<A>, table: A) {
+        world.resource_mut::<bevy::asset::Assets<A>>().insert(id, table);
+    }
+    let Some(tables) = world.get_resource::<Tables>() else {
+        return Err(LubanError::Table("tables not found".to_string()).into());
+    };
+    match table {
+        "TbDamageType" => {
+            let handle = tables.tb_damage_type.id();
+            insert(world, handle, crate::damage::TbDamageType::from_source(bytes)?);
+        }
+        "TbAbility" => {
+            let handle = tables.tb_ability.id();
+            insert(world, handle, crate::effect::TbAbility::from_source(bytes)?);
+        }
+        "TbBuff" => {
+            let handle = tables.tb_buff.id();
+            insert(world, handle, crate::effect::TbBuff::from_source(bytes)?);
+        }
+        "TbLayerTag" => {
+            let handle = tables.tb_layer_tag.id();
+            insert(world, handle, crate::layertag::TbLayerTag::from_source(bytes)?);
+        }
+        "TbMonster" => {
+            let handle = tables.tb_monster.id();
+            insert(world, handle, crate::unit::TbMonster::from_source(bytes)?);
+        }
+        "TbNpc" => {
+            let handle = tables.tb_npc.id();
+            insert(world, handle, crate::unit::TbNpc::from_source(bytes)?);
+        }
+        "TbPlayer" => {
+            let handle = tables.tb_player.id();
+            insert(world, handle, crate::unit::TbPlayer::from_source(bytes)?);
+        }
+        "TbRelationShip" => {
+            let handle = tables.tb_relation_ship.id();
+            insert(world, handle, crate::unit::TbRelationShip::from_source(bytes)?);
+        }
+        "TbItem" => {
+            let handle = tables.tb_item.id();
+            insert(world, handle, crate::item::TbItem::from_source(bytes)?);
+        }
+        "TbGlobal" => {
+            let handle = tables.tb_global.id();
+            insert(world, handle, crate::global::TbGlobal::from_source(bytes)?);
+        }
+        "TbMultiIndexList" => {
+            let handle = tables.tb_multi_index_list.id();
+            insert(world, handle, crate::test::TbMultiIndexList::from_source(bytes)?);
+        }
+        "TbMultiUnionIndexList" => {
+            let handle = tables.tb_multi_union_index_list.id();
+            insert(world, handle, crate::test::TbMultiUnionIndexList::from_source(bytes)?);
+        }
+        "TbNullIndexList" => {
+            let handle = tables.tb_null_index_list.id();
+            insert(world, handle, crate::test::TbNullIndexList::from_source(bytes)?);
+        }
+        _ => return Err(LubanError::Table(format!("table not found: {}", table)).into()),
+    }
+    Ok(())
+}
+
 #[derive(Default)]
 pub struct TableProtocolPlugin;
 
@@ -212,6 +596,8 @@ impl bevy::app::Plugin for TableProtocolPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         use client::ComponentSyncMode;
         use lightyear::prelude::*;
+        app.register_component::<TbDamageTypeRow>(ChannelDirection::ServerToClient)
+            .add_prediction(ComponentSyncMode::Once);
         app.register_component::<TbAbilityRow>(ChannelDirection::ServerToClient)
             .add_prediction(ComponentSyncMode::Once);
         app.register_component::<TbBuffRow>(ChannelDirection::ServerToClient)
@@ -233,6 +619,7 @@ impl bevy::app::Plugin for TableProtocolPlugin {
     }
 }
 
+pub mod damage;
 pub mod effect;
 pub mod layertag;
 pub mod unit;
@@ -249,11 +636,25 @@ pub struct vector2 {
 
 impl vector2{
     pub fn new(json: &serde_json::Value) -> Result<vector2, LubanError> {
-        let x = (json["x"].as_f64().unwrap() as f32);
-        let y = (json["y"].as_f64().unwrap() as f32);
+        let x = (json["x"].as_f64().ok_or_else(|| LubanError::field("x", "float", &json["x"]))? as f32);
+        let y = (json["y"].as_f64().ok_or_else(|| LubanError::field("y", "float", &json["y"]))? as f32);
         
         Ok(vector2 { x, y, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<vector2, LubanError> {
+        let x = buf.read_float().map_err(|e| LubanError::bin_field("x", e))?;
+        let y = buf.read_float().map_err(|e| LubanError::bin_field("y", e))?;
+        
+        Ok(vector2 { x, y, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_float(self.x);
+        buf.write_float(self.y);
+    }
+
+    pub const __ID__: i32 = 337790799;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -265,12 +666,28 @@ pub struct vector3 {
 
 impl vector3{
     pub fn new(json: &serde_json::Value) -> Result<vector3, LubanError> {
-        let x = (json["x"].as_f64().unwrap() as f32);
-        let y = (json["y"].as_f64().unwrap() as f32);
-        let z = (json["z"].as_f64().unwrap() as f32);
+        let x = (json["x"].as_f64().ok_or_else(|| LubanError::field("x", "float", &json["x"]))? as f32);
+        let y = (json["y"].as_f64().ok_or_else(|| LubanError::field("y", "float", &json["y"]))? as f32);
+        let z = (json["z"].as_f64().ok_or_else(|| LubanError::field("z", "float", &json["z"]))? as f32);
+        
+        Ok(vector3 { x, y, z, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<vector3, LubanError> {
+        let x = buf.read_float().map_err(|e| LubanError::bin_field("x", e))?;
+        let y = buf.read_float().map_err(|e| LubanError::bin_field("y", e))?;
+        let z = buf.read_float().map_err(|e| LubanError::bin_field("z", e))?;
         
         Ok(vector3 { x, y, z, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_float(self.x);
+        buf.write_float(self.y);
+        buf.write_float(self.z);
+    }
+
+    pub const __ID__: i32 = 337790800;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -283,13 +700,31 @@ pub struct vector4 {
 
 impl vector4{
     pub fn new(json: &serde_json::Value) -> Result<vector4, LubanError> {
-        let x = (json["x"].as_f64().unwrap() as f32);
-        let y = (json["y"].as_f64().unwrap() as f32);
-        let z = (json["z"].as_f64().unwrap() as f32);
-        let w = (json["w"].as_f64().unwrap() as f32);
+        let x = (json["x"].as_f64().ok_or_else(|| LubanError::field("x", "float", &json["x"]))? as f32);
+        let y = (json["y"].as_f64().ok_or_else(|| LubanError::field("y", "float", &json["y"]))? as f32);
+        let z = (json["z"].as_f64().ok_or_else(|| LubanError::field("z", "float", &json["z"]))? as f32);
+        let w = (json["w"].as_f64().ok_or_else(|| LubanError::field("w", "float", &json["w"]))? as f32);
         
         Ok(vector4 { x, y, z, w, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<vector4, LubanError> {
+        let x = buf.read_float().map_err(|e| LubanError::bin_field("x", e))?;
+        let y = buf.read_float().map_err(|e| LubanError::bin_field("y", e))?;
+        let z = buf.read_float().map_err(|e| LubanError::bin_field("z", e))?;
+        let w = buf.read_float().map_err(|e| LubanError::bin_field("w", e))?;
+        
+        Ok(vector4 { x, y, z, w, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_float(self.x);
+        buf.write_float(self.y);
+        buf.write_float(self.z);
+        buf.write_float(self.w);
+    }
+
+    pub const __ID__: i32 = 337790801;
 }
 
 
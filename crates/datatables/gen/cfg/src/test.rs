@@ -32,14 +32,34 @@ pub struct MultiIndexList {
 
 impl MultiIndexList{
     pub fn new(json: &serde_json::Value) -> Result<MultiIndexList, LubanError> {
-        let id1 = (json["id1"].as_i64().unwrap() as i32);
-        let id2 = (json["id2"].as_i64().unwrap() as i32);
-        let id3 = json["id3"].as_str().unwrap().to_string();
-        let num = (json["num"].as_i64().unwrap() as i32);
-        let desc = json["desc"].as_str().unwrap().to_string();
+        let id1 = (json["id1"].as_i64().ok_or_else(|| LubanError::field("id1", "int", &json["id1"]))? as i32);
+        let id2 = (json["id2"].as_i64().ok_or_else(|| LubanError::field("id2", "int", &json["id2"]))? as i32);
+        let id3 = json["id3"].as_str().ok_or_else(|| LubanError::field("id3", "string", &json["id3"]))?.to_string();
+        let num = (json["num"].as_i64().ok_or_else(|| LubanError::field("num", "int", &json["num"]))? as i32);
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
         
         Ok(MultiIndexList { id1, id2, id3, num, desc, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<MultiIndexList, LubanError> {
+        let id1 = buf.read_int().map_err(|e| LubanError::bin_field("id1", e))?;
+        let id2 = buf.read_int().map_err(|e| LubanError::bin_field("id2", e))?;
+        let id3 = buf.read_string().map_err(|e| LubanError::bin_field("id3", e))?;
+        let num = buf.read_int().map_err(|e| LubanError::bin_field("num", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        
+        Ok(MultiIndexList { id1, id2, id3, num, desc, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id1);
+        buf.write_int(self.id2);
+        buf.write_string(&self.id3);
+        buf.write_int(self.num);
+        buf.write_string(&self.desc);
+    }
+
+    pub const __ID__: i32 = 2016237651;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -53,14 +73,34 @@ pub struct MultiUnionIndexList {
 
 impl MultiUnionIndexList{
     pub fn new(json: &serde_json::Value) -> Result<MultiUnionIndexList, LubanError> {
-        let id1 = (json["id1"].as_i64().unwrap() as i32);
-        let id2 = (json["id2"].as_i64().unwrap() as i32);
-        let id3 = json["id3"].as_str().unwrap().to_string();
-        let num = (json["num"].as_i64().unwrap() as i32);
-        let desc = json["desc"].as_str().unwrap().to_string();
+        let id1 = (json["id1"].as_i64().ok_or_else(|| LubanError::field("id1", "int", &json["id1"]))? as i32);
+        let id2 = (json["id2"].as_i64().ok_or_else(|| LubanError::field("id2", "int", &json["id2"]))? as i32);
+        let id3 = json["id3"].as_str().ok_or_else(|| LubanError::field("id3", "string", &json["id3"]))?.to_string();
+        let num = (json["num"].as_i64().ok_or_else(|| LubanError::field("num", "int", &json["num"]))? as i32);
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        
+        Ok(MultiUnionIndexList { id1, id2, id3, num, desc, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<MultiUnionIndexList, LubanError> {
+        let id1 = buf.read_int().map_err(|e| LubanError::bin_field("id1", e))?;
+        let id2 = buf.read_int().map_err(|e| LubanError::bin_field("id2", e))?;
+        let id3 = buf.read_string().map_err(|e| LubanError::bin_field("id3", e))?;
+        let num = buf.read_int().map_err(|e| LubanError::bin_field("num", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
         
         Ok(MultiUnionIndexList { id1, id2, id3, num, desc, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id1);
+        buf.write_int(self.id2);
+        buf.write_string(&self.id3);
+        buf.write_int(self.num);
+        buf.write_string(&self.desc);
+    }
+
+    pub const __ID__: i32 = 1966847134;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -71,11 +111,25 @@ pub struct NullIndexList {
 
 impl NullIndexList{
     pub fn new(json: &serde_json::Value) -> Result<NullIndexList, LubanError> {
-        let x = (json["x"].as_i64().unwrap() as i32);
-        let y = (json["y"].as_i64().unwrap() as i32);
+        let x = (json["x"].as_i64().ok_or_else(|| LubanError::field("x", "int", &json["x"]))? as i32);
+        let y = (json["y"].as_i64().ok_or_else(|| LubanError::field("y", "int", &json["y"]))? as i32);
+        
+        Ok(NullIndexList { x, y, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<NullIndexList, LubanError> {
+        let x = buf.read_int().map_err(|e| LubanError::bin_field("x", e))?;
+        let y = buf.read_int().map_err(|e| LubanError::bin_field("y", e))?;
         
         Ok(NullIndexList { x, y, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.x);
+        buf.write_int(self.y);
+    }
+
+    pub const __ID__: i32 = -573800883;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -84,13 +138,35 @@ pub struct Shape {
 
 impl Shape {
     pub fn new(json: &serde_json::Value) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
-        let type_id = json["$type"].as_str().unwrap();
+        let type_id = json["$type"].as_str().ok_or_else(|| LubanError::field("$type", "string", &json["$type"]))?;
         match type_id {
             "Circle" => Ok(std::sync::Arc::new(crate::test::Circle::new(json)?)),
             "Rectangle" => Ok(std::sync::Arc::new(crate::test::Rectangle::new(json)?)),
             _ => Err(LubanError::Bean(format!("Invalid type for Shape:{}", type_id)))
         }
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<std::sync::Arc<AbstractBase>, LubanError> {
+        let type_id = buf.read_int().map_err(|e| LubanError::bin_field("$type", e))?;
+        match type_id {
+            crate::test::Circle::__ID__ => Ok(std::sync::Arc::new(crate::test::Circle::new_bin(buf)?)),
+            crate::test::Rectangle::__ID__ => Ok(std::sync::Arc::new(crate::test::Rectangle::new_bin(buf)?)),
+            _ => Err(LubanError::Bean(format!("Invalid type for Shape:{}", type_id)))
+        }
+    }
+
+    pub fn write_bin(value: &AbstractBase, buf: &mut luban_lib::ByteBuf) {
+        if let Some(value) = value.downcast_ref::<crate::test::Circle>() {
+            buf.write_int(crate::test::Circle::__ID__);
+            value.write_bin(buf);
+            return;
+        }
+        if let Some(value) = value.downcast_ref::<crate::test::Rectangle>() {
+            buf.write_int(crate::test::Rectangle::__ID__);
+            value.write_bin(buf);
+            return;
+        }
+    }
 }
 
 pub trait TShape {
@@ -126,10 +202,22 @@ pub struct Circle {
 
 impl Circle{
     pub fn new(json: &serde_json::Value) -> Result<Circle, LubanError> {
-        let radius = (json["radius"].as_f64().unwrap() as f32);
+        let radius = (json["radius"].as_f64().ok_or_else(|| LubanError::field("radius", "float", &json["radius"]))? as f32);
         
         Ok(Circle { radius, })
     }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Circle, LubanError> {
+        let radius = buf.read_float().map_err(|e| LubanError::bin_field("radius", e))?;
+        
+        Ok(Circle { radius, })
+    }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_float(self.radius);
+    }
+
+    pub const __ID__: i32 = 2131829196;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -143,11 +231,25 @@ pub struct Rectangle {
 
 impl Rectangle{
     pub fn new(json: &serde_json::Value) -> Result<Rectangle, LubanError> {
-        let width = (json["width"].as_f64().unwrap() as f32);
-        let height = (json["height"].as_f64().unwrap() as f32);
+        let width = (json["width"].as_f64().ok_or_else(|| LubanError::field("width", "float", &json["width"]))? as f32);
+        let height = (json["height"].as_f64().ok_or_else(|| LubanError::field("height", "float", &json["height"]))? as f32);
+        
+        Ok(Rectangle { width, height, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Rectangle, LubanError> {
+        let width = buf.read_float().map_err(|e| LubanError::bin_field("width", e))?;
+        let height = buf.read_float().map_err(|e| LubanError::bin_field("height", e))?;
         
         Ok(Rectangle { width, height, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_float(self.width);
+        buf.write_float(self.height);
+    }
+
+    pub const __ID__: i32 = -31893773;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -164,13 +266,31 @@ pub struct TestExcelBean1 {
 
 impl TestExcelBean1{
     pub fn new(json: &serde_json::Value) -> Result<TestExcelBean1, LubanError> {
-        let x1 = (json["x1"].as_i64().unwrap() as i32);
-        let x2 = json["x2"].as_str().unwrap().to_string();
-        let x3 = (json["x3"].as_i64().unwrap() as i32);
-        let x4 = (json["x4"].as_f64().unwrap() as f32);
+        let x1 = (json["x1"].as_i64().ok_or_else(|| LubanError::field("x1", "int", &json["x1"]))? as i32);
+        let x2 = json["x2"].as_str().ok_or_else(|| LubanError::field("x2", "string", &json["x2"]))?.to_string();
+        let x3 = (json["x3"].as_i64().ok_or_else(|| LubanError::field("x3", "int", &json["x3"]))? as i32);
+        let x4 = (json["x4"].as_f64().ok_or_else(|| LubanError::field("x4", "float", &json["x4"]))? as f32);
+        
+        Ok(TestExcelBean1 { x1, x2, x3, x4, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<TestExcelBean1, LubanError> {
+        let x1 = buf.read_int().map_err(|e| LubanError::bin_field("x1", e))?;
+        let x2 = buf.read_string().map_err(|e| LubanError::bin_field("x2", e))?;
+        let x3 = buf.read_int().map_err(|e| LubanError::bin_field("x3", e))?;
+        let x4 = buf.read_float().map_err(|e| LubanError::bin_field("x4", e))?;
         
         Ok(TestExcelBean1 { x1, x2, x3, x4, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.x1);
+        buf.write_string(&self.x2);
+        buf.write_int(self.x3);
+        buf.write_float(self.x4);
+    }
+
+    pub const __ID__: i32 = -1738345160;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -185,12 +305,28 @@ pub struct TestExcelBean2 {
 
 impl TestExcelBean2{
     pub fn new(json: &serde_json::Value) -> Result<TestExcelBean2, LubanError> {
-        let y1 = (json["y1"].as_i64().unwrap() as i32);
-        let y2 = json["y2"].as_str().unwrap().to_string();
-        let y3 = (json["y3"].as_f64().unwrap() as f32);
+        let y1 = (json["y1"].as_i64().ok_or_else(|| LubanError::field("y1", "int", &json["y1"]))? as i32);
+        let y2 = json["y2"].as_str().ok_or_else(|| LubanError::field("y2", "string", &json["y2"]))?.to_string();
+        let y3 = (json["y3"].as_f64().ok_or_else(|| LubanError::field("y3", "float", &json["y3"]))? as f32);
+        
+        Ok(TestExcelBean2 { y1, y2, y3, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<TestExcelBean2, LubanError> {
+        let y1 = buf.read_int().map_err(|e| LubanError::bin_field("y1", e))?;
+        let y2 = buf.read_string().map_err(|e| LubanError::bin_field("y2", e))?;
+        let y3 = buf.read_float().map_err(|e| LubanError::bin_field("y3", e))?;
         
         Ok(TestExcelBean2 { y1, y2, y3, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.y1);
+        buf.write_string(&self.y2);
+        buf.write_float(self.y3);
+    }
+
+    pub const __ID__: i32 = -1738345159;
 }
 
 
@@ -200,16 +336,31 @@ pub struct TbMultiIndexList {
     pub data_map_id1: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>>,
     pub data_map_id2: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>>,
     pub data_map_id3: bevy::utils::HashMap<String, std::sync::Arc<crate::test::MultiIndexList>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbMultiIndexList {
     pub fn new(json: &serde_json::Value) -> Result<TbMultiIndexList, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiIndexList>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::test::MultiIndexList::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbMultiIndexList expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::test::MultiIndexList::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbMultiIndexList", row_id(x, "", index)));
+                    continue;
+                }
+            };
             data_list.push(row.clone());
         }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
         let mut data_map_id1: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
         let mut data_map_id2: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
         let mut data_map_id3: bevy::utils::HashMap<String, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
@@ -219,14 +370,71 @@ impl TbMultiIndexList {
             data_map_id3.insert(x.id3.clone(), x.clone());
         }
 
+        let (source, content_hash) = TbMultiIndexList::canonical_source(&data_list);
     Ok(TbMultiIndexList { 
             data_list,
             data_map_id1,
             data_map_id2,
             data_map_id3,
+            content_hash,
+            source,
         })
     }
 
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbMultiIndexList, LubanError> {
+        let mut data_list: Vec<std::sync::Arc<crate::test::MultiIndexList>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbMultiIndexList: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::test::MultiIndexList::new_bin(&mut buf).map_err(|e| e.in_row("TbMultiIndexList", format!("#{}", index)))?);
+            data_list.push(row.clone());
+        }
+        let mut data_map_id1: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
+        let mut data_map_id2: bevy::utils::HashMap<i32, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
+        let mut data_map_id3: bevy::utils::HashMap<String, std::sync::Arc<crate::test::MultiIndexList>> = Default::default();
+        for x in &data_list {
+            data_map_id1.insert(x.id1.clone(), x.clone());
+            data_map_id2.insert(x.id2.clone(), x.clone());
+            data_map_id3.insert(x.id3.clone(), x.clone());
+        }
+
+        let (source, content_hash) = TbMultiIndexList::canonical_source(&data_list);
+    Ok(TbMultiIndexList { 
+            data_list,
+            data_map_id1,
+            data_map_id2,
+            data_map_id3,
+            content_hash,
+            source,
+        })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbMultiIndexList, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbMultiIndexList::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbMultiIndexList::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbMultiIndexList, TableLoaderError> {
+        Ok(TbMultiIndexList::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::test::MultiIndexList>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
+    }
+
     pub fn get_by_id1(&self, key: &i32) -> Option<std::sync::Arc<crate::test::MultiIndexList>> {
         self.data_map_id1.get(key).map(|x| x.clone())
     }
@@ -240,6 +448,14 @@ impl TbMultiIndexList {
 
 impl luban_lib::table::Table for TbMultiIndexList {
     type Value = std::sync::Arc<crate::test::MultiIndexList>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 impl luban_lib::table::ListTable for TbMultiIndexList {}
 
@@ -302,17 +518,17 @@ impl bevy::asset::AssetLoader for TbMultiIndexListLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbMultiIndexListLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbMultiIndexList::new(&t).unwrap();
-        bevy::log::info!("TbMultiIndexListLoader loading over");
+        let tb = TbMultiIndexList::from_bytes(bytes)?;
+        bevy::log::info!("TbMultiIndexListLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -321,14 +537,52 @@ impl bevy::asset::AssetLoader for TbMultiIndexListLoader {
 pub struct TbMultiUnionIndexList {
     pub data_list: Vec<std::sync::Arc<crate::test::MultiUnionIndexList>>,
     pub data_map_union: bevy::utils::HashMap<(i32, i32, String), std::sync::Arc<crate::test::MultiUnionIndexList>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbMultiUnionIndexList {
     pub fn new(json: &serde_json::Value) -> Result<TbMultiUnionIndexList, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::MultiUnionIndexList>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::test::MultiUnionIndexList::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbMultiUnionIndexList expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::test::MultiUnionIndexList::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbMultiUnionIndexList", row_id(x, "", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+        let mut data_map_union: bevy::utils::HashMap<(i32, i32, String), std::sync::Arc<crate::test::MultiUnionIndexList>> = Default::default();
+        for x in &data_list {
+            data_map_union.insert((x.id1, x.id2, x.id3.clone()), x.clone());
+        }
+
+        let (source, content_hash) = TbMultiUnionIndexList::canonical_source(&data_list);
+    Ok(TbMultiUnionIndexList { 
+            data_list,
+            data_map_union,
+            content_hash,
+            source,
+        })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbMultiUnionIndexList, LubanError> {
+        let mut data_list: Vec<std::sync::Arc<crate::test::MultiUnionIndexList>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbMultiUnionIndexList: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::test::MultiUnionIndexList::new_bin(&mut buf).map_err(|e| e.in_row("TbMultiUnionIndexList", format!("#{}", index)))?);
             data_list.push(row.clone());
         }
         let mut data_map_union: bevy::utils::HashMap<(i32, i32, String), std::sync::Arc<crate::test::MultiUnionIndexList>> = Default::default();
@@ -336,12 +590,40 @@ impl TbMultiUnionIndexList {
             data_map_union.insert((x.id1, x.id2, x.id3.clone()), x.clone());
         }
 
+        let (source, content_hash) = TbMultiUnionIndexList::canonical_source(&data_list);
     Ok(TbMultiUnionIndexList { 
             data_list,
             data_map_union,
+            content_hash,
+            source,
         })
     }
 
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbMultiUnionIndexList, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbMultiUnionIndexList::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbMultiUnionIndexList::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbMultiUnionIndexList, TableLoaderError> {
+        Ok(TbMultiUnionIndexList::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::test::MultiUnionIndexList>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
+    }
+
     pub fn get(&self, key: &(i32, i32, String)) -> Option<std::sync::Arc<crate::test::MultiUnionIndexList>> {
         self.data_map_union.get(key).map(|x| x.clone())
     }
@@ -349,6 +631,14 @@ impl TbMultiUnionIndexList {
 
 impl luban_lib::table::Table for TbMultiUnionIndexList {
     type Value = std::sync::Arc<crate::test::MultiUnionIndexList>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 impl luban_lib::table::ListTable for TbMultiUnionIndexList {}
 
@@ -403,17 +693,17 @@ impl bevy::asset::AssetLoader for TbMultiUnionIndexListLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbMultiUnionIndexListLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbMultiUnionIndexList::new(&t).unwrap();
-        bevy::log::info!("TbMultiUnionIndexListLoader loading over");
+        let tb = TbMultiUnionIndexList::from_bytes(bytes)?;
+        bevy::log::info!("TbMultiUnionIndexListLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -421,26 +711,95 @@ impl bevy::asset::AssetLoader for TbMultiUnionIndexListLoader {
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbNullIndexList {
     pub data_list: Vec<std::sync::Arc<crate::test::NullIndexList>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbNullIndexList {
     pub fn new(json: &serde_json::Value) -> Result<TbNullIndexList, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::test::NullIndexList>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::test::NullIndexList::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbNullIndexList expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::test::NullIndexList::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbNullIndexList", row_id(x, "", index)));
+                    continue;
+                }
+            };
             data_list.push(row.clone());
         }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
 
+        let (source, content_hash) = TbNullIndexList::canonical_source(&data_list);
     Ok(TbNullIndexList { 
             data_list,
+            content_hash,
+            source,
         })
     }
 
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbNullIndexList, LubanError> {
+        let mut data_list: Vec<std::sync::Arc<crate::test::NullIndexList>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbNullIndexList: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::test::NullIndexList::new_bin(&mut buf).map_err(|e| e.in_row("TbNullIndexList", format!("#{}", index)))?);
+            data_list.push(row.clone());
+        }
+
+        let (source, content_hash) = TbNullIndexList::canonical_source(&data_list);
+    Ok(TbNullIndexList { 
+            data_list,
+            content_hash,
+            source,
+        })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbNullIndexList, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbNullIndexList::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbNullIndexList::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbNullIndexList, TableLoaderError> {
+        Ok(TbNullIndexList::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::test::NullIndexList>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
+    }
+
 }
 
 impl luban_lib::table::Table for TbNullIndexList {
     type Value = std::sync::Arc<crate::test::NullIndexList>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 impl luban_lib::table::ListTable for TbNullIndexList {}
 
@@ -475,17 +834,17 @@ impl bevy::asset::AssetLoader for TbNullIndexListLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbNullIndexListLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbNullIndexList::new(&t).unwrap();
-        bevy::log::info!("TbNullIndexListLoader loading over");
+        let tb = TbNullIndexList::from_bytes(bytes)?;
+        bevy::log::info!("TbNullIndexListLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
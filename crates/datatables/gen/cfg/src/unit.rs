@@ -29,6 +29,25 @@ impl From<i32> for RelationShipType {
     }
 }
 
+impl RelationShipType {
+    pub fn try_from_value(value: i32) -> Result<Self, LubanError> {
+        match value {
+            0 => Ok(RelationShipType::None),
+            1 => Ok(RelationShipType::Hostility),
+            2 => Ok(RelationShipType::Friendly),
+            _ => Err(LubanError::Bean(format!("Invalid value for RelationShipType:{}", value))),
+        }
+    }
+
+    pub fn to_value(&self) -> i32 {
+        match self {
+            RelationShipType::None => 0,
+            RelationShipType::Hostility => 1,
+            RelationShipType::Friendly => 2,
+        }
+    }
+}
+
 #[derive(bevy::reflect::Reflect, Debug)]
 pub struct Monster {
     /// 这是id
@@ -43,13 +62,31 @@ pub struct Monster {
 
 impl Monster{
     pub fn new(json: &serde_json::Value) -> Result<Monster, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let camp = (json["camp"].as_i64().unwrap() as i32);
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let camp = (json["camp"].as_i64().ok_or_else(|| LubanError::field("camp", "int", &json["camp"]))? as i32);
+        
+        Ok(Monster { id, name, desc, camp, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Monster, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let camp = buf.read_int().map_err(|e| LubanError::bin_field("camp", e))?;
         
         Ok(Monster { id, name, desc, camp, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_int(self.camp);
+    }
+
+    pub const __ID__: i32 = 922420560;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -66,13 +103,31 @@ pub struct Npc {
 
 impl Npc{
     pub fn new(json: &serde_json::Value) -> Result<Npc, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let camp = (json["camp"].as_i64().unwrap() as i32);
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let camp = (json["camp"].as_i64().ok_or_else(|| LubanError::field("camp", "int", &json["camp"]))? as i32);
+        
+        Ok(Npc { id, name, desc, camp, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Npc, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let camp = buf.read_int().map_err(|e| LubanError::bin_field("camp", e))?;
         
         Ok(Npc { id, name, desc, camp, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_int(self.camp);
+    }
+
+    pub const __ID__: i32 = -293825705;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -93,15 +148,37 @@ pub struct Player {
 
 impl Player{
     pub fn new(json: &serde_json::Value) -> Result<Player, LubanError> {
-        let id = (json["id"].as_i64().unwrap() as i32);
-        let name = json["name"].as_str().unwrap().to_string();
-        let desc = json["desc"].as_str().unwrap().to_string();
-        let camp = (json["camp"].as_i64().unwrap() as i32);
-        let capsule_radius = (json["capsule_radius"].as_f64().unwrap() as f32);
-        let capsule_height = (json["capsule_height"].as_f64().unwrap() as f32);
+        let id = (json["id"].as_i64().ok_or_else(|| LubanError::field("id", "int", &json["id"]))? as i32);
+        let name = json["name"].as_str().ok_or_else(|| LubanError::field("name", "string", &json["name"]))?.to_string();
+        let desc = json["desc"].as_str().ok_or_else(|| LubanError::field("desc", "string", &json["desc"]))?.to_string();
+        let camp = (json["camp"].as_i64().ok_or_else(|| LubanError::field("camp", "int", &json["camp"]))? as i32);
+        let capsule_radius = (json["capsule_radius"].as_f64().ok_or_else(|| LubanError::field("capsule_radius", "float", &json["capsule_radius"]))? as f32);
+        let capsule_height = (json["capsule_height"].as_f64().ok_or_else(|| LubanError::field("capsule_height", "float", &json["capsule_height"]))? as f32);
+        
+        Ok(Player { id, name, desc, camp, capsule_radius, capsule_height, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<Player, LubanError> {
+        let id = buf.read_int().map_err(|e| LubanError::bin_field("id", e))?;
+        let name = buf.read_string().map_err(|e| LubanError::bin_field("name", e))?;
+        let desc = buf.read_string().map_err(|e| LubanError::bin_field("desc", e))?;
+        let camp = buf.read_int().map_err(|e| LubanError::bin_field("camp", e))?;
+        let capsule_radius = buf.read_float().map_err(|e| LubanError::bin_field("capsule_radius", e))?;
+        let capsule_height = buf.read_float().map_err(|e| LubanError::bin_field("capsule_height", e))?;
         
         Ok(Player { id, name, desc, camp, capsule_radius, capsule_height, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.id);
+        buf.write_string(&self.name);
+        buf.write_string(&self.desc);
+        buf.write_int(self.camp);
+        buf.write_float(self.capsule_radius);
+        buf.write_float(self.capsule_height);
+    }
+
+    pub const __ID__: i32 = -164604245;
 }
 
 #[derive(bevy::reflect::Reflect, Debug)]
@@ -116,19 +193,41 @@ pub struct RelationShip {
 
 impl RelationShip{
     pub fn new(json: &serde_json::Value) -> Result<RelationShip, LubanError> {
-        let active_camp = (json["active_camp"].as_i64().unwrap() as i32);
-        let passive_camp = (json["passive_camp"].as_i64().unwrap() as i32);
-        let relationship_type = json["relationship_type"].as_i64().unwrap().into();
+        let active_camp = (json["active_camp"].as_i64().ok_or_else(|| LubanError::field("active_camp", "int", &json["active_camp"]))? as i32);
+        let passive_camp = (json["passive_camp"].as_i64().ok_or_else(|| LubanError::field("passive_camp", "int", &json["passive_camp"]))? as i32);
+        let relationship_type = crate::unit::RelationShipType::try_from_value(json["relationship_type"].as_i64().ok_or_else(|| LubanError::field("relationship_type", "enum", &json["relationship_type"]))? as i32).map_err(|e| e.in_field("relationship_type"))?;
+        
+        Ok(RelationShip { active_camp, passive_camp, relationship_type, })
+    }
+
+    pub fn new_bin(buf: &mut luban_lib::ByteBuf) -> Result<RelationShip, LubanError> {
+        let active_camp = buf.read_int().map_err(|e| LubanError::bin_field("active_camp", e))?;
+        let passive_camp = buf.read_int().map_err(|e| LubanError::bin_field("passive_camp", e))?;
+        let relationship_type = crate::unit::RelationShipType::try_from_value(buf.read_int().map_err(|e| LubanError::bin_field("relationship_type", e))?).map_err(|e| e.in_field("relationship_type"))?;
         
         Ok(RelationShip { active_camp, passive_camp, relationship_type, })
     }
+
+    pub fn write_bin(&self, buf: &mut luban_lib::ByteBuf) {
+        buf.write_int(self.active_camp);
+        buf.write_int(self.passive_camp);
+        buf.write_int(self.relationship_type.to_value());
+    }
+
+    pub const __ID__: i32 = -746969438;
 }
 
 
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbMonster {
     pub data_list: Vec<std::sync::Arc<crate::unit::Monster>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Monster>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Monster>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbMonster {
@@ -136,13 +235,65 @@ impl TbMonster {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Monster>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::unit::Monster>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::unit::Monster::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbMonster expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::unit::Monster::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbMonster", row_id(x, "id", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbMonster::canonical_source(&data_list);
+        Ok(TbMonster { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbMonster, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Monster>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::unit::Monster>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbMonster: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::unit::Monster::new_bin(&mut buf).map_err(|e| e.in_row("TbMonster", format!("#{}", index)))?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
 
-        Ok(TbMonster { data_map, data_list })
+        let (source, content_hash) = TbMonster::canonical_source(&data_list);
+        Ok(TbMonster { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbMonster, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbMonster::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbMonster::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbMonster, TableLoaderError> {
+        Ok(TbMonster::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::unit::Monster>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::unit::Monster>> {
@@ -159,6 +310,14 @@ impl std::ops::Index<i32> for TbMonster {
 }
 impl luban_lib::table::Table for TbMonster {
     type Value = std::sync::Arc<crate::unit::Monster>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbMonsterKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -217,6 +376,22 @@ impl luban_lib::table::MapTable for TbMonster {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbMonsterRow {
+    type Table = TbMonster;
+
+    fn key(&self) -> &TbMonsterKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbMonster as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -237,17 +412,17 @@ impl bevy::asset::AssetLoader for TbMonsterLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbMonsterLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbMonster::new(&t).unwrap();
-        bevy::log::info!("TbMonsterLoader loading over");
+        let tb = TbMonster::from_bytes(bytes)?;
+        bevy::log::info!("TbMonsterLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -255,7 +430,13 @@ impl bevy::asset::AssetLoader for TbMonsterLoader {
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbNpc {
     pub data_list: Vec<std::sync::Arc<crate::unit::Npc>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Npc>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Npc>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbNpc {
@@ -263,13 +444,65 @@ impl TbNpc {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Npc>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::unit::Npc>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::unit::Npc::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbNpc expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::unit::Npc::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbNpc", row_id(x, "id", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbNpc::canonical_source(&data_list);
+        Ok(TbNpc { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbNpc, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Npc>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::unit::Npc>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbNpc: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::unit::Npc::new_bin(&mut buf).map_err(|e| e.in_row("TbNpc", format!("#{}", index)))?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
 
-        Ok(TbNpc { data_map, data_list })
+        let (source, content_hash) = TbNpc::canonical_source(&data_list);
+        Ok(TbNpc { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbNpc, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbNpc::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbNpc::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbNpc, TableLoaderError> {
+        Ok(TbNpc::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::unit::Npc>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::unit::Npc>> {
@@ -286,6 +519,14 @@ impl std::ops::Index<i32> for TbNpc {
 }
 impl luban_lib::table::Table for TbNpc {
     type Value = std::sync::Arc<crate::unit::Npc>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbNpcKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -344,6 +585,22 @@ impl luban_lib::table::MapTable for TbNpc {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbNpcRow {
+    type Table = TbNpc;
+
+    fn key(&self) -> &TbNpcKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbNpc as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -364,17 +621,17 @@ impl bevy::asset::AssetLoader for TbNpcLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbNpcLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbNpc::new(&t).unwrap();
-        bevy::log::info!("TbNpcLoader loading over");
+        let tb = TbNpc::from_bytes(bytes)?;
+        bevy::log::info!("TbNpcLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -382,7 +639,13 @@ impl bevy::asset::AssetLoader for TbNpcLoader {
 #[derive(Debug, bevy::reflect::Reflect, bevy::asset::Asset)]
 pub struct TbPlayer {
     pub data_list: Vec<std::sync::Arc<crate::unit::Player>>,
-    pub data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Player>>,
+    /// 和MapTable::get_shared_data_map共享，热加载时保留旧表的行不需要复制。
+    pub data_map: std::sync::Arc<bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Player>>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbPlayer {
@@ -390,13 +653,65 @@ impl TbPlayer {
         let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Player>> = Default::default();
         let mut data_list: Vec<std::sync::Arc<crate::unit::Player>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::unit::Player::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbPlayer expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::unit::Player::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbPlayer", row_id(x, "id", index)));
+                    continue;
+                }
+            };
+            data_list.push(row.clone());
+            data_map.insert(row.id.clone(), row.clone());
+        }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
+
+        let (source, content_hash) = TbPlayer::canonical_source(&data_list);
+        Ok(TbPlayer { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbPlayer, LubanError> {
+        let mut data_map: bevy::utils::HashMap<i32, std::sync::Arc<crate::unit::Player>> = Default::default();
+        let mut data_list: Vec<std::sync::Arc<crate::unit::Player>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbPlayer: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::unit::Player::new_bin(&mut buf).map_err(|e| e.in_row("TbPlayer", format!("#{}", index)))?);
             data_list.push(row.clone());
             data_map.insert(row.id.clone(), row.clone());
         }
 
-        Ok(TbPlayer { data_map, data_list })
+        let (source, content_hash) = TbPlayer::canonical_source(&data_list);
+        Ok(TbPlayer { data_map: std::sync::Arc::new(data_map), data_list, content_hash, source })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbPlayer, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbPlayer::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbPlayer::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbPlayer, TableLoaderError> {
+        Ok(TbPlayer::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::unit::Player>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
     }
 
     pub fn get(&self, key: &i32) -> Option<std::sync::Arc<crate::unit::Player>> {
@@ -413,6 +728,14 @@ impl std::ops::Index<i32> for TbPlayer {
 }
 impl luban_lib::table::Table for TbPlayer {
     type Value = std::sync::Arc<crate::unit::Player>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 pub type TbPlayerKey = i32;
 #[derive(Debug, Default, Clone, bevy::reflect::Reflect, bevy::prelude::Component, serde::Serialize, serde::Deserialize)]
@@ -471,6 +794,22 @@ impl luban_lib::table::MapTable for TbPlayer {
     fn get_data_map(&self) -> &Self::Map {
         &self.data_map
     }
+
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map> {
+        self.data_map.clone()
+    }
+}
+
+impl luban_lib::table::TableRow for TbPlayerRow {
+    type Table = TbPlayer;
+
+    fn key(&self) -> &TbPlayerKey {
+        &self.key
+    }
+
+    fn set_data(&mut self, data: Option<<TbPlayer as luban_lib::table::Table>::Value>) {
+        self.data = data;
+    }
 }
 
 
@@ -491,17 +830,17 @@ impl bevy::asset::AssetLoader for TbPlayerLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbPlayerLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbPlayer::new(&t).unwrap();
-        bevy::log::info!("TbPlayerLoader loading over");
+        let tb = TbPlayer::from_bytes(bytes)?;
+        bevy::log::info!("TbPlayerLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -510,27 +849,93 @@ impl bevy::asset::AssetLoader for TbPlayerLoader {
 pub struct TbRelationShip {
     pub data_list: Vec<std::sync::Arc<crate::unit::RelationShip>>,
     pub data_map_union: bevy::utils::HashMap<(i32, i32), std::sync::Arc<crate::unit::RelationShip>>,
+    /// 解析后的行的哈希，和文件的格式、换行和排版无关。
+    pub content_hash: u64,
+    /// 解析后的行重新编码的二进制内容，同步给其他端时用from_source解析。
+    #[reflect(ignore)]
+    pub source: Vec<u8>,
 }
 
 impl TbRelationShip {
     pub fn new(json: &serde_json::Value) -> Result<TbRelationShip, LubanError> {
         let mut data_list: Vec<std::sync::Arc<crate::unit::RelationShip>> = vec![];
 
-        for x in json.as_array().unwrap() {
-            let row = std::sync::Arc::new(crate::unit::RelationShip::new(&x)?);
+        let mut errors = vec![];
+        for (index, x) in json.as_array().ok_or_else(|| LubanError::Table("TbRelationShip expect array".to_string()))?.iter().enumerate() {
+            let row = match crate::unit::RelationShip::new(&x) {
+                Ok(row) => std::sync::Arc::new(row),
+                Err(e) => {
+                    errors.push(e.in_row("TbRelationShip", row_id(x, "", index)));
+                    continue;
+                }
+            };
             data_list.push(row.clone());
         }
+        if !errors.is_empty() {
+            return Err(LubanError::Rows(errors));
+        }
         let mut data_map_union: bevy::utils::HashMap<(i32, i32), std::sync::Arc<crate::unit::RelationShip>> = Default::default();
         for x in &data_list {
             data_map_union.insert((x.active_camp, x.passive_camp.clone()), x.clone());
         }
 
+        let (source, content_hash) = TbRelationShip::canonical_source(&data_list);
     Ok(TbRelationShip { 
             data_list,
             data_map_union,
+            content_hash,
+            source,
         })
     }
 
+    pub fn new_bin(mut buf: luban_lib::ByteBuf) -> Result<TbRelationShip, LubanError> {
+        let mut data_list: Vec<std::sync::Arc<crate::unit::RelationShip>> = vec![];
+
+        let n = buf.read_size().map_err(|e| LubanError::Table(format!("TbRelationShip: {}", e)))?;
+        // bin出错后无法确定下一行的位置，只返回第一个出错的行。
+        for index in 0..n {
+            let row = std::sync::Arc::new(crate::unit::RelationShip::new_bin(&mut buf).map_err(|e| e.in_row("TbRelationShip", format!("#{}", index)))?);
+            data_list.push(row.clone());
+        }
+        let mut data_map_union: bevy::utils::HashMap<(i32, i32), std::sync::Arc<crate::unit::RelationShip>> = Default::default();
+        for x in &data_list {
+            data_map_union.insert((x.active_camp, x.passive_camp.clone()), x.clone());
+        }
+
+        let (source, content_hash) = TbRelationShip::canonical_source(&data_list);
+    Ok(TbRelationShip { 
+            data_list,
+            data_map_union,
+            content_hash,
+            source,
+        })
+    }
+
+    /// 按照TABLE_FILE_EXTENSION的格式解析，加载本地的表格文件时使用。
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<TbRelationShip, TableLoaderError> {
+        #[cfg(not(feature = "bin"))]
+        let tb = TbRelationShip::new(&serde_json::from_slice::<serde_json::Value>(&bytes)?)?;
+        #[cfg(feature = "bin")]
+        let tb = TbRelationShip::new_bin(luban_lib::ByteBuf::new(bytes))?;
+        Ok(tb)
+    }
+
+    /// 解析其他端的source，source总是二进制格式，和本地的TABLE_FILE_EXTENSION无关。
+    pub fn from_source(source: Vec<u8>) -> Result<TbRelationShip, TableLoaderError> {
+        Ok(TbRelationShip::new_bin(luban_lib::ByteBuf::new(source))?)
+    }
+
+    /// 解析后的行按照bin的格式重新编码，json和bin解析出相同的行时得到相同的内容和哈希。
+    fn canonical_source(rows: &[std::sync::Arc<crate::unit::RelationShip>]) -> (Vec<u8>, u64) {
+        let mut buf = luban_lib::ByteBuf::with_capacity(0);
+        buf.write_size(rows.len());
+        for row in rows {
+            row.write_bin(&mut buf);
+        }
+        let content_hash = luban_lib::content_hash(&buf.bytes);
+        (buf.bytes, content_hash)
+    }
+
     pub fn get(&self, key: &(i32, i32)) -> Option<std::sync::Arc<crate::unit::RelationShip>> {
         self.data_map_union.get(key).map(|x| x.clone())
     }
@@ -538,6 +943,14 @@ impl TbRelationShip {
 
 impl luban_lib::table::Table for TbRelationShip {
     type Value = std::sync::Arc<crate::unit::RelationShip>;
+
+    fn content_hash(&self) -> u64 {
+        self.content_hash
+    }
+
+    fn source(&self) -> &[u8] {
+        &self.source
+    }
 }
 impl luban_lib::table::ListTable for TbRelationShip {}
 
@@ -592,17 +1005,17 @@ impl bevy::asset::AssetLoader for TbRelationShipLoader {
         load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         bevy::log::info!("TbRelationShipLoader loading start");
+        let start = std::time::Instant::now();
         let mut bytes = Vec::new();
         use bevy::asset::AsyncReadExt;
         reader.read_to_end(&mut bytes).await?;
-        let t = serde_json::from_slice::<serde_json::Value>(&bytes)?;
-        let tb = TbRelationShip::new(&t).unwrap();
-        bevy::log::info!("TbRelationShipLoader loading over");
+        let tb = TbRelationShip::from_bytes(bytes)?;
+        bevy::log::info!("TbRelationShipLoader loading over, {:?}", start.elapsed());
         Ok(tb)
     }
 
     fn extensions(&self) -> &[&str] {
-        &["json"]
+        &[TABLE_FILE_EXTENSION]
     }
 }
 
@@ -5,7 +5,44 @@ pub mod prelude {
     pub use crate::*;
 }
 
-use std::cell::UnsafeCell;
+/// FNV-1a，不依赖平台和编译器版本，用于比较客户端和服务器的表格内容。
+pub fn content_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// 读取的数据不足或者格式不对，一般是导出的数据和生成的代码不匹配。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ByteBufError {
+    NotEnoughData {
+        position: usize,
+        size: usize,
+        remaining: usize,
+    },
+    InvalidData {
+        position: usize,
+    },
+}
+
+impl std::error::Error for ByteBufError {}
+
+impl std::fmt::Display for ByteBufError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ByteBufError::NotEnoughData {
+                position,
+                size,
+                remaining,
+            } => write!(
+                f,
+                "not enough data at {}, need {} bytes but {} remaining",
+                position, size, remaining
+            ),
+            ByteBufError::InvalidData { position } => write!(f, "invalid data at {}", position),
+        }
+    }
+}
 
 pub struct ByteBuf {
     pub reader_index: usize,
@@ -51,154 +88,156 @@ impl ByteBuf {
         self.bytes[self.reader_index..self.writer_index].to_vec()
     }
 
-    pub fn read_bool(&mut self) -> bool {
-        self.ensure_read(1);
+    pub fn read_bool(&mut self) -> Result<bool, ByteBufError> {
+        self.ensure_read(1)?;
         let result = self.bytes[self.reader_index] != 0;
         self.reader_index += 1;
-        result
+        Ok(result)
     }
 
-    pub fn read_byte(&mut self) -> u8 {
-        self.ensure_read(1);
+    pub fn read_byte(&mut self) -> Result<u8, ByteBufError> {
+        self.ensure_read(1)?;
         let result = self.bytes[self.reader_index];
         self.reader_index += 1;
-        result
+        Ok(result)
     }
 
-    pub fn read_short(&mut self) -> i16 {
-        self.ensure_read(1);
+    pub fn read_short(&mut self) -> Result<i16, ByteBufError> {
+        self.ensure_read(1)?;
         let h = self.bytes[self.reader_index];
         if h < 0x80 {
             self.reader_index += 1;
-            return h as i16;
+            return Ok(h as i16);
         }
 
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.ensure_read(2)?;
             let x = (((h & 0x3f) as i16) << 8) | (self.bytes[self.reader_index + 1] as i16);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
 
         if h < 0xff {
-            self.ensure_read(3);
+            self.ensure_read(3)?;
             let x = ((self.bytes[self.reader_index + 1] as i16) << 8)
                 | (self.bytes[self.reader_index + 2] as i16);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
 
-        panic!("Invalid data")
+        Err(ByteBufError::InvalidData {
+            position: self.reader_index,
+        })
     }
 
-    pub fn read_uint(&mut self) -> u32 {
-        self.ensure_read(1);
+    pub fn read_uint(&mut self) -> Result<u32, ByteBufError> {
+        self.ensure_read(1)?;
         let h = self.bytes[self.reader_index] as u32;
         if h < 0x80 {
             self.reader_index += 1;
-            return h;
+            return Ok(h);
         }
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.ensure_read(2)?;
             let x = ((h & 0x3f) << 8) | (self.bytes[self.reader_index + 1] as u32);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
         if h < 0xe0 {
-            self.ensure_read(3);
+            self.ensure_read(3)?;
             let x = ((h & 0x1f) << 16)
                 | ((self.bytes[self.reader_index + 1] as u32) << 8)
                 | (self.bytes[self.reader_index + 2] as u32);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
         if h < 0xf0 {
-            self.ensure_read(4);
+            self.ensure_read(4)?;
             let x = ((h & 0x0f) << 24)
                 | ((self.bytes[self.reader_index + 1] as u32) << 16)
                 | ((self.bytes[self.reader_index + 2] as u32) << 8)
                 | (self.bytes[self.reader_index + 3] as u32);
             self.reader_index += 4;
-            x
+            Ok(x)
         } else {
-            self.ensure_read(5);
+            self.ensure_read(5)?;
             let x = ((self.bytes[self.reader_index + 1] as u32) << 24)
                 | ((self.bytes[self.reader_index + 2] as u32) << 16)
                 | ((self.bytes[self.reader_index + 3] as u32) << 8)
                 | (self.bytes[self.reader_index + 4] as u32);
             self.reader_index += 5;
-            x
+            Ok(x)
         }
     }
 
-    pub fn read_int(&mut self) -> i32 {
-        self.read_uint() as i32
+    pub fn read_int(&mut self) -> Result<i32, ByteBufError> {
+        Ok(self.read_uint()? as i32)
     }
 
-    pub fn read_ulong(&mut self) -> u64 {
-        self.ensure_read(1);
+    pub fn read_ulong(&mut self) -> Result<u64, ByteBufError> {
+        self.ensure_read(1)?;
         let h = self.bytes[self.reader_index];
         if h < 0x80 {
             self.reader_index += 1;
-            return h as u64;
+            return Ok(h as u64);
         }
         if h < 0xc0 {
-            self.ensure_read(2);
+            self.ensure_read(2)?;
             let x = (((h & 0x3f) as u64) << 8) | (self.bytes[self.reader_index + 1] as u64);
             self.reader_index += 2;
-            return x;
+            return Ok(x);
         }
         if h < 0xe0 {
-            self.ensure_read(3);
+            self.ensure_read(3)?;
             let x = (((h & 0x1f) as u64) << 16)
                 | ((self.bytes[self.reader_index + 1] as u64) << 8)
                 | (self.bytes[self.reader_index + 2] as u64);
             self.reader_index += 3;
-            return x;
+            return Ok(x);
         }
         if h < 0xf0 {
-            self.ensure_read(4);
+            self.ensure_read(4)?;
             let x = (((h & 0x0f) as u64) << 24)
                 | ((self.bytes[self.reader_index + 1] as u64) << 16)
                 | ((self.bytes[self.reader_index + 2] as u64) << 8)
                 | (self.bytes[self.reader_index + 3] as u64);
             self.reader_index += 4;
-            return x;
+            return Ok(x);
         }
         if h < 0xf8 {
-            self.ensure_read(5);
+            self.ensure_read(5)?;
             let xl = ((self.bytes[self.reader_index + 1] as u64) << 24)
                 | ((self.bytes[self.reader_index + 2] as u64) << 16)
                 | ((self.bytes[self.reader_index + 3] as u64) << 8)
                 | (self.bytes[self.reader_index + 4] as u64);
             let xh = (h & 0x07) as u64;
             self.reader_index += 5;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xfc {
-            self.ensure_read(6);
+            self.ensure_read(6)?;
             let xl = ((self.bytes[self.reader_index + 2] as u64) << 24)
                 | ((self.bytes[self.reader_index + 3] as u64) << 16)
                 | ((self.bytes[self.reader_index + 4] as u64) << 8)
                 | (self.bytes[self.reader_index + 5] as u64);
             let xh = (((h & 0x03) as u64) << 8) | (self.bytes[self.reader_index + 1] as u64);
             self.reader_index += 6;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xfe {
-            self.ensure_read(7);
+            self.ensure_read(7)?;
             let xl = ((self.bytes[self.reader_index + 3] as u64) << 24)
                 | ((self.bytes[self.reader_index + 4] as u64) << 16)
                 | ((self.bytes[self.reader_index + 5] as u64) << 8)
                 | (self.bytes[self.reader_index + 6] as u64);
             let xh = (((h & 0x01) as u64) << 16)
                 | ((self.bytes[self.reader_index + 1] as u64) << 8)
-                | (self.bytes[self.reader_index + 1] as u64);
+                | (self.bytes[self.reader_index + 2] as u64);
             self.reader_index += 7;
-            return (xh << 32) | xl;
+            return Ok((xh << 32) | xl);
         }
         if h < 0xff {
-            self.ensure_read(8);
+            self.ensure_read(8)?;
             let xl = ((self.bytes[self.reader_index + 4] as u64) << 24)
                 | ((self.bytes[self.reader_index + 5] as u64) << 16)
                 | ((self.bytes[self.reader_index + 6] as u64) << 8)
@@ -207,9 +246,9 @@ impl ByteBuf {
                 | ((self.bytes[self.reader_index + 2] as u64) << 8)
                 | (self.bytes[self.reader_index + 3] as u64);
             self.reader_index += 8;
-            (xh << 32) | xl
+            Ok((xh << 32) | xl)
         } else {
-            self.ensure_read(9);
+            self.ensure_read(9)?;
             let xl = ((self.bytes[self.reader_index + 5] as u64) << 24)
                 | ((self.bytes[self.reader_index + 6] as u64) << 16)
                 | ((self.bytes[self.reader_index + 7] as u64) << 8)
@@ -219,73 +258,125 @@ impl ByteBuf {
                 | ((self.bytes[self.reader_index + 3] as u64) << 8)
                 | (self.bytes[self.reader_index + 4] as u64);
             self.reader_index += 9;
-            (xh << 32) | xl
+            Ok((xh << 32) | xl)
         }
     }
 
-    pub fn read_long(&mut self) -> i64 {
-        self.read_ulong() as i64
+    pub fn read_long(&mut self) -> Result<i64, ByteBufError> {
+        Ok(self.read_ulong()? as i64)
     }
 
-    pub fn read_float(&mut self) -> f32 {
-        self.ensure_read(4);
-        let b = &self.bytes[self.reader_index] as *const u8;
-        let mut x = 0_f32;
-        unsafe {
-            if (b as u64) % 8 == 0 {
-                x = *(b as *const f32)
-            } else {
-                let c = UnsafeCell::new(x);
-                *(c.get() as *mut u32) = (*b.offset(0) as u32)
-                    | ((*b.offset(1) as u32) << 8)
-                    | ((*b.offset(2) as u32) << 16)
-                    | ((*b.offset(3) as u32) << 24);
-            }
-        }
-
+    pub fn read_float(&mut self) -> Result<f32, ByteBufError> {
+        self.ensure_read(4)?;
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.bytes[self.reader_index..self.reader_index + 4]);
         self.reader_index += 4;
-        x
-    }
-
-    pub fn read_double(&mut self) -> f64 {
-        self.ensure_read(8);
-        let b = &self.bytes[self.reader_index] as *const u8;
-        let mut x = 0_f64;
-        unsafe {
-            if (b as u64) % 8 == 0 {
-                x = *(b as *const f64)
-            } else {
-                let low = (*b.offset(0) as u64)
-                    | ((*b.offset(1) as u64) << 8)
-                    | ((*b.offset(2) as u64) << 16)
-                    | ((*b.offset(3) as u64) << 24);
-                let high = (*b.offset(4) as u64)
-                    | ((*b.offset(5) as u64) << 8)
-                    | ((*b.offset(6) as u64) << 16)
-                    | ((*b.offset(7) as u64) << 24);
-                let c = UnsafeCell::new(x);
-                *(c.get() as *mut u64) = ((high) << 32) | (low)
-            }
-        }
+        Ok(f32::from_le_bytes(bytes))
+    }
 
+    pub fn read_double(&mut self) -> Result<f64, ByteBufError> {
+        self.ensure_read(8)?;
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&self.bytes[self.reader_index..self.reader_index + 8]);
         self.reader_index += 8;
-        x
+        Ok(f64::from_le_bytes(bytes))
     }
 
-    pub fn read_size(&mut self) -> usize {
-        self.read_uint() as usize
+    pub fn read_size(&mut self) -> Result<usize, ByteBufError> {
+        Ok(self.read_uint()? as usize)
     }
 
-    pub fn read_string(&mut self) -> String {
-        let n = self.read_size();
+    pub fn read_string(&mut self) -> Result<String, ByteBufError> {
+        let n = self.read_size()?;
         if n > 0 {
-            self.ensure_read(n);
+            self.ensure_read(n)?;
             let s = String::from_utf8_lossy(&self.bytes[self.reader_index..self.reader_index + n]);
             self.reader_index += n;
-            return s.to_string();
+            return Ok(s.to_string());
+        }
+
+        Ok("".to_string())
+    }
+
+    // 写入和读取使用相同的编码，用于生成规范化的表格内容，整数总是使用最短的编码。
+
+    pub fn write_bool(&mut self, x: bool) {
+        self.write_raw(&[x as u8]);
+    }
+
+    pub fn write_byte(&mut self, x: u8) {
+        self.write_raw(&[x]);
+    }
+
+    pub fn write_short(&mut self, x: i16) {
+        if (0..0x80).contains(&x) {
+            self.write_raw(&[x as u8]);
+        } else if (0..0x4000).contains(&x) {
+            self.write_raw(&[(x >> 8) as u8 | 0x80, x as u8]);
+        } else {
+            self.write_raw(&[0xc0, (x >> 8) as u8, x as u8]);
+        }
+    }
+
+    pub fn write_uint(&mut self, x: u32) {
+        if x < 0x80 {
+            self.write_raw(&[x as u8]);
+        } else if x < 0x4000 {
+            self.write_raw(&[(x >> 8) as u8 | 0x80, x as u8]);
+        } else if x < 0x200000 {
+            self.write_raw(&[(x >> 16) as u8 | 0xc0, (x >> 8) as u8, x as u8]);
+        } else if x < 0x10000000 {
+            self.write_raw(&[
+                (x >> 24) as u8 | 0xe0,
+                (x >> 16) as u8,
+                (x >> 8) as u8,
+                x as u8,
+            ]);
+        } else {
+            self.write_raw(&[0xf0]);
+            self.write_raw(&x.to_be_bytes());
+        }
+    }
+
+    pub fn write_int(&mut self, x: i32) {
+        self.write_uint(x as u32);
+    }
+
+    pub fn write_ulong(&mut self, x: u64) {
+        if x < 0x10000000 {
+            self.write_uint(x as u32);
+        } else {
+            self.write_raw(&[0xff]);
+            self.write_raw(&x.to_be_bytes());
         }
+    }
+
+    pub fn write_long(&mut self, x: i64) {
+        self.write_ulong(x as u64);
+    }
+
+    pub fn write_float(&mut self, x: f32) {
+        self.write_raw(&x.to_le_bytes());
+    }
 
-        "".to_string()
+    pub fn write_double(&mut self, x: f64) {
+        self.write_raw(&x.to_le_bytes());
+    }
+
+    pub fn write_size(&mut self, x: usize) {
+        self.write_uint(x as u32);
+    }
+
+    pub fn write_string(&mut self, x: &str) {
+        self.write_size(x.len());
+        self.write_raw(x.as_bytes());
+    }
+
+    /// 追加已经编码的数据，例如排序后的map元素。
+    pub fn write_raw(&mut self, bytes: &[u8]) {
+        self.bytes.truncate(self.writer_index);
+        self.bytes.extend_from_slice(bytes);
+        self.writer_index = self.bytes.len();
     }
     //region internal
 
@@ -302,10 +393,15 @@ impl ByteBuf {
     }
 
     #[inline]
-    fn ensure_read(&self, size: usize) {
+    fn ensure_read(&self, size: usize) -> Result<(), ByteBufError> {
         if self.reader_index + size > self.writer_index {
-            panic!("Not enough data")
+            return Err(ByteBufError::NotEnoughData {
+                position: self.reader_index,
+                size,
+                remaining: self.size(),
+            });
         }
+        Ok(())
     }
 
     #[allow(dead_code)]
@@ -334,3 +430,98 @@ impl PartialEq<Self> for ByteBuf {
 }
 
 impl Eq for ByteBuf {}
+
+#[cfg(test)]
+mod tests {
+    use super::{content_hash, ByteBuf, ByteBufError};
+
+    #[test]
+    fn stable_content_hash() {
+        assert_eq!(content_hash(b""), 0xcbf29ce484222325);
+        assert_eq!(content_hash(b"a"), 0xaf63dc4c8601ec8c);
+        assert_ne!(content_hash(b"ab"), content_hash(b"ba"));
+    }
+
+    #[test]
+    fn read_unaligned_float_and_long() {
+        let mut bytes = vec![0x01];
+        bytes.extend_from_slice(&1.5f32.to_le_bytes());
+        bytes.extend_from_slice(&(-2.25f64).to_le_bytes());
+        bytes.extend_from_slice(&[0xfd, 0x02, 0x03, 0x00, 0x00, 0x00, 0x04]);
+        let mut buf = ByteBuf::new(bytes);
+
+        assert_eq!(buf.read_bool(), Ok(true));
+        assert_eq!(buf.read_float(), Ok(1.5));
+        assert_eq!(buf.read_double(), Ok(-2.25));
+        assert_eq!(buf.read_ulong(), Ok((((1 << 16) | (2 << 8) | 3) << 32) | 4));
+        assert_eq!(buf.size(), 0);
+    }
+
+    #[test]
+    fn write_then_read() {
+        let mut buf = ByteBuf::with_capacity(0);
+        let ints = [
+            0,
+            1,
+            0x7f,
+            0x80,
+            0x3fff,
+            0x4000,
+            0x1fffff,
+            0x200000,
+            0xfffffff,
+            i32::MAX,
+            -1,
+        ];
+        for x in ints {
+            buf.write_int(x);
+        }
+        let longs = [0, 0x80, 0xfffffff, 0x10000000, i64::MAX, -1];
+        for x in longs {
+            buf.write_long(x);
+        }
+        for x in [0i16, 0x7f, 0x80, 0x3fff, 0x4000, -1] {
+            buf.write_short(x);
+        }
+        buf.write_bool(true);
+        buf.write_float(1.5);
+        buf.write_double(-2.25);
+        buf.write_string("表格");
+
+        for x in ints {
+            assert_eq!(buf.read_int(), Ok(x));
+        }
+        for x in longs {
+            assert_eq!(buf.read_long(), Ok(x));
+        }
+        for x in [0i16, 0x7f, 0x80, 0x3fff, 0x4000, -1] {
+            assert_eq!(buf.read_short(), Ok(x));
+        }
+        assert_eq!(buf.read_bool(), Ok(true));
+        assert_eq!(buf.read_float(), Ok(1.5));
+        assert_eq!(buf.read_double(), Ok(-2.25));
+        assert_eq!(buf.read_string(), Ok("表格".to_string()));
+        assert_eq!(buf.size(), 0);
+    }
+
+    #[test]
+    fn read_truncated_data() {
+        // 字符串长度为3，但只有2个字节。
+        let mut buf = ByteBuf::new(vec![0x03, b'a', b'b']);
+        assert_eq!(
+            buf.read_string(),
+            Err(ByteBufError::NotEnoughData {
+                position: 1,
+                size: 3,
+                remaining: 2,
+            })
+        );
+
+        let mut buf = ByteBuf::new(vec![0xff]);
+        assert_eq!(
+            buf.read_short(),
+            Err(ByteBufError::InvalidData { position: 0 })
+        );
+        assert!(ByteBuf::new(vec![]).read_int().is_err());
+    }
+}
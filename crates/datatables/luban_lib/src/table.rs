@@ -2,6 +2,12 @@ use bevy::asset::Asset;
 
 pub trait Table: Asset {
     type Value;
+
+    /// 解析后的行的哈希，客户端和服务器用来确认表格一致，和文件格式无关。
+    fn content_hash(&self) -> u64;
+
+    /// 计算哈希的二进制内容，可以用对应表的from_source重新解析。
+    fn source(&self) -> &[u8];
 }
 
 pub trait OneTable: Table {
@@ -20,6 +26,9 @@ pub trait MapTable: Table {
     fn get_data_list(&self) -> &Self::List;
 
     fn get_data_map(&self) -> &Self::Map;
+
+    /// 和表共享的data_map，热加载时用来保留旧表的行，不需要复制整个map。
+    fn get_shared_data_map(&self) -> std::sync::Arc<Self::Map>;
 }
 
 pub trait MultiUnionIndexListTable: ListTable {
@@ -53,3 +62,12 @@ pub trait NotIndexListTable: ListTable {
 
     fn get_data_list(&self) -> &Self::List;
 }
+
+/// MapTable的行组件，热加载时根据key更新data。
+pub trait TableRow: bevy::prelude::Component {
+    type Table: MapTable;
+
+    fn key(&self) -> &<Self::Table as MapTable>::Key;
+
+    fn set_data(&mut self, data: Option<<Self::Table as Table>::Value>);
+}
@@ -1,4 +1,5 @@
 pub mod tables_system_param;
+pub mod validate;
 
 use std::sync::{
     atomic::{AtomicBool, Ordering},
@@ -9,9 +10,12 @@ use bevy::{prelude::*, tasks::AsyncComputeTaskPool};
 
 use atom_utils::asset_barrier::{AllAssetBarrier, AssetBarrierStatus};
 pub use cfg::*;
+use validate::{
+    report_table_validation, validate_table_refs, TableValidationReport, TableValidationSet,
+};
 
 /// loaded 仅仅会触发一次，reload 不会触发。
-/// 加载和热加载都会触发TablesUpdateEvent.
+/// 加载和热加载都会触发TablesUpdateEvent，热加载还会逐行触发TableRowChanged。
 #[derive(Debug, Default, Hash, PartialEq, Eq, Clone, States)]
 pub enum TableLoadingState {
     #[default]
@@ -31,6 +35,7 @@ impl Plugin for DataTablePlugin {
         app.add_plugins(TableAssetsPlugin)
             .insert_resource(AllAssetBarrier::default())
             .init_resource::<Tables>()
+            .init_resource::<TableValidationReport>()
             .insert_state(TableLoadingState::default())
             .add_systems(Startup, start_load_tables)
             .add_systems(
@@ -40,6 +45,22 @@ impl Plugin for DataTablePlugin {
             .add_systems(
                 OnExit(TableLoadingState::Loading),
                 clear_table_loading_status,
+            )
+            .configure_sets(
+                OnEnter(TableLoadingState::Loaded),
+                (
+                    TableValidationSet::Tables,
+                    TableValidationSet::Extension,
+                    TableValidationSet::Report,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                OnEnter(TableLoadingState::Loaded),
+                (
+                    validate_table_refs.in_set(TableValidationSet::Tables),
+                    report_table_validation.in_set(TableValidationSet::Report),
+                ),
             );
     }
 }
@@ -104,3 +125,198 @@ fn clear_table_loading_status(
     all_asset_barrier.remove_asset_barrier(&tables_barrier_state.barrier_key);
     commands.remove_resource::<TablesBarrierStatus>();
 }
+
+#[cfg(test)]
+mod tests {
+    use cfg::{
+        effect::{TbAbility, TbBuff},
+        item::TbItem,
+        layertag::TbLayerTag,
+        table_row_diff,
+        unit::{TbMonster, TbNpc, TbPlayer, TbRelationShip},
+        LubanError, TableRowChangeKind,
+    };
+    use luban_lib::ByteBuf;
+    use serde_json::json;
+
+    use crate::validate::{TableRefs, TableValidationReport};
+
+    fn read_table(file: &str) -> serde_json::Value {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/datatables")
+            .join(file);
+        serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+    }
+
+    fn field_error(e: &LubanError) -> (&str, &str, &str) {
+        match e {
+            LubanError::Field {
+                table, row, field, ..
+            } => (table.as_str(), row.as_str(), field.as_str()),
+            e => panic!("expect field error, found {:?}", e),
+        }
+    }
+
+    #[test]
+    fn table_errors_with_row_and_field() {
+        let mut items = read_table("item_tbitem.json");
+        assert!(TbItem::new(&items).is_ok());
+
+        items[0]["exchange_list"][1]["num"] = json!("two");
+        items[2]["quality"] = json!(9);
+        items[3].as_object_mut().unwrap().remove("price");
+        let id = |index: usize| items[index]["id"].to_string();
+        let (id0, id2, id3) = (id(0), id(2), id(3));
+
+        let errors = TbItem::new(&items).unwrap_err().flatten();
+        let errors: Vec<_> = errors.iter().map(field_error).collect();
+        assert_eq!(
+            errors,
+            vec![
+                ("TbItem", id0.as_str(), "exchange_list[1].num"),
+                ("TbItem", id2.as_str(), "quality"),
+                ("TbItem", id3.as_str(), "price"),
+            ]
+        );
+
+        let layertags = json!([
+            { "raw_layertag": "A.B", "desc": "A", "counter": true },
+            { "raw_layertag": "A.C", "desc": "C", "counter": 1 },
+        ]);
+        let e = TbLayerTag::new(&layertags).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "TbLayerTag row \"A.C\" field counter: expect bool, found 1"
+        );
+    }
+
+    #[test]
+    fn bin_table_errors_with_row_and_field() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/datatables/item_tbitem.bytes");
+        let mut bytes = std::fs::read(path).unwrap();
+        let rows = TbItem::new_bin(ByteBuf::new(bytes.clone()))
+            .unwrap()
+            .data_list
+            .len();
+
+        bytes.pop();
+        let e = TbItem::new_bin(ByteBuf::new(bytes)).unwrap_err();
+        let row = format!("#{}", rows - 1);
+        assert_eq!(
+            field_error(&e),
+            ("TbItem", row.as_str(), "exchange_column.num")
+        );
+    }
+
+    #[test]
+    fn validate_checked_in_table_refs() {
+        let ability = TbAbility::new(&read_table("effect_tbability.json")).unwrap();
+        let buff = TbBuff::new(&read_table("effect_tbbuff.json")).unwrap();
+        let layer_tag = TbLayerTag::new(&read_table("layertag_tblayertag.json")).unwrap();
+        let monster = TbMonster::new(&read_table("unit_tbmonster.json")).unwrap();
+        let npc = TbNpc::new(&read_table("unit_tbnpc.json")).unwrap();
+        let player = TbPlayer::new(&read_table("unit_tbplayer.json")).unwrap();
+        let relation_ship = TbRelationShip::new(&read_table("unit_tbrelationship.json")).unwrap();
+        let item = TbItem::new(&read_table("item_tbitem.json")).unwrap();
+
+        let mut refs = TableRefs {
+            ability: &ability,
+            buff: &buff,
+            layer_tag: &layer_tag,
+            monster: &monster,
+            npc: &npc,
+            player: &player,
+            relation_ship: &relation_ship,
+            item: &item,
+        };
+        let mut report = TableValidationReport::default();
+        refs.validate(&mut report);
+        assert!(report.is_empty(), "{}", report);
+
+        let mut abilities = read_table("effect_tbability.json");
+        abilities[0]["start_added_layertags"][0]["raw_layertag"] = json!("A.X");
+        // 隐式的父LayerTag和查询表达式不报错
+        abilities[0]["start_required_layertags"] = json!(["A.B", "any(A.D, A.B.*)"]);
+        let id = abilities[0]["id"].to_string();
+        let ability = TbAbility::new(&abilities).unwrap();
+        let mut npcs = read_table("unit_tbnpc.json");
+        npcs[0]["camp"] = json!(99);
+        let npc_id = npcs[0]["id"].to_string();
+        let npc = TbNpc::new(&npcs).unwrap();
+        refs.ability = &ability;
+        refs.npc = &npc;
+
+        let mut report = TableValidationReport::default();
+        refs.validate(&mut report);
+        let errors: Vec<_> = report.errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(
+            errors,
+            vec![
+                format!(
+                    "TbAbility row {} field start_added_layertags[0].raw_layertag: A.X not found in TbLayerTag",
+                    id
+                ),
+                format!(
+                    "TbNpc row {} field camp: 99 not found in TbRelationShip.active_camp",
+                    npc_id
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn diff_rows_after_reload() {
+        let mut items = read_table("item_tbitem.json");
+        let old = TbItem::new(&items).unwrap();
+        assert!(table_row_diff(&old.data_map, &TbItem::new(&items).unwrap().data_map).is_empty());
+
+        let id = |item: &serde_json::Value| item["id"].as_i64().unwrap() as i32;
+        let (modified, removed) = (id(&items[0]), id(&items[1]));
+        items[0]["exchange_list"][1]["num"] = json!(4);
+        let mut added = items.as_array_mut().unwrap().remove(1);
+        added["id"] = json!(99999);
+        items.as_array_mut().unwrap().push(added);
+
+        let new = TbItem::new(&items).unwrap();
+        let mut changes = table_row_diff(&old.data_map, &new.data_map);
+        changes.sort_by_key(|(key, _)| *key);
+        let mut expected = vec![
+            (modified, TableRowChangeKind::Modified),
+            (removed, TableRowChangeKind::Removed),
+            (99999, TableRowChangeKind::Added),
+        ];
+        expected.sort_by_key(|(key, _)| *key);
+        assert_eq!(changes, expected);
+    }
+
+    #[test]
+    fn content_hash_ignores_file_format() {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/datatables/item_tbitem.bytes");
+        let bin = TbItem::new_bin(ByteBuf::new(std::fs::read(path).unwrap())).unwrap();
+        let json = TbItem::new(&read_table("item_tbitem.json")).unwrap();
+        assert_eq!(bin.content_hash, json.content_hash);
+        assert_eq!(bin.source, json.source);
+    }
+
+    #[test]
+    #[cfg(not(feature = "bin"))]
+    fn content_hash_ignores_formatting() {
+        let mut items = read_table("item_tbitem.json");
+        let pretty = serde_json::to_vec_pretty(&items).unwrap();
+        let compact = serde_json::to_vec(&items).unwrap();
+        let hash = TbItem::from_bytes(pretty).unwrap().content_hash;
+        assert_eq!(
+            TbItem::from_bytes(compact.clone()).unwrap().content_hash,
+            hash
+        );
+
+        // 客户端用服务器的source重新解析，得到相同的哈希
+        let source = TbItem::from_bytes(compact).unwrap().source;
+        assert_eq!(TbItem::from_source(source).unwrap().content_hash, hash);
+
+        items[0]["price"] = json!(items[0]["price"].as_i64().unwrap() + 1);
+        assert_ne!(TbItem::new(&items).unwrap().content_hash, hash);
+    }
+}
@@ -0,0 +1,235 @@
+use bevy::{prelude::*, utils::HashSet};
+use layertag::{query::validate_raw_layertags, registry::LayerTagRegistry};
+use thiserror::Error;
+
+use cfg::{
+    effect::{RevertableLayerTag, TbAbility, TbBuff},
+    item::TbItem,
+    layertag::TbLayerTag,
+    unit::{TbMonster, TbNpc, TbPlayer, TbRelationShip},
+    Tables,
+};
+
+/// 表加载后的检查顺序，其他crate的检查放在Extension中，最后统一输出报告。
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableValidationSet {
+    Tables,
+    Extension,
+    Report,
+}
+
+/// 引用的值在目标中不存在。
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{table} row {row} field {field}: {value} not found in {target}")]
+pub struct TableRefError {
+    pub table: &'static str,
+    pub row: String,
+    pub field: String,
+    pub value: String,
+    pub target: &'static str,
+}
+
+/// 一次检查的所有错误，在TableLoadingState::Loaded时生成。
+#[derive(Debug, Default, Clone, Resource)]
+pub struct TableValidationReport {
+    pub errors: Vec<TableRefError>,
+}
+
+impl TableValidationReport {
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn check(
+        &mut self,
+        found: bool,
+        table: &'static str,
+        row: impl ToString,
+        field: impl Into<String>,
+        value: impl ToString,
+        target: &'static str,
+    ) {
+        if !found {
+            self.errors.push(TableRefError {
+                table,
+                row: row.to_string(),
+                field: field.into(),
+                value: value.to_string(),
+                target,
+            });
+        }
+    }
+}
+
+impl std::fmt::Display for TableValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "table validation: {} errors", self.errors.len())?;
+        for e in self.errors.iter() {
+            write!(f, "\n  {}", e)?;
+        }
+        Ok(())
+    }
+}
+
+/// 定义中声明了ref的表。
+pub struct TableRefs<'a> {
+    pub ability: &'a TbAbility,
+    pub buff: &'a TbBuff,
+    pub layer_tag: &'a TbLayerTag,
+    pub monster: &'a TbMonster,
+    pub npc: &'a TbNpc,
+    pub player: &'a TbPlayer,
+    pub relation_ship: &'a TbRelationShip,
+    pub item: &'a TbItem,
+}
+
+impl TableRefs<'_> {
+    pub fn validate(&self, report: &mut TableValidationReport) {
+        // 和运行时一样注册隐式的父LayerTag，查询表达式按语法解析。
+        let mut registry = LayerTagRegistry::default();
+        for raw_layertag in self.layer_tag.data_map.keys() {
+            registry.register_raw(raw_layertag);
+        }
+        let layertag = |raw_layertag: &String| {
+            validate_raw_layertags(&registry, std::iter::once(raw_layertag.as_str())).is_empty()
+        };
+
+        macro_rules! validate_layertags {
+            ($table:expr, $name:literal) => {
+                for row in $table.data_list.iter() {
+                    for (field, raw_layertags) in [
+                        ("start_required_layertags", &row.start_required_layertags),
+                        ("start_disabled_layertags", &row.start_disabled_layertags),
+                        ("abort_required_layertags", &row.abort_required_layertags),
+                        ("abort_disabled_layertags", &row.abort_disabled_layertags),
+                    ] {
+                        for (i, raw_layertag) in raw_layertags.iter().enumerate() {
+                            report.check(
+                                layertag(raw_layertag),
+                                $name,
+                                row.id,
+                                format!("{}[{}]", field, i),
+                                raw_layertag,
+                                "TbLayerTag",
+                            );
+                        }
+                    }
+                    for (field, revertables) in [
+                        ("start_added_layertags", &row.start_added_layertags),
+                        ("start_removed_layertags", &row.start_removed_layertags),
+                    ] {
+                        for (i, RevertableLayerTag { raw_layertag, .. }) in
+                            revertables.iter().enumerate()
+                        {
+                            report.check(
+                                layertag(raw_layertag),
+                                $name,
+                                row.id,
+                                format!("{}[{}].raw_layertag", field, i),
+                                raw_layertag,
+                                "TbLayerTag",
+                            );
+                        }
+                    }
+                }
+            };
+        }
+        validate_layertags!(self.ability, "TbAbility");
+        validate_layertags!(self.buff, "TbBuff");
+
+        let camps: HashSet<i32> = self
+            .relation_ship
+            .data_list
+            .iter()
+            .map(|row| row.active_camp)
+            .collect();
+        macro_rules! validate_camp {
+            ($table:expr, $name:literal) => {
+                for row in $table.data_list.iter() {
+                    report.check(
+                        camps.contains(&row.camp),
+                        $name,
+                        row.id,
+                        "camp",
+                        row.camp,
+                        "TbRelationShip.active_camp",
+                    );
+                }
+            };
+        }
+        validate_camp!(self.player, "TbPlayer");
+        validate_camp!(self.npc, "TbNpc");
+        validate_camp!(self.monster, "TbMonster");
+
+        for row in self.item.data_list.iter() {
+            report.check(
+                self.item.data_map.contains_key(&row.upgrade_to_item_id),
+                "TbItem",
+                row.id,
+                "upgrade_to_item_id",
+                row.upgrade_to_item_id,
+                "TbItem",
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn validate_table_refs(
+    tables: Res<Tables>,
+    ability: Res<Assets<TbAbility>>,
+    buff: Res<Assets<TbBuff>>,
+    layer_tag: Res<Assets<TbLayerTag>>,
+    monster: Res<Assets<TbMonster>>,
+    npc: Res<Assets<TbNpc>>,
+    player: Res<Assets<TbPlayer>>,
+    relation_ship: Res<Assets<TbRelationShip>>,
+    item: Res<Assets<TbItem>>,
+    mut report: ResMut<TableValidationReport>,
+) {
+    *report = TableValidationReport::default();
+
+    let (
+        Some(ability),
+        Some(buff),
+        Some(layer_tag),
+        Some(monster),
+        Some(npc),
+        Some(player),
+        Some(relation_ship),
+        Some(item),
+    ) = (
+        ability.get(&tables.tb_ability),
+        buff.get(&tables.tb_buff),
+        layer_tag.get(&tables.tb_layer_tag),
+        monster.get(&tables.tb_monster),
+        npc.get(&tables.tb_npc),
+        player.get(&tables.tb_player),
+        relation_ship.get(&tables.tb_relation_ship),
+        item.get(&tables.tb_item),
+    )
+    else {
+        warn!("validate_table_refs: tables are not loaded");
+        return;
+    };
+
+    TableRefs {
+        ability,
+        buff,
+        layer_tag,
+        monster,
+        npc,
+        player,
+        relation_ship,
+        item,
+    }
+    .validate(&mut report);
+}
+
+pub(crate) fn report_table_validation(report: Res<TableValidationReport>) {
+    if report.is_empty() {
+        info!("table validation passed");
+    } else {
+        error!("{}", *report);
+    }
+}
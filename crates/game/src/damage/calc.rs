@@ -0,0 +1,199 @@
+use bevy::reflect::Reflect;
+use datatables::damage::{DamageMitigation, DamageType};
+use serde::{Deserialize, Serialize};
+
+/// 护甲和抗性的减免常数，value等于常数时减免一半。
+pub const MITIGATION_CONSTANT: f32 = 100.0;
+
+/// 暴击倍率的下限，属性没有设置时暴击不会降低伤害。
+pub const MIN_CRIT_MULTIPLIER: f32 = 1.0;
+
+/// 伤害计算时攻击者的属性快照。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct AttackerCapture {
+    pub crit_chance: f32,
+    pub crit_multiplier: f32,
+}
+
+/// 伤害计算时受击者的属性快照。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect)]
+pub struct DefenderCapture {
+    pub armor: f32,
+    pub resistance: f32,
+    pub shield: f32,
+}
+
+/// 伤害计算的每一步结果。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Reflect, Serialize, Deserialize)]
+pub struct DamageBreakdown {
+    /// 输入的伤害。
+    pub raw: f32,
+    /// 护甲或者抗性减免后的伤害。
+    pub mitigated: f32,
+    pub critical: bool,
+    /// 暴击后的伤害，没有暴击时等于mitigated。
+    pub final_damage: f32,
+    /// 被护盾吸收的伤害。
+    pub absorbed: f32,
+    /// 扣除生命值的伤害。
+    pub hp_damage: f32,
+    pub immune: bool,
+}
+
+/// 减免后剩余的伤害比例，value为负数时增加伤害，最多两倍。
+pub fn mitigation_multiplier(value: f32) -> f32 {
+    if value >= 0.0 {
+        MITIGATION_CONSTANT / (MITIGATION_CONSTANT + value)
+    } else {
+        2.0 - MITIGATION_CONSTANT / (MITIGATION_CONSTANT - value)
+    }
+}
+
+pub fn mitigate(damage: f32, mitigation: &DamageMitigation, defender: &DefenderCapture) -> f32 {
+    match mitigation {
+        DamageMitigation::None => damage,
+        DamageMitigation::Armor => damage * mitigation_multiplier(defender.armor),
+        DamageMitigation::Resistance => damage * mitigation_multiplier(defender.resistance),
+    }
+}
+
+/// roll为[0, 1)的随机数。
+pub fn is_critical(crit_chance: f32, roll: f32) -> bool {
+    roll < crit_chance.clamp(0.0, 1.0)
+}
+
+/// 返回(护盾吸收的伤害, 剩余的伤害)。
+pub fn absorb_by_shield(damage: f32, shield: f32) -> (f32, f32) {
+    let absorbed = damage.min(shield.max(0.0));
+    (absorbed, damage - absorbed)
+}
+
+/// 依次计算免疫、减免、暴击和护盾，damage_type为None时直接扣除生命值。
+pub fn calculate_damage(
+    raw: f32,
+    damage_type: Option<&DamageType>,
+    attacker: &AttackerCapture,
+    defender: &DefenderCapture,
+    immune: bool,
+    crit_roll: f32,
+) -> DamageBreakdown {
+    let raw = raw.max(0.0);
+    let mut breakdown = DamageBreakdown {
+        raw,
+        ..Default::default()
+    };
+    if immune {
+        breakdown.immune = true;
+        return breakdown;
+    }
+
+    let Some(damage_type) = damage_type else {
+        breakdown.mitigated = raw;
+        breakdown.final_damage = raw;
+        breakdown.hp_damage = raw;
+        return breakdown;
+    };
+
+    breakdown.mitigated = mitigate(raw, &damage_type.mitigation, defender);
+    breakdown.critical = damage_type.can_crit && is_critical(attacker.crit_chance, crit_roll);
+    breakdown.final_damage = if breakdown.critical {
+        breakdown.mitigated * attacker.crit_multiplier.max(MIN_CRIT_MULTIPLIER)
+    } else {
+        breakdown.mitigated
+    };
+
+    if damage_type.ignore_shield {
+        breakdown.hp_damage = breakdown.final_damage;
+    } else {
+        (breakdown.absorbed, breakdown.hp_damage) =
+            absorb_by_shield(breakdown.final_damage, defender.shield);
+    }
+    breakdown
+}
+
+#[cfg(test)]
+mod tests {
+    use datatables::damage::{DamageMitigation, DamageType};
+
+    use super::{
+        absorb_by_shield, calculate_damage, is_critical, mitigation_multiplier, AttackerCapture,
+        DefenderCapture,
+    };
+
+    fn damage_type(
+        mitigation: DamageMitigation,
+        can_crit: bool,
+        ignore_shield: bool,
+    ) -> DamageType {
+        DamageType {
+            id: 1,
+            name: String::new(),
+            desc: String::new(),
+            mitigation,
+            can_crit,
+            ignore_shield,
+            immune_layertag: String::new(),
+        }
+    }
+
+    #[test]
+    fn mitigation_formula() {
+        assert_eq!(mitigation_multiplier(0.0), 1.0);
+        assert_eq!(mitigation_multiplier(100.0), 0.5);
+        assert_eq!(mitigation_multiplier(300.0), 0.25);
+        assert_eq!(mitigation_multiplier(-100.0), 1.5);
+        assert!(mitigation_multiplier(-1.0e6) < 2.0);
+    }
+
+    #[test]
+    fn critical_and_shield() {
+        assert!(is_critical(0.3, 0.29));
+        assert!(!is_critical(0.3, 0.3));
+        assert!(!is_critical(-1.0, 0.0));
+        assert!(is_critical(2.0, 0.99));
+
+        assert_eq!(absorb_by_shield(30.0, 50.0), (30.0, 0.0));
+        assert_eq!(absorb_by_shield(30.0, 10.0), (10.0, 20.0));
+        assert_eq!(absorb_by_shield(30.0, -5.0), (0.0, 30.0));
+    }
+
+    #[test]
+    fn damage_pipeline() {
+        let attacker = AttackerCapture {
+            crit_chance: 0.5,
+            crit_multiplier: 2.0,
+        };
+        let defender = DefenderCapture {
+            armor: 100.0,
+            resistance: 300.0,
+            shield: 40.0,
+        };
+
+        let physical = damage_type(DamageMitigation::Armor, true, false);
+        let breakdown = calculate_damage(200.0, Some(&physical), &attacker, &defender, false, 0.1);
+        assert_eq!(breakdown.mitigated, 100.0);
+        assert!(breakdown.critical);
+        assert_eq!(breakdown.final_damage, 200.0);
+        assert_eq!(breakdown.absorbed, 40.0);
+        assert_eq!(breakdown.hp_damage, 160.0);
+
+        let magic = damage_type(DamageMitigation::Resistance, true, false);
+        let breakdown = calculate_damage(200.0, Some(&magic), &attacker, &defender, false, 0.9);
+        assert_eq!(breakdown.mitigated, 50.0);
+        assert!(!breakdown.critical);
+        assert_eq!(breakdown.hp_damage, 10.0);
+
+        let pure = damage_type(DamageMitigation::None, false, true);
+        let breakdown = calculate_damage(200.0, Some(&pure), &attacker, &defender, false, 0.0);
+        assert!(!breakdown.critical);
+        assert_eq!(breakdown.absorbed, 0.0);
+        assert_eq!(breakdown.hp_damage, 200.0);
+
+        let breakdown = calculate_damage(200.0, None, &attacker, &defender, false, 0.0);
+        assert_eq!(breakdown.hp_damage, 200.0);
+
+        let breakdown = calculate_damage(200.0, Some(&physical), &attacker, &defender, true, 0.0);
+        assert!(breakdown.immune);
+        assert_eq!(breakdown.hp_damage, 0.0);
+    }
+}
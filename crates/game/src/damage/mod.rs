@@ -1,41 +1,417 @@
-use ability::attribute::{
-    attribute_set::AttributeSet,
-    implement::{attr_base::BASE_VALUE_LAYER, attr_modifier::AddAttrModifier},
-    AttributeLayer,
+pub mod calc;
+
+use ability::{
+    ability::trigger::{AbilityTriggerEvent, AbilityTriggerPayload},
+    attribute::{
+        attribute_set::AttributeSet,
+        implement::{attr_base::BASE_VALUE_LAYER, attr_modifier::AddAttrModifier},
+    },
+    graph::node::implement::damage::EffectApplyDamageEvent,
+    stateset::{init_state_layertag_registry, StateLayerTagContainer, StateLayerTagRegistry},
+};
+use bevy::{prelude::*, utils::HashMap};
+use datatables::{
+    damage::{DamageType, TbDamageType, TbDamageTypeKey},
+    layertag::TbLayerTag,
+    tables_system_param::TableReader,
+    TableRowChangeKind, TableRowChanged, TablesLoadedEvent,
 };
-use bevy::prelude::*;
+use layertag::query::LayerTagQuery;
 
 use crate::unit::attr_set::{CharacterAttributeSet, CharacterAttributeType};
 
+use self::calc::{calculate_damage, AttackerCapture, DamageBreakdown, DefenderCapture};
+
 #[derive(Event)]
 pub struct DamageEvent {
     pub target: Entity,
     pub source: Entity,
-    pub damage_layer: AttributeLayer,
+    /// None时不计算减免和暴击，直接扣除生命值。
+    pub damage_type: Option<TbDamageTypeKey>,
     pub damage: f32,
 }
 
+/// 伤害计算完成并修改属性后发送，同时触发在target上。
+#[derive(Event, Debug, Clone)]
+pub struct DamageApplied {
+    pub target: Entity,
+    pub source: Entity,
+    pub damage_type: Option<TbDamageTypeKey>,
+    pub breakdown: DamageBreakdown,
+}
+
+/// 伤害类型的免疫查询，表加载和热加载时解析，解析失败的伤害类型不能免疫。
+#[derive(Resource, Default, Debug)]
+pub struct DamageImmuneQueries(pub HashMap<TbDamageTypeKey, LayerTagQuery>);
+
 pub struct DamagePlugin;
 
 impl Plugin for DamagePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<DamageEvent>()
+            .add_event::<DamageApplied>()
+            .init_resource::<DamageImmuneQueries>()
+            .add_observer(trigger_effect_apply_damage)
+            .add_observer(trigger_damage_taken_ability_trigger)
+            .add_systems(
+                First,
+                update_damage_immune_queries.after(init_state_layertag_registry),
+            )
             .add_systems(PostUpdate, apply_damage);
     }
 }
 
+fn parse_immune_query(
+    damage_type: &DamageType,
+    registry: &StateLayerTagRegistry,
+) -> Option<LayerTagQuery> {
+    if damage_type.immune_layertag.is_empty() {
+        return None;
+    }
+    LayerTagQuery::parse(&damage_type.immune_layertag, &registry.0)
+        .inspect_err(|e| error!("damage type {} immune layertag: {}", damage_type.id, e))
+        .ok()
+}
+
+/// 表加载和LayerTag变化时重新解析所有伤害类型，伤害类型热加载时只解析变化的行。
+fn update_damage_immune_queries(
+    mut loaded_reader: EventReader<TablesLoadedEvent>,
+    mut layertag_changed_reader: EventReader<TableRowChanged<TbLayerTag>>,
+    mut row_changed_reader: EventReader<TableRowChanged<TbDamageType>>,
+    damage_type_table: TableReader<TbDamageType>,
+    state_registry: Res<StateLayerTagRegistry>,
+    mut immune_queries: ResMut<DamageImmuneQueries>,
+) {
+    let rebuild = loaded_reader.read().len() > 0 || layertag_changed_reader.read().len() > 0;
+    if rebuild {
+        row_changed_reader.clear();
+        let Some(list) = damage_type_table.get_data_list_in_map_table() else {
+            return;
+        };
+        immune_queries.0 = list
+            .iter()
+            .filter_map(|row| Some((row.id, parse_immune_query(row, &state_registry)?)))
+            .collect();
+        return;
+    }
+
+    for event in row_changed_reader.read() {
+        immune_queries.0.remove(&event.key);
+        if event.kind == TableRowChangeKind::Removed {
+            continue;
+        }
+        if let Some(query) = damage_type_table
+            .get_row(&event.key)
+            .and_then(|row| parse_immune_query(&row, &state_registry))
+        {
+            immune_queries.0.insert(event.key, query);
+        }
+    }
+}
+
+/// effect graph中的伤害节点造成的伤害。
+fn trigger_effect_apply_damage(
+    trigger: Trigger<EffectApplyDamageEvent>,
+    mut damage_events: EventWriter<DamageEvent>,
+) {
+    let event = trigger.event();
+    damage_events.send(DamageEvent {
+        target: trigger.entity(),
+        source: event.instigator,
+        damage_type: event.damage_type,
+        damage: event.damage,
+    });
+}
+
+/// 扣除了生命值的伤害触发受击者的DamageTaken技能，免疫或者被护盾完全吸收时不触发。
+fn trigger_damage_taken_ability_trigger(trigger: Trigger<DamageApplied>, mut commands: Commands) {
+    let event = trigger.event();
+    if event.breakdown.immune || event.breakdown.hp_damage <= 0.0 {
+        return;
+    }
+
+    commands.trigger_targets(
+        AbilityTriggerEvent(AbilityTriggerPayload::DamageTaken {
+            instigator: event.source,
+            damage: event.breakdown.hp_damage,
+        }),
+        event.target,
+    );
+}
+
+fn capture_attacker(attr_set: &CharacterAttributeSet) -> AttackerCapture {
+    let value = |attribute_type| {
+        attr_set
+            .get_attr_final_value(attribute_type)
+            .unwrap_or_default()
+    };
+    AttackerCapture {
+        crit_chance: value(CharacterAttributeType::CritChance),
+        crit_multiplier: value(CharacterAttributeType::CritMultiplier),
+    }
+}
+
+fn capture_defender(attr_set: &CharacterAttributeSet) -> DefenderCapture {
+    let value = |attribute_type| {
+        attr_set
+            .get_attr_final_value(attribute_type)
+            .unwrap_or_default()
+    };
+    DefenderCapture {
+        armor: value(CharacterAttributeType::Armor),
+        resistance: value(CharacterAttributeType::Resistance),
+        shield: value(CharacterAttributeType::Shield),
+    }
+}
+
+fn hp_ratio(attr_set: &CharacterAttributeSet) -> f32 {
+    let hp = attr_set
+        .get_attr_final_value(CharacterAttributeType::Hp)
+        .unwrap_or_default();
+    let max_hp = attr_set
+        .get_attr_final_value(CharacterAttributeType::MaxHp)
+        .unwrap_or_default();
+    if max_hp > 0.0 {
+        hp / max_hp
+    } else {
+        0.0
+    }
+}
+
 fn apply_damage(
+    mut commands: Commands,
     mut damage_events: EventReader<DamageEvent>,
+    mut damage_applied_events: EventWriter<DamageApplied>,
     mut attr_set: Query<&mut CharacterAttributeSet>,
+    state_query: Query<&StateLayerTagContainer>,
+    immune_queries: Res<DamageImmuneQueries>,
+    damage_type_table: TableReader<TbDamageType>,
 ) {
     for event in damage_events.read() {
-        if let Ok(mut target_attr) = attr_set.get_mut(event.target) {
-            let modifier = AddAttrModifier::<CharacterAttributeSet>::new(
+        let damage_type = event.damage_type.and_then(|key| {
+            let row = damage_type_table.get_row(&key);
+            if row.is_none() {
+                warn!("damage type {} not found, apply raw damage", key);
+            }
+            row
+        });
+
+        let immune = event
+            .damage_type
+            .and_then(|key| immune_queries.0.get(&key))
+            .zip(state_query.get(event.target).ok())
+            .is_some_and(|(query, container)| query.matches(&container.0));
+
+        let attacker = attr_set
+            .get(event.source)
+            .map(capture_attacker)
+            .unwrap_or_default();
+        let Ok(mut target_attr) = attr_set.get_mut(event.target) else {
+            continue;
+        };
+
+        let breakdown = calculate_damage(
+            event.damage,
+            damage_type.as_deref(),
+            &attacker,
+            &capture_defender(&target_attr),
+            immune,
+            rand::random::<f32>(),
+        );
+
+        let previous_ratio = hp_ratio(&target_attr);
+        if breakdown.absorbed > 0.0 {
+            target_attr.apply_modify(AddAttrModifier::<CharacterAttributeSet>::new(
+                CharacterAttributeType::Shield,
+                BASE_VALUE_LAYER,
+                -breakdown.absorbed,
+            ));
+        }
+        if breakdown.hp_damage > 0.0 {
+            target_attr.apply_modify(AddAttrModifier::<CharacterAttributeSet>::new(
                 CharacterAttributeType::Hp,
                 BASE_VALUE_LAYER,
-                -event.damage,
+                -breakdown.hp_damage,
+            ));
+            commands.trigger_targets(
+                AbilityTriggerEvent(AbilityTriggerPayload::HpChanged {
+                    previous_ratio,
+                    ratio: hp_ratio(&target_attr),
+                }),
+                event.target,
+            );
+        }
+        if breakdown.critical {
+            commands.trigger_targets(
+                AbilityTriggerEvent(AbilityTriggerPayload::CriticalHitDealt {
+                    target: event.target,
+                    damage: breakdown.final_damage,
+                }),
+                event.source,
+            );
+        }
+
+        let damage_applied = DamageApplied {
+            target: event.target,
+            source: event.source,
+            damage_type: event.damage_type,
+            breakdown,
+        };
+        commands.trigger_targets(damage_applied.clone(), event.target);
+        damage_applied_events.send(damage_applied);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ability::{
+        ability::trigger::{AbilityTriggerEvent, AbilityTriggerPayload},
+        stateset::{StateLayerTagContainer, StateLayerTagRegistry},
+        AbilitySubsystemPlugin,
+    };
+    use bevy::{prelude::*, state::app::StatesPlugin};
+    use datatables::{
+        damage::{DamageMitigation, DamageType},
+        DataTablePlugin,
+    };
+    use layertag::{container_op::LayerTagContainer, registry::LayerTagRegistry};
+
+    use crate::unit::attr_set::CharacterAttributeSet;
+
+    use super::{
+        calc::DamageBreakdown, parse_immune_query, trigger_damage_taken_ability_trigger,
+        DamageApplied, DamageEvent, DamagePlugin,
+    };
+
+    #[derive(Resource, Default)]
+    struct ReceivedTriggers(Vec<(Entity, AbilityTriggerPayload)>);
+
+    #[derive(Resource, Default)]
+    struct ReceivedDamage(Vec<DamageApplied>);
+
+    /// 和游戏一样通过插件组装，不手动初始化ability的资源。
+    #[test]
+    fn damage_plugin_with_ability_subsystem() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            DataTablePlugin,
+            AbilitySubsystemPlugin,
+            DamagePlugin,
+        ))
+        .init_resource::<ReceivedDamage>()
+        .add_observer(
+            |trigger: Trigger<DamageApplied>, mut received: ResMut<ReceivedDamage>| {
+                received.0.push(trigger.event().clone());
+            },
+        );
+
+        let source = app.world_mut().spawn(CharacterAttributeSet::default()).id();
+        let target = app
+            .world_mut()
+            .spawn((
+                CharacterAttributeSet::default(),
+                StateLayerTagContainer::default(),
+            ))
+            .id();
+        app.world_mut().send_event(DamageEvent {
+            target,
+            source,
+            damage_type: None,
+            damage: 10.0,
+        });
+        app.update();
+
+        let received = &app.world().resource::<ReceivedDamage>().0;
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].target, target);
+        assert_eq!(received[0].breakdown.raw, 10.0);
+    }
+
+    #[test]
+    fn trigger_damage_taken_after_mitigation() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ReceivedTriggers>()
+            .add_observer(trigger_damage_taken_ability_trigger)
+            .add_observer(
+                |trigger: Trigger<AbilityTriggerEvent>, mut received: ResMut<ReceivedTriggers>| {
+                    received
+                        .0
+                        .push((trigger.entity(), trigger.event().0.clone()));
+                },
             );
-            target_attr.apply_modify(modifier);
+
+        let source = app.world_mut().spawn_empty().id();
+        let target = app.world_mut().spawn_empty().id();
+        let damage_applied = |breakdown| DamageApplied {
+            target,
+            source,
+            damage_type: None,
+            breakdown,
+        };
+
+        for breakdown in [
+            DamageBreakdown {
+                raw: 10.0,
+                hp_damage: 6.0,
+                ..default()
+            },
+            // 免疫和护盾完全吸收的伤害不触发
+            DamageBreakdown {
+                raw: 10.0,
+                immune: true,
+                ..default()
+            },
+            DamageBreakdown {
+                raw: 10.0,
+                absorbed: 10.0,
+                ..default()
+            },
+        ] {
+            app.world_mut()
+                .trigger_targets(damage_applied(breakdown), target);
         }
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<ReceivedTriggers>().0,
+            vec![(
+                target,
+                AbilityTriggerPayload::DamageTaken {
+                    instigator: source,
+                    damage: 6.0,
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_immune_query_once() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("State.Invincible");
+        let registry = StateLayerTagRegistry(registry);
+        let damage_type = |immune_layertag: &str| DamageType {
+            id: 1,
+            name: "fire".into(),
+            desc: String::new(),
+            mitigation: DamageMitigation::None,
+            can_crit: false,
+            ignore_shield: false,
+            immune_layertag: immune_layertag.into(),
+        };
+
+        assert!(parse_immune_query(&damage_type(""), &registry).is_none());
+        assert!(parse_immune_query(&damage_type("State.Missing"), &registry).is_none());
+
+        let query = parse_immune_query(&damage_type("State.*"), &registry).unwrap();
+        let mut container = StateLayerTagContainer::default();
+        assert!(!query.matches(&container.0));
+        container
+            .0
+            .add_layertag(registry.0.request_from_raw("State.Invincible").unwrap());
+        assert!(query.matches(&container.0));
     }
 }
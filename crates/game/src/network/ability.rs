@@ -0,0 +1,1227 @@
+use ability::{
+    ability::{
+        comp::{Ability, AbilityCooldown, AbilityExecuteState},
+        event::{can_start_ability_entity, AbilityStartCondition, AbilityStartEvent},
+        layertag::tag::{AbilityAddedLayerTagContainer, AbilityRemovedLayerTagContainer},
+        node::ability_entry::EffectNodeAbilityEntry,
+        target::{AbilityTargetData, AbilityTargetRule, AbilityTargetValidator},
+    },
+    attribute::attribute_set::AttributeSet,
+    buff::{layer::BuffLayer, state::Buff, timer::BuffTime},
+    graph::{event::EffectGraphExecEvent, state::EffectGraphState},
+    stateset::StateLayerTagContainer,
+};
+use bevy::{
+    ecs::{
+        entity::{EntityMapper, MapEntities},
+        system::SystemParam,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use datatables::effect::{TbAbilityKey, TbAbilityRow, TbBuffKey, TbBuffRow};
+use layertag::{container_op::LayerTagContainer, layertag::LayerTag};
+use lightyear::prelude::{
+    client::{ComponentSyncMode, Predicted},
+    *,
+};
+
+use crate::unit::{
+    attr_set::{CharacterAttributeSet, CharacterAttributeType},
+    player::PlayerId,
+};
+
+use super::protocol::DefaultChannel;
+
+/// 预测的请求超过这个时间没有收到服务器的回复，按照拒绝处理。
+pub const ABILITY_PREDICTION_TIMEOUT: f32 = 1.0;
+
+/// 剩余时间的误差在这个范围内时不同步，客户端自己计时。
+pub const NET_TIME_TOLERANCE: f32 = 0.5;
+
+/// 客户端为每次预测的技能释放分配的key，服务器在回复中带回，用于对账。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub struct AbilityPredictionKey(pub u32);
+
+/// 客户端请求释放技能，owner为服务器上的实体。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbilityActivateRequest {
+    pub owner: Entity,
+    pub ability_id: TbAbilityKey,
+    pub target: AbilityTargetData,
+    pub prediction_key: AbilityPredictionKey,
+}
+
+impl MapEntities for AbilityActivateRequest {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.owner = entity_mapper.map_entity(self.owner);
+        if let AbilityTargetData::Entity(target) = &mut self.target {
+            *target = entity_mapper.map_entity(*target);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum AbilityActivateResult {
+    Confirmed,
+    Rejected(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AbilityActivateResponse {
+    pub prediction_key: AbilityPredictionKey,
+    pub result: AbilityActivateResult,
+}
+
+/// 服务器上由客户端请求开始的技能，客户端已经预测播放了表现，技能不再执行时移除。
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbilityPredictedBy(pub ClientId);
+
+/// 客户端释放技能，触发在预测的owner实体上。
+#[derive(Debug, Event, Clone)]
+pub struct ClientAbilityActivateEvent {
+    pub ability_id: TbAbilityKey,
+    pub target: AbilityTargetData,
+}
+
+/// 预测失败，技能已经回滚，触发在客户端的技能实体上，用于停止表现。
+#[derive(Debug, Event, Clone)]
+pub struct AbilityPredictionRollbackEvent {
+    pub prediction_key: AbilityPredictionKey,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct NetAbilityState {
+    pub ability_id: TbAbilityKey,
+    pub execute_state: AbilityExecuteState,
+    pub cooldown: f32,
+}
+
+/// owner上所有技能的状态，服务器同步到客户端。
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct NetAbilityStates(pub Vec<NetAbilityState>);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct NetBuffState {
+    pub buff_id: TbBuffKey,
+    pub layer: i32,
+    pub remaining: f32,
+}
+
+/// owner上所有buff的层数和剩余时间，服务器同步到客户端。
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct NetBuffStates(pub Vec<NetBuffState>);
+
+/// 客户端本地计时的剩余时间，和同步的状态按下标对应。
+/// 同步的状态变化时重置，服务器同步的组件不在客户端修改。
+#[derive(Component, Debug, Default, Clone, PartialEq)]
+pub struct NetStateTimers {
+    pub ability_cooldowns: Vec<f32>,
+    pub buff_remaining: Vec<f32>,
+}
+
+impl NetStateTimers {
+    fn reset_abilities(&mut self, states: &NetAbilityStates) {
+        self.ability_cooldowns = states.0.iter().map(|state| state.cooldown).collect();
+    }
+
+    fn reset_buffs(&mut self, states: &NetBuffStates) {
+        self.buff_remaining = states.0.iter().map(|state| state.remaining).collect();
+    }
+
+    fn tick(&mut self, delta: f32) {
+        for time in self
+            .ability_cooldowns
+            .iter_mut()
+            .chain(self.buff_remaining.iter_mut())
+        {
+            *time = (*time - delta).max(0.0);
+        }
+    }
+}
+
+/// 属性的最终值，服务器同步到客户端。
+#[derive(Component, Debug, Default, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+pub struct NetAttributeValues {
+    pub hp: f32,
+    pub max_hp: f32,
+    pub move_speed: f32,
+    pub max_move_speed: f32,
+}
+
+impl NetAttributeValues {
+    fn from_attribute_set(attribute_set: &CharacterAttributeSet) -> Self {
+        let value = |attribute_type| {
+            attribute_set
+                .get_attr_final_value(attribute_type)
+                .unwrap_or_default()
+        };
+        Self {
+            hp: value(CharacterAttributeType::Hp),
+            max_hp: value(CharacterAttributeType::MaxHp),
+            move_speed: value(CharacterAttributeType::MoveSpeed),
+            max_move_speed: value(CharacterAttributeType::MaxMoveSpped),
+        }
+    }
+}
+
+type AbilityLayerTagOps<'a> = (
+    &'a AbilityAddedLayerTagContainer,
+    &'a AbilityRemovedLayerTagContainer,
+);
+
+type AbilityStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static TbAbilityRow,
+        &'static AbilityExecuteState,
+        Option<&'static AbilityCooldown>,
+    ),
+    With<Ability>,
+>;
+
+/// 预测开始前的冷却和预测修改的owner状态标签，拒绝或者超时时恢复。
+/// 只撤销这次预测的修改，期间buff、其他技能和同步添加移除的状态标签保留。
+#[derive(Debug, Clone, Default)]
+struct AbilityPredictionSnapshot {
+    cooldown: Option<AbilityCooldown>,
+    /// 技能开始时添加的状态标签。
+    added_layertags: Vec<LayerTag>,
+    /// 技能开始时移除的，预测前owner拥有的状态标签。
+    removed_layertags: Vec<LayerTag>,
+}
+
+impl AbilityPredictionSnapshot {
+    fn new(
+        cooldown: Option<&AbilityCooldown>,
+        layertag_ops: Option<AbilityLayerTagOps>,
+        owner_layertags: Option<&StateLayerTagContainer>,
+    ) -> Self {
+        let mut snapshot = Self {
+            cooldown: cooldown.copied(),
+            ..default()
+        };
+        if let Some((added_tag, removed_tag)) = layertag_ops {
+            snapshot.added_layertags = added_tag
+                .layer_tag_container
+                .iter_layertag()
+                .cloned()
+                .collect();
+            snapshot.removed_layertags = removed_tag
+                .layer_tag_container
+                .iter_layertag()
+                .filter(|layertag| {
+                    owner_layertags.is_some_and(|owner| owner.0.exist_layertag(layertag))
+                })
+                .cloned()
+                .collect();
+        }
+        snapshot
+    }
+}
+
+#[derive(Debug)]
+struct PendingAbilityActivation {
+    ability: Entity,
+    owner: Entity,
+    elapsed: f32,
+    snapshot: AbilityPredictionSnapshot,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct AbilityPredictionState {
+    next_key: u32,
+    pending: HashMap<AbilityPredictionKey, PendingAbilityActivation>,
+}
+
+impl AbilityPredictionState {
+    fn next_key(&mut self) -> AbilityPredictionKey {
+        self.next_key = self.next_key.wrapping_add(1);
+        AbilityPredictionKey(self.next_key)
+    }
+
+    pub fn is_pending(&self, ability: Entity) -> bool {
+        self.pending
+            .values()
+            .any(|pending| pending.ability == ability)
+    }
+}
+
+pub(crate) struct AbilityProtocolPlugin;
+
+impl Plugin for AbilityProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        // messages
+        app.register_message::<AbilityActivateRequest>(ChannelDirection::ClientToServer)
+            .add_map_entities();
+        app.register_message::<AbilityActivateResponse>(ChannelDirection::ServerToClient);
+
+        // components
+        app.register_component::<NetAbilityStates>(ChannelDirection::ServerToClient)
+            .add_prediction(ComponentSyncMode::Simple);
+        app.register_component::<NetBuffStates>(ChannelDirection::ServerToClient)
+            .add_prediction(ComponentSyncMode::Simple);
+        app.register_component::<NetAttributeValues>(ChannelDirection::ServerToClient)
+            .add_prediction(ComponentSyncMode::Simple);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AbilityNetClientPlugin;
+
+impl Plugin for AbilityNetClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AbilityPredictionState>()
+            .add_event::<ClientAbilityActivateEvent>()
+            .add_event::<AbilityPredictionRollbackEvent>()
+            .add_observer(trigger_client_ability_activate)
+            .add_systems(
+                Update,
+                (
+                    receive_ability_activate_response,
+                    timeout_ability_prediction,
+                    tick_net_state_timers,
+                )
+                    .chain()
+                    .after(MainSet::Receive),
+            );
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct AbilityNetServerPlugin;
+
+impl Plugin for AbilityNetServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            receive_ability_activate_request.after(MainSet::EmitEvents),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                clear_ability_predicted_by,
+                sync_net_ability_states,
+                sync_net_buff_states,
+                sync_net_attribute_values,
+            ),
+        );
+    }
+}
+
+fn find_ability(
+    children: Option<&Children>,
+    ability_query: &AbilityStateQuery,
+    ability_id: TbAbilityKey,
+) -> Option<Entity> {
+    children.into_iter().flatten().copied().find(|child| {
+        ability_query
+            .get(*child)
+            .is_ok_and(|(row, _, _)| *row.key() == ability_id)
+    })
+}
+
+fn can_activate(state: &AbilityExecuteState, cooldown: Option<&AbilityCooldown>) -> bool {
+    *state == AbilityExecuteState::Inactive && cooldown.is_none_or(|cooldown| cooldown.is_ready())
+}
+
+/// 预测技能开始，然后请求服务器确认。
+/// 本地只设置冷却和执行表现节点，伤害和buff等由服务器执行后同步。
+fn trigger_client_ability_activate(
+    trigger: Trigger<ClientAbilityActivateEvent>,
+    mut commands: Commands,
+    mut connection: ResMut<client::ConnectionManager>,
+    mut prediction_state: ResMut<AbilityPredictionState>,
+    owner_query: Query<(
+        Option<&Children>,
+        Option<&Predicted>,
+        Option<&StateLayerTagContainer>,
+    )>,
+    target_query: Query<&Predicted>,
+    ability_query: AbilityStateQuery,
+    start_condition_query: Query<AbilityStartCondition>,
+    layertag_ops_query: Query<AbilityLayerTagOps<'static>>,
+) {
+    let owner = trigger.entity();
+    let event = trigger.event();
+    let Ok((children, predicted, layertags)) = owner_query.get(owner) else {
+        return;
+    };
+
+    let Some(ability) = find_ability(children, &ability_query, event.ability_id) else {
+        warn!("ability {} not found on {:?}", event.ability_id, owner);
+        return;
+    };
+    let Ok((_, state, cooldown)) = ability_query.get(ability) else {
+        return;
+    };
+    if !can_activate(state, cooldown) || prediction_state.is_pending(ability) {
+        return;
+    }
+    // 服务器会拒绝，不预测也不请求。
+    if !can_start_ability_entity(&start_condition_query, ability, layertags) {
+        debug!(
+            "ability {} start condition not matched on {:?}",
+            event.ability_id, owner
+        );
+        return;
+    }
+
+    // 服务器只知道确认的实体。
+    let confirmed = |entity: Entity| {
+        target_query
+            .get(entity)
+            .ok()
+            .and_then(|predicted| predicted.confirmed_entity)
+            .unwrap_or(entity)
+    };
+    let mut target = event.target.clone();
+    if let AbilityTargetData::Entity(target_entity) = &mut target {
+        *target_entity = confirmed(*target_entity);
+    }
+
+    let prediction_key = prediction_state.next_key();
+    let mut request = AbilityActivateRequest {
+        owner: predicted
+            .and_then(|predicted| predicted.confirmed_entity)
+            .unwrap_or(owner),
+        ability_id: event.ability_id,
+        target,
+        prediction_key,
+    };
+    if let Err(e) = connection.send_message::<DefaultChannel, _>(&mut request) {
+        warn!("send ability activate request failed: {:?}", e);
+        return;
+    }
+
+    prediction_state.pending.insert(
+        prediction_key,
+        PendingAbilityActivation {
+            ability,
+            owner,
+            elapsed: 0.0,
+            snapshot: AbilityPredictionSnapshot::new(
+                cooldown,
+                layertag_ops_query.get(ability).ok(),
+                layertags,
+            ),
+        },
+    );
+    let targets = match event.target {
+        AbilityTargetData::Entity(target) => vec![target],
+        _ => Vec::new(),
+    };
+    commands.trigger_targets(
+        AbilityStartEvent {
+            target: event.target.clone(),
+            targets,
+            predicted: true,
+        },
+        ability,
+    );
+}
+
+/// 恢复预测前的冷却，撤销预测修改的owner状态标签，并中断预测执行的图。
+#[derive(SystemParam)]
+struct AbilityPredictionRollback<'w, 's> {
+    commands: Commands<'w, 's>,
+    cooldown_query: Query<'w, 's, &'static mut AbilityCooldown>,
+    owner_query: Query<'w, 's, &'static mut StateLayerTagContainer>,
+}
+
+impl AbilityPredictionRollback<'_, '_> {
+    fn rollback(
+        &mut self,
+        pending: PendingAbilityActivation,
+        prediction_key: AbilityPredictionKey,
+        reason: String,
+    ) {
+        let PendingAbilityActivation {
+            ability,
+            owner,
+            elapsed,
+            snapshot,
+        } = pending;
+        warn!(
+            "ability {:?} prediction {:?} rollback: {}",
+            ability, prediction_key, reason
+        );
+
+        if let (Ok(mut cooldown), Some(snapshot)) =
+            (self.cooldown_query.get_mut(ability), snapshot.cooldown)
+        {
+            cooldown.remaining = (snapshot.remaining - elapsed).max(0.0);
+        }
+        if let Ok(mut layertags) = self.owner_query.get_mut(owner) {
+            layertags
+                .0
+                .remove_layertags(snapshot.added_layertags.iter());
+            layertags
+                .0
+                .add_layertags(snapshot.removed_layertags.into_iter());
+        }
+
+        self.commands.trigger_targets(
+            EffectGraphExecEvent {
+                entry_exec_pin: EffectNodeAbilityEntry::OUTPUT_EXEC_ABORT.into(),
+                execute_in_graph_state: Some(EffectGraphState::Active),
+                slot_value_map: None,
+            },
+            ability,
+        );
+        self.commands.trigger_targets(
+            AbilityPredictionRollbackEvent {
+                prediction_key,
+                reason,
+            },
+            ability,
+        );
+    }
+}
+
+/// 确认时只移除等待的预测，拒绝时回滚。
+fn resolve_ability_prediction(
+    prediction_state: &mut AbilityPredictionState,
+    rollback: &mut AbilityPredictionRollback,
+    response: &AbilityActivateResponse,
+) {
+    let Some(pending) = prediction_state.pending.remove(&response.prediction_key) else {
+        return;
+    };
+
+    if let AbilityActivateResult::Rejected(reason) = &response.result {
+        rollback.rollback(pending, response.prediction_key, reason.clone());
+    }
+}
+
+fn receive_ability_activate_response(
+    mut events: EventReader<client::MessageEvent<AbilityActivateResponse>>,
+    mut prediction_state: ResMut<AbilityPredictionState>,
+    mut rollback: AbilityPredictionRollback,
+) {
+    for event in events.read() {
+        resolve_ability_prediction(&mut prediction_state, &mut rollback, event.message());
+    }
+}
+
+fn timeout_ability_prediction(
+    mut prediction_state: ResMut<AbilityPredictionState>,
+    mut rollback: AbilityPredictionRollback,
+    time: Res<Time>,
+) {
+    let mut timeout = Vec::new();
+    for (prediction_key, pending) in prediction_state.pending.iter_mut() {
+        pending.elapsed += time.delta_secs();
+        if pending.elapsed > ABILITY_PREDICTION_TIMEOUT {
+            timeout.push(*prediction_key);
+        }
+    }
+
+    for prediction_key in timeout {
+        if let Some(pending) = prediction_state.pending.remove(&prediction_key) {
+            rollback.rollback(pending, prediction_key, "timeout".into());
+        }
+    }
+}
+
+/// 同步的状态变化时重置本地计时，之后客户端自己计时。
+#[allow(clippy::type_complexity)]
+fn tick_net_state_timers(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            Option<Ref<NetAbilityStates>>,
+            Option<Ref<NetBuffStates>>,
+            Option<&mut NetStateTimers>,
+        ),
+        Or<(With<NetAbilityStates>, With<NetBuffStates>)>,
+    >,
+    time: Res<Time>,
+) {
+    for (entity, ability_states, buff_states, timers) in query.iter_mut() {
+        let Some(mut timers) = timers else {
+            let mut timers = NetStateTimers::default();
+            if let Some(states) = ability_states.as_deref() {
+                timers.reset_abilities(states);
+            }
+            if let Some(states) = buff_states.as_deref() {
+                timers.reset_buffs(states);
+            }
+            commands.entity(entity).insert(timers);
+            continue;
+        };
+
+        if let Some(states) = ability_states.filter(|states| states.is_changed()) {
+            timers.reset_abilities(&states);
+        }
+        if let Some(states) = buff_states.filter(|states| states.is_changed()) {
+            timers.reset_buffs(&states);
+        }
+        timers.tick(time.delta_secs());
+    }
+}
+
+/// 服务器验证客户端的技能释放请求。
+#[derive(SystemParam)]
+struct AbilityRequestValidator<'w, 's> {
+    owner_query: Query<
+        'w,
+        's,
+        (
+            Option<&'static Children>,
+            Option<&'static PlayerId>,
+            Option<&'static StateLayerTagContainer>,
+        ),
+    >,
+    ability_query: AbilityStateQuery<'w, 's>,
+    start_condition_query: Query<'w, 's, AbilityStartCondition>,
+    rule_query: Query<'w, 's, &'static AbilityTargetRule>,
+    target_validator: AbilityTargetValidator<'w, 's>,
+}
+
+impl AbilityRequestValidator<'_, '_> {
+    /// 返回技能实体和目标实体。
+    fn validate(
+        &self,
+        client_id: ClientId,
+        request: &AbilityActivateRequest,
+    ) -> Result<(Entity, Vec<Entity>), String> {
+        let (children, player_id, layertags) = self
+            .owner_query
+            .get(request.owner)
+            .map_err(|_| format!("owner {:?} not found", request.owner))?;
+        if player_id.is_none_or(|player_id| player_id.0 != client_id) {
+            return Err(format!(
+                "owner {:?} is not controlled by client",
+                request.owner
+            ));
+        }
+
+        let Some(ability) = find_ability(children, &self.ability_query, request.ability_id) else {
+            return Err(format!("ability {} not found", request.ability_id));
+        };
+        let Ok((_, state, cooldown)) = self.ability_query.get(ability) else {
+            return Err(format!("ability {} not found", request.ability_id));
+        };
+        if !can_activate(state, cooldown) {
+            return Err(format!("ability {} can not activate", request.ability_id));
+        }
+        if !can_start_ability_entity(&self.start_condition_query, ability, layertags) {
+            return Err(format!(
+                "ability {} start condition not matched",
+                request.ability_id
+            ));
+        }
+
+        let targets = self
+            .target_validator
+            .validate(
+                request.owner,
+                self.rule_query.get(ability).ok(),
+                &request.target,
+            )
+            .map_err(|e| e.to_string())?;
+        Ok((ability, targets))
+    }
+}
+
+/// 验证通过后在服务器开始技能，返回回复客户端的结果。
+fn activate_requested_ability(
+    commands: &mut Commands,
+    validator: &AbilityRequestValidator,
+    client_id: ClientId,
+    request: &AbilityActivateRequest,
+) -> AbilityActivateResult {
+    match validator.validate(client_id, request) {
+        Ok((ability, targets)) => {
+            commands
+                .entity(ability)
+                .insert(AbilityPredictedBy(client_id));
+            commands.trigger_targets(
+                AbilityStartEvent {
+                    target: request.target.clone(),
+                    targets,
+                    predicted: false,
+                },
+                ability,
+            );
+            AbilityActivateResult::Confirmed
+        }
+        Err(reason) => {
+            warn!(
+                "client {:?} ability {} request rejected: {}",
+                client_id, request.ability_id, reason
+            );
+            AbilityActivateResult::Rejected(reason)
+        }
+    }
+}
+
+/// 服务器验证技能释放请求，通过后开始技能，并回复客户端。
+fn receive_ability_activate_request(
+    mut commands: Commands,
+    mut events: EventReader<server::MessageEvent<AbilityActivateRequest>>,
+    mut connection: ResMut<server::ConnectionManager>,
+    validator: AbilityRequestValidator,
+) {
+    for event in events.read() {
+        let client_id = *event.context();
+        let request = event.message();
+        let result = activate_requested_ability(&mut commands, &validator, client_id, request);
+
+        let mut response = AbilityActivateResponse {
+            prediction_key: request.prediction_key,
+            result,
+        };
+        if let Err(e) = connection.send_message_to_target::<DefaultChannel, _>(
+            &mut response,
+            NetworkTarget::Single(client_id),
+        ) {
+            warn!("send ability activate response failed: {:?}", e);
+        }
+    }
+}
+
+fn clear_ability_predicted_by(
+    mut commands: Commands,
+    ability_query: Query<(Entity, &AbilityExecuteState), With<AbilityPredictedBy>>,
+) {
+    for (ability, state) in ability_query.iter() {
+        if *state != AbilityExecuteState::Active {
+            commands.entity(ability).remove::<AbilityPredictedBy>();
+        }
+    }
+}
+
+/// 只在变化时插入，避免每帧同步。
+fn insert_if_changed<T: Component>(
+    commands: &mut Commands,
+    entity: Entity,
+    current: Option<&T>,
+    value: T,
+    is_synced: impl Fn(&T, &T) -> bool,
+) {
+    if current.is_none_or(|current| !is_synced(current, &value)) {
+        commands.entity(entity).insert(value);
+    }
+}
+
+fn is_time_synced(lhs: f32, rhs: f32) -> bool {
+    (lhs - rhs).abs() <= NET_TIME_TOLERANCE
+}
+
+fn is_ability_states_synced(lhs: &NetAbilityStates, rhs: &NetAbilityStates) -> bool {
+    lhs.0.len() == rhs.0.len()
+        && lhs.0.iter().zip(rhs.0.iter()).all(|(lhs, rhs)| {
+            lhs.ability_id == rhs.ability_id
+                && lhs.execute_state == rhs.execute_state
+                && is_time_synced(lhs.cooldown, rhs.cooldown)
+        })
+}
+
+fn is_buff_states_synced(lhs: &NetBuffStates, rhs: &NetBuffStates) -> bool {
+    lhs.0.len() == rhs.0.len()
+        && lhs.0.iter().zip(rhs.0.iter()).all(|(lhs, rhs)| {
+            lhs.buff_id == rhs.buff_id
+                && lhs.layer == rhs.layer
+                && is_time_synced(lhs.remaining, rhs.remaining)
+        })
+}
+
+fn sync_net_ability_states(
+    mut commands: Commands,
+    owner_query: Query<(Entity, &Children, Option<&NetAbilityStates>), With<Replicating>>,
+    ability_query: AbilityStateQuery,
+) {
+    for (owner, children, current) in owner_query.iter() {
+        let states: Vec<NetAbilityState> = children
+            .iter()
+            .filter_map(|child| ability_query.get(*child).ok())
+            .map(|(row, execute_state, cooldown)| NetAbilityState {
+                ability_id: *row.key(),
+                execute_state: *execute_state,
+                cooldown: cooldown.map_or(0.0, |cooldown| cooldown.remaining),
+            })
+            .collect();
+        if states.is_empty() && current.is_none() {
+            continue;
+        }
+        insert_if_changed(
+            &mut commands,
+            owner,
+            current,
+            NetAbilityStates(states),
+            is_ability_states_synced,
+        );
+    }
+}
+
+fn sync_net_buff_states(
+    mut commands: Commands,
+    owner_query: Query<(Entity, &Children, Option<&NetBuffStates>), With<Replicating>>,
+    buff_query: Query<(&TbBuffRow, &BuffLayer, &BuffTime), With<Buff>>,
+) {
+    for (owner, children, current) in owner_query.iter() {
+        let states: Vec<NetBuffState> = children
+            .iter()
+            .filter_map(|child| buff_query.get(*child).ok())
+            .map(|(row, layer, time)| NetBuffState {
+                buff_id: *row.key(),
+                layer: layer.layer(),
+                remaining: time.once_timer.remaining_secs(),
+            })
+            .collect();
+        if states.is_empty() && current.is_none() {
+            continue;
+        }
+        insert_if_changed(
+            &mut commands,
+            owner,
+            current,
+            NetBuffStates(states),
+            is_buff_states_synced,
+        );
+    }
+}
+
+fn sync_net_attribute_values(
+    mut commands: Commands,
+    query: Query<(Entity, &CharacterAttributeSet, Option<&NetAttributeValues>), With<Replicating>>,
+) {
+    for (entity, attribute_set, current) in query.iter() {
+        insert_if_changed(
+            &mut commands,
+            entity,
+            current,
+            NetAttributeValues::from_attribute_set(attribute_set),
+            PartialEq::eq,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ability::{
+        ability::{
+            comp::{Ability, AbilityCooldown, AbilityExecuteState},
+            event::AbilityStartEvent,
+            layertag::tag::{
+                AbilityAddedLayerTagContainer, AbilityRemovedLayerTagContainer,
+                AbilityStartDisableLayerTagContainer, AbilityStartLayerTagQuery,
+                AbilityStartRequiredLayerTagContainer,
+            },
+            target::AbilityTargetData,
+        },
+        stateset::StateLayerTagContainer,
+    };
+    use avian3d::prelude::SpatialQueryPipeline;
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+    use datatables::{effect::TbAbilityRow, unit::TbRelationShip, Tables};
+    use layertag::{
+        container_op::LayerTagContainer, count_container::CountLayerTagContainer,
+        layertag::StaticLayerTag,
+    };
+    use lightyear::prelude::ClientId;
+
+    use crate::unit::player::PlayerId;
+
+    use super::{
+        activate_requested_ability, is_ability_states_synced, resolve_ability_prediction,
+        tick_net_state_timers, timeout_ability_prediction, AbilityActivateRequest,
+        AbilityActivateResponse, AbilityActivateResult, AbilityPredictionKey,
+        AbilityPredictionRollback, AbilityPredictionRollbackEvent, AbilityPredictionSnapshot,
+        AbilityPredictionState, AbilityRequestValidator, NetAbilityState, NetAbilityStates,
+        NetStateTimers, PendingAbilityActivation, ABILITY_PREDICTION_TIMEOUT,
+    };
+
+    const CLIENT: ClientId = ClientId::Netcode(1);
+    const ABILITY_ID: i32 = 1;
+    const PREDICTED_TAG: StaticLayerTag = StaticLayerTag::new("test.ability.predicted");
+    const STUNNED_TAG: StaticLayerTag = StaticLayerTag::new("test.ability.stunned");
+
+    #[derive(Debug, Default, Resource)]
+    struct Started(Vec<(Entity, bool)>);
+
+    #[derive(Debug, Default, Resource)]
+    struct RolledBack(Vec<(AbilityPredictionKey, String)>);
+
+    fn new_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<SpatialQueryPipeline>()
+            .init_resource::<Tables>()
+            .init_resource::<Assets<TbRelationShip>>()
+            .init_resource::<AbilityPredictionState>()
+            .init_resource::<Started>()
+            .init_resource::<RolledBack>()
+            .insert_resource(Time::<()>::default())
+            .add_observer(
+                |trigger: Trigger<AbilityStartEvent>, mut started: ResMut<Started>| {
+                    started
+                        .0
+                        .push((trigger.entity(), trigger.event().predicted));
+                },
+            )
+            .add_observer(
+                |trigger: Trigger<AbilityPredictionRollbackEvent>,
+                 mut rolled_back: ResMut<RolledBack>| {
+                    let event = trigger.event();
+                    rolled_back
+                        .0
+                        .push((event.prediction_key, event.reason.clone()));
+                },
+            );
+        app
+    }
+
+    /// owner和它的一个冷却完毕的技能，技能开始时添加PREDICTED_TAG。
+    fn spawn_owner(app: &mut App) -> (Entity, Entity) {
+        let mut added = CountLayerTagContainer::default();
+        added.add_layertag(PREDICTED_TAG.layertag());
+        let owner = app
+            .world_mut()
+            .spawn((
+                PlayerId(CLIENT),
+                StateLayerTagContainer(CountLayerTagContainer::default()),
+            ))
+            .id();
+        let ability = app
+            .world_mut()
+            .spawn((
+                Ability,
+                TbAbilityRow::new(ABILITY_ID, None),
+                AbilityExecuteState::Inactive,
+                AbilityCooldown::default(),
+                AbilityAddedLayerTagContainer {
+                    layer_tag_container: added,
+                    ..default()
+                },
+                AbilityRemovedLayerTagContainer::default(),
+            ))
+            .set_parent(owner)
+            .id();
+        (owner, ability)
+    }
+
+    fn activate(
+        app: &mut App,
+        client_id: ClientId,
+        request: AbilityActivateRequest,
+    ) -> AbilityActivateResult {
+        app.world_mut()
+            .run_system_once(
+                move |mut commands: Commands, validator: AbilityRequestValidator| {
+                    activate_requested_ability(&mut commands, &validator, client_id, &request)
+                },
+            )
+            .unwrap()
+    }
+
+    /// 模拟客户端的预测：记录快照，然后设置冷却并添加状态标签。
+    fn predict(
+        app: &mut App,
+        owner: Entity,
+        ability: Entity,
+        prediction_key: AbilityPredictionKey,
+    ) {
+        let world = app.world();
+        let snapshot = AbilityPredictionSnapshot::new(
+            world.get::<AbilityCooldown>(ability),
+            world
+                .get::<AbilityAddedLayerTagContainer>(ability)
+                .zip(world.get::<AbilityRemovedLayerTagContainer>(ability)),
+            world.get::<StateLayerTagContainer>(owner),
+        );
+        app.world_mut()
+            .resource_mut::<AbilityPredictionState>()
+            .pending
+            .insert(
+                prediction_key,
+                PendingAbilityActivation {
+                    ability,
+                    owner,
+                    elapsed: 0.0,
+                    snapshot,
+                },
+            );
+        app.world_mut()
+            .get_mut::<AbilityCooldown>(ability)
+            .unwrap()
+            .remaining = 5.0;
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(PREDICTED_TAG.layertag());
+    }
+
+    fn advance(app: &mut App, secs: f32) {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(secs));
+        app.update();
+    }
+
+    fn resolve(app: &mut App, response: AbilityActivateResponse) {
+        app.world_mut()
+            .run_system_once(
+                move |mut prediction_state: ResMut<AbilityPredictionState>,
+                      mut rollback: AbilityPredictionRollback| {
+                    resolve_ability_prediction(&mut prediction_state, &mut rollback, &response);
+                },
+            )
+            .unwrap();
+    }
+
+    fn is_restored(app: &App, owner: Entity, ability: Entity) -> bool {
+        app.world()
+            .get::<AbilityCooldown>(ability)
+            .unwrap()
+            .is_ready()
+            && !app
+                .world()
+                .get::<StateLayerTagContainer>(owner)
+                .unwrap()
+                .0
+                .exist_layertag(&PREDICTED_TAG.layertag())
+    }
+
+    #[test]
+    fn validate_activate_request() {
+        let mut app = new_app();
+        let (owner, ability) = spawn_owner(&mut app);
+        let request = |ability_id| AbilityActivateRequest {
+            owner,
+            ability_id,
+            target: AbilityTargetData::None,
+            prediction_key: AbilityPredictionKey(1),
+        };
+
+        assert!(matches!(
+            activate(&mut app, ClientId::Netcode(2), request(ABILITY_ID)),
+            AbilityActivateResult::Rejected(_)
+        ));
+        assert!(matches!(
+            activate(&mut app, CLIENT, request(ABILITY_ID + 1)),
+            AbilityActivateResult::Rejected(_)
+        ));
+        assert!(matches!(
+            activate(
+                &mut app,
+                CLIENT,
+                AbilityActivateRequest {
+                    owner: ability,
+                    ..request(ABILITY_ID)
+                }
+            ),
+            AbilityActivateResult::Rejected(_)
+        ));
+
+        app.world_mut()
+            .get_mut::<AbilityCooldown>(ability)
+            .unwrap()
+            .remaining = 1.0;
+        assert!(matches!(
+            activate(&mut app, CLIENT, request(ABILITY_ID)),
+            AbilityActivateResult::Rejected(_)
+        ));
+        assert!(app.world().resource::<Started>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<AbilityCooldown>(ability)
+            .unwrap()
+            .remaining = 0.0;
+        assert_eq!(
+            activate(&mut app, CLIENT, request(ABILITY_ID)),
+            AbilityActivateResult::Confirmed
+        );
+        assert_eq!(app.world().resource::<Started>().0, vec![(ability, false)]);
+    }
+
+    #[test]
+    fn reject_blocked_start_condition() {
+        let mut app = new_app();
+        let (owner, ability) = spawn_owner(&mut app);
+        let mut disable = CountLayerTagContainer::default();
+        disable.add_layertag(STUNNED_TAG.layertag());
+        app.world_mut().entity_mut(ability).insert((
+            AbilityStartRequiredLayerTagContainer::default(),
+            AbilityStartDisableLayerTagContainer(disable),
+            AbilityStartLayerTagQuery::default(),
+        ));
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(STUNNED_TAG.layertag());
+
+        let request = AbilityActivateRequest {
+            owner,
+            ability_id: ABILITY_ID,
+            target: AbilityTargetData::None,
+            prediction_key: AbilityPredictionKey(1),
+        };
+        assert!(matches!(
+            activate(&mut app, CLIENT, request.clone()),
+            AbilityActivateResult::Rejected(_)
+        ));
+        assert!(app.world().resource::<Started>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .remove_layertag(&STUNNED_TAG.layertag());
+        assert_eq!(
+            activate(&mut app, CLIENT, request),
+            AbilityActivateResult::Confirmed
+        );
+        assert_eq!(app.world().resource::<Started>().0, vec![(ability, false)]);
+    }
+
+    #[test]
+    fn rollback_rejected_prediction() {
+        let mut app = new_app();
+        let (owner, ability) = spawn_owner(&mut app);
+
+        let confirmed = AbilityPredictionKey(1);
+        predict(&mut app, owner, ability, confirmed);
+        resolve(
+            &mut app,
+            AbilityActivateResponse {
+                prediction_key: confirmed,
+                result: AbilityActivateResult::Confirmed,
+            },
+        );
+        assert!(!app
+            .world()
+            .resource::<AbilityPredictionState>()
+            .is_pending(ability));
+        assert!(!is_restored(&app, owner, ability));
+        assert!(app.world().resource::<RolledBack>().0.is_empty());
+
+        app.world_mut()
+            .get_mut::<AbilityCooldown>(ability)
+            .unwrap()
+            .remaining = 0.0;
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .remove_layertag(&PREDICTED_TAG.layertag());
+
+        let rejected = AbilityPredictionKey(2);
+        predict(&mut app, owner, ability, rejected);
+        // 等待回复期间buff添加的状态标签不受回滚影响。
+        app.world_mut()
+            .get_mut::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .add_layertag(STUNNED_TAG.layertag());
+        resolve(
+            &mut app,
+            AbilityActivateResponse {
+                prediction_key: rejected,
+                result: AbilityActivateResult::Rejected("out of range".into()),
+            },
+        );
+        assert!(is_restored(&app, owner, ability));
+        assert!(app
+            .world()
+            .get::<StateLayerTagContainer>(owner)
+            .unwrap()
+            .0
+            .exist_layertag(&STUNNED_TAG.layertag()));
+        assert_eq!(
+            app.world().resource::<RolledBack>().0,
+            vec![(rejected, "out of range".into())]
+        );
+    }
+
+    #[test]
+    fn rollback_prediction_timeout() {
+        let mut app = new_app();
+        app.add_systems(Update, timeout_ability_prediction);
+        let (owner, ability) = spawn_owner(&mut app);
+
+        let prediction_key = AbilityPredictionKey(1);
+        predict(&mut app, owner, ability, prediction_key);
+
+        advance(&mut app, ABILITY_PREDICTION_TIMEOUT * 0.5);
+        assert!(app
+            .world()
+            .resource::<AbilityPredictionState>()
+            .is_pending(ability));
+        assert!(!is_restored(&app, owner, ability));
+
+        advance(&mut app, ABILITY_PREDICTION_TIMEOUT);
+        assert!(!app
+            .world()
+            .resource::<AbilityPredictionState>()
+            .is_pending(ability));
+        assert!(is_restored(&app, owner, ability));
+        assert_eq!(
+            app.world().resource::<RolledBack>().0,
+            vec![(prediction_key, "timeout".into())]
+        );
+    }
+
+    #[test]
+    fn sync_states_with_tolerance() {
+        let states = |cooldown, execute_state| {
+            NetAbilityStates(vec![NetAbilityState {
+                ability_id: ABILITY_ID,
+                execute_state,
+                cooldown,
+            }])
+        };
+        let synced = states(3.0, AbilityExecuteState::Inactive);
+        assert!(is_ability_states_synced(
+            &synced,
+            &states(3.4, AbilityExecuteState::Inactive)
+        ));
+        assert!(!is_ability_states_synced(
+            &synced,
+            &states(3.6, AbilityExecuteState::Inactive)
+        ));
+        assert!(!is_ability_states_synced(
+            &synced,
+            &states(3.0, AbilityExecuteState::Active)
+        ));
+        assert!(!is_ability_states_synced(
+            &synced,
+            &NetAbilityStates::default()
+        ));
+
+        // 客户端只修改本地计时，同步的状态变化时重置
+        let mut app = new_app();
+        app.add_systems(Update, tick_net_state_timers);
+        let owner = app.world_mut().spawn(synced.clone()).id();
+        advance(&mut app, 0.0);
+        advance(&mut app, 1.0);
+        assert_eq!(
+            app.world()
+                .get::<NetStateTimers>(owner)
+                .unwrap()
+                .ability_cooldowns,
+            vec![2.0]
+        );
+        assert_eq!(app.world().get::<NetAbilityStates>(owner), Some(&synced));
+
+        app.world_mut()
+            .entity_mut(owner)
+            .insert(states(5.0, AbilityExecuteState::Active));
+        advance(&mut app, 1.0);
+        assert_eq!(
+            app.world()
+                .get::<NetStateTimers>(owner)
+                .unwrap()
+                .ability_cooldowns,
+            vec![4.0]
+        );
+    }
+}
@@ -1,6 +1,10 @@
 use std::ops::Not;
 
 use crate::camera::GameCameraPlugin;
+use crate::network::ability::AbilityNetClientPlugin;
+use crate::network::cue::GameplayCueNetClientPlugin;
+use crate::network::damage::DamageNetClientPlugin;
+use crate::network::table::TableHandshakeNetClientPlugin;
 use crate::input::setting::PlayerInputPlugin;
 use crate::scene::SceneClientPlugin;
 use crate::state::{GameState, GameStatePlugin};
@@ -36,6 +40,10 @@ impl Plugin for GameClientPlugin {
         .add_plugins(TransformFollowPlugin)
         .add_plugins(GameCameraPlugin)
         .add_plugins(PlayerInputPlugin)
+        .add_plugins(AbilityNetClientPlugin)
+        .add_plugins(GameplayCueNetClientPlugin)
+        .add_plugins(DamageNetClientPlugin)
+        .add_plugins(TableHandshakeNetClientPlugin)
         .add_plugins(PhysicsDebugPlugin::new(FixedUpdate))
         .add_systems(OnEnter(GameState::InitGame), client_startup)
         .add_systems(Last, client_shutdown)
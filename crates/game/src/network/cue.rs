@@ -0,0 +1,239 @@
+use ability::{
+    cue::{
+        GameplayCueAppExt, GameplayCueEvent, GameplayCueKind, GameplayCueParams, GAMEPLAY_CUE_ROOT,
+    },
+    stateset::StateLayerTagRegistry,
+};
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
+use lightyear::prelude::*;
+
+use crate::unit::player::PlayerId;
+
+use super::{ability::AbilityPredictedBy, protocol::DefaultChannel};
+
+/// 服务器上触发的表现，同步到所有客户端，客户端用LayerTag注册表还原。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameplayCueMessage {
+    pub target: Entity,
+    pub raw_cue: String,
+    pub kind: GameplayCueKind,
+    pub params: GameplayCueParams,
+}
+
+impl MapEntities for GameplayCueMessage {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+        if let Some(instigator) = &mut self.params.instigator {
+            *instigator = entity_mapper.map_entity(*instigator);
+        }
+    }
+}
+
+pub(crate) struct GameplayCueProtocolPlugin;
+
+impl Plugin for GameplayCueProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_message::<GameplayCueMessage>(ChannelDirection::ServerToClient)
+            .add_map_entities();
+    }
+}
+
+/// 客户端接收服务器的表现，并注册默认的表现处理，具体的表现在客户端的插件中注册。
+#[derive(Debug, Default)]
+pub struct GameplayCueNetClientPlugin;
+
+impl Plugin for GameplayCueNetClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_gameplay_cue_handler(GAMEPLAY_CUE_ROOT, log_gameplay_cue)
+            .add_systems(Update, receive_gameplay_cue_message.after(MainSet::Receive));
+    }
+}
+
+/// 服务器不注册表现处理，只转发给客户端。
+#[derive(Debug, Default)]
+pub struct GameplayCueNetServerPlugin;
+
+impl Plugin for GameplayCueNetServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(send_gameplay_cue_message);
+    }
+}
+
+fn log_gameplay_cue(_commands: &mut Commands, target: Entity, event: &GameplayCueEvent) {
+    debug!(
+        "gameplay cue {} {:?} on {:?} has no handler",
+        event.cue, event.kind, target
+    );
+}
+
+/// 表现的instigator(没有时为target)正在执行客户端请求的技能时，返回预测了这次表现的客户端。
+fn predicting_client(
+    owner: Entity,
+    owner_query: &Query<(&PlayerId, Option<&Children>)>,
+    predicted_query: &Query<&AbilityPredictedBy>,
+) -> Option<ClientId> {
+    let (player_id, children) = owner_query.get(owner).ok()?;
+    children
+        .into_iter()
+        .flatten()
+        .filter_map(|child| predicted_query.get(*child).ok())
+        .any(|predicted_by| predicted_by.0 == player_id.0)
+        .then_some(player_id.0)
+}
+
+/// 预测的客户端已经在本地播放了表现，不再发送给它。
+fn send_gameplay_cue_message(
+    trigger: Trigger<GameplayCueEvent>,
+    mut connection: ResMut<server::ConnectionManager>,
+    replicating_query: Query<(), With<Replicating>>,
+    owner_query: Query<(&PlayerId, Option<&Children>)>,
+    predicted_query: Query<&AbilityPredictedBy>,
+) {
+    let target = trigger.entity();
+    if !replicating_query.contains(target) {
+        return;
+    }
+
+    let event = trigger.event();
+    let owner = event.params.instigator.unwrap_or(target);
+    let network_target = match predicting_client(owner, &owner_query, &predicted_query) {
+        Some(client_id) => NetworkTarget::AllExceptSingle(client_id),
+        None => NetworkTarget::All,
+    };
+    let mut message = GameplayCueMessage {
+        target,
+        raw_cue: event.cue.raw_layertag(),
+        kind: event.kind,
+        params: event.params,
+    };
+    if let Err(e) =
+        connection.send_message_to_target::<DefaultChannel, _>(&mut message, network_target)
+    {
+        warn!("send gameplay cue {} failed: {:?}", message.raw_cue, e);
+    }
+}
+
+fn receive_gameplay_cue_message(
+    mut commands: Commands,
+    mut events: EventReader<client::MessageEvent<GameplayCueMessage>>,
+    registry: Res<StateLayerTagRegistry>,
+) {
+    for event in events.read() {
+        dispatch_gameplay_cue_message(&mut commands, &registry, event.message());
+    }
+}
+
+/// 用本地的LayerTag注册表还原表现，触发在目标上交给表现处理。
+fn dispatch_gameplay_cue_message(
+    commands: &mut Commands,
+    registry: &StateLayerTagRegistry,
+    message: &GameplayCueMessage,
+) {
+    let Some(cue) = registry.0.request_from_raw(&message.raw_cue) else {
+        warn!("gameplay cue {} is not registered", message.raw_cue);
+        return;
+    };
+    commands.trigger_targets(
+        GameplayCueEvent {
+            cue,
+            kind: message.kind,
+            params: message.params,
+        },
+        message.target,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+
+    use ability::{
+        cue::{GameplayCueKind, GameplayCueParams},
+        stateset::StateLayerTagRegistry,
+        AbilitySubsystemPlugin,
+    };
+    use bevy::{
+        log::{
+            tracing::{self, field::Field, Subscriber},
+            tracing_subscriber::{
+                self,
+                layer::{Context, SubscriberExt},
+                Layer,
+            },
+        },
+        prelude::*,
+        state::app::StatesPlugin,
+    };
+    use datatables::DataTablePlugin;
+    use lightyear::prelude::client;
+
+    use super::{dispatch_gameplay_cue_message, GameplayCueMessage, GameplayCueNetClientPlugin};
+
+    /// 记录所有日志的message，用来确认表现到达了默认的日志处理。
+    #[derive(Clone, Default)]
+    struct MessageLayer(Arc<Mutex<Vec<String>>>);
+
+    struct MessageVisitor<'a>(&'a mut Vec<String>);
+
+    impl tracing::field::Visit for MessageVisitor<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            if field.name() == "message" {
+                self.0.push(format!("{:?}", value));
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for MessageLayer {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+            event.record(&mut MessageVisitor(&mut self.0.lock().unwrap()));
+        }
+    }
+
+    #[test]
+    fn received_cue_reaches_log_handler() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            DataTablePlugin,
+            AbilitySubsystemPlugin,
+        ))
+        .add_event::<client::MessageEvent<GameplayCueMessage>>()
+        .add_plugins(GameplayCueNetClientPlugin);
+        app.world_mut()
+            .resource_mut::<StateLayerTagRegistry>()
+            .0
+            .register_raw("Cue.Hit.Fire");
+
+        let target = app.world_mut().spawn_empty().id();
+        let message = GameplayCueMessage {
+            target,
+            raw_cue: "Cue.Hit.Fire".into(),
+            kind: GameplayCueKind::OnExecute,
+            params: GameplayCueParams::default(),
+        };
+
+        let layer = MessageLayer::default();
+        let subscriber = tracing_subscriber::registry().with(layer.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            let world = app.world_mut();
+            world.resource_scope(|world, registry: Mut<StateLayerTagRegistry>| {
+                dispatch_gameplay_cue_message(&mut world.commands(), &registry, &message);
+            });
+            world.flush();
+        });
+
+        let expected = format!(
+            "gameplay cue Cue.Hit.Fire OnExecute on {:?} has no handler",
+            target
+        );
+        assert!(layer.0.lock().unwrap().contains(&expected));
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::{
+    ecs::entity::{EntityMapper, MapEntities},
+    prelude::*,
+};
+use datatables::damage::TbDamageTypeKey;
+use lightyear::prelude::*;
+
+use crate::damage::{calc::DamageBreakdown, DamageApplied};
+
+use super::protocol::DefaultChannel;
+
+/// 服务器结算的伤害，同步到所有客户端用于飘字和受击表现。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DamageAppliedMessage {
+    pub target: Entity,
+    pub source: Entity,
+    pub damage_type: Option<TbDamageTypeKey>,
+    pub breakdown: DamageBreakdown,
+}
+
+impl MapEntities for DamageAppliedMessage {
+    fn map_entities<M: EntityMapper>(&mut self, entity_mapper: &mut M) {
+        self.target = entity_mapper.map_entity(self.target);
+        self.source = entity_mapper.map_entity(self.source);
+    }
+}
+
+impl From<&DamageApplied> for DamageAppliedMessage {
+    fn from(event: &DamageApplied) -> Self {
+        Self {
+            target: event.target,
+            source: event.source,
+            damage_type: event.damage_type,
+            breakdown: event.breakdown,
+        }
+    }
+}
+
+impl From<&DamageAppliedMessage> for DamageApplied {
+    fn from(message: &DamageAppliedMessage) -> Self {
+        Self {
+            target: message.target,
+            source: message.source,
+            damage_type: message.damage_type,
+            breakdown: message.breakdown,
+        }
+    }
+}
+
+pub(crate) struct DamageProtocolPlugin;
+
+impl Plugin for DamageProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_message::<DamageAppliedMessage>(ChannelDirection::ServerToClient)
+            .add_map_entities();
+    }
+}
+
+/// 客户端只接收结算结果，作为`DamageApplied`事件发送，不触发受击技能。
+#[derive(Debug, Default)]
+pub struct DamageNetClientPlugin;
+
+impl Plugin for DamageNetClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageApplied>().add_systems(
+            Update,
+            receive_damage_applied_message.after(MainSet::Receive),
+        );
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct DamageNetServerPlugin;
+
+impl Plugin for DamageNetServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(send_damage_applied_message);
+    }
+}
+
+fn send_damage_applied_message(
+    trigger: Trigger<DamageApplied>,
+    mut connection: ResMut<server::ConnectionManager>,
+    replicating_query: Query<(), With<Replicating>>,
+) {
+    if !replicating_query.contains(trigger.entity()) {
+        return;
+    }
+
+    let mut message = DamageAppliedMessage::from(trigger.event());
+    if let Err(e) =
+        connection.send_message_to_target::<DefaultChannel, _>(&mut message, NetworkTarget::All)
+    {
+        warn!(
+            "send damage applied on {:?} failed: {:?}",
+            message.target, e
+        );
+    }
+}
+
+fn receive_damage_applied_message(
+    mut events: EventReader<client::MessageEvent<DamageAppliedMessage>>,
+    mut damage_applied_events: EventWriter<DamageApplied>,
+) {
+    for event in events.read() {
+        damage_applied_events.send(event.message().into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::entity::{EntityHashMap, MapEntities},
+        prelude::*,
+    };
+
+    use crate::damage::{calc::DamageBreakdown, DamageApplied};
+
+    use super::DamageAppliedMessage;
+
+    #[test]
+    fn map_damage_applied_entities() {
+        let server_target = Entity::from_raw(1);
+        let server_source = Entity::from_raw(2);
+        let client_target = Entity::from_raw(11);
+        let client_source = Entity::from_raw(12);
+        let damage_applied = DamageApplied {
+            target: server_target,
+            source: server_source,
+            damage_type: Some(2),
+            breakdown: DamageBreakdown {
+                raw: 10.0,
+                hp_damage: 6.0,
+                ..default()
+            },
+        };
+
+        let mut message = DamageAppliedMessage::from(&damage_applied);
+        let mut entity_map = EntityHashMap::from_iter([
+            (server_target, client_target),
+            (server_source, client_source),
+        ]);
+        message.map_entities(&mut entity_map);
+
+        let received = DamageApplied::from(&message);
+        assert_eq!(received.target, client_target);
+        assert_eq!(received.source, client_source);
+        assert_eq!(received.damage_type, Some(2));
+        assert_eq!(received.breakdown, damage_applied.breakdown);
+    }
+}
@@ -1,6 +1,10 @@
+pub mod ability;
 pub mod app_build;
 pub mod client;
+pub mod cue;
+pub mod damage;
 pub mod protocol;
 pub mod server;
 pub mod shared;
+pub mod table;
 pub mod bevy_bundle;
@@ -16,6 +16,11 @@ use lightyear::{
 
 use crate::{input::setting::PlayerAction, unit::UnitProtocolPlugin};
 
+use super::{
+    ability::AbilityProtocolPlugin, cue::GameplayCueProtocolPlugin, damage::DamageProtocolPlugin,
+    table::TableHandshakeProtocolPlugin,
+};
+
 #[derive(Channel)]
 pub struct DefaultChannel;
 
@@ -35,7 +40,11 @@ pub(crate) struct ProtocolPlugin;
 impl Plugin for ProtocolPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(TableProtocolPlugin);
+        app.add_plugins(TableHandshakeProtocolPlugin);
         app.add_plugins(UnitProtocolPlugin);
+        app.add_plugins(AbilityProtocolPlugin);
+        app.add_plugins(GameplayCueProtocolPlugin);
+        app.add_plugins(DamageProtocolPlugin);
 
         // messages
         app.register_message::<TestMessage>(ChannelDirection::Bidirectional);
@@ -14,8 +14,16 @@ use rand::Rng;
 use server::ServerCommands;
 
 use crate::{
+    damage::DamagePlugin,
     input::setting::{apply_action_state_to_player_movement, PlayerAction},
-    network::shared::REPLICATION_GROUP,
+    network::{
+        ability::AbilityNetServerPlugin, cue::GameplayCueNetServerPlugin,
+        damage::DamageNetServerPlugin, shared::REPLICATION_GROUP,
+        table::TableHandshakeNetServerPlugin,
+    },
+    projectile::{
+        implement::direction_projectile::ProjectileLineSpeedPlugin, plugin::ProjectilePlugin,
+    },
     scene::SceneServerPlugin,
     state::GameState,
     unit::{
@@ -30,6 +38,13 @@ impl Plugin for GameServerPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         // add our server-specific logic. Here we will just start listening for incoming connections
         app.add_plugins(SceneServerPlugin)
+            .add_plugins(AbilityNetServerPlugin)
+            .add_plugins(DamagePlugin)
+            .add_plugins(DamageNetServerPlugin)
+            .add_plugins(ProjectilePlugin)
+            .add_plugins(ProjectileLineSpeedPlugin)
+            .add_plugins(GameplayCueNetServerPlugin)
+            .add_plugins(TableHandshakeNetServerPlugin)
             .add_systems(OnEnter(GameState::InitGame), start_server)
             .add_systems(
                 PreUpdate,
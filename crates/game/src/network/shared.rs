@@ -1,3 +1,4 @@
+use ability::AbilitySubsystemPlugin;
 use avian3d::prelude::{LinearVelocity, Position, RigidBody, Rotation};
 use bevy::{
     app::{Plugin, PostUpdate},
@@ -38,6 +39,8 @@ pub struct GameSharedPlugin;
 impl Plugin for GameSharedPlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.add_plugins(ProtocolPlugin)
+            // ability system
+            .add_plugins(AbilitySubsystemPlugin)
             // physics
             .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
             // Screen Diagnostics
@@ -0,0 +1,505 @@
+use bevy::{prelude::*, utils::HashMap};
+use client::ClientCommands;
+use datatables::{override_table, table_source, TableHashes, TableLoadingState, Tables};
+use lightyear::prelude::*;
+
+/// 表格同步使用单独的可靠通道，表格内容较大时不阻塞游戏的消息。
+#[derive(Channel)]
+pub struct TableChannel;
+
+/// 单条表格内容消息的最大字节数，lightyear的可靠通道分片有上限，超过时分块发送。
+pub const TABLE_SYNC_CHUNK_SIZE: usize = 32 * 1024;
+
+/// 客户端连接并且服务器的表加载完成后，服务器发送所有表的内容哈希。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableHashMessage {
+    pub hashes: TableHashes,
+}
+
+/// 客户端请求服务器的表格内容，用于覆盖本地不一致的表。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSyncRequest {
+    pub tables: Vec<String>,
+}
+
+/// 服务器的表格解析后重新编码的二进制内容，和本地加载的文件格式无关。
+/// 按`TABLE_SYNC_CHUNK_SIZE`分块，在有序通道中按chunk的顺序到达。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableSyncMessage {
+    pub table: String,
+    pub chunk: u32,
+    pub chunk_count: u32,
+    pub source: Vec<u8>,
+}
+
+/// 把一张表的内容拆分为多条消息，空的内容也会发送一条消息。
+pub fn split_table_source(table: &str, source: &[u8]) -> Vec<TableSyncMessage> {
+    let chunk_count = source.len().div_ceil(TABLE_SYNC_CHUNK_SIZE).max(1);
+    (0..chunk_count)
+        .map(|chunk| {
+            let start = chunk * TABLE_SYNC_CHUNK_SIZE;
+            let end = (start + TABLE_SYNC_CHUNK_SIZE).min(source.len());
+            TableSyncMessage {
+                table: table.to_string(),
+                chunk: chunk as u32,
+                chunk_count: chunk_count as u32,
+                source: source[start..end].to_vec(),
+            }
+        })
+        .collect()
+}
+
+/// 客户端正在接收的表格内容。
+#[derive(Debug, Default, Resource)]
+struct PendingTableChunks(HashMap<String, Vec<u8>>);
+
+impl PendingTableChunks {
+    /// 收到最后一块时返回完整的内容，块的顺序不对时丢弃这张表。
+    fn push(&mut self, message: TableSyncMessage) -> Option<Vec<u8>> {
+        let TableSyncMessage {
+            table,
+            chunk,
+            chunk_count,
+            source,
+        } = message;
+        if chunk == 0 {
+            self.0.insert(table.clone(), Vec::new());
+        }
+        let Some(pending) = self.0.get_mut(&table) else {
+            warn!("table {} chunk {} arrived without chunk 0", table, chunk);
+            return None;
+        };
+        pending.extend(source);
+        if chunk + 1 < chunk_count {
+            return None;
+        }
+        self.0.remove(&table)
+    }
+}
+
+/// 客户端的表和服务器不一致时的处理。
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub enum TableMismatchPolicy {
+    /// 断开连接。
+    Reject,
+    /// 只输出警告，继续使用本地的表。
+    #[default]
+    Warn,
+    /// 请求服务器的表格内容覆盖本地的表。
+    Override,
+}
+
+/// 一张表的哈希不一致，None表示这一端没有这张表。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableHashMismatch {
+    pub table: String,
+    pub local: Option<u64>,
+    pub remote: Option<u64>,
+}
+
+impl std::fmt::Display for TableHashMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "table {} hash mismatch, local: {:?}, remote: {:?}",
+            self.table, self.local, self.remote
+        )
+    }
+}
+
+/// 按表名比较两端的哈希，结果按表名排序。
+pub fn compare_table_hashes(local: &TableHashes, remote: &TableHashes) -> Vec<TableHashMismatch> {
+    let mut tables: Vec<&String> = local.0.keys().chain(remote.0.keys()).collect();
+    tables.sort();
+    tables.dedup();
+
+    tables
+        .into_iter()
+        .filter_map(|table| {
+            let (local, remote) = (local.0.get(table).copied(), remote.0.get(table).copied());
+            (local != remote).then(|| TableHashMismatch {
+                table: table.clone(),
+                local,
+                remote,
+            })
+        })
+        .collect()
+}
+
+/// 表格加载完成并且所有表的哈希都已经计算，服务器发送和客户端比较哈希都要等到这时。
+fn table_hashes_ready(
+    state: Res<State<TableLoadingState>>,
+    tables: Res<Tables>,
+    hashes: Res<TableHashes>,
+) -> bool {
+    *state.get() == TableLoadingState::Loaded && hashes.0.len() == tables.table_handle_map.len()
+}
+
+/// 服务器的表还没有加载完成时连接的客户端，加载完成后再发送哈希。
+#[derive(Debug, Default, Resource)]
+struct PendingTableHashClients(Vec<ClientId>);
+
+/// 本地的表还没有加载完成时收到的服务器哈希，加载完成后再比较。
+#[derive(Debug, Default, Resource)]
+struct PendingTableHashes(Option<TableHashes>);
+
+pub(crate) struct TableHandshakeProtocolPlugin;
+
+impl Plugin for TableHandshakeProtocolPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_message::<TableHashMessage>(ChannelDirection::ServerToClient);
+        app.register_message::<TableSyncRequest>(ChannelDirection::ClientToServer);
+        app.register_message::<TableSyncMessage>(ChannelDirection::ServerToClient);
+        app.add_channel::<TableChannel>(ChannelSettings {
+            mode: ChannelMode::OrderedReliable(ReliableSettings::default()),
+            ..default()
+        });
+    }
+}
+
+/// 客户端的表加载完成后比较服务器的哈希，按照TableMismatchPolicy处理不一致的表。
+#[derive(Debug, Default)]
+pub struct TableHandshakeNetClientPlugin;
+
+impl Plugin for TableHandshakeNetClientPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TableMismatchPolicy>()
+            .init_resource::<PendingTableHashes>()
+            .init_resource::<PendingTableChunks>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        receive_table_hash_message,
+                        check_table_hashes
+                            .pipe(handle_table_hash_mismatches)
+                            .run_if(table_hashes_ready),
+                    )
+                        .chain(),
+                    receive_table_sync_message,
+                )
+                    .after(MainSet::Receive),
+            );
+    }
+}
+
+/// 服务器在客户端连接并且本地的表加载完成后发送哈希，并回复客户端的表格内容请求。
+#[derive(Debug, Default)]
+pub struct TableHandshakeNetServerPlugin;
+
+impl Plugin for TableHandshakeNetServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingTableHashClients>().add_systems(
+            Update,
+            (
+                (
+                    queue_table_hash_clients,
+                    send_table_hash_message.run_if(table_hashes_ready),
+                )
+                    .chain(),
+                receive_table_sync_request,
+            )
+                .after(MainSet::EmitEvents),
+        );
+    }
+}
+
+fn queue_table_hash_clients(
+    mut connections: EventReader<server::ConnectEvent>,
+    mut disconnections: EventReader<server::DisconnectEvent>,
+    mut pending: ResMut<PendingTableHashClients>,
+) {
+    // 本地客户端和服务器使用同一份表
+    pending.0.extend(
+        connections
+            .read()
+            .map(|event| event.client_id)
+            .filter(|client_id| !client_id.is_local()),
+    );
+    for event in disconnections.read() {
+        pending.0.retain(|client_id| *client_id != event.client_id);
+    }
+}
+
+fn send_table_hash_message(
+    mut pending: ResMut<PendingTableHashClients>,
+    mut connection: ResMut<server::ConnectionManager>,
+    hashes: Res<TableHashes>,
+) {
+    for client_id in pending.0.drain(..) {
+        let mut message = TableHashMessage {
+            hashes: hashes.clone(),
+        };
+        if let Err(e) = connection.send_message_to_target::<TableChannel, _>(
+            &mut message,
+            NetworkTarget::Single(client_id),
+        ) {
+            warn!("send table hashes to {:?} failed: {:?}", client_id, e);
+        }
+    }
+}
+
+fn receive_table_sync_request(
+    mut commands: Commands,
+    mut events: EventReader<server::MessageEvent<TableSyncRequest>>,
+) {
+    for event in events.read() {
+        let client_id = *event.context();
+        let tables = event.message().tables.clone();
+        commands.queue(move |world: &mut World| {
+            for table in tables {
+                let Some(source) = table_source(world, &table) else {
+                    warn!("client {:?} request unknown table {}", client_id, table);
+                    continue;
+                };
+                let mut connection = world.resource_mut::<server::ConnectionManager>();
+                for mut message in split_table_source(&table, &source) {
+                    if let Err(e) = connection.send_message_to_target::<TableChannel, _>(
+                        &mut message,
+                        NetworkTarget::Single(client_id),
+                    ) {
+                        warn!(
+                            "send table {} chunk {} to {:?} failed: {:?}",
+                            table, message.chunk, client_id, e
+                        );
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn receive_table_hash_message(
+    mut events: EventReader<client::MessageEvent<TableHashMessage>>,
+    mut pending: ResMut<PendingTableHashes>,
+) {
+    for event in events.read() {
+        pending.0 = Some(event.message().hashes.clone());
+    }
+}
+
+/// 没有收到服务器的哈希时为None。
+fn check_table_hashes(
+    mut pending: ResMut<PendingTableHashes>,
+    hashes: Res<TableHashes>,
+) -> Option<Vec<TableHashMismatch>> {
+    let remote = pending.0.take()?;
+    Some(compare_table_hashes(&hashes, &remote))
+}
+
+fn handle_table_hash_mismatches(
+    In(mismatches): In<Option<Vec<TableHashMismatch>>>,
+    mut commands: Commands,
+    mut connection: ResMut<client::ConnectionManager>,
+    policy: Res<TableMismatchPolicy>,
+) {
+    let Some(mismatches) = mismatches else {
+        return;
+    };
+    if mismatches.is_empty() {
+        info!("table hashes match server");
+        return;
+    }
+
+    match *policy {
+        TableMismatchPolicy::Reject => {
+            for mismatch in mismatches.iter() {
+                error!("{}", mismatch);
+            }
+            commands.disconnect_client();
+        }
+        TableMismatchPolicy::Warn => {
+            for mismatch in mismatches.iter() {
+                warn!("{}", mismatch);
+            }
+        }
+        TableMismatchPolicy::Override => {
+            // 服务器没有的表无法覆盖，保留本地的表
+            let mut message = TableSyncRequest {
+                tables: mismatches
+                    .into_iter()
+                    .filter(|mismatch| mismatch.remote.is_some())
+                    .map(|mismatch| mismatch.table)
+                    .collect(),
+            };
+            info!("request server tables: {:?}", message.tables);
+            if let Err(e) = connection.send_message::<TableChannel, _>(&mut message) {
+                warn!("send table sync request failed: {:?}", e);
+            }
+        }
+    }
+}
+
+fn receive_table_sync_message(
+    mut commands: Commands,
+    mut events: EventReader<client::MessageEvent<TableSyncMessage>>,
+    mut pending: ResMut<PendingTableChunks>,
+) {
+    for event in events.read() {
+        let table = event.message().table.clone();
+        let Some(source) = pending.push(event.message().clone()) else {
+            continue;
+        };
+        commands.queue(
+            move |world: &mut World| match override_table(world, &table, source) {
+                Ok(()) => info!("table {} is overridden by server", table),
+                Err(e) => error!("override table {} failed: {}", table, e),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{prelude::*, state::app::StatesPlugin};
+    use datatables::{item::TbItem, override_table, TableHashes, TableLoadingState, Tables};
+
+    use super::{
+        check_table_hashes, compare_table_hashes, split_table_source, table_hashes_ready,
+        PendingTableChunks, PendingTableHashes, TableHashMismatch, TABLE_SYNC_CHUNK_SIZE,
+    };
+
+    fn read_item_table() -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../assets/datatables/item_tbitem.json");
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn override_mismatched_table() {
+        let server_source = read_item_table();
+        let client_source = String::from_utf8(server_source.clone())
+            .unwrap()
+            .replacen("\"price\": 100,", "\"price\": 101,", 1)
+            .into_bytes();
+        let server = TbItem::from_bytes(server_source).unwrap();
+        let client = TbItem::from_bytes(client_source).unwrap();
+
+        let hashes = |item: u64, global: Option<u64>| {
+            let mut hashes = TableHashes::default();
+            hashes.0.insert("TbItem".into(), item);
+            if let Some(global) = global {
+                hashes.0.insert("TbGlobal".into(), global);
+            }
+            hashes
+        };
+        let server_hashes = hashes(server.content_hash, Some(1));
+        assert!(compare_table_hashes(&server_hashes, &server_hashes).is_empty());
+        assert_eq!(
+            compare_table_hashes(&hashes(client.content_hash, None), &server_hashes),
+            vec![
+                TableHashMismatch {
+                    table: "TbGlobal".into(),
+                    local: None,
+                    remote: Some(1),
+                },
+                TableHashMismatch {
+                    table: "TbItem".into(),
+                    local: Some(client.content_hash),
+                    remote: Some(server.content_hash),
+                },
+            ]
+        );
+
+        let mut world = World::new();
+        let mut assets = Assets::<TbItem>::default();
+        let tables = Tables {
+            tb_item: assets.add(client),
+            ..default()
+        };
+        let handle = tables.tb_item.clone();
+        world.insert_resource(assets);
+        world.insert_resource(tables);
+
+        override_table(&mut world, "TbItem", server.source.clone()).unwrap();
+        let item = world.resource::<Assets<TbItem>>().get(&handle).unwrap();
+        assert_eq!(item.content_hash, server.content_hash);
+        assert_eq!(item.data_map[&10000].price, 100);
+
+        assert!(override_table(&mut world, "TbMissing", vec![]).is_err());
+    }
+
+    #[test]
+    fn split_and_join_table_chunks() {
+        let source = (0..TABLE_SYNC_CHUNK_SIZE * 2 + 1)
+            .map(|index| index as u8)
+            .collect::<Vec<_>>();
+        let messages = split_table_source("TbItem", &source);
+        assert_eq!(messages.len(), 3);
+        assert!(messages
+            .iter()
+            .all(|message| message.source.len() <= TABLE_SYNC_CHUNK_SIZE));
+
+        let mut pending = PendingTableChunks::default();
+        let mut joined = messages
+            .into_iter()
+            .filter_map(|message| pending.push(message))
+            .collect::<Vec<_>>();
+        assert_eq!(joined.pop(), Some(source));
+        assert!(joined.is_empty());
+        assert!(pending.0.is_empty());
+
+        // 空的表也有一条消息
+        let empty = split_table_source("TbEmpty", &[]);
+        assert_eq!(empty.len(), 1);
+        assert_eq!(pending.push(empty[0].clone()), Some(vec![]));
+
+        // 缺少第一块时丢弃
+        let mut messages = split_table_source("TbItem", &source);
+        messages.remove(0);
+        assert!(messages
+            .into_iter()
+            .all(|message| pending.push(message).is_none()));
+    }
+
+    #[derive(Debug, Default, Resource)]
+    struct Checked(Vec<Vec<TableHashMismatch>>);
+
+    #[test]
+    fn check_hashes_after_tables_loaded() {
+        let mut tables = Tables::default();
+        tables.table_handle_map.insert(
+            std::any::TypeId::of::<TbItem>(),
+            Handle::<TbItem>::default().untyped(),
+        );
+        let mut server_hashes = TableHashes::default();
+        server_hashes.0.insert("TbItem".into(), 1);
+
+        let mut app = App::new();
+        app.add_plugins(StatesPlugin)
+            .insert_state(TableLoadingState::Loading)
+            .insert_resource(tables)
+            .init_resource::<TableHashes>()
+            .init_resource::<Checked>()
+            // 服务器的哈希在本地的表加载完成之前到达
+            .insert_resource(PendingTableHashes(Some(server_hashes.clone())))
+            .add_systems(
+                Update,
+                check_table_hashes
+                    .pipe(
+                        |In(mismatches): In<Option<Vec<TableHashMismatch>>>,
+                         mut checked: ResMut<Checked>| {
+                            checked.0.extend(mismatches);
+                        },
+                    )
+                    .run_if(table_hashes_ready),
+            );
+
+        app.update();
+        assert!(app.world().resource::<Checked>().0.is_empty());
+
+        // 状态已经是Loaded，但是表的哈希还没有计算
+        app.world_mut()
+            .resource_mut::<NextState<TableLoadingState>>()
+            .set(TableLoadingState::Loaded);
+        app.update();
+        assert!(app.world().resource::<Checked>().0.is_empty());
+        assert!(app.world().resource::<PendingTableHashes>().0.is_some());
+
+        app.insert_resource(server_hashes);
+        app.update();
+        assert_eq!(app.world().resource::<Checked>().0, vec![vec![]]);
+        assert!(app.world().resource::<PendingTableHashes>().0.is_none());
+    }
+}
@@ -1,7 +1,8 @@
+use ability::ability::trigger::{AbilityTriggerEvent, AbilityTriggerPayload};
 use avian3d::prelude::CollisionStarted;
 use bevy::prelude::*;
 
-use super::{lifetime::ProjectileState, Projectile};
+use super::{lifetime::ProjectileState, Projectile, ProjectileOwner};
 
 // 击中次数
 #[derive(Debug, Component, Default)]
@@ -30,6 +31,27 @@ pub fn trigger_projectile_hit(
     hit_count.time_after_hit.push(0.0);
 }
 
+/// 转发给发射投射物的单位，命中点为命中时投射物的位置。
+pub fn trigger_projectile_hit_ability_trigger(
+    trigger: Trigger<ProjectileHitEvent>,
+    mut commands: Commands,
+    query: Query<(&ProjectileOwner, Option<&GlobalTransform>), With<Projectile>>,
+) {
+    let event = trigger.event();
+    let Ok((owner, transform)) = query.get(event.projectile) else {
+        return;
+    };
+
+    commands.trigger_targets(
+        AbilityTriggerEvent(AbilityTriggerPayload::ProjectileHit {
+            projectile_class: owner.projectile_class.clone(),
+            target: event.target,
+            point: transform.map(GlobalTransform::translation).unwrap_or_default(),
+        }),
+        owner.owner,
+    );
+}
+
 pub fn update_hit_time(
     mut query: Query<&mut ProjectileHitCount, With<Projectile>>,
     time: Res<Time<Fixed>>,
@@ -41,34 +63,84 @@ pub fn update_hit_time(
     }
 }
 
+/// 投射物在发射者的位置生成，不会命中发射者。
 pub fn read_collision_start_event(
     mut commands: Commands,
     mut events: EventReader<CollisionStarted>,
-    query: Query<&ProjectileState, With<Projectile>>,
+    query: Query<(&ProjectileState, Option<&ProjectileOwner>), With<Projectile>>,
 ) {
     for event in events.read() {
-        if let Ok(state) = query.get(event.0) {
-            if *state == ProjectileState::Running {
-                commands.trigger_targets(
-                    ProjectileHitEvent {
-                        projectile: event.0,
-                        target: event.1,
-                    },
-                    event.0,
-                );
+        for (projectile, target) in [(event.0, event.1), (event.1, event.0)] {
+            let Ok((state, owner)) = query.get(projectile) else {
+                continue;
+            };
+            if *state != ProjectileState::Running
+                || owner.is_some_and(|owner| owner.owner == target)
+            {
+                continue;
             }
+            commands.trigger_targets(ProjectileHitEvent { projectile, target }, projectile);
         }
+    }
+}
 
-        if let Ok(state) = query.get(event.1) {
-            if *state == ProjectileState::Running {
-                commands.trigger_targets(
-                    ProjectileHitEvent {
-                        projectile: event.1,
-                        target: event.0,
-                    },
-                    event.1,
-                );
-            }
+#[cfg(test)]
+mod tests {
+    use ability::ability::trigger::{AbilityTriggerEvent, AbilityTriggerPayload};
+    use bevy::prelude::*;
+
+    use crate::projectile::{Projectile, ProjectileOwner};
+
+    use super::{trigger_projectile_hit_ability_trigger, ProjectileHitEvent};
+
+    #[derive(Resource, Default)]
+    struct ReceivedTriggers(Vec<(Entity, AbilityTriggerPayload)>);
+
+    #[test]
+    fn forward_hit_to_owner() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ReceivedTriggers>()
+            .add_observer(trigger_projectile_hit_ability_trigger)
+            .add_observer(
+                |trigger: Trigger<AbilityTriggerEvent>, mut received: ResMut<ReceivedTriggers>| {
+                    received
+                        .0
+                        .push((trigger.entity(), trigger.event().0.clone()));
+                },
+            );
+
+        let owner = app.world_mut().spawn_empty().id();
+        let target = app.world_mut().spawn_empty().id();
+        let projectile = app
+            .world_mut()
+            .spawn((
+                Projectile,
+                ProjectileOwner {
+                    owner,
+                    projectile_class: "arrow".into(),
+                },
+                GlobalTransform::from_translation(Vec3::X),
+            ))
+            .id();
+        let unowned = app.world_mut().spawn(Projectile).id();
+
+        for projectile in [projectile, unowned] {
+            app.world_mut()
+                .trigger_targets(ProjectileHitEvent { projectile, target }, projectile);
         }
+        app.update();
+
+        assert_eq!(
+            app.world().resource::<ReceivedTriggers>().0,
+            vec![(
+                owner,
+                AbilityTriggerPayload::ProjectileHit {
+                    projectile_class: "arrow".into(),
+                    target,
+                    point: Vec3::X,
+                }
+            )]
+        );
     }
 }
@@ -125,7 +125,12 @@ pub fn destroy_projectile(
         for opportunity in opportunity_or.opportunity_or.iter() {
             match opportunity {
                 ProjectileDestroyOpportunity::Hit(count, delay_time) => {
-                    if hit_count.count >= *count && hit_count.time_after_hit[*count] >= *delay_time
+                    // time_after_hit按命中顺序记录，第count次命中在count - 1
+                    if hit_count.count >= *count
+                        && hit_count
+                            .time_after_hit
+                            .get(count.saturating_sub(1))
+                            .is_some_and(|time| *time >= *delay_time)
                     {
                         to_destroy = true;
                     }
@@ -4,8 +4,18 @@ pub mod plugin;
 pub mod lifetime;
 pub mod hit;
 pub mod effect;
+pub mod spawn;
 
-use bevy::prelude::Component;
+use std::borrow::Cow;
+
+use bevy::prelude::{Component, Entity};
 
 #[derive(Debug, Default, Component)]
 pub struct Projectile;
+
+/// 发射投射物的单位和投射物类型，由生成投射物的地方插入，命中时转发给单位的技能触发。
+#[derive(Debug, Component, Clone)]
+pub struct ProjectileOwner {
+    pub owner: Entity,
+    pub projectile_class: Cow<'static, str>,
+}
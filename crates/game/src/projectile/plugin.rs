@@ -1,11 +1,18 @@
-use bevy::app::{FixedPostUpdate, FixedPreUpdate, Plugin};
+use bevy::{
+    app::{FixedPostUpdate, FixedPreUpdate, Plugin},
+    prelude::IntoSystemConfigs,
+};
 
 use super::{
-    hit::{trigger_projectile_hit, update_hit_time, ProjectileHitEvent},
+    hit::{
+        read_collision_start_event, trigger_projectile_hit, trigger_projectile_hit_ability_trigger,
+        update_hit_time, ProjectileHitEvent,
+    },
     lifetime::{
         destroy_projectile, trigger_projectile_end, trigger_projectile_start, update_lifetime,
         ProjectileEndEvent, ProjectileStartEvent,
     },
+    spawn::spawn_effect_projectile,
 };
 
 pub struct ProjectilePlugin;
@@ -18,7 +25,12 @@ impl Plugin for ProjectilePlugin {
             .add_observer(trigger_projectile_start)
             .add_observer(trigger_projectile_end)
             .add_observer(trigger_projectile_hit)
+            .add_observer(trigger_projectile_hit_ability_trigger)
+            .add_observer(spawn_effect_projectile)
             .add_systems(FixedPreUpdate, (update_lifetime, update_hit_time))
-            .add_systems(FixedPostUpdate, destroy_projectile);
+            .add_systems(
+                FixedPostUpdate,
+                (read_collision_start_event, destroy_projectile).chain(),
+            );
     }
 }
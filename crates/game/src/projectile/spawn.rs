@@ -0,0 +1,198 @@
+use ability::graph::node::implement::projectile::EffectSpawnProjectileEvent;
+use avian3d::prelude::{Collider, CollisionEventsEnabled, LinearVelocity, RigidBody, Sensor};
+use bevy::prelude::*;
+
+use super::{
+    implement::{
+        direction_projectile::{ProjectileLineMovement, ProjectileLineSpeedBundle},
+        ProjectileBaseBundle,
+    },
+    lifetime::{
+        ProjectileDestroyOpportunity, ProjectileDestroyOpportunityOr, ProjectileStartEvent,
+    },
+    movement::SpeedVariant,
+    ProjectileOwner,
+};
+
+/// 投射物类型还没有表格配置，没有命中时最多存在的时间。
+pub const EFFECT_PROJECTILE_LIFETIME: f32 = 10.0;
+pub const EFFECT_PROJECTILE_RADIUS: f32 = 0.2;
+
+/// effect graph中的生成投射物节点，从instigator的位置发射直线投射物，命中一次后销毁。
+pub fn spawn_effect_projectile(
+    trigger: Trigger<EffectSpawnProjectileEvent>,
+    mut commands: Commands,
+    transform_query: Query<&GlobalTransform>,
+) {
+    let event = trigger.event();
+    let Ok(instigator_transform) = transform_query.get(event.instigator) else {
+        warn!(
+            "projectile {} instigator {:?} has no transform",
+            event.projectile_class, event.instigator
+        );
+        return;
+    };
+    let direction = match event.direction {
+        Some(direction) => Dir3::new(direction),
+        None => Ok(instigator_transform.forward()),
+    };
+    let Ok(direction) = direction else {
+        warn!(
+            "projectile {} has invalid direction {:?}",
+            event.projectile_class, event.direction
+        );
+        return;
+    };
+
+    let projectile = commands
+        .spawn((
+            ProjectileLineSpeedBundle {
+                projectile_base: ProjectileBaseBundle {
+                    destroy_opportunity: ProjectileDestroyOpportunityOr {
+                        opportunity_or: vec![
+                            ProjectileDestroyOpportunity::Hit(1, 0.0),
+                            ProjectileDestroyOpportunity::Time(EFFECT_PROJECTILE_LIFETIME),
+                        ],
+                    },
+                    ..default()
+                },
+                movement: ProjectileLineMovement {
+                    speed: SpeedVariant::Constant(event.speed),
+                    direction,
+                },
+            },
+            ProjectileOwner {
+                owner: event.instigator,
+                projectile_class: event.projectile_class.clone(),
+            },
+            Transform::from_translation(instigator_transform.translation()),
+            RigidBody::Kinematic,
+            LinearVelocity::default(),
+            Collider::sphere(EFFECT_PROJECTILE_RADIUS),
+            Sensor,
+            CollisionEventsEnabled,
+        ))
+        .id();
+    commands.trigger_targets(ProjectileStartEvent { projectile }, projectile);
+}
+
+#[cfg(test)]
+mod tests {
+    use ability::{
+        ability::trigger::{AbilityTriggerEvent, AbilityTriggerPayload},
+        graph::node::implement::projectile::EffectSpawnProjectileEvent,
+    };
+    use avian3d::prelude::{CollisionStarted, LinearVelocity};
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::projectile::{
+        hit::{read_collision_start_event, ProjectileHitCount},
+        implement::direction_projectile::ProjectileLineSpeedPlugin,
+        lifetime::ProjectileState,
+        plugin::ProjectilePlugin,
+        Projectile, ProjectileOwner,
+    };
+
+    #[derive(Resource, Default)]
+    struct ReceivedTriggers(Vec<(Entity, AbilityTriggerPayload)>);
+
+    #[test]
+    fn spawn_projectile_from_instigator() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ProjectilePlugin, ProjectileLineSpeedPlugin));
+
+        let instigator = app
+            .world_mut()
+            .spawn(GlobalTransform::from_translation(Vec3::X))
+            .id();
+        let spawn_event = |direction| EffectSpawnProjectileEvent {
+            instigator,
+            target: None,
+            projectile_class: "fire_ball".into(),
+            direction,
+            speed: 5.0,
+        };
+        // 方向为0时不生成
+        app.world_mut().trigger(spawn_event(Some(Vec3::ZERO)));
+        app.world_mut().trigger(spawn_event(Some(Vec3::Z)));
+        app.world_mut().flush();
+
+        let mut query = app.world_mut().query_filtered::<(
+            &ProjectileOwner,
+            &ProjectileState,
+            &Transform,
+            &LinearVelocity,
+        ), With<Projectile>>();
+        let projectiles = query.iter(app.world()).collect::<Vec<_>>();
+        assert_eq!(projectiles.len(), 1);
+        let (owner, state, transform, linear_velocity) = projectiles[0];
+        assert_eq!(owner.owner, instigator);
+        assert_eq!(owner.projectile_class, "fire_ball");
+        assert_eq!(*state, ProjectileState::Running);
+        assert_eq!(transform.translation, Vec3::X);
+        assert_eq!(linear_velocity.0, Vec3::Z * 5.0);
+    }
+
+    /// 节点生成的投射物碰撞后转发给发射者的技能触发，碰到发射者自己时忽略。
+    #[test]
+    fn spawned_projectile_hit_reaches_owner() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, ProjectilePlugin, ProjectileLineSpeedPlugin))
+            .add_event::<CollisionStarted>()
+            .init_resource::<ReceivedTriggers>()
+            .add_observer(
+                |trigger: Trigger<AbilityTriggerEvent>, mut received: ResMut<ReceivedTriggers>| {
+                    received
+                        .0
+                        .push((trigger.entity(), trigger.event().0.clone()));
+                },
+            );
+
+        let instigator = app
+            .world_mut()
+            .spawn(GlobalTransform::from_translation(Vec3::X))
+            .id();
+        let target = app.world_mut().spawn_empty().id();
+        app.world_mut().trigger(EffectSpawnProjectileEvent {
+            instigator,
+            target: None,
+            projectile_class: "fire_ball".into(),
+            direction: Some(Vec3::Z),
+            speed: 5.0,
+        });
+        app.world_mut().flush();
+
+        let projectile = app
+            .world_mut()
+            .query_filtered::<Entity, With<Projectile>>()
+            .iter(app.world())
+            .next()
+            .unwrap();
+        app.world_mut().send_event_batch([
+            CollisionStarted(instigator, projectile),
+            CollisionStarted(target, projectile),
+        ]);
+        app.world_mut()
+            .run_system_once(read_collision_start_event)
+            .unwrap();
+
+        assert_eq!(
+            app.world()
+                .get::<ProjectileHitCount>(projectile)
+                .unwrap()
+                .count,
+            1
+        );
+        assert_eq!(
+            app.world().resource::<ReceivedTriggers>().0,
+            vec![(
+                instigator,
+                AbilityTriggerPayload::ProjectileHit {
+                    projectile_class: "fire_ball".into(),
+                    target,
+                    point: Vec3::ZERO,
+                }
+            )]
+        );
+    }
+}
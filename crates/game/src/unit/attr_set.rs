@@ -12,6 +12,11 @@ pub enum CharacterAttributeType {
     MaxHp,
     MoveSpeed,
     MaxMoveSpped,
+    Armor,
+    Resistance,
+    CritChance,
+    CritMultiplier,
+    Shield,
 }
 
 // attribute set derive, to generate AttributeSetEnum with #[attribute] macro];
@@ -21,6 +26,11 @@ pub struct CharacterAttributeSet {
     max_hp: Box<ValueAttribute>,
     move_speed: Box<ValuePercentAttribute>,
     max_move_spped: Box<ValuePercentAttribute>,
+    armor: Box<ValueAttribute>,
+    resistance: Box<ValueAttribute>,
+    crit_chance: Box<ValueAttribute>,
+    crit_multiplier: Box<ValueAttribute>,
+    shield: Box<ValueAttribute>,
 }
 
 impl AttributeSet for CharacterAttributeSet {
@@ -31,6 +41,11 @@ impl AttributeSet for CharacterAttributeSet {
             CharacterAttributeType::MoveSpeed => Some(self.move_speed.get_final_value()),
             CharacterAttributeType::MaxHp => Some(self.max_hp.get_final_value()),
             CharacterAttributeType::MaxMoveSpped => Some(self.max_move_spped.get_final_value()),
+            CharacterAttributeType::Armor => Some(self.armor.get_final_value()),
+            CharacterAttributeType::Resistance => Some(self.resistance.get_final_value()),
+            CharacterAttributeType::CritChance => Some(self.crit_chance.get_final_value()),
+            CharacterAttributeType::CritMultiplier => Some(self.crit_multiplier.get_final_value()),
+            CharacterAttributeType::Shield => Some(self.shield.get_final_value()),
         }
     }
 
@@ -44,6 +59,11 @@ impl AttributeSet for CharacterAttributeSet {
             CharacterAttributeType::MoveSpeed => self.move_speed.as_mut(),
             CharacterAttributeType::MaxHp => self.max_hp.as_mut(),
             CharacterAttributeType::MaxMoveSpped => self.max_move_spped.as_mut(),
+            CharacterAttributeType::Armor => self.armor.as_mut(),
+            CharacterAttributeType::Resistance => self.resistance.as_mut(),
+            CharacterAttributeType::CritChance => self.crit_chance.as_mut(),
+            CharacterAttributeType::CritMultiplier => self.crit_multiplier.as_mut(),
+            CharacterAttributeType::Shield => self.shield.as_mut(),
         }
     }
 
@@ -53,6 +73,11 @@ impl AttributeSet for CharacterAttributeSet {
             CharacterAttributeType::MoveSpeed => self.move_speed.as_ref(),
             CharacterAttributeType::MaxHp => self.max_hp.as_ref(),
             CharacterAttributeType::MaxMoveSpped => self.max_move_spped.as_ref(),
+            CharacterAttributeType::Armor => self.armor.as_ref(),
+            CharacterAttributeType::Resistance => self.resistance.as_ref(),
+            CharacterAttributeType::CritChance => self.crit_chance.as_ref(),
+            CharacterAttributeType::CritMultiplier => self.crit_multiplier.as_ref(),
+            CharacterAttributeType::Shield => self.shield.as_ref(),
         }
     }
 }
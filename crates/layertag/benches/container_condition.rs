@@ -0,0 +1,75 @@
+//! 比较位集合条件判断和原来的线性扫描。
+//!
+//! cargo bench -p layertag
+
+use std::{hint::black_box, time::Instant};
+
+use layertag::{
+    container_op::{
+        LayerTagContainer, LayerTagContainerConditionRequired, LayerTagContainerConditionWithout,
+    },
+    count_container::CountLayerTagContainer,
+    layertag::LayerTag,
+    registry::LayerTagRegistry,
+};
+
+const ITERATIONS: u32 = 100_000;
+
+/// 原来的实现，对每个LayerTag线性扫描容器。
+fn linear_required(container: &impl LayerTagContainer, required: &impl LayerTagContainer) -> bool {
+    required
+        .iter_layertag()
+        .all(|x| container.iter_layertag().any(|y| x.tags() == y.tags()))
+}
+
+fn linear_without(container: &impl LayerTagContainer, without: &impl LayerTagContainer) -> bool {
+    without
+        .iter_layertag()
+        .all(|x| !container.iter_layertag().any(|y| x.tags() == y.tags()))
+}
+
+fn bench(name: &str, mut f: impl FnMut() -> bool) {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(f());
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>10.1} ns/iter",
+        name,
+        elapsed.as_nanos() as f64 / ITERATIONS as f64
+    );
+}
+
+fn container(layertags: &[LayerTag]) -> CountLayerTagContainer {
+    let mut container = CountLayerTagContainer::default();
+    container.add_layertags(layertags.iter().cloned());
+    container
+}
+
+fn main() {
+    let mut registry = LayerTagRegistry::default();
+    let layertags: Vec<LayerTag> = (0..64)
+        .map(|index| {
+            let raw = format!("state.group{}.tag{}", index % 8, index);
+            registry.register_raw(&raw);
+            registry.request_from_raw(&raw).unwrap()
+        })
+        .collect();
+
+    for size in [4, 16, 64] {
+        let state = container(&layertags[..size]);
+        let required = container(&layertags[size - 4..size]);
+        let without = container(&layertags[64 - 4..]);
+
+        println!("container size: {}", size);
+        bench("linear required", || linear_required(&state, &required));
+        bench("bitset required", || {
+            state.condition(LayerTagContainerConditionRequired, &required)
+        });
+        bench("linear without", || linear_without(&state, &without));
+        bench("bitset without", || {
+            state.condition(LayerTagContainerConditionWithout, &without)
+        });
+    }
+}
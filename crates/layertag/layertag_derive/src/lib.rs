@@ -1,194 +1,119 @@
-/// 废弃代码
-/// 留作copy用。
-use proc_macro::TokenStream;
-use quote::quote;
-use syn::{
-    parse::{Parse, ParseStream, Parser},
-    parse_macro_input,
-    punctuated::Punctuated,
-    Attribute, Data, DeriveInput, Ident, Result, Token,
-};
-
-#[proc_macro_derive(LayerTag, attributes(layer_tag, layer_tag_counter))]
-pub fn derive_layertag(input: TokenStream) -> TokenStream {
-    let derive_input = parse_macro_input!(input as DeriveInput);
-
-    let ident = derive_input.ident.clone();
-    let tags = get_layer_tag_attribute(&derive_input.attrs);
-    let (impl_generics, ty_generics, where_clause) = derive_input.generics.split_for_impl();
-
-    let counter = get_layer_tag_counter_attribute(&derive_input);
-    let data = impl_layer_tag_data(&derive_input);
-
-    let ts = quote!(
-        impl #impl_generics layertag::layertag::LayerTagClone for #ident #ty_generics #where_clause {
-            fn box_clone(&self) -> Box<dyn layertag::layertag::LayerTag> {
-                Box::new(self.clone())
-            }
-        }
-
-        impl #impl_generics layertag::layertag::LayerTag for #ident #ty_generics #where_clause {
-            fn tag(&self) -> &[layertag::tag::Tag] {
-                static CELL:once_cell::sync::OnceCell<Vec<layertag::tag::Tag>> = once_cell::sync::OnceCell::new();
-                CELL.get_or_init(||{
-                    vec![#tags]
-                }).as_slice()
-            }
-        }
-
-        #counter
-
-        #data
-    );
-
-    ts.into()
-}
-
-enum Arg {
-    Str(String),
-    Ident(Ident),
-}
-
-impl Parse for Arg {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let lookahead = input.lookahead1();
-        if lookahead.peek(Ident) {
-            let ident = input.parse::<Ident>()?;
-            Ok(Arg::Ident(ident))
-        } else if lookahead.peek(syn::LitStr) {
-            let lit_str = input.parse::<syn::LitStr>()?;
-            Ok(Arg::Str(lit_str.value()))
-        } else {
-            Err(lookahead.error())
-        }
-    }
-}
-
-struct Args {
-    args: Punctuated<Arg, Token![,]>,
-}
+/// 从LayerTag表生成编译期检查的LayerTag常量。
+use std::{collections::BTreeMap, path::PathBuf};
 
-impl Parse for Args {
-    fn parse(input: ParseStream) -> Result<Self> {
-        let args = Punctuated::parse_terminated(input)?;
-        Ok(Args { args })
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, LitStr};
+
+const DELIMITER: char = '.';
+
+/// 读取LayerTag表的json，为每一个LayerTag和它隐式的父LayerTag生成常量。
+/// 路径相对于调用crate的`CARGO_MANIFEST_DIR`，表修改后会重新编译。
+///
+/// ```ignore
+/// layertag_derive::layertag_table!("../../assets/datatables/layertag_tblayertag.json");
+///
+/// // "State.Debuff.Poison" -> STATE_DEBUFF_POISON
+/// registry.request_static(STATE_DEBUFF_POISON);
+/// // 所有常量，用于启动时检查表和生成代码是否一致。
+/// ALL.iter();
+/// ```
+#[proc_macro]
+pub fn layertag_table(input: TokenStream) -> TokenStream {
+    let path_lit = parse_macro_input!(input as LitStr);
+    match impl_layertag_table(&path_lit) {
+        Ok(ts) => ts.into(),
+        Err(e) => e.to_compile_error().into(),
     }
 }
 
-fn get_layer_tag_attribute(attrs: &[Attribute]) -> proc_macro2::TokenStream {
-    let mut tags: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-    for attr in attrs.iter() {
-        if let syn::Meta::List(meta_list) = &attr.meta {
-            if meta_list.path.is_ident("layer_tag") {
-                let parser = Args::parse;
-                if let Ok(Args { args }) = parser.parse(meta_list.tokens.clone().into()) {
-                    for arg in args.iter() {
-                        match arg {
-                            Arg::Str(s) => {
-                                tags.extend::<proc_macro2::TokenStream>(quote!(
-                                    layertag::tag::Tag::new(#s),
-                                ));
-                            }
-                            Arg::Ident(ident) => {
-                                tags.extend::<proc_macro2::TokenStream>(quote!(
-                                    layertag::tag::Tag::new(#ident),
-                                ));
-                            }
-                        }
-                    }
-                }
-            }
+fn impl_layertag_table(path_lit: &LitStr) -> syn::Result<proc_macro2::TokenStream> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|e| syn::Error::new(path_lit.span(), e.to_string()))?;
+    let path = PathBuf::from(manifest_dir).join(path_lit.value());
+    let content = std::fs::read_to_string(&path).map_err(|e| {
+        syn::Error::new(
+            path_lit.span(),
+            format!("read layertag table {} failed: {}", path.display(), e),
+        )
+    })?;
+    let rows: Vec<serde_json::Value> = serde_json::from_str(&content).map_err(|e| {
+        syn::Error::new(
+            path_lit.span(),
+            format!("parse layertag table {} failed: {}", path.display(), e),
+        )
+    })?;
+
+    // 有序，保证生成的代码稳定。父LayerTag没有描述。
+    let mut layertags: BTreeMap<String, Option<String>> = BTreeMap::new();
+    for row in rows.iter() {
+        let Some(raw_layertag) = row["raw_layertag"].as_str() else {
+            return Err(syn::Error::new(
+                path_lit.span(),
+                format!("layertag row without raw_layertag: {}", row),
+            ));
+        };
+        let desc = row["desc"].as_str().unwrap_or_default().to_string();
+
+        for (index, _) in raw_layertag.match_indices(DELIMITER) {
+            layertags
+                .entry(raw_layertag[..index].to_string())
+                .or_insert(None);
         }
+        layertags.insert(raw_layertag.to_string(), Some(desc));
     }
-    tags
-}
-
-fn get_layer_tag_counter_attribute(derive_input: &DeriveInput) -> proc_macro2::TokenStream {
-    let mut counter: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
-
-    let ident = derive_input.ident.clone();
-    let (impl_generics, ty_generics, where_clause) = derive_input.generics.split_for_impl();
-
-    if let Data::Struct(data) = &derive_input.data {
-        data.fields.iter().for_each(|field| {
-            if let Some(field_ident) = &field.ident {
-                let attrs = &field.attrs;
-                for attr in attrs {
-                    if let syn::Meta::Path(path) = &attr.meta {
-                        if path.is_ident("layer_tag_counter") {
-                            counter.extend::<proc_macro2::TokenStream>(quote!(
-                                impl #impl_generics layertag::layertag::LayerTagCounter for #ident #ty_generics #where_clause {
-                                    fn increase_count(&mut self) {
-                                        self.#field_ident += 1;
-                                    }
-
-                                    fn decrease_count(&mut self) {
-                                        self.#field_ident -= 1;
-                                    }
-
-                                    fn count(&self) -> usize {
-                                        self.#field_ident
-                                    }
-
-                                    fn reset_count(&mut self) {
-                                        self.#field_ident = 0;
-                                    }
-                                }
-                            ));
-                        }
-                    }
-                }
-            }
-        });
-    }
-
-    if counter.is_empty() {
-        counter.extend::<proc_macro2::TokenStream>(quote!(
-            impl #impl_generics layertag::layertag::LayerTagCounter for #ident #ty_generics #where_clause {
-                fn increase_count(&mut self) {}
 
-                fn decrease_count(&mut self) {}
-
-                fn count(&self) -> usize { 0 }
+    let mut idents: BTreeMap<String, &str> = BTreeMap::new();
+    let mut consts = proc_macro2::TokenStream::new();
+    let mut all = proc_macro2::TokenStream::new();
+    for (raw_layertag, desc) in layertags.iter() {
+        let name = const_name(raw_layertag).ok_or_else(|| {
+            syn::Error::new(
+                path_lit.span(),
+                format!("layertag {} can not be a const name", raw_layertag),
+            )
+        })?;
+        if let Some(other) = idents.insert(name.clone(), raw_layertag) {
+            return Err(syn::Error::new(
+                path_lit.span(),
+                format!(
+                    "layertag {} and {} have the same const name {}",
+                    other, raw_layertag, name
+                ),
+            ));
+        }
 
-                fn reset_count(&mut self) {}
-            }
+        let ident = format_ident!("{}", name);
+        let doc = match desc {
+            Some(desc) if !desc.is_empty() => format!("`{}`: {}", raw_layertag, desc),
+            _ => format!("`{}`", raw_layertag),
+        };
+        consts.extend(quote!(
+            #[doc = #doc]
+            pub const #ident: ::layertag::layertag::StaticLayerTag =
+                ::layertag::layertag::StaticLayerTag::new(#raw_layertag);
         ));
+        all.extend(quote!(#ident,));
     }
 
-    counter
-}
+    let path = path.display().to_string();
+    Ok(quote!(
+        const _: &[u8] = include_bytes!(#path);
 
-fn impl_layer_tag_data(derive_input: &DeriveInput) -> proc_macro2::TokenStream {
-    let mut data: proc_macro2::TokenStream = proc_macro2::TokenStream::new();
+        #consts
 
-    let ident = derive_input.ident.clone();
-    let (impl_generics, ty_generics, where_clause) = derive_input.generics.split_for_impl();
-
-    let mut non_counter_field_count = 0;
-    if let Data::Struct(data) = &derive_input.data {
-        data.fields.iter().for_each(|field| {
-            non_counter_field_count += 1;
-            let attrs = &field.attrs;
-            for attr in attrs {
-                if let syn::Meta::Path(path) = &attr.meta {
-                    if path.is_ident("layer_tag_counter") {
-                        non_counter_field_count -= 1;
-                    }
-                }
-            }
-        });
-    }
-
-    if non_counter_field_count <= 0 {
-        data.extend::<proc_macro2::TokenStream>(quote!(
-            impl #impl_generics layertag::layertag::LayerTagData for #ident #ty_generics #where_clause {
-                fn cmp_data_same_type_inner(&self, _rhs: &dyn layertag::layertag::LayerTag) -> bool {
-                    true
-                }
-            }
-        ));
-    }
+        pub const ALL: &[::layertag::layertag::StaticLayerTag] = &[#all];
+    ))
+}
 
-    data
+/// "State.Debuff" -> "STATE_DEBUFF"
+fn const_name(raw_layertag: &str) -> Option<String> {
+    let name = raw_layertag
+        .split(DELIMITER)
+        .map(|tag| tag.to_uppercase())
+        .collect::<Vec<_>>()
+        .join("_");
+    let valid = name.chars().next().is_some_and(|c| c.is_alphabetic())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+    valid.then_some(name)
 }
@@ -0,0 +1,189 @@
+use bevy::reflect::Reflect;
+
+use crate::registry::layertag_ancestors;
+
+/// LayerTag的紧凑整数id，由全局的interner分配，同一个LayerTag在进程内id不变。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Reflect)]
+pub struct LayerTagId(pub u32);
+
+impl LayerTagId {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// 以LayerTagId为下标的位集合。
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct LayerTagBitSet {
+    words: Vec<u64>,
+}
+
+impl LayerTagBitSet {
+    const WORD_BITS: usize = u64::BITS as usize;
+
+    pub fn insert(&mut self, id: LayerTagId) -> bool {
+        let (word, bit) = Self::position(id);
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        let existed = self.words[word] & bit != 0;
+        self.words[word] |= bit;
+        !existed
+    }
+
+    pub fn remove(&mut self, id: LayerTagId) -> bool {
+        let (word, bit) = Self::position(id);
+        match self.words.get_mut(word) {
+            Some(value) if *value & bit != 0 => {
+                *value &= !bit;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, id: LayerTagId) -> bool {
+        let (word, bit) = Self::position(id);
+        self.words.get(word).is_some_and(|value| value & bit != 0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|value| *value == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.clear();
+    }
+
+    /// self中的所有id都在other中。
+    pub fn is_subset(&self, other: &LayerTagBitSet) -> bool {
+        self.words.iter().enumerate().all(|(index, value)| {
+            let other_value = other.words.get(index).copied().unwrap_or(0);
+            value & !other_value == 0
+        })
+    }
+
+    pub fn intersects(&self, other: &LayerTagBitSet) -> bool {
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .any(|(lhs, rhs)| lhs & rhs != 0)
+    }
+
+    pub fn is_disjoint(&self, other: &LayerTagBitSet) -> bool {
+        !self.intersects(other)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = LayerTagId> + '_ {
+        self.words.iter().enumerate().flat_map(|(index, value)| {
+            (0..Self::WORD_BITS)
+                .filter(move |bit| value & (1 << bit) != 0)
+                .map(move |bit| LayerTagId((index * Self::WORD_BITS + bit) as u32))
+        })
+    }
+
+    fn position(id: LayerTagId) -> (usize, u64) {
+        (
+            id.index() / Self::WORD_BITS,
+            1 << (id.index() % Self::WORD_BITS),
+        )
+    }
+}
+
+/// 容器中LayerTag的位表示。
+///
+/// exact只包含添加的LayerTag，hierarchy还包含它们隐式的父LayerTag，
+/// 父LayerTag在hierarchy_counts中计数，所有子LayerTag移除后才从hierarchy中移除。
+#[derive(Debug, Clone, Default, Reflect)]
+pub struct LayerTagBits {
+    exact: LayerTagBitSet,
+    hierarchy: LayerTagBitSet,
+    hierarchy_counts: Vec<u32>,
+}
+
+impl LayerTagBits {
+    pub fn exact(&self) -> &LayerTagBitSet {
+        &self.exact
+    }
+
+    pub fn hierarchy(&self) -> &LayerTagBitSet {
+        &self.hierarchy
+    }
+
+    pub fn insert(&mut self, id: LayerTagId) {
+        if !self.exact.insert(id) {
+            return;
+        }
+
+        for &ancestor in layertag_ancestors(id).iter() {
+            if ancestor.index() >= self.hierarchy_counts.len() {
+                self.hierarchy_counts.resize(ancestor.index() + 1, 0);
+            }
+            self.hierarchy_counts[ancestor.index()] += 1;
+            self.hierarchy.insert(ancestor);
+        }
+    }
+
+    pub fn remove(&mut self, id: LayerTagId) {
+        if !self.exact.remove(id) {
+            return;
+        }
+
+        for &ancestor in layertag_ancestors(id).iter() {
+            let count = &mut self.hierarchy_counts[ancestor.index()];
+            *count -= 1;
+            if *count == 0 {
+                self.hierarchy.remove(ancestor);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{layertag::LayerTag, tag::Tag};
+
+    use super::{LayerTagBitSet, LayerTagBits, LayerTagId};
+
+    #[test]
+    fn bitset_op() {
+        let mut lhs = LayerTagBitSet::default();
+        let mut rhs = LayerTagBitSet::default();
+        lhs.insert(LayerTagId(3));
+        rhs.insert(LayerTagId(3));
+        rhs.insert(LayerTagId(130));
+
+        assert!(lhs.is_subset(&rhs));
+        assert!(!rhs.is_subset(&lhs));
+        assert!(lhs.intersects(&rhs));
+        assert_eq!(
+            rhs.iter().collect::<Vec<_>>(),
+            vec![LayerTagId(3), LayerTagId(130)]
+        );
+
+        rhs.remove(LayerTagId(3));
+        assert!(lhs.is_disjoint(&rhs));
+        assert!(rhs.is_subset(&rhs));
+        rhs.remove(LayerTagId(130));
+        assert!(rhs.is_empty());
+    }
+
+    #[test]
+    fn hierarchy_bits() {
+        let parent = LayerTag::new(vec![Tag::new("bits"), Tag::new("a")]);
+        let child_b = LayerTag::new(vec![Tag::new("bits"), Tag::new("a"), Tag::new("b")]);
+        let child_c = LayerTag::new(vec![Tag::new("bits"), Tag::new("a"), Tag::new("c")]);
+
+        let mut bits = LayerTagBits::default();
+        bits.insert(child_b.id());
+        bits.insert(child_c.id());
+        assert!(!bits.exact().contains(parent.id()));
+        assert!(bits.hierarchy().contains(parent.id()));
+
+        bits.remove(child_b.id());
+        assert!(bits.hierarchy().contains(parent.id()));
+        bits.remove(child_c.id());
+        assert!(!bits.hierarchy().contains(parent.id()));
+        assert!(bits.hierarchy().is_empty());
+    }
+}
@@ -1,10 +1,11 @@
-use std::ops::Not;
-
-use crate::layertag::LayerTag;
+use crate::{bitset::LayerTagBits, layertag::LayerTag};
 
 pub trait LayerTagContainer {
     fn iter_layertag(&self) -> impl Iterator<Item = &LayerTag>;
 
+    /// 容器中LayerTag的位表示，条件判断使用位运算。
+    fn layertag_bits(&self) -> &LayerTagBits;
+
     fn exist_layertag(&self, tag: &LayerTag) -> bool;
 
     fn add_layertag(&mut self, layertag: LayerTag);
@@ -42,6 +43,7 @@ pub trait LayerTagContainerCondition {
     fn condition(&self, lhs: &impl LayerTagContainer, rhs: &impl LayerTagContainer) -> bool;
 }
 
+/// container包含required中的所有LayerTag。
 pub struct LayerTagContainerConditionRequired;
 
 impl LayerTagContainerCondition for LayerTagContainerConditionRequired {
@@ -51,11 +53,13 @@ impl LayerTagContainerCondition for LayerTagContainerConditionRequired {
         required: &impl LayerTagContainer,
     ) -> bool {
         required
-            .iter_layertag()
-            .all(|x| container.iter_layertag().any(|y| x.exact_match(y)))
+            .layertag_bits()
+            .exact()
+            .is_subset(container.layertag_bits().exact())
     }
 }
 
+/// container不包含without中的任何LayerTag。
 pub struct LayerTagContainerConditionWithout;
 
 impl LayerTagContainerCondition for LayerTagContainerConditionWithout {
@@ -64,8 +68,42 @@ impl LayerTagContainerCondition for LayerTagContainerConditionWithout {
         container: &impl LayerTagContainer,
         without: &impl LayerTagContainer,
     ) -> bool {
-        without
-            .iter_layertag()
-            .all(|x| container.iter_layertag().any(|y| x.exact_match(y)).not())
+        container
+            .layertag_bits()
+            .exact()
+            .is_disjoint(without.layertag_bits().exact())
+    }
+}
+
+/// container对required中的每个LayerTag，包含它自身或者它的子LayerTag。
+/// 比如required为"a.b"时，container中有"a.b.c"也满足。
+pub struct LayerTagContainerConditionPartialRequired;
+
+impl LayerTagContainerCondition for LayerTagContainerConditionPartialRequired {
+    fn condition(
+        &self,
+        container: &impl LayerTagContainer,
+        required: &impl LayerTagContainer,
+    ) -> bool {
+        required
+            .layertag_bits()
+            .exact()
+            .is_subset(container.layertag_bits().hierarchy())
+    }
+}
+
+/// container不包含without中的任何LayerTag以及它们的子LayerTag。
+pub struct LayerTagContainerConditionPartialWithout;
+
+impl LayerTagContainerCondition for LayerTagContainerConditionPartialWithout {
+    fn condition(
+        &self,
+        container: &impl LayerTagContainer,
+        without: &impl LayerTagContainer,
+    ) -> bool {
+        container
+            .layertag_bits()
+            .hierarchy()
+            .is_disjoint(without.layertag_bits().exact())
     }
 }
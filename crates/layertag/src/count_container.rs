@@ -1,4 +1,4 @@
-use std::{fmt::Debug, ops::Deref};
+use std::fmt::Debug;
 
 use bevy::{
     prelude::{Component, ReflectComponent},
@@ -6,24 +6,42 @@ use bevy::{
 };
 
 use crate::{
+    bitset::LayerTagBits,
     container_op::{LayerTagContainer, LayerTagContainerCondition, LayerTagContainerOp},
     count_layertag::CountLayerTag,
     layertag::LayerTag,
 };
 
+/// 计数从0变为1，或者从1变为0时记录的变化。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayerTagChange {
+    Added(LayerTag),
+    Removed(LayerTag),
+}
+
+/// 带计数的LayerTag容器，计数从0变为1时加入位集合，从1变为0时移除。
 #[derive(Debug, Clone, Component, Default, Reflect)]
 #[reflect(Component)]
 pub struct CountLayerTagContainer {
-    layertags: Vec<CountLayerTag>,
+    /// 以LayerTagId为下标，计数为0的位置为None。
+    layertags: Vec<Option<CountLayerTag>>,
+    bits: LayerTagBits,
+    /// 只有开启后才记录变化，见`with_change_log`。
+    #[reflect(ignore)]
+    changes: Option<Vec<LayerTagChange>>,
 }
 
 impl LayerTagContainer for CountLayerTagContainer {
     fn iter_layertag(&self) -> impl Iterator<Item = &LayerTag> {
-        self.layertags.iter().map(|x| x.layertag())
+        self.layertags.iter().flatten().map(|x| x.layertag())
+    }
+
+    fn layertag_bits(&self) -> &LayerTagBits {
+        &self.bits
     }
 
     fn exist_layertag(&self, tag: &LayerTag) -> bool {
-        self.layertags.iter().any(|x| x.exact_match(tag))
+        self.bits.exact().contains(tag.id())
     }
 
     fn add_layertags(&mut self, layertags: impl Iterator<Item = LayerTag>) {
@@ -39,30 +57,77 @@ impl LayerTagContainer for CountLayerTagContainer {
     }
 
     fn add_layertag(&mut self, layertag: LayerTag) {
-        if let Some(existed_tag) = self.layertags.iter_mut().find(|x| x.exact_match(&layertag)) {
-            existed_tag.increase_count();
-        } else {
-            let mut tag = CountLayerTag::new(layertag);
-            tag.reset_count();
-            tag.increase_count();
-            self.layertags.push(tag);
+        let index = layertag.id().index();
+        if self.bits.exact().contains(layertag.id()) {
+            if let Some(existed_tag) = self.layertags[index].as_mut() {
+                existed_tag.increase_count();
+            }
+            return;
+        }
+
+        self.bits.insert(layertag.id());
+        if let Some(changes) = self.changes.as_mut() {
+            changes.push(LayerTagChange::Added(layertag.clone()));
         }
+        if index >= self.layertags.len() {
+            self.layertags.resize(index + 1, None);
+        }
+        let mut tag = CountLayerTag::new(layertag);
+        tag.increase_count();
+        self.layertags[index] = Some(tag);
     }
 
     fn remove_layertag(&mut self, layertag: &LayerTag) {
-        self.layertags.retain_mut(|x| {
-            if x.deref().exact_match(layertag) {
-                x.decrease_count();
-                return x.count() > 0;
+        if !self.bits.exact().contains(layertag.id()) {
+            return;
+        }
+
+        let slot = &mut self.layertags[layertag.id().index()];
+        let Some(existed_tag) = slot.as_mut() else {
+            return;
+        };
+        existed_tag.decrease_count();
+        if existed_tag.count() <= 0 {
+            *slot = None;
+            self.bits.remove(layertag.id());
+            if let Some(changes) = self.changes.as_mut() {
+                changes.push(LayerTagChange::Removed(layertag.clone()));
             }
-            true
-        });
+        }
     }
 }
 
 impl CountLayerTagContainer {
+    /// 记录LayerTag的添加和移除，需要定期调用`take_changes`取出。
+    pub fn with_change_log() -> Self {
+        Self {
+            changes: Some(Vec::new()),
+            ..Default::default()
+        }
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.changes
+            .as_ref()
+            .is_some_and(|changes| !changes.is_empty())
+    }
+
+    pub fn take_changes(&mut self) -> Vec<LayerTagChange> {
+        self.changes
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.exact().is_empty()
+    }
+
     pub fn get_layertag(&self, layertag: &LayerTag) -> Option<&CountLayerTag> {
-        self.layertags.iter().find(|x| x.exact_match(layertag))
+        if !self.exist_layertag(layertag) {
+            return None;
+        }
+        self.layertags.get(layertag.id().index())?.as_ref()
     }
 }
 
@@ -84,30 +149,88 @@ impl CountLayerTagContainer {
 mod tests {
     extern crate self as layertag;
 
-    use crate::{container_op::LayerTagContainer, layertag::LayerTag, tag::Tag};
+    use crate::{
+        container_op::{
+            LayerTagContainer, LayerTagContainerConditionPartialRequired,
+            LayerTagContainerConditionPartialWithout, LayerTagContainerConditionRequired,
+            LayerTagContainerConditionWithout,
+        },
+        layertag::LayerTag,
+        tag::Tag,
+    };
 
-    use super::CountLayerTagContainer;
+    use super::{CountLayerTagContainer, LayerTagChange};
 
     #[test]
     fn test_layer_counter() {
         let mut layer_tag_container = CountLayerTagContainer::default();
 
         let tag_a = LayerTag::new(vec![Tag::new("a"), Tag::new("b")]);
-        assert_eq!(layer_tag_container.layertags.len(), 0);
+        assert_eq!(layer_tag_container.iter_layertag().count(), 0);
 
         layer_tag_container.add_layertag(tag_a.clone());
-        assert_eq!(layer_tag_container.layertags.len(), 1);
+        assert_eq!(layer_tag_container.iter_layertag().count(), 1);
         assert_eq!(layer_tag_container.get_layertag(&tag_a).unwrap().count(), 1);
 
         layer_tag_container.add_layertag(tag_a.clone());
-        assert_eq!(layer_tag_container.layertags.len(), 1);
+        assert_eq!(layer_tag_container.iter_layertag().count(), 1);
         assert_eq!(layer_tag_container.get_layertag(&tag_a).unwrap().count(), 2);
 
         layer_tag_container.remove_layertag(&tag_a);
-        assert_eq!(layer_tag_container.layertags.len(), 1);
+        assert_eq!(layer_tag_container.iter_layertag().count(), 1);
         assert_eq!(layer_tag_container.get_layertag(&tag_a).unwrap().count(), 1);
 
         layer_tag_container.remove_layertag(&tag_a);
-        assert_eq!(layer_tag_container.layertags.len(), 0);
+        assert_eq!(layer_tag_container.iter_layertag().count(), 0);
+    }
+
+    #[test]
+    fn test_condition() {
+        let stunned = LayerTag::new_from_raw("count.state.stunned");
+        let frozen = LayerTag::new_from_raw("count.state.frozen");
+        let state = LayerTag::new_from_raw("count.state");
+
+        let mut container = CountLayerTagContainer::default();
+        container.add_layertag(stunned.clone());
+
+        let mut required = CountLayerTagContainer::default();
+        required.add_layertag(stunned.clone());
+        assert!(container.condition(LayerTagContainerConditionRequired, &required));
+        required.add_layertag(frozen.clone());
+        assert!(!container.condition(LayerTagContainerConditionRequired, &required));
+
+        let mut parent = CountLayerTagContainer::default();
+        parent.add_layertag(state.clone());
+        assert!(!container.condition(LayerTagContainerConditionRequired, &parent));
+        assert!(container.condition(LayerTagContainerConditionPartialRequired, &parent));
+        assert!(container.condition(LayerTagContainerConditionWithout, &parent));
+        assert!(!container.condition(LayerTagContainerConditionPartialWithout, &parent));
+
+        container.remove_layertag(&stunned);
+        assert!(container.condition(LayerTagContainerConditionPartialWithout, &parent));
+    }
+
+    #[test]
+    fn test_change_log() {
+        let tag_a = LayerTag::new_from_raw("count.log.a");
+        let mut container = CountLayerTagContainer::with_change_log();
+        container.add_layertag(tag_a.clone());
+        container.add_layertag(tag_a.clone());
+        container.remove_layertag(&tag_a);
+        assert_eq!(
+            container.take_changes(),
+            vec![LayerTagChange::Added(tag_a.clone())]
+        );
+
+        container.remove_layertag(&tag_a);
+        assert_eq!(
+            container.take_changes(),
+            vec![LayerTagChange::Removed(tag_a)]
+        );
+        assert!(!container.has_changes());
+
+        let mut container = CountLayerTagContainer::default();
+        container.add_layertag(LayerTag::new_from_raw("count.log.a"));
+        assert!(!container.has_changes());
     }
 }
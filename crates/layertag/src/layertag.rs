@@ -5,12 +5,19 @@ use std::{
 };
 
 use bevy::reflect::Reflect;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::tag::Tag;
+use crate::{
+    bitset::LayerTagId,
+    registry::{intern_layertag, layertag_parent},
+    tag::Tag,
+};
 
+/// 创建时通过全局interner获得id，比较只需要比较id。
 #[derive(Debug, Clone, Reflect, PartialEq, Eq, Hash)]
 pub struct LayerTag {
-    pub tags: Cow<'static, [Tag]>,
+    tags: Cow<'static, [Tag]>,
+    id: LayerTagId,
 }
 
 impl LayerTag {
@@ -23,9 +30,24 @@ impl Display for LayerTag {
     }
 }
 
+/// 序列化为"a.b.c"格式的字符串。
+impl Serialize for LayerTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.raw_layertag())
+    }
+}
+
+impl<'de> Deserialize<'de> for LayerTag {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw_layertag = String::deserialize(deserializer)?;
+        Ok(LayerTag::new_from_raw(&raw_layertag))
+    }
+}
+
 impl LayerTag {
     pub(crate) fn new(tags: Vec<Tag>) -> Self {
         Self {
+            id: intern_layertag(&tags),
             tags: Cow::Owned(tags),
         }
     }
@@ -35,9 +57,7 @@ impl LayerTag {
             .split(LayerTag::DELIMITER)
             .map(|x| Tag::new(x.to_owned()))
             .collect();
-        Self {
-            tags: Cow::Owned(tags),
-        }
+        Self::new(tags)
     }
 }
 
@@ -46,6 +66,14 @@ impl LayerTag {
         &self.tags
     }
 
+    pub fn id(&self) -> LayerTagId {
+        self.id
+    }
+
+    pub fn parent_id(&self) -> Option<LayerTagId> {
+        layertag_parent(self.id)
+    }
+
     pub fn raw_layertag(&self) -> String {
         self.tags
             .iter()
@@ -61,7 +89,7 @@ impl LayerTag {
     /// "a.b" != "a.b.c"
     /// ```
     pub fn exact_match(&self, rhs: &LayerTag) -> bool {
-        self.tags() == rhs.tags()
+        self.id == rhs.id
     }
 
     /// two tag exact match
@@ -101,6 +129,37 @@ impl LayerTag {
     }
 }
 
+/// 编译期生成的LayerTag常量，由`layertag_derive::layertag_table!`从LayerTag表生成。
+/// 使用时再intern，和注册表中的LayerTag有相同的id。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StaticLayerTag(&'static str);
+
+impl StaticLayerTag {
+    pub const fn new(raw_layertag: &'static str) -> Self {
+        Self(raw_layertag)
+    }
+
+    pub const fn raw_layertag(&self) -> &'static str {
+        self.0
+    }
+
+    pub fn layertag(&self) -> LayerTag {
+        LayerTag::new_from_raw(self.0)
+    }
+}
+
+impl Display for StaticLayerTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<StaticLayerTag> for LayerTag {
+    fn from(value: StaticLayerTag) -> Self {
+        value.layertag()
+    }
+}
+
 #[cfg(test)]
 mod test {
     extern crate self as layertag;
@@ -1,3 +1,4 @@
+pub mod bitset;
 pub mod builder;
 /// layer tag crate.
 /// tree data structure for layer tag
@@ -7,6 +8,7 @@ pub mod container_op;
 pub mod count_container;
 pub mod count_layertag;
 pub mod layertag;
+pub mod query;
 pub mod registry;
 pub mod single_container;
 pub mod tag;
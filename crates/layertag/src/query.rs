@@ -0,0 +1,376 @@
+use std::fmt::{self, Display};
+
+use bevy::reflect::Reflect;
+use thiserror::Error;
+
+use crate::{container_op::LayerTagContainer, layertag::LayerTag, registry::LayerTagRegistry};
+
+/// LayerTag查询表达式。
+///
+/// 字符串语法:
+/// ```ignore
+/// "State.Stunned"                              精确匹配
+/// "State.Debuff.*"                             匹配自身或者任意子LayerTag
+/// "any(State.Stunned, State.Frozen)"           任意一个满足
+/// "all(State.Wet, none(State.Debuff.*))"       可以嵌套
+/// ```
+#[derive(Debug, Clone, PartialEq, Reflect)]
+#[reflect(no_field_bounds)]
+pub enum LayerTagQuery {
+    Exact(LayerTag),
+    Hierarchical(LayerTag),
+    Any(Vec<LayerTagQuery>),
+    All(Vec<LayerTagQuery>),
+    None(Vec<LayerTagQuery>),
+}
+
+impl Default for LayerTagQuery {
+    /// 空的all，总是满足。
+    fn default() -> Self {
+        LayerTagQuery::All(Vec::new())
+    }
+}
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum LayerTagQueryError {
+    #[error("layertag {0} is not registered")]
+    UnknownLayerTag(String),
+    #[error("unexpected end of query, expect {0}")]
+    UnexpectedEnd(&'static str),
+    #[error("unexpected {found:?} at {position}, expect {expect}")]
+    UnexpectedToken {
+        position: usize,
+        found: char,
+        expect: &'static str,
+    },
+}
+
+impl LayerTagQuery {
+    const HIERARCHICAL_SUFFIX: &'static str = ".*";
+
+    /// 是否是表达式，不是表达式的字符串是普通的LayerTag。
+    pub fn is_expression(raw: &str) -> bool {
+        raw.contains('(') || raw.trim_end().ends_with(Self::HIERARCHICAL_SUFFIX)
+    }
+
+    pub fn parse(input: &str, registry: &LayerTagRegistry) -> Result<Self, LayerTagQueryError> {
+        let mut parser = LayerTagQueryParser {
+            input,
+            position: 0,
+            registry,
+        };
+        let query = parser.parse_query()?;
+        parser.skip_whitespace();
+        match parser.peek() {
+            Some(found) => Err(LayerTagQueryError::UnexpectedToken {
+                position: parser.position,
+                found,
+                expect: "end of query",
+            }),
+            None => Ok(query),
+        }
+    }
+
+    /// 和容器条件Required/Without相同的查询。
+    pub fn from_condition(
+        required: &impl LayerTagContainer,
+        without: &impl LayerTagContainer,
+    ) -> Self {
+        let mut queries: Vec<LayerTagQuery> = required
+            .iter_layertag()
+            .cloned()
+            .map(LayerTagQuery::Exact)
+            .collect();
+        let without: Vec<LayerTagQuery> = without
+            .iter_layertag()
+            .cloned()
+            .map(LayerTagQuery::Exact)
+            .collect();
+        if !without.is_empty() {
+            queries.push(LayerTagQuery::None(without));
+        }
+        LayerTagQuery::All(queries)
+    }
+
+    /// 空的all，总是满足。
+    pub fn is_empty(&self) -> bool {
+        matches!(self, LayerTagQuery::All(queries) if queries.is_empty())
+    }
+
+    pub fn matches(&self, container: &impl LayerTagContainer) -> bool {
+        match self {
+            LayerTagQuery::Exact(layertag) => {
+                container.layertag_bits().exact().contains(layertag.id())
+            }
+            LayerTagQuery::Hierarchical(layertag) => container
+                .layertag_bits()
+                .hierarchy()
+                .contains(layertag.id()),
+            LayerTagQuery::Any(queries) => queries.iter().any(|query| query.matches(container)),
+            LayerTagQuery::All(queries) => queries.iter().all(|query| query.matches(container)),
+            LayerTagQuery::None(queries) => !queries.iter().any(|query| query.matches(container)),
+        }
+    }
+
+    /// 查询中引用的所有LayerTag。
+    pub fn layertags(&self) -> Vec<&LayerTag> {
+        match self {
+            LayerTagQuery::Exact(layertag) | LayerTagQuery::Hierarchical(layertag) => {
+                vec![layertag]
+            }
+            LayerTagQuery::Any(queries)
+            | LayerTagQuery::All(queries)
+            | LayerTagQuery::None(queries) => {
+                queries.iter().flat_map(|query| query.layertags()).collect()
+            }
+        }
+    }
+}
+
+/// 检查表中的LayerTag和查询表达式，返回未注册的LayerTag或者解析失败的表达式。
+pub fn validate_raw_layertags<'a>(
+    registry: &LayerTagRegistry,
+    raw_layertags: impl Iterator<Item = &'a str>,
+) -> Vec<(&'a str, LayerTagQueryError)> {
+    raw_layertags
+        .filter_map(|raw_layertag| {
+            if LayerTagQuery::is_expression(raw_layertag) {
+                LayerTagQuery::parse(raw_layertag, registry)
+                    .err()
+                    .map(|e| (raw_layertag, e))
+            } else if registry.request_from_raw(raw_layertag).is_none() {
+                Some((
+                    raw_layertag,
+                    LayerTagQueryError::UnknownLayerTag(raw_layertag.to_string()),
+                ))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+impl Display for LayerTagQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, queries) = match self {
+            LayerTagQuery::Exact(layertag) => return write!(f, "{}", layertag),
+            LayerTagQuery::Hierarchical(layertag) => {
+                return write!(f, "{}{}", layertag, LayerTagQuery::HIERARCHICAL_SUFFIX)
+            }
+            LayerTagQuery::Any(queries) => ("any", queries),
+            LayerTagQuery::All(queries) => ("all", queries),
+            LayerTagQuery::None(queries) => ("none", queries),
+        };
+
+        write!(f, "{}(", name)?;
+        for (index, query) in queries.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", query)?;
+        }
+        write!(f, ")")
+    }
+}
+
+struct LayerTagQueryParser<'a> {
+    input: &'a str,
+    position: usize,
+    registry: &'a LayerTagRegistry,
+}
+
+impl LayerTagQueryParser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn is_layertag_char(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '.' || c == '*'
+    }
+
+    fn expect(
+        &mut self,
+        expect: char,
+        expect_name: &'static str,
+    ) -> Result<(), LayerTagQueryError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(c) if c == expect => {
+                self.position += c.len_utf8();
+                Ok(())
+            }
+            Some(found) => Err(LayerTagQueryError::UnexpectedToken {
+                position: self.position,
+                found,
+                expect: expect_name,
+            }),
+            None => Err(LayerTagQueryError::UnexpectedEnd(expect_name)),
+        }
+    }
+
+    fn parse_query(&mut self) -> Result<LayerTagQuery, LayerTagQueryError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while let Some(c) = self.peek().filter(|c| Self::is_layertag_char(*c)) {
+            self.position += c.len_utf8();
+        }
+        let word = &self.input[start..self.position];
+
+        if word.is_empty() {
+            return match self.peek() {
+                Some(found) => Err(LayerTagQueryError::UnexpectedToken {
+                    position: self.position,
+                    found,
+                    expect: "layertag or any/all/none",
+                }),
+                None => Err(LayerTagQueryError::UnexpectedEnd(
+                    "layertag or any/all/none",
+                )),
+            };
+        }
+
+        self.skip_whitespace();
+        if self.peek() == Some('(') {
+            let queries = self.parse_list()?;
+            return match word {
+                "any" => Ok(LayerTagQuery::Any(queries)),
+                "all" => Ok(LayerTagQuery::All(queries)),
+                "none" => Ok(LayerTagQuery::None(queries)),
+                _ => Err(LayerTagQueryError::UnexpectedToken {
+                    position: start,
+                    found: word.chars().next().unwrap(),
+                    expect: "any/all/none",
+                }),
+            };
+        }
+
+        let (raw_layertag, hierarchical) =
+            match word.strip_suffix(LayerTagQuery::HIERARCHICAL_SUFFIX) {
+                Some(raw_layertag) => (raw_layertag, true),
+                None => (word, false),
+            };
+        let layertag = self
+            .registry
+            .request_from_raw(raw_layertag)
+            .ok_or_else(|| LayerTagQueryError::UnknownLayerTag(raw_layertag.to_string()))?;
+        if hierarchical {
+            Ok(LayerTagQuery::Hierarchical(layertag))
+        } else {
+            Ok(LayerTagQuery::Exact(layertag))
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Vec<LayerTagQuery>, LayerTagQueryError> {
+        self.expect('(', "(")?;
+        let mut queries = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.position += 1;
+            return Ok(queries);
+        }
+
+        loop {
+            queries.push(self.parse_query()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(')') => {
+                    self.position += 1;
+                    return Ok(queries);
+                }
+                Some(found) => {
+                    return Err(LayerTagQueryError::UnexpectedToken {
+                        position: self.position,
+                        found,
+                        expect: ", or )",
+                    })
+                }
+                None => return Err(LayerTagQueryError::UnexpectedEnd(")")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        container_op::LayerTagContainer, count_container::CountLayerTagContainer,
+        registry::LayerTagRegistry, single_container::SingleLayerTagContainer,
+    };
+
+    use super::{LayerTagQuery, LayerTagQueryError};
+
+    fn registry() -> LayerTagRegistry {
+        let mut registry = LayerTagRegistry::default();
+        for raw in [
+            "query.state.stunned",
+            "query.state.frozen",
+            "query.state.wet",
+            "query.debuff.poison",
+            "query.debuff.burn",
+        ] {
+            registry.register_raw(raw);
+        }
+        registry
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let registry = registry();
+        let raw = "all(any(query.state.stunned, query.state.frozen), none(query.debuff.*))";
+        let query = LayerTagQuery::parse(raw, &registry).unwrap();
+        assert_eq!(query.to_string(), raw);
+        assert_eq!(query.layertags().len(), 3);
+
+        assert!(LayerTagQuery::is_expression(raw));
+        assert!(LayerTagQuery::is_expression("query.debuff.*"));
+        assert!(!LayerTagQuery::is_expression("query.debuff"));
+
+        assert_eq!(
+            LayerTagQuery::parse("any(query.state.unknown)", &registry),
+            Err(LayerTagQueryError::UnknownLayerTag(
+                "query.state.unknown".into()
+            ))
+        );
+        assert_eq!(
+            LayerTagQuery::parse("any(query.state.wet", &registry),
+            Err(LayerTagQueryError::UnexpectedEnd(")"))
+        );
+        assert!(LayerTagQuery::parse("some(query.state.wet)", &registry).is_err());
+    }
+
+    #[test]
+    fn match_containers() {
+        let registry = registry();
+        let query = LayerTagQuery::parse(
+            "all(any(query.state.stunned, query.state.frozen), none(query.debuff.*))",
+            &registry,
+        )
+        .unwrap();
+
+        let mut single = SingleLayerTagContainer::default();
+        let mut count = CountLayerTagContainer::default();
+        assert!(!query.matches(&single));
+        assert!(!query.matches(&count));
+
+        let frozen = registry.request_from_raw("query.state.frozen").unwrap();
+        single.add_layertag(frozen.clone());
+        count.add_layertag(frozen);
+        assert!(query.matches(&single));
+        assert!(query.matches(&count));
+
+        let burn = registry.request_from_raw("query.debuff.burn").unwrap();
+        single.add_layertag(burn.clone());
+        count.add_layertag(burn);
+        assert!(!query.matches(&single));
+        assert!(!query.matches(&count));
+
+        assert!(LayerTagQuery::default().matches(&count));
+    }
+}
@@ -1,45 +1,153 @@
-use bevy::{reflect::Reflect, utils::HashSet};
+use std::sync::{Arc, RwLock};
 
-use crate::layertag::LayerTag;
+use bevy::{reflect::Reflect, utils::HashMap};
+use once_cell::sync::Lazy;
+
+use crate::{
+    bitset::{LayerTagBitSet, LayerTagId},
+    layertag::{LayerTag, StaticLayerTag},
+    tag::Tag,
+};
 
 pub trait FromTagRegistry {
     fn from_tag_registry() -> Self;
 }
 
+/// 全局的LayerTag interner，为每个LayerTag分配id，"a.b.c"会隐式分配"a.b"和"a"的id。
+#[derive(Debug, Default)]
+struct LayerTagInterner {
+    ids: HashMap<Vec<Tag>, LayerTagId>,
+    /// 以id为下标，包含自身的所有祖先id，分配id时一起写入。
+    ancestors: Vec<Arc<[LayerTagId]>>,
+}
+
+static INTERNER: Lazy<RwLock<LayerTagInterner>> = Lazy::new(Default::default);
+
+pub(crate) fn intern_layertag(tags: &[Tag]) -> LayerTagId {
+    if let Some(id) = INTERNER.read().unwrap().ids.get(tags) {
+        return *id;
+    }
+
+    let mut guard = INTERNER.write().unwrap();
+    let interner = &mut *guard;
+    let mut intern = |tags: &[Tag], parent: Option<LayerTagId>| {
+        if let Some(id) = interner.ids.get(tags) {
+            return *id;
+        }
+        let id = LayerTagId(interner.ids.len() as u32);
+        let mut ancestors = vec![id];
+        if let Some(parent) = parent {
+            ancestors.extend_from_slice(&interner.ancestors[parent.index()]);
+        }
+        interner.ancestors.push(ancestors.into());
+        interner.ids.insert(tags.to_vec(), id);
+        id
+    };
+
+    // 空的LayerTag没有父LayerTag。
+    let mut id = intern(&tags[..tags.len().min(1)], None);
+    for len in 2..=tags.len() {
+        id = intern(&tags[..len], Some(id));
+    }
+    id
+}
+
+pub fn layertag_parent(id: LayerTagId) -> Option<LayerTagId> {
+    layertag_ancestors(id).get(1).copied()
+}
+
+/// 包含自身的所有祖先id，从自身开始，未分配的id返回空。
+pub fn layertag_ancestors(id: LayerTagId) -> Arc<[LayerTagId]> {
+    INTERNER
+        .read()
+        .unwrap()
+        .ancestors
+        .get(id.index())
+        .cloned()
+        .unwrap_or_else(|| Arc::new([]))
+}
+
 /// register layer tag.
 /// 1. 至少保证了不会获得无效的LayerTag.
+/// 2. 注册"a.b.c"时，同时注册隐式的父LayerTag"a.b"和"a"。
 #[derive(Default, Debug, Reflect)]
 pub struct LayerTagRegistry {
-    layertags: HashSet<LayerTag>,
+    layertags: HashMap<String, LayerTag>,
+    registered: LayerTagBitSet,
 }
 
 impl LayerTagRegistry {
     pub fn register_raw(&mut self, raw_layertag: &str) {
-        let layertag = LayerTag::new_from_raw(raw_layertag);
-        self.layertags.insert(layertag);
+        self.register(LayerTag::new_from_raw(raw_layertag));
     }
 
     pub fn register(&mut self, layertag: LayerTag) {
-        self.layertags.insert(layertag);
+        for len in 1..layertag.tags().len() {
+            let parent = LayerTag::new(layertag.tags()[..len].to_vec());
+            self.insert(parent);
+        }
+        self.insert(layertag);
+    }
+
+    fn insert(&mut self, layertag: LayerTag) {
+        if self.registered.insert(layertag.id()) {
+            self.layertags.insert(layertag.raw_layertag(), layertag);
+        }
+    }
+
+    /// 移除LayerTag，仍然是其他LayerTag的父LayerTag时保留，隐式注册的父LayerTag也不会移除。
+    pub fn unregister_raw(&mut self, raw_layertag: &str) -> bool {
+        let Some(id) = self.request_id_from_raw(raw_layertag) else {
+            return false;
+        };
+        if self
+            .layertags
+            .values()
+            .any(|other| layertag_ancestors(other.id())[1..].contains(&id))
+        {
+            return false;
+        }
+        self.layertags.remove(raw_layertag);
+        self.registered.remove(id)
     }
 
     pub fn request_from_raw(&self, raw_layertag: &str) -> Option<LayerTag> {
+        self.layertags.get(raw_layertag).cloned()
+    }
+
+    pub fn request_id_from_raw(&self, raw_layertag: &str) -> Option<LayerTagId> {
         self.layertags
-            .iter()
-            .find(|layertag| layertag.raw_layertag() == raw_layertag)
-            .cloned()
+            .get(raw_layertag)
+            .map(|layertag| layertag.id())
+    }
+
+    /// 编译期常量对应的LayerTag，表和生成代码不一致时返回None。
+    pub fn request_static(&self, layertag: StaticLayerTag) -> Option<LayerTag> {
+        self.request_from_raw(layertag.raw_layertag())
+    }
+
+    pub fn contains(&self, layertag: &LayerTag) -> bool {
+        self.registered.contains(layertag.id())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &LayerTag> {
+        self.layertags.values()
     }
 
     pub fn clear(&mut self) {
         self.layertags.clear();
+        self.registered.clear();
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{layertag::LayerTag, tag::Tag};
+    use crate::{
+        layertag::{LayerTag, StaticLayerTag},
+        tag::Tag,
+    };
 
-    use super::LayerTagRegistry;
+    use super::{LayerTagRegistry, layertag_ancestors, layertag_parent};
 
     #[test]
     fn register_layertag() {
@@ -59,4 +167,47 @@ mod tests {
         assert_eq!(new_tag_inst, Some(layertag));
         assert!(registry.request_from_raw("safj").is_none());
     }
+
+    #[test]
+    fn register_implicit_parent() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("registry.a.b");
+
+        let child = registry.request_from_raw("registry.a.b").unwrap();
+        let parent = registry.request_from_raw("registry.a").unwrap();
+        let root = registry.request_from_raw("registry").unwrap();
+        assert_eq!(layertag_parent(child.id()), Some(parent.id()));
+        assert_eq!(
+            *layertag_ancestors(child.id()),
+            [child.id(), parent.id(), root.id()]
+        );
+        assert_eq!(
+            LayerTag::new_from_raw("registry.a.b").id(),
+            child.id(),
+            "same layertag should be interned to same id"
+        );
+    }
+
+    #[test]
+    fn unregister_keep_parent() {
+        let mut registry = LayerTagRegistry::default();
+        registry.register_raw("unregister.a.b");
+        registry.register_raw("unregister.c");
+
+        assert!(!registry.unregister_raw("unregister.a"));
+        assert!(registry.unregister_raw("unregister.a.b"));
+        assert!(registry.request_from_raw("unregister.a.b").is_none());
+        assert!(registry.request_from_raw("unregister.a").is_some());
+        assert!(!registry.unregister_raw("unregister.a.b"));
+    }
+
+    #[test]
+    fn request_static_layertag() {
+        const CHILD: StaticLayerTag = StaticLayerTag::new("registry.static.child");
+        let mut registry = LayerTagRegistry::default();
+        assert!(registry.request_static(CHILD).is_none());
+
+        registry.register_raw(CHILD.raw_layertag());
+        assert_eq!(registry.request_static(CHILD), Some(CHILD.layertag()));
+    }
 }
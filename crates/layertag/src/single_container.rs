@@ -7,6 +7,7 @@ use bevy::{
 };
 
 use crate::{
+    bitset::LayerTagBits,
     container_op::{LayerTagContainer, LayerTagContainerCondition, LayerTagContainerOp},
     layertag::LayerTag,
 };
@@ -15,6 +16,7 @@ use crate::{
 #[reflect(Component)]
 pub struct SingleLayerTagContainer {
     layertags: HashSet<LayerTag>,
+    bits: LayerTagBits,
 }
 
 impl LayerTagContainer for SingleLayerTagContainer {
@@ -22,28 +24,35 @@ impl LayerTagContainer for SingleLayerTagContainer {
         Box::new(self.layertags.iter())
     }
 
+    fn layertag_bits(&self) -> &LayerTagBits {
+        &self.bits
+    }
+
     fn exist_layertag(&self, tag: &LayerTag) -> bool {
-        self.layertags.iter().any(|x| x.exact_match(tag))
+        self.bits.exact().contains(tag.id())
     }
 
     fn add_layertags(&mut self, layertags: impl Iterator<Item = LayerTag>) {
         for layertag in layertags {
-            self.layertags.insert(layertag.clone());
+            self.add_layertag(layertag);
         }
     }
 
     fn remove_layertags<'a>(&mut self, layertags: impl Iterator<Item = &'a LayerTag>) {
         for layertag in layertags {
-            self.layertags.remove(layertag);
+            self.remove_layertag(layertag);
         }
     }
 
     fn add_layertag(&mut self, layertag: LayerTag) {
-        self.layertags.insert(layertag.clone());
+        self.bits.insert(layertag.id());
+        self.layertags.insert(layertag);
     }
 
     fn remove_layertag(&mut self, layertag: &LayerTag) {
-        self.layertags.remove(layertag);
+        if self.layertags.remove(layertag) {
+            self.bits.remove(layertag.id());
+        }
     }
 }
 
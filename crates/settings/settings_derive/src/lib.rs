@@ -2,15 +2,44 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(Setting)]
+/// `#[setting(custom_validate)]`时不生成默认的SettingValidate，由类型自己实现。
+#[proc_macro_derive(Setting, attributes(setting))]
 pub fn derive_setting(input: TokenStream) -> TokenStream {
     let derive_input = parse_macro_input!(input as DeriveInput);
 
+    let mut custom_validate = false;
+    for attr in derive_input.attrs.iter() {
+        if !attr.path().is_ident("setting") {
+            continue;
+        }
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("custom_validate") {
+                custom_validate = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported setting attribute"))
+            }
+        });
+        if let Err(e) = result {
+            return e.to_compile_error().into();
+        }
+    }
+
     let ident = derive_input.ident.clone();
     let (impl_generics, ty_generics, where_clause) = derive_input.generics.split_for_impl();
 
+    let validate = if custom_validate {
+        quote!()
+    } else {
+        quote!(
+            impl #impl_generics settings::SettingValidate for #ident #ty_generics #where_clause {}
+        )
+    };
+
     let ts = quote!(
         impl #impl_generics settings::Setting for #ident #ty_generics #where_clause {}
+
+        #validate
     );
 
     ts.into()
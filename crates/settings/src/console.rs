@@ -0,0 +1,598 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use bevy::{
+    prelude::*,
+    reflect::{
+        serde::{TypedReflectDeserializer, TypedReflectSerializer},
+        GetPath, ReflectRef, TypeRegistry,
+    },
+};
+use serde::de::DeserializeSeed;
+use thiserror::Error;
+
+use crate::{
+    launch::LaunchSettingOverrides,
+    load::{InnerSettingHandle, SettingUpdateEvent},
+    persist::PersistSettingEvent,
+    setting_path::SettingsPath,
+    Setting, SettingValidationErrors,
+};
+
+pub const SETTING_COMMAND: &str = "setting";
+const PERSIST_FLAG: &str = "--persist";
+
+const USAGE: &str = "setting list [Type] | get Type.field | set Type.field value [--persist] | reset Type.field [--persist] | save Type";
+
+#[derive(Debug, Error)]
+pub enum SettingCommandError {
+    #[error("usage: {}", USAGE)]
+    Usage,
+    #[error("unknown setting {0}")]
+    UnknownSetting(String),
+    #[error("setting {setting} has no field {path}")]
+    UnknownField { setting: String, path: String },
+    #[error("invalid value for {path}: {message}")]
+    InvalidValue { path: String, message: String },
+    #[error("setting is invalid: {0}")]
+    Invalid(SettingValidationErrors),
+    #[error("setting {0} is not loaded")]
+    NotLoaded(String),
+}
+
+/// 控制台中的配置命令，字段路径为`Type.field`，和启动参数的覆盖相同。
+/// 字段通过类型注册表反射读写，值按照字段的类型以ron格式解析。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingCommand {
+    List {
+        setting: Option<String>,
+    },
+    Get {
+        path: String,
+    },
+    Set {
+        path: String,
+        value: String,
+        persist: bool,
+    },
+    Reset {
+        path: String,
+        persist: bool,
+    },
+    Save {
+        setting: String,
+    },
+}
+
+impl SettingCommand {
+    /// 开头的`setting`可以省略。
+    pub fn parse(line: &str) -> Result<Self, SettingCommandError> {
+        let line = line.trim();
+        let line = line
+            .strip_prefix(SETTING_COMMAND)
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            .unwrap_or(line)
+            .trim_start();
+        let (line, persist) = match line.strip_suffix(PERSIST_FLAG) {
+            Some(line) if line.ends_with(char::is_whitespace) => (line.trim_end(), true),
+            _ => (line, false),
+        };
+
+        let (name, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let (arg, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let value = value.trim();
+
+        let command = match (name, arg, value) {
+            ("list", "", "") => SettingCommand::List { setting: None },
+            ("list", setting, "") => SettingCommand::List {
+                setting: Some(setting.to_string()),
+            },
+            ("get", path, "") if !path.is_empty() => SettingCommand::Get {
+                path: path.to_string(),
+            },
+            ("set", path, value) if !path.is_empty() && !value.is_empty() => SettingCommand::Set {
+                path: path.to_string(),
+                value: value.to_string(),
+                persist,
+            },
+            ("reset", path, "") if !path.is_empty() => SettingCommand::Reset {
+                path: path.to_string(),
+                persist,
+            },
+            ("save", setting, "") if !setting.is_empty() => SettingCommand::Save {
+                setting: setting.to_string(),
+            },
+            _ => return Err(SettingCommandError::Usage),
+        };
+        if persist
+            && !matches!(
+                command,
+                SettingCommand::Set { .. } | SettingCommand::Reset { .. }
+            )
+        {
+            return Err(SettingCommandError::Usage);
+        }
+        Ok(command)
+    }
+
+    fn setting(&self) -> Option<&str> {
+        match self {
+            SettingCommand::List { setting } => setting.as_deref(),
+            SettingCommand::Get { path }
+            | SettingCommand::Set { path, .. }
+            | SettingCommand::Reset { path, .. } => Some(path.split('.').next().unwrap_or(path)),
+            SettingCommand::Save { setting } => Some(setting),
+        }
+    }
+}
+
+/// 执行控制台中输入的一行命令，结果通过SettingCommandOutput返回。
+#[derive(Debug, Clone, Event)]
+pub struct SettingCommandEvent {
+    pub line: String,
+}
+
+impl SettingCommandEvent {
+    pub fn new(line: impl Into<String>) -> Self {
+        Self { line: line.into() }
+    }
+}
+
+#[derive(Debug, Event)]
+pub struct SettingCommandOutput {
+    pub line: String,
+    pub result: Result<String, SettingCommandError>,
+}
+
+type SettingCommandFn =
+    Arc<dyn Fn(&mut World, &SettingCommand) -> Result<String, SettingCommandError> + Send + Sync>;
+
+/// 每个SettingPlugin<S>注册的命令，key为S的short type path。
+#[derive(Default, Resource)]
+pub struct SettingConsoleRegistry {
+    commands: BTreeMap<&'static str, SettingCommandFn>,
+}
+
+impl SettingConsoleRegistry {
+    pub fn register<S>(&mut self)
+    where
+        S: Setting,
+    {
+        self.commands
+            .insert(S::short_type_path(), Arc::new(run_setting_command::<S>));
+    }
+
+    pub fn settings(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.commands.keys().copied()
+    }
+
+    fn get(&self, setting: &str) -> Option<SettingCommandFn> {
+        self.commands
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(setting))
+            .map(|(_, command)| command.clone())
+    }
+}
+
+pub(crate) fn run_setting_commands(world: &mut World) {
+    let events: Vec<_> = world
+        .resource_mut::<Events<SettingCommandEvent>>()
+        .drain()
+        .collect();
+    for event in events {
+        let result = run_setting_command_line(world, &event.line);
+        match &result {
+            Ok(output) => info!("{}\n{}", event.line, output),
+            Err(e) => warn!("{}: {}", event.line, e),
+        }
+        world.send_event(SettingCommandOutput {
+            line: event.line,
+            result,
+        });
+    }
+}
+
+pub fn run_setting_command_line(
+    world: &mut World,
+    line: &str,
+) -> Result<String, SettingCommandError> {
+    let command = SettingCommand::parse(line)?;
+    let Some(setting) = command.setting() else {
+        let registry = world.resource::<SettingConsoleRegistry>();
+        return Ok(registry.settings().collect::<Vec<_>>().join("\n"));
+    };
+    let run = world
+        .resource::<SettingConsoleRegistry>()
+        .get(setting)
+        .ok_or_else(|| SettingCommandError::UnknownSetting(setting.to_string()))?;
+    run(world, &command)
+}
+
+fn run_setting_command<S>(
+    world: &mut World,
+    command: &SettingCommand,
+) -> Result<String, SettingCommandError>
+where
+    S: Setting,
+{
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+    match command {
+        SettingCommand::List { .. } => {
+            let mut lines = Vec::new();
+            list_fields(
+                world.resource::<S>().as_partial_reflect(),
+                String::new(),
+                &registry,
+                &mut lines,
+            );
+            Ok(lines.join("\n"))
+        }
+        SettingCommand::Get { path } => {
+            let setting = world.resource::<S>();
+            let path = resolve(setting, path)?;
+            let value = field(setting, &path)?;
+            Ok(format!("{} = {}", path, display_value(value, &registry)))
+        }
+        SettingCommand::Set {
+            path,
+            value,
+            persist,
+        } => {
+            let setting = world.resource::<S>();
+            let path = resolve(setting, path)?;
+            let value = parse_field_value(field(setting, &path)?, &path, value, &registry)?;
+            set_field::<S>(world, &path, value.as_ref(), *persist, &registry)
+        }
+        SettingCommand::Reset { path, persist } => {
+            let path = resolve(world.resource::<S>(), path)?;
+            // 恢复为游戏配置和启动参数覆盖之后的值。
+            let handle = world.resource::<InnerSettingHandle<S>>();
+            let game = handle
+                .game_handle
+                .as_ref()
+                .and_then(|game| world.resource::<Assets<S>>().get(game))
+                .cloned()
+                .ok_or_else(|| SettingCommandError::NotLoaded(S::short_type_path().to_string()))?;
+            let base = world
+                .resource::<LaunchSettingOverrides>()
+                .apply(game, false);
+            set_field::<S>(world, &path, field(&base, &path)?, *persist, &registry)
+        }
+        SettingCommand::Save { .. } => {
+            persist_setting::<S>(world);
+            Ok(format!("save {}", S::short_type_path()))
+        }
+    }
+}
+
+/// 通过反射修改字段，值的类型不匹配或者校验失败时不修改。
+fn set_field<S>(
+    world: &mut World,
+    path: &str,
+    value: &dyn PartialReflect,
+    persist: bool,
+    registry: &TypeRegistry,
+) -> Result<String, SettingCommandError>
+where
+    S: Setting,
+{
+    let mut setting = world.resource::<S>().clone();
+    setting
+        .reflect_path_mut(path)
+        .map_err(|e| invalid_value(path, e))?
+        .try_apply(value)
+        .map_err(|e| invalid_value(path, e))?;
+    setting.validate().map_err(SettingCommandError::Invalid)?;
+
+    let output = format!(
+        "{} = {}",
+        path,
+        display_value(field(&setting, path)?, registry)
+    );
+    *world.resource_mut::<S>() = setting;
+    world.send_event(SettingUpdateEvent::<S>::default());
+    if persist {
+        persist_setting::<S>(world);
+    }
+    Ok(output)
+}
+
+fn persist_setting<S>(world: &mut World)
+where
+    S: Setting,
+{
+    let event = PersistSettingEvent {
+        persist_path: world.resource::<SettingsPath<S>>().clone(),
+        data: Arc::new(world.resource::<S>().clone()),
+    };
+    world.send_event(event);
+}
+
+/// 按照反射的字段名解析`Type.field`中的字段路径，优先精确匹配，其次忽略大小写。
+fn resolve<S>(setting: &S, path: &str) -> Result<String, SettingCommandError>
+where
+    S: Setting,
+{
+    let unknown = || SettingCommandError::UnknownField {
+        setting: S::short_type_path().to_string(),
+        path: path.to_string(),
+    };
+    let (_, field) = path.split_once('.').ok_or_else(unknown)?;
+
+    let mut current = setting.as_partial_reflect();
+    let mut resolved = Vec::new();
+    for key in field.split('.') {
+        let ReflectRef::Struct(value) = current.reflect_ref() else {
+            return Err(unknown());
+        };
+        let index = (0..value.field_len())
+            .find(|index| value.name_at(*index) == Some(key))
+            .or_else(|| {
+                (0..value.field_len()).find(|index| {
+                    value
+                        .name_at(*index)
+                        .is_some_and(|name| name.eq_ignore_ascii_case(key))
+                })
+            })
+            .ok_or_else(unknown)?;
+        resolved.push(value.name_at(index).ok_or_else(unknown)?.to_string());
+        current = value.field_at(index).ok_or_else(unknown)?;
+    }
+    Ok(resolved.join("."))
+}
+
+fn field<'a, S>(setting: &'a S, path: &str) -> Result<&'a dyn PartialReflect, SettingCommandError>
+where
+    S: Setting,
+{
+    setting
+        .reflect_path(path)
+        .map_err(|_| SettingCommandError::UnknownField {
+            setting: S::short_type_path().to_string(),
+            path: path.to_string(),
+        })
+}
+
+/// 按照字段当前值的类型，从类型注册表中找到反序列化方式解析ron格式的值。
+fn parse_field_value(
+    field: &dyn PartialReflect,
+    path: &str,
+    value: &str,
+    registry: &TypeRegistry,
+) -> Result<Box<dyn PartialReflect>, SettingCommandError> {
+    let registration = field
+        .get_represented_type_info()
+        .and_then(|info| registry.get(info.type_id()))
+        .ok_or_else(|| {
+            invalid_value(
+                path,
+                format!("{} is not registered", field.reflect_type_path()),
+            )
+        })?;
+    let mut deserializer =
+        ron::Deserializer::from_str(value).map_err(|e| invalid_value(path, e))?;
+    TypedReflectDeserializer::new(registration, registry)
+        .deserialize(&mut deserializer)
+        .map_err(|e| invalid_value(path, e))
+}
+
+fn invalid_value(path: &str, message: impl ToString) -> SettingCommandError {
+    SettingCommandError::InvalidValue {
+        path: path.to_string(),
+        message: message.to_string(),
+    }
+}
+
+fn list_fields(
+    value: &dyn PartialReflect,
+    prefix: String,
+    registry: &TypeRegistry,
+    lines: &mut Vec<String>,
+) {
+    match value.reflect_ref() {
+        ReflectRef::Struct(value) if value.field_len() > 0 => {
+            for index in 0..value.field_len() {
+                let (Some(key), Some(value)) = (value.name_at(index), value.field_at(index)) else {
+                    continue;
+                };
+                let path = if prefix.is_empty() {
+                    key.to_string()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                list_fields(value, path, registry, lines);
+            }
+        }
+        _ => lines.push(format!("{} = {}", prefix, display_value(value, registry))),
+    }
+}
+
+fn display_value(value: &dyn PartialReflect, registry: &TypeRegistry) -> String {
+    ron::ser::to_string(&TypedReflectSerializer::new(value, registry))
+        .unwrap_or_else(|_| format!("{:?}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use bevy::{asset::Asset, prelude::*};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        launch::LaunchSettingOverrides,
+        load::{InnerSettingHandle, SettingUpdateEvent},
+        persist::PersistSettingEvent,
+        setting_path::SettingsPath,
+        Setting, SettingValidate, SettingValidationErrors,
+    };
+
+    use super::{
+        run_setting_command_line, SettingCommand, SettingCommandError, SettingConsoleRegistry,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+    enum Scheme {
+        Simple,
+        Neighbor,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+    struct Range {
+        start: f32,
+        end: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource, Asset, Reflect)]
+    struct TestSetting {
+        chunk_depth: u8,
+        range: Range,
+        scheme: Scheme,
+    }
+
+    impl Default for TestSetting {
+        fn default() -> Self {
+            Self {
+                chunk_depth: 5,
+                range: Range {
+                    start: -32.0,
+                    end: 32.0,
+                },
+                scheme: Scheme::Simple,
+            }
+        }
+    }
+
+    impl SettingValidate for TestSetting {
+        fn validate(&self) -> Result<(), SettingValidationErrors> {
+            let mut errors = SettingValidationErrors::default();
+            if self.range.end < self.range.start {
+                errors.push("range.end", "must be greater than start");
+            }
+            errors.into_result()
+        }
+    }
+
+    impl Setting for TestSetting {}
+
+    fn new_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<AppTypeRegistry>();
+        world
+            .resource::<AppTypeRegistry>()
+            .write()
+            .register::<TestSetting>();
+        world.init_resource::<TestSetting>();
+        world.init_resource::<LaunchSettingOverrides>();
+        world.init_resource::<Assets<TestSetting>>();
+        world.init_resource::<Events<SettingUpdateEvent<TestSetting>>>();
+        world.init_resource::<Events<PersistSettingEvent<TestSetting>>>();
+        world.insert_resource(SettingsPath::<TestSetting> {
+            user_config_dir: Some(PathBuf::from("")),
+            ..Default::default()
+        });
+        let game = world
+            .resource_mut::<Assets<TestSetting>>()
+            .add(TestSetting::default());
+        world.insert_resource(InnerSettingHandle::<TestSetting> {
+            game_handle: Some(game),
+            user_handle: None,
+        });
+        let mut registry = SettingConsoleRegistry::default();
+        registry.register::<TestSetting>();
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn parse_setting_command() {
+        assert_eq!(
+            SettingCommand::parse("setting list").unwrap(),
+            SettingCommand::List { setting: None }
+        );
+        assert_eq!(
+            SettingCommand::parse("set TestSetting.range (start: 0.0, end: 1.0) --persist")
+                .unwrap(),
+            SettingCommand::Set {
+                path: "TestSetting.range".to_string(),
+                value: "(start: 0.0, end: 1.0)".to_string(),
+                persist: true,
+            }
+        );
+        assert!(matches!(
+            SettingCommand::parse("setting save TestSetting --persist"),
+            Err(SettingCommandError::Usage)
+        ));
+        assert!(matches!(
+            SettingCommand::parse("setting get"),
+            Err(SettingCommandError::Usage)
+        ));
+    }
+
+    #[test]
+    fn run_setting_console_command() {
+        let mut world = new_world();
+
+        assert_eq!(
+            run_setting_command_line(&mut world, "setting list").unwrap(),
+            "TestSetting"
+        );
+        assert_eq!(
+            run_setting_command_line(&mut world, "setting get testsetting.Range.End").unwrap(),
+            "range.end = 32.0"
+        );
+        assert!(
+            run_setting_command_line(&mut world, "setting list TestSetting")
+                .unwrap()
+                .contains("scheme = Simple")
+        );
+
+        assert_eq!(
+            run_setting_command_line(&mut world, "setting set TestSetting.chunk_depth 6").unwrap(),
+            "chunk_depth = 6"
+        );
+        run_setting_command_line(
+            &mut world,
+            "setting set TestSetting.scheme Neighbor --persist",
+        )
+        .unwrap();
+        let setting = world.resource::<TestSetting>();
+        assert_eq!(setting.chunk_depth, 6);
+        assert_eq!(setting.scheme, Scheme::Neighbor);
+        assert_eq!(
+            world
+                .resource::<Events<SettingUpdateEvent<TestSetting>>>()
+                .len(),
+            2
+        );
+        assert_eq!(
+            world
+                .resource::<Events<PersistSettingEvent<TestSetting>>>()
+                .len(),
+            1
+        );
+
+        // 类型错误和校验失败时不修改。
+        assert!(matches!(
+            run_setting_command_line(&mut world, "setting set TestSetting.chunk_depth deep"),
+            Err(SettingCommandError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            run_setting_command_line(&mut world, "setting set TestSetting.range.end -64.0"),
+            Err(SettingCommandError::Invalid(_))
+        ));
+        assert!(matches!(
+            run_setting_command_line(&mut world, "setting get TestSetting.missing"),
+            Err(SettingCommandError::UnknownField { .. })
+        ));
+        assert!(matches!(
+            run_setting_command_line(&mut world, "setting get OtherSetting.value"),
+            Err(SettingCommandError::UnknownSetting(_))
+        ));
+        assert_eq!(world.resource::<TestSetting>().chunk_depth, 6);
+
+        run_setting_command_line(&mut world, "setting reset TestSetting.chunk_depth").unwrap();
+        assert_eq!(world.resource::<TestSetting>().chunk_depth, 5);
+    }
+}
@@ -0,0 +1,412 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::{
+    migrate::{parse_untyped, SettingMigrations},
+    setting_path::SettingsPath,
+    Setting,
+};
+
+#[derive(Debug, Error)]
+pub enum SettingFileError {
+    #[error("could not read setting: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse setting: {0}")]
+    RonParse(#[from] ron::error::SpannedError),
+    #[error("could not serialize setting: {0}")]
+    Ron(#[from] ron::Error),
+    #[error("could not convert setting: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("setting version {version} is newer than current version {current}")]
+    NewerVersion { version: u32, current: u32 },
+}
+
+/// 用户配置，只保存和游戏配置不同的字段。
+/// 结构体按字段递归比较，数组和其他值不同时整体覆盖。
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct SettingOverride<S>
+where
+    S: Setting,
+{
+    pub diff: Value,
+    _phantom: PhantomData<S>,
+}
+
+impl<S> Default for SettingOverride<S>
+where
+    S: Setting,
+{
+    fn default() -> Self {
+        Self::new(Value::Object(Map::new()))
+    }
+}
+
+impl<S> SettingOverride<S>
+where
+    S: Setting,
+{
+    pub fn new(diff: Value) -> Self {
+        Self {
+            diff,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn from_setting(game: &S, user: &S) -> Result<Self, SettingFileError> {
+        Ok(Self::new(setting_diff(game, user)?))
+    }
+
+    pub fn apply(&self, game: &S) -> Result<S, SettingFileError> {
+        apply_setting_diff(game, &self.diff)
+    }
+
+    /// 重置字段为游戏配置中的值，返回是否存在这个字段的覆盖。
+    pub fn reset_field(&mut self, path: &str) -> bool {
+        reset_diff_field(&mut self.diff, path)
+    }
+
+    /// 字段使用other中的覆盖，other中没有这个字段的覆盖时使用游戏配置中的值。
+    pub fn restore_field(&mut self, other: &SettingOverride<S>, path: &str) {
+        self.reset_field(path);
+        if let Some(value) = other.diff.pointer(&field_pointer(path)) {
+            set_diff_field(&mut self.diff, path, value.clone());
+        }
+    }
+
+    /// 之前保存的完整配置按照游戏配置转换为diff，包含游戏配置所有的字段时认为是完整配置。
+    /// 返回None时不是完整配置，之后新增的字段不在完整配置中，按diff使用也能得到相同的配置。
+    pub fn migrate_legacy(&self, game: &S) -> Result<Option<Self>, SettingFileError> {
+        let Value::Object(game_value) = serde_json::to_value(game)? else {
+            return Ok(None);
+        };
+        let is_legacy = self.diff.as_object().is_some_and(|diff| {
+            !game_value.is_empty() && game_value.keys().all(|key| diff.contains_key(key))
+        });
+        if !is_legacy {
+            return Ok(None);
+        }
+        let legacy = self.apply(game)?;
+        Self::from_setting(game, &legacy).map(Some)
+    }
+
+    /// 之前保存的完整配置也按diff读取，加载后由`migrate_legacy`转换。
+    pub fn from_ron(
+        content: &str,
+        migrations: &SettingMigrations<S>,
+    ) -> Result<Self, SettingFileError> {
+        Ok(Self::new(migrations.parse(content)?))
+    }
+
+    pub fn to_ron(&self, migrations: &SettingMigrations<S>) -> Result<String, SettingFileError> {
+        migrations.to_ron(&self.diff)
+    }
+}
+
+/// 只保留user中和game不同的字段。
+pub fn setting_diff<S>(game: &S, user: &S) -> Result<Value, SettingFileError>
+where
+    S: Serialize,
+{
+    let game = serde_json::to_value(game)?;
+    let user = serde_json::to_value(user)?;
+    Ok(value_diff(&game, &user).unwrap_or_else(|| Value::Object(Map::new())))
+}
+
+pub fn apply_setting_diff<S>(game: &S, diff: &Value) -> Result<S, SettingFileError>
+where
+    S: Serialize + DeserializeOwned,
+{
+    let mut value = serde_json::to_value(game)?;
+    merge_value(&mut value, diff);
+    Ok(serde_json::from_value(value)?)
+}
+
+/// 不存在的父字段创建为map。
+pub fn set_diff_field(diff: &mut Value, path: &str, value: Value) {
+    let mut current = diff;
+    for key in path.split('.') {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        let Value::Object(map) = current else {
+            unreachable!()
+        };
+        current = map.entry(key).or_insert(Value::Null);
+    }
+    *current = value;
+}
+
+pub(crate) fn field_pointer(path: &str) -> String {
+    format!("/{}", path.replace('.', "/"))
+}
+
+/// 字段路径和SettingFieldError相同，使用`.`分隔。
+pub fn reset_diff_field(diff: &mut Value, path: &str) -> bool {
+    let Some((parent, field)) = path.rsplit_once('.') else {
+        return diff
+            .as_object_mut()
+            .is_some_and(|map| map.remove(path).is_some());
+    };
+
+    let pointer = field_pointer(parent);
+    let removed = diff
+        .pointer_mut(&pointer)
+        .and_then(Value::as_object_mut)
+        .is_some_and(|map| map.remove(field).is_some());
+    // 父字段没有其他覆盖时一起移除。
+    if removed
+        && diff
+            .pointer(&pointer)
+            .and_then(Value::as_object)
+            .is_some_and(Map::is_empty)
+    {
+        reset_diff_field(diff, parent);
+    }
+    removed
+}
+
+/// 按照配置中的字段名字匹配，不区分大小写。
+pub(crate) fn resolve_field_path(setting: &Value, path: &str) -> Option<String> {
+    let mut current = setting;
+    let mut keys = Vec::new();
+    for key in path.split('.') {
+        let map = current.as_object()?;
+        let (key, value) = map
+            .get_key_value(key)
+            .or_else(|| map.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)))?;
+        keys.push(key.as_str());
+        current = value;
+    }
+    Some(keys.join("."))
+}
+
+/// 按照ron解析字段的值，枚举的变体名字和解析失败的值作为字符串。
+pub(crate) fn parse_field_value(value: &str) -> Value {
+    let value = value.trim();
+    let is_identifier = value
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_identifier && !matches!(value, "true" | "false" | "None") {
+        return Value::String(value.to_string());
+    }
+    parse_untyped(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
+
+fn value_diff(game: &Value, user: &Value) -> Option<Value> {
+    match (game, user) {
+        (Value::Object(game), Value::Object(user)) if !is_variant_changed(game, user) => {
+            let diff: Map<String, Value> = user
+                .iter()
+                .filter_map(|(key, user)| match game.get(key) {
+                    Some(game) => value_diff(game, user).map(|diff| (key.clone(), diff)),
+                    None => Some((key.clone(), user.clone())),
+                })
+                .collect();
+            (!diff.is_empty()).then(|| Value::Object(diff))
+        }
+        _ => (game != user).then(|| user.clone()),
+    }
+}
+
+fn merge_value(value: &mut Value, diff: &Value) {
+    match (value, diff) {
+        (Value::Object(value), Value::Object(diff)) if !is_variant_changed(value, diff) => {
+            for (key, diff) in diff.iter() {
+                match value.get_mut(key) {
+                    Some(value) => merge_value(value, diff),
+                    None => {
+                        value.insert(key.clone(), diff.clone());
+                    }
+                }
+            }
+        }
+        (value, diff) => *value = diff.clone(),
+    }
+}
+
+/// 枚举序列化为只有一个key的map，切换变体时整体覆盖。
+fn is_variant_changed(a: &Map<String, Value>, b: &Map<String, Value>) -> bool {
+    a.len() == 1 && b.len() == 1 && a.keys().next() != b.keys().next()
+}
+
+pub(crate) struct SettingOverrideLoader<S>
+where
+    S: Setting,
+{
+    extensions: Vec<&'static str>,
+    migrations: SettingMigrations<S>,
+}
+
+impl<S> SettingOverrideLoader<S>
+where
+    S: Setting,
+{
+    pub fn new(migrations: SettingMigrations<S>) -> Self {
+        Self {
+            extensions: vec![SettingsPath::<S>::extension().leak()],
+            migrations,
+        }
+    }
+}
+
+impl<S> AssetLoader for SettingOverrideLoader<S>
+where
+    S: Setting,
+{
+    type Asset = SettingOverride<S>;
+    type Settings = ();
+    type Error = SettingFileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        SettingOverride::from_ron(&String::from_utf8_lossy(&bytes), &self.migrations)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Asset, prelude::Resource, reflect::Reflect};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::{migrate::SettingMigrations, Setting, SettingValidate};
+
+    use super::SettingOverride;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+    enum Scheme {
+        Simple,
+        Neighbor { radius: u32 },
+        Far { distance: f32 },
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+    struct Range {
+        start: f32,
+        end: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource, Asset, Reflect)]
+    struct TestSetting {
+        size: f32,
+        name: Option<String>,
+        range: Range,
+        scheme: Scheme,
+        areas: Vec<u32>,
+    }
+
+    impl Default for TestSetting {
+        fn default() -> Self {
+            Self {
+                size: 16.0,
+                name: None,
+                range: Range {
+                    start: -32.0,
+                    end: 32.0,
+                },
+                scheme: Scheme::Simple,
+                areas: vec![1, 2, 3],
+            }
+        }
+    }
+
+    impl SettingValidate for TestSetting {}
+
+    impl Setting for TestSetting {}
+
+    #[test]
+    fn diff_round_trip() {
+        let game = TestSetting::default();
+        let mut user = game.clone();
+        user.name = Some("user".to_string());
+        user.range.end = 64.0;
+        user.scheme = Scheme::Neighbor { radius: 2 };
+        user.areas = vec![4];
+
+        let diff = SettingOverride::from_setting(&game, &user).unwrap();
+        assert_eq!(
+            diff.diff,
+            json!({
+                "name": "user",
+                "range": { "end": 64.0 },
+                "scheme": { "Neighbor": { "radius": 2 } },
+                "areas": [4],
+            })
+        );
+
+        let migrations = SettingMigrations::default();
+        let content = diff.to_ron(&migrations).unwrap();
+        let loaded = SettingOverride::from_ron(&content, &migrations).unwrap();
+        assert_eq!(loaded.diff, diff.diff);
+        assert_eq!(loaded.apply(&game).unwrap(), user);
+
+        // 游戏配置修改的默认值，没有被用户覆盖的字段使用新的值。
+        let mut new_game = game.clone();
+        new_game.size = 32.0;
+        new_game.range.start = -64.0;
+        let merged = loaded.apply(&new_game).unwrap();
+        assert_eq!(merged.size, 32.0);
+        assert_eq!(merged.range.start, -64.0);
+        assert_eq!(merged.range.end, 64.0);
+
+        // 切换枚举变体。
+        let mut other = user.clone();
+        other.scheme = Scheme::Far { distance: 8.0 };
+        let diff = SettingOverride::from_setting(&user, &other).unwrap();
+        assert_eq!(diff.apply(&user).unwrap(), other);
+
+        let same = SettingOverride::from_setting(&game, &game).unwrap();
+        assert_eq!(same.diff, json!({}));
+        assert_eq!(same.apply(&game).unwrap(), game);
+    }
+
+    #[test]
+    fn reset_field_and_legacy_file() {
+        let game = TestSetting::default();
+        let mut user = game.clone();
+        user.size = 8.0;
+        user.range.end = 64.0;
+
+        let mut diff = SettingOverride::from_setting(&game, &user).unwrap();
+        assert!(!diff.reset_field("range.start"));
+        assert!(diff.reset_field("range.end"));
+        assert_eq!(diff.diff, json!({ "size": 8.0 }));
+        assert!(diff.reset_field("size"));
+        assert_eq!(diff.apply(&game).unwrap(), game);
+
+        // 之前保存的完整配置。
+        let content = ron::ser::to_string(&user).unwrap();
+        let legacy = SettingOverride::from_ron(&content, &SettingMigrations::default()).unwrap();
+        assert_eq!(legacy.apply(&game).unwrap(), user);
+        let migrated = legacy.migrate_legacy(&game).unwrap().unwrap();
+        assert_eq!(
+            migrated.diff,
+            json!({ "size": 8.0, "range": { "end": 64.0 } })
+        );
+        assert!(migrated.migrate_legacy(&game).unwrap().is_none());
+
+        // 迁移后没有被用户修改的字段使用游戏配置新的值。
+        let mut new_game = game.clone();
+        new_game.areas = vec![5];
+        assert_eq!(migrated.apply(&new_game).unwrap().areas, vec![5]);
+    }
+}
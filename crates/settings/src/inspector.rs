@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_inspector_egui::bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::console::{
+    SettingCommand, SettingCommandEvent, SettingCommandOutput, SettingConsoleRegistry,
+};
+
+const HISTORY_LEN: usize = 64;
+
+/// 配置的控制台和编辑窗口，通过SettingCommandEvent修改配置，需要在SettingsPlugin之后添加。
+#[derive(Debug, Default)]
+pub struct SettingInspectorPlugin;
+
+impl Plugin for SettingInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin);
+        }
+        app.init_resource::<SettingInspector>().add_systems(
+            Update,
+            (
+                toggle_setting_inspector,
+                read_setting_command_output,
+                setting_inspector_ui,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct SettingInspector {
+    pub open: bool,
+    pub toggle_key: KeyCode,
+    /// 修改之后保存到用户配置。
+    pub persist: bool,
+    input: String,
+    history: Vec<String>,
+    /// setting => (字段路径, 值)，来自list命令的输出。
+    fields: BTreeMap<String, Vec<(String, String)>>,
+    /// 正在编辑的值，key为`Type.field`。
+    value_buffers: BTreeMap<String, String>,
+}
+
+impl Default for SettingInspector {
+    fn default() -> Self {
+        Self {
+            open: false,
+            toggle_key: KeyCode::F10,
+            persist: false,
+            input: String::new(),
+            history: Vec::new(),
+            fields: BTreeMap::new(),
+            value_buffers: BTreeMap::new(),
+        }
+    }
+}
+
+impl SettingInspector {
+    fn push_history(&mut self, line: String) {
+        self.history.push(line);
+        if self.history.len() > HISTORY_LEN {
+            self.history.remove(0);
+        }
+    }
+}
+
+fn toggle_setting_inspector(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut inspector: ResMut<SettingInspector>,
+    registry: Res<SettingConsoleRegistry>,
+    mut command_event: EventWriter<SettingCommandEvent>,
+) {
+    if !keyboard_input.just_pressed(inspector.toggle_key) {
+        return;
+    }
+    inspector.open = !inspector.open;
+    if inspector.open {
+        for setting in registry.settings() {
+            command_event.send(SettingCommandEvent::new(format!(
+                "setting list {}",
+                setting
+            )));
+        }
+    }
+}
+
+/// list命令的输出更新字段，修改字段之后重新list。
+fn read_setting_command_output(
+    mut inspector: ResMut<SettingInspector>,
+    mut outputs: EventReader<SettingCommandOutput>,
+    mut command_event: EventWriter<SettingCommandEvent>,
+) {
+    for output in outputs.read() {
+        let command = SettingCommand::parse(&output.line).ok();
+        match (&command, &output.result) {
+            (
+                Some(SettingCommand::List {
+                    setting: Some(setting),
+                }),
+                Ok(result),
+            ) => {
+                let fields = result
+                    .lines()
+                    .filter_map(|line| line.split_once(" = "))
+                    .map(|(path, value)| (path.to_string(), value.to_string()))
+                    .collect();
+                inspector.fields.insert(setting.clone(), fields);
+                continue;
+            }
+            (
+                Some(SettingCommand::Set { path, .. } | SettingCommand::Reset { path, .. }),
+                Ok(_),
+            ) => {
+                if let Some((setting, _)) = path.split_once('.') {
+                    inspector.value_buffers.remove(path);
+                    command_event.send(SettingCommandEvent::new(format!(
+                        "setting list {}",
+                        setting
+                    )));
+                }
+            }
+            _ => {}
+        }
+
+        let line = match &output.result {
+            Ok(result) => format!("> {}\n{}", output.line, result),
+            Err(e) => format!("> {}\n{}", output.line, e),
+        };
+        inspector.push_history(line);
+    }
+}
+
+fn setting_inspector_ui(
+    mut contexts: EguiContexts,
+    mut inspector: ResMut<SettingInspector>,
+    mut command_event: EventWriter<SettingCommandEvent>,
+) {
+    if !inspector.open {
+        return;
+    }
+    let inspector = inspector.as_mut();
+    let mut open = inspector.open;
+
+    egui::Window::new("Settings")
+        .open(&mut open)
+        .default_width(420.0)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let response = ui.text_edit_singleline(&mut inspector.input);
+                let submit = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Run").clicked() || submit) && !inspector.input.trim().is_empty() {
+                    let line = std::mem::take(&mut inspector.input);
+                    command_event.send(SettingCommandEvent::new(line));
+                }
+                ui.checkbox(&mut inspector.persist, "persist");
+            });
+
+            egui::ScrollArea::vertical()
+                .id_salt("setting_inspector_history")
+                .max_height(120.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in inspector.history.iter() {
+                        ui.monospace(line);
+                    }
+                });
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .id_salt("setting_inspector_fields")
+                .show(ui, |ui| {
+                    let persist = if inspector.persist { " --persist" } else { "" };
+                    for (setting, fields) in inspector.fields.iter() {
+                        egui::CollapsingHeader::new(setting.as_str()).show(ui, |ui| {
+                            egui::Grid::new(setting.as_str())
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for (path, value) in fields.iter() {
+                                        let key = format!("{}.{}", setting, path);
+                                        ui.label(path.as_str());
+                                        let buffer = inspector
+                                            .value_buffers
+                                            .entry(key.clone())
+                                            .or_insert_with(|| value.clone());
+                                        ui.text_edit_singleline(buffer);
+                                        if ui.button("Set").clicked() {
+                                            command_event.send(SettingCommandEvent::new(format!(
+                                                "setting set {} {}{}",
+                                                key, buffer, persist
+                                            )));
+                                        }
+                                        if ui.button("Reset").clicked() {
+                                            command_event.send(SettingCommandEvent::new(format!(
+                                                "setting reset {}{}",
+                                                key, persist
+                                            )));
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    }
+                });
+        });
+    inspector.open = open;
+}
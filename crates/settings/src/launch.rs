@@ -0,0 +1,323 @@
+use std::fmt::{Display, Formatter};
+
+use bevy::prelude::*;
+use serde_json::{Map, Value};
+
+use crate::{
+    diff::{apply_setting_diff, parse_field_value, resolve_field_path, set_diff_field},
+    Setting,
+};
+
+pub const SETTING_ENV_PREFIX: &str = "ATOM_SETTING__";
+pub const SETTING_ARG: &str = "--set";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LaunchOverrideSource {
+    CommandLine,
+    Env(String),
+}
+
+impl Display for LaunchOverrideSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LaunchOverrideSource::CommandLine => write!(f, "{}", SETTING_ARG),
+            LaunchOverrideSource::Env(name) => write!(f, "env {}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchSettingOverride {
+    /// Setting的short type path。
+    pub setting: String,
+    /// 字段路径使用`.`分隔。
+    pub path: String,
+    pub value: String,
+    pub source: LaunchOverrideSource,
+}
+
+/// 启动参数和环境变量中的配置，在游戏配置和用户配置之上，不会保存。
+/// `--set TerrainSetting.chunk_depth=6`，`ATOM_SETTING__TerrainSetting__chunk_depth=6`。
+/// 类型和字段的名字不区分大小写，环境变量先应用，启动参数后应用。
+#[derive(Debug, Clone, Default, Resource)]
+pub struct LaunchSettingOverrides {
+    pub overrides: Vec<LaunchSettingOverride>,
+}
+
+impl LaunchSettingOverrides {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1), std::env::vars())
+    }
+
+    pub fn parse(
+        args: impl IntoIterator<Item = String>,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut vars: Vec<_> = vars
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let key = name.strip_prefix(SETTING_ENV_PREFIX)?.replace("__", ".");
+                Some((name, key, value))
+            })
+            .collect();
+        vars.sort();
+
+        let mut overrides = Vec::new();
+        for (name, key, value) in vars {
+            Self::push(&mut overrides, &key, value, LaunchOverrideSource::Env(name));
+        }
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let entry = if arg == SETTING_ARG {
+                args.next()
+            } else {
+                arg.strip_prefix(SETTING_ARG)
+                    .and_then(|entry| entry.strip_prefix('='))
+                    .map(str::to_string)
+            };
+            let Some(entry) = entry else {
+                continue;
+            };
+            match entry.split_once('=') {
+                Some((key, value)) => Self::push(
+                    &mut overrides,
+                    key,
+                    value.to_string(),
+                    LaunchOverrideSource::CommandLine,
+                ),
+                None => warn!(
+                    "invalid setting override {}, expect Type.field=value",
+                    entry
+                ),
+            }
+        }
+
+        Self { overrides }
+    }
+
+    fn push(
+        overrides: &mut Vec<LaunchSettingOverride>,
+        key: &str,
+        value: String,
+        source: LaunchOverrideSource,
+    ) {
+        match key.trim().split_once('.') {
+            Some((setting, path)) if !setting.is_empty() && !path.is_empty() => {
+                overrides.push(LaunchSettingOverride {
+                    setting: setting.to_string(),
+                    path: path.to_string(),
+                    value,
+                    source,
+                })
+            }
+            _ => warn!(
+                "invalid setting override {} from {}, expect Type.field",
+                key, source
+            ),
+        }
+    }
+
+    pub fn iter_setting<S>(&self) -> impl Iterator<Item = &LaunchSettingOverride>
+    where
+        S: Setting,
+    {
+        self.overrides
+            .iter()
+            .filter(|o| o.setting.eq_ignore_ascii_case(S::short_type_path()))
+    }
+
+    /// 生成覆盖层的diff，返回diff和每个覆盖在配置中实际的字段路径。
+    /// 配置中不存在的字段忽略。
+    pub fn setting_diff<S>(&self, setting: &Value) -> (Value, Vec<(String, &LaunchSettingOverride)>)
+    where
+        S: Setting,
+    {
+        let mut diff = Value::Object(Map::new());
+        let mut applied = Vec::new();
+        for launch in self.iter_setting::<S>() {
+            if let Some(path) = resolve_field_path(setting, &launch.path) {
+                set_diff_field(&mut diff, &path, parse_field_value(&launch.value));
+                applied.push((path, launch));
+            }
+        }
+        (diff, applied)
+    }
+
+    /// 被覆盖的字段路径，保存用户配置时这些字段不保存。
+    pub fn setting_paths<S>(&self, setting: &S) -> Vec<String>
+    where
+        S: Setting,
+    {
+        if self.iter_setting::<S>().next().is_none() {
+            return Vec::new();
+        }
+        serde_json::to_value(setting).map_or(Vec::new(), |value| {
+            let (_, applied) = self.setting_diff::<S>(&value);
+            applied.into_iter().map(|(path, _)| path).collect()
+        })
+    }
+
+    /// 在合并后的配置上应用覆盖层，report时输出每个覆盖的字段和来源。
+    pub(crate) fn apply<S>(&self, setting: S, report: bool) -> S
+    where
+        S: Setting,
+    {
+        if self.iter_setting::<S>().next().is_none() {
+            return setting;
+        }
+        let value = match serde_json::to_value(&setting) {
+            Ok(value) => value,
+            Err(e) => {
+                error!("convert setting {} failed: {}", S::short_type_path(), e);
+                return setting;
+            }
+        };
+
+        let (diff, applied) = self.setting_diff::<S>(&value);
+        if report {
+            for launch in self.iter_setting::<S>() {
+                match applied.iter().find(|(_, x)| std::ptr::eq(*x, launch)) {
+                    Some((path, _)) => info!(
+                        "setting override {}.{} = {} from {}",
+                        S::short_type_path(),
+                        path,
+                        launch.value,
+                        launch.source
+                    ),
+                    None => warn!(
+                        "setting {} has no field {}, ignore override from {}",
+                        S::short_type_path(),
+                        launch.path,
+                        launch.source
+                    ),
+                }
+            }
+        }
+
+        apply_setting_diff(&setting, &diff).unwrap_or_else(|e| {
+            error!(
+                "apply setting {} overrides failed: {}",
+                S::short_type_path(),
+                e
+            );
+            setting
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Asset, prelude::Resource, reflect::Reflect};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    use crate::{
+        diff::{apply_setting_diff, SettingOverride},
+        Setting, SettingValidate,
+    };
+
+    use super::{LaunchOverrideSource, LaunchSettingOverrides};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+    enum Scheme {
+        Simple,
+        Neighbor,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+    struct Range {
+        start: f32,
+        end: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource, Asset, Reflect)]
+    struct TestSetting {
+        chunk_depth: u8,
+        range: Range,
+        scheme: Scheme,
+    }
+
+    impl Default for TestSetting {
+        fn default() -> Self {
+            Self {
+                chunk_depth: 5,
+                range: Range {
+                    start: -32.0,
+                    end: 32.0,
+                },
+                scheme: Scheme::Simple,
+            }
+        }
+    }
+
+    impl SettingValidate for TestSetting {}
+
+    impl Setting for TestSetting {}
+
+    #[test]
+    fn launch_override_layer() {
+        let launch = LaunchSettingOverrides::parse(
+            [
+                "server",
+                "--set",
+                "TestSetting.chunk_depth=6",
+                "--set=testsetting.scheme=Neighbor",
+                "--set",
+                "TestSetting.missing=1",
+                "--set",
+                "OtherSetting.value=1",
+            ]
+            .map(String::from),
+            [
+                ("ATOM_SETTING__TESTSETTING__RANGE__END", "64"),
+                ("ATOM_SETTING__TestSetting__chunk_depth", "4"),
+                ("PATH", "/usr/bin"),
+            ]
+            .map(|(k, v)| (k.to_string(), v.to_string())),
+        );
+        assert_eq!(launch.overrides.len(), 6);
+        assert_eq!(launch.iter_setting::<TestSetting>().count(), 5);
+
+        let game = TestSetting::default();
+        let (diff, applied) =
+            launch.setting_diff::<TestSetting>(&serde_json::to_value(&game).unwrap());
+        assert_eq!(
+            diff,
+            json!({ "chunk_depth": 6, "range": { "end": 64 }, "scheme": "Neighbor" })
+        );
+        assert_eq!(
+            applied
+                .iter()
+                .map(|(path, launch)| (path.as_str(), &launch.source))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    "range.end",
+                    &LaunchOverrideSource::Env("ATOM_SETTING__TESTSETTING__RANGE__END".into())
+                ),
+                (
+                    "chunk_depth",
+                    &LaunchOverrideSource::Env("ATOM_SETTING__TestSetting__chunk_depth".into())
+                ),
+                ("chunk_depth", &LaunchOverrideSource::CommandLine),
+                ("scheme", &LaunchOverrideSource::CommandLine),
+            ]
+        );
+
+        let setting: TestSetting = apply_setting_diff(&game, &diff).unwrap();
+        assert_eq!(setting.chunk_depth, 6);
+        assert_eq!(setting.range.end, 64.0);
+        assert_eq!(setting.scheme, Scheme::Neighbor);
+
+        // 保存时覆盖的字段使用用户配置中的值。
+        let mut user_setting = game.clone();
+        user_setting.range.end = 48.0;
+        let user = SettingOverride::from_setting(&game, &user_setting).unwrap();
+        let mut persisted = SettingOverride::from_setting(&game, &setting).unwrap();
+        for (path, _) in applied.iter() {
+            persisted.restore_field(&user, path);
+        }
+        assert_eq!(persisted.diff, json!({ "range": { "end": 48.0 } }));
+    }
+}
@@ -5,12 +5,13 @@
 ///      支持读取，也可以覆盖。
 ///   2. 用户在运行时修改配置后生成的配置
 ///       需要能够指定目录。(Saved/Config路径, 默认保存路径)如何配置热加载: 添加新的AssetSource
-///       必须能够读取和保存。
-///   3. 根据配置路径保存数据, 导出数据到任意地方
-///   4. 如何避免保存数据命令比较延迟，导致后续写入数据，导致写入数据错误。写入时复制一份传递到事件中。
-///   5. 如何避免读取数据(热加载，手动reload)，设置值，之后读取的配置覆盖了设置的值。(热加载，或者手动加载)
+///       必须能够读取和保存。只保存和游戏配置不同的字段，游戏配置修改的默认值对用户配置生效。
+///   3. 启动参数和环境变量的覆盖，在用户配置之上，不保存。见LaunchSettingOverrides。
+///   4. 根据配置路径保存数据, 导出数据到任意地方
+///   5. 如何避免保存数据命令比较延迟，导致后续写入数据，导致写入数据错误。写入时复制一份传递到事件中。
+///   6. 如何避免读取数据(热加载，手动reload)，设置值，之后读取的配置覆盖了设置的值。(热加载，或者手动加载)
 ///      设置值之后，立即写数据，这会触发热加载，会进行第二次读取数据的操作，避免新设置的值被之前读取的值覆盖。
-///   6. 如何处理热加载， 如果是自己保存的数据，不触发热更新。如果是外部修改，触发热更新。
+///   7. 如何处理热加载， 如果是自己保存的数据，不触发热更新。如果是外部修改，触发热更新。
 ///      都触发不影响正确性，数据相同，只是读取开销。
 ///      filename is TypePath::short_type_path() + ".toml"
 ///
@@ -24,13 +25,22 @@
 /// 添加三个事件：保存配置。加载配置(运行时插入resource使用)。配置修改了的事件(用于热加载的后续逻辑处理)。
 
 /// 由于不支持，运行时添加plugin，运行时添加resource暂不支持。支不支持没有什么大的影响。。。。。
+pub mod console;
+pub mod diff;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod launch;
 pub mod load;
+pub mod migrate;
 pub mod persist;
 pub mod setting_path;
-pub mod toml_diff;
+pub mod validate;
 
 use bevy::utils::TypeIdMap;
 pub use settings_derive::Setting;
+pub use validate::{
+    SettingFieldError, SettingValidate, SettingValidationErrors, SettingValidationFailed,
+};
 
 use std::fmt::Debug;
 use std::path::PathBuf;
@@ -38,16 +48,22 @@ use std::path::PathBuf;
 use atom_utils::async_event::AsyncEventPlugin;
 use bevy::asset::io::{AssetSourceBuilder, AssetSourceId};
 use bevy::prelude::*;
+use bevy::reflect::GetTypeRegistration;
 
-use bevy_common_assets::ron::RonAssetPlugin;
+use console::{
+    run_setting_commands, SettingCommandEvent, SettingCommandOutput, SettingConsoleRegistry,
+};
+use diff::{SettingOverride, SettingOverrideLoader};
+use launch::LaunchSettingOverrides;
 use load::{create_game_setting, handle_persist_setting_end_event, SettingUpdateEvent};
+use migrate::{migrate_setting_files, SettingLoader, SettingMigrations};
 use persist::PersistSettingEndEvent;
 use serde::{Deserialize, Serialize};
 
 use crate::load::{
     refresh_final_settings, start_load_settings, InnerSettingHandle, SettingLoadStageWrap,
 };
-use crate::persist::{persist, PersistSettingEvent};
+use crate::persist::{persist, reset_setting_field, PersistSettingEvent, ResetSettingFieldEvent};
 use crate::setting_path::SettingsPath;
 
 #[derive(Debug)]
@@ -101,29 +117,34 @@ impl Plugin for SettingsPlugin {
             std::fs::create_dir_all(&self.user_source_config.base_path).unwrap();
         }
 
-        app.insert_resource(SettingsLoadStatus {
-            status: TypeIdMap::default(),
-        })
-        .insert_resource(SettingsSource {
-            game_source_id: self.game_source_config.source_id.clone(),
-            game_source_path: self.game_source_config.base_path.clone(),
-            user_source_id: self.user_source_config.source_id.clone(),
-            user_source_path: self.user_source_config.base_path.clone(),
-        })
-        .register_asset_source(
-            self.game_source_config.source_id.clone(),
-            AssetSourceBuilder::platform_default(
-                self.game_source_config.base_path.to_str().unwrap(),
-                None,
-            ),
-        )
-        .register_asset_source(
-            self.user_source_config.source_id.clone(),
-            AssetSourceBuilder::platform_default(
-                self.user_source_config.base_path.to_str().unwrap(),
-                None,
-            ),
-        );
+        app.insert_resource(LaunchSettingOverrides::from_env())
+            .init_resource::<SettingConsoleRegistry>()
+            .add_event::<SettingCommandEvent>()
+            .add_event::<SettingCommandOutput>()
+            .add_systems(Update, run_setting_commands)
+            .insert_resource(SettingsLoadStatus {
+                status: TypeIdMap::default(),
+            })
+            .insert_resource(SettingsSource {
+                game_source_id: self.game_source_config.source_id.clone(),
+                game_source_path: self.game_source_config.base_path.clone(),
+                user_source_id: self.user_source_config.source_id.clone(),
+                user_source_path: self.user_source_config.base_path.clone(),
+            })
+            .register_asset_source(
+                self.game_source_config.source_id.clone(),
+                AssetSourceBuilder::platform_default(
+                    self.game_source_config.base_path.to_str().unwrap(),
+                    None,
+                ),
+            )
+            .register_asset_source(
+                self.user_source_config.source_id.clone(),
+                AssetSourceBuilder::platform_default(
+                    self.user_source_config.base_path.to_str().unwrap(),
+                    None,
+                ),
+            );
     }
 }
 
@@ -135,6 +156,21 @@ where
 {
     // 全局默认配置，必须设置。相对于全局的SettingsPlugin的source path。
     pub paths: SettingsPath<S>,
+    pub migrations: SettingMigrations<S>,
+}
+
+impl<S> SettingPlugin<S>
+where
+    S: Setting,
+{
+    /// 添加从当前版本到下一个版本的迁移，见SettingMigrations。
+    pub fn with_migration(
+        mut self,
+        migration: impl Fn(&mut serde_json::Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations = self.migrations.with_migration(migration);
+        self
+    }
 }
 
 impl<S> Default for SettingPlugin<S>
@@ -148,6 +184,7 @@ where
                 user_config_dir: Some(PathBuf::from("")),
                 ..Default::default()
             },
+            migrations: SettingMigrations::default(),
         }
     }
 }
@@ -162,19 +199,29 @@ where
             "must insert SettingsPlugin before SettingPlugin<S>"
         );
 
-        let extension = SettingsPath::<S>::extension();
-        app
-            // .add_plugins(TomlAssetPlugin::<S>::new(&[extension.leak()]))
-            .add_plugins(RonAssetPlugin::<S>::new(&[extension.leak()]))
+        app.world_mut()
+            .resource_mut::<SettingConsoleRegistry>()
+            .register::<S>();
+        app.register_type::<S>()
             .insert_resource(self.paths.clone())
+            .insert_resource(self.migrations.clone())
             .init_resource::<InnerSettingHandle<S>>()
             .init_resource::<SettingLoadStageWrap<S>>()
             .init_resource::<S>()
             .init_asset::<S>()
+            // 用户配置和游戏配置的后缀相同，根据加载的asset类型选择loader。
+            .init_asset::<SettingOverride<S>>()
+            .register_asset_loader(SettingLoader::new(self.migrations.clone()))
+            .register_asset_loader(SettingOverrideLoader::new(self.migrations.clone()))
             .add_event::<PersistSettingEvent<S>>()
+            .add_event::<ResetSettingFieldEvent<S>>()
             .add_plugins(AsyncEventPlugin::<PersistSettingEndEvent<S>>::default())
             .add_event::<SettingUpdateEvent<S>>()
-            .add_systems(Startup, create_game_setting::<S>)
+            .add_event::<SettingValidationFailed<S>>()
+            .add_systems(
+                Startup,
+                (migrate_setting_files::<S>, create_game_setting::<S>).chain(),
+            )
             .add_systems(
                 PreUpdate,
                 (
@@ -184,19 +231,23 @@ where
                 )
                     .chain(),
             )
-            .add_systems(Last, persist::<S>);
+            .add_systems(Last, (reset_setting_field::<S>, persist::<S>).chain());
     }
 }
 
 /// settings limits:
 ///   1. all fields must be Optional
 pub trait Setting:
-    Resource + Clone + Serialize + TypePath + Default + for<'a> Deserialize<'a> + Asset + Debug
+    Resource
+    + Clone
+    + Serialize
+    + TypePath
+    + Reflect
+    + GetTypeRegistration
+    + Default
+    + for<'a> Deserialize<'a>
+    + Asset
+    + Debug
+    + SettingValidate
 {
 }
-
-/// settings validate
-/// TODO: Add Setting Validate Feature
-pub trait SettingValidate {
-    fn validate(&self) -> bool;
-}
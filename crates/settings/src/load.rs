@@ -2,11 +2,13 @@ use bevy::{
     asset::{AssetPath, LoadState},
     prelude::*,
 };
-use serde_merge::omerge;
 
 use crate::{
+    diff::SettingOverride,
+    launch::LaunchSettingOverrides,
     persist::{PersistSettingEndEvent, PersistSettingEvent},
     setting_path::SettingsPath,
+    validate::{validate_with_fallback, SettingValidationFailed},
     Setting, SettingsLoadStatus, SettingsSource,
 };
 
@@ -82,7 +84,7 @@ where
     S: Setting,
 {
     pub game_handle: Option<Handle<S>>,
-    pub user_handle: Option<Handle<S>>,
+    pub user_handle: Option<Handle<SettingOverride<S>>>,
 }
 
 pub(crate) fn handle_persist_setting_end_event<S>(
@@ -188,40 +190,64 @@ pub(crate) fn start_load_settings<S>(
     }
 }
 
+/// 游戏配置应用用户配置的diff和启动参数的覆盖，校验失败的字段回退到上一次有效的值。
+/// 第一次加载时上一次有效的值为游戏配置，热加载时为当前的配置。
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn refresh_final_settings<S>(
-    mut asset_event_reader: EventReader<AssetEvent<S>>,
+    mut game_event_reader: EventReader<AssetEvent<S>>,
+    mut user_event_reader: EventReader<AssetEvent<SettingOverride<S>>>,
     handle: Res<InnerSettingHandle<S>>,
     mut load_stage: ResMut<SettingLoadStageWrap<S>>,
     assets: Res<Assets<S>>,
+    user_assets: Res<Assets<SettingOverride<S>>>,
+    launch: Res<LaunchSettingOverrides>,
     mut setting_update_event: EventWriter<SettingUpdateEvent<S>>,
+    mut validation_failed_event: EventWriter<SettingValidationFailed<S>>,
+    mut persist_event: EventWriter<PersistSettingEvent<S>>,
+    paths: Res<SettingsPath<S>>,
     mut s: ResMut<S>,
     mut settings_status: ResMut<SettingsLoadStatus>,
 ) where
     S: Setting,
 {
-    asset_event_reader.read().for_each(|event| match event {
-        AssetEvent::Added { id } | AssetEvent::Modified { id } => {
-            if let Some(game) = &handle.game_handle {
-                if game.id() == *id {
-                    if let SettingLoadStage::Loading(loaded) = &mut load_stage.setting_load_stage {
-                        loaded.game = LoadState::Loaded;
-                    }
-                }
-            }
+    let game_changed = game_event_reader
+        .read()
+        .filter_map(loaded_asset_id)
+        .filter(|id| handle.game_handle.as_ref().is_some_and(|x| x.id() == *id))
+        .count()
+        > 0;
+    let user_changed = user_event_reader
+        .read()
+        .filter_map(loaded_asset_id)
+        .filter(|id| handle.user_handle.as_ref().is_some_and(|x| x.id() == *id))
+        .count()
+        > 0;
+
+    // 热加载，两个配置都已经加载过。
+    if (game_changed || user_changed) && load_stage.setting_load_stage == SettingLoadStage::LoadOver
+    {
+        load_stage.setting_load_stage = SettingLoadStage::Loading(SettingLoadState {
+            game: LoadState::Loaded,
+            user: LoadState::Loaded,
+        });
+    }
 
-            if let Some(user) = &handle.user_handle {
-                if user.id() == *id {
-                    if let SettingLoadStage::Loading(loaded) = &mut load_stage.setting_load_stage {
-                        loaded.user = LoadState::Loaded;
-                    }
-                }
-            }
+    if let SettingLoadStage::Loading(loaded) = &mut load_stage.setting_load_stage {
+        if game_changed {
+            loaded.game = LoadState::Loaded;
         }
-        _ => {}
-    });
+        if user_changed {
+            loaded.user = LoadState::Loaded;
+        }
+    }
 
     if let SettingLoadStage::Loading(loaded) = &load_stage.setting_load_stage {
         if loaded.game.is_loaded() && loaded.user.is_loaded() {
+            let status = settings_status
+                .status
+                .get_mut(&TypeId::of::<S>())
+                .expect("must have");
+
             let game_asset = handle
                 .game_handle
                 .as_ref()
@@ -229,18 +255,61 @@ pub(crate) fn refresh_final_settings<S>(
             let user_asset = handle
                 .user_handle
                 .as_ref()
-                .and_then(|user| assets.get(user));
+                .and_then(|user| user_assets.get(user));
             if let (Some(game), Some(user)) = (game_asset, user_asset) {
-                *s = omerge(game, user).unwrap();
+                let fallback = if *status {
+                    s.clone()
+                } else if let Err(errors) = game.validate() {
+                    error!(
+                        "game setting {} is invalid: {}",
+                        S::short_type_path(),
+                        errors
+                    );
+                    s.clone()
+                } else {
+                    game.clone()
+                };
+
+                // 之前保存的完整配置转换为diff，配置更新后保存。
+                let migrated = user.migrate_legacy(game).unwrap_or_else(|e| {
+                    error!(
+                        "migrate user setting {} failed: {}",
+                        S::short_type_path(),
+                        e
+                    );
+                    None
+                });
+                let user = migrated.as_ref().unwrap_or(user);
+
+                let merged = user.apply(game).unwrap_or_else(|e| {
+                    error!("apply user setting {} failed: {}", S::short_type_path(), e);
+                    game.clone()
+                });
+                let merged = launch.apply(merged, !*status);
+                let (setting, errors) = validate_with_fallback(merged.clone(), &fallback);
+                if let Some(errors) = errors {
+                    error!(
+                        "user setting {} is invalid, fallback to the last valid value: {}",
+                        S::short_type_path(),
+                        errors
+                    );
+                    validation_failed_event.send(SettingValidationFailed {
+                        errors,
+                        rejected: merged,
+                    });
+                }
+                *s = setting;
                 setting_update_event.send_default();
+                if migrated.is_some() {
+                    info!("migrate legacy user setting {}", S::short_type_path());
+                    persist_event.send(PersistSettingEvent {
+                        persist_path: paths.clone(),
+                        data: Arc::new(s.clone()),
+                    });
+                }
             }
 
             load_stage.setting_load_stage = SettingLoadStage::LoadOver;
-
-            let status = settings_status
-                .status
-                .get_mut(&TypeId::of::<S>())
-                .expect("must have");
             *status = true;
 
             info!("setting load over: {:?}", *s);
@@ -248,6 +317,13 @@ pub(crate) fn refresh_final_settings<S>(
     }
 }
 
+fn loaded_asset_id<A: Asset>(event: &AssetEvent<A>) -> Option<AssetId<A>> {
+    match event {
+        AssetEvent::Added { id } | AssetEvent::Modified { id } => Some(*id),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use bevy::{asset::Asset, reflect::TypePath};
@@ -0,0 +1,439 @@
+use std::{
+    fmt::{Debug, Formatter},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use ron::ser::PrettyConfig;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{diff::SettingFileError, setting_path::SettingsPath, Setting, SettingsSource};
+
+/// 保存的配置中记录版本的字段，没有这个字段的配置版本为0。
+pub const SETTING_VERSION_KEY: &str = "setting_version";
+
+type SettingMigrationFn = Arc<dyn Fn(&mut Value) + Send + Sync>;
+
+/// 配置的迁移，第N个迁移把版本N的配置转换为版本N+1，当前版本为迁移的数量。
+/// 迁移同时用于游戏配置和用户配置的diff，用户配置中只有修改过的字段，迁移需要允许字段不存在。
+/// 值和diff相同，使用serde_json::Value，枚举为变体名字的字符串或者只有一个key的map。
+#[derive(Resource)]
+pub struct SettingMigrations<S>
+where
+    S: Setting,
+{
+    migrations: Vec<SettingMigrationFn>,
+    _phantom: PhantomData<S>,
+}
+
+impl<S> Default for SettingMigrations<S>
+where
+    S: Setting,
+{
+    fn default() -> Self {
+        Self {
+            migrations: Vec::new(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S> Clone for SettingMigrations<S>
+where
+    S: Setting,
+{
+    fn clone(&self) -> Self {
+        Self {
+            migrations: self.migrations.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S> Debug for SettingMigrations<S>
+where
+    S: Setting,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SettingMigrations")
+            .field("current_version", &self.current_version())
+            .finish()
+    }
+}
+
+impl<S> SettingMigrations<S>
+where
+    S: Setting,
+{
+    /// 添加从当前版本到下一个版本的迁移。
+    pub fn with_migration(
+        mut self,
+        migration: impl Fn(&mut Value) + Send + Sync + 'static,
+    ) -> Self {
+        self.migrations.push(Arc::new(migration));
+        self
+    }
+
+    pub fn current_version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+
+    pub fn migrate(&self, value: &mut Value, version: u32) -> Result<(), SettingFileError> {
+        let current = self.current_version();
+        if version > current {
+            return Err(SettingFileError::NewerVersion { version, current });
+        }
+        for migration in self.migrations[version as usize..].iter() {
+            migration(value);
+        }
+        Ok(())
+    }
+
+    /// 读取配置并迁移到当前版本。
+    /// 当前版本的配置先按照S解析，避免ron的非类型化解析丢失枚举的变体名字。
+    /// 用户配置只有修改过的字段，按S解析后只保留文件中存在的字段。
+    pub fn parse(&self, content: &str) -> Result<Value, SettingFileError> {
+        let untyped = parse_untyped(content);
+        let version = untyped
+            .as_ref()
+            .map_or(0, |value| setting_version(value).unwrap_or(0));
+        if version == self.current_version() {
+            if let (Ok(untyped), Ok(setting)) = (untyped.as_ref(), ron::from_str::<S>(content)) {
+                let mut value = serde_json::to_value(setting)?;
+                retain_present_fields(&mut value, untyped);
+                return Ok(value);
+            }
+        }
+
+        let mut value = untyped?;
+        if let Some(map) = value.as_object_mut() {
+            map.remove(SETTING_VERSION_KEY);
+        }
+        self.migrate(&mut value, version)?;
+        Ok(value)
+    }
+
+    /// 保存为map的格式，第一个字段为当前版本。
+    pub fn to_ron(&self, value: &Value) -> Result<String, SettingFileError> {
+        let mut versioned = serde_json::Map::new();
+        versioned.insert(
+            SETTING_VERSION_KEY.to_string(),
+            Value::from(self.current_version()),
+        );
+        if let Value::Object(map) = value {
+            versioned.extend(map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        Ok(ron::ser::to_string_pretty(
+            &Value::Object(versioned),
+            PrettyConfig::default(),
+        )?)
+    }
+
+    /// 迁移文件，旧的文件备份为`<file>.v<version>.bak`，返回备份的路径。
+    pub fn migrate_file(&self, path: &Path) -> Result<Option<PathBuf>, SettingFileError> {
+        let content = std::fs::read_to_string(path)?;
+        let version = parse_untyped(&content)
+            .ok()
+            .and_then(|value| setting_version(&value))
+            .unwrap_or(0);
+        if version == self.current_version() {
+            return Ok(None);
+        }
+
+        let value = self.parse(&content)?;
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".v{}.bak", version));
+        let backup = PathBuf::from(backup);
+        std::fs::copy(path, &backup)?;
+        std::fs::write(path, self.to_ron(&value)?)?;
+        Ok(Some(backup))
+    }
+}
+
+/// ron解析到serde的Content时保留枚举变体的名字，untagged先解析为Content。
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UntypedSetting {
+    Value(Value),
+}
+
+pub(crate) fn parse_untyped(content: &str) -> Result<Value, ron::error::SpannedError> {
+    ron::from_str::<UntypedSetting>(content).map(|UntypedSetting::Value(value)| value)
+}
+
+/// 按S解析时缺少的Option和default字段会被补全，作为用户配置的diff时会覆盖游戏配置的值。
+fn retain_present_fields(typed: &mut Value, untyped: &Value) {
+    let (Value::Object(typed), Value::Object(untyped)) = (typed, untyped) else {
+        return;
+    };
+    // 枚举变体是只有一个key的map，非类型化解析没有变体名字，整体保留。
+    if typed.len() == 1 && !untyped.is_empty() && !typed.keys().any(|key| untyped.contains_key(key))
+    {
+        return;
+    }
+
+    typed.retain(|key, _| untyped.contains_key(key));
+    for (key, value) in typed.iter_mut() {
+        retain_present_fields(value, &untyped[key]);
+    }
+}
+
+fn setting_version(value: &Value) -> Option<u32> {
+    value
+        .get(SETTING_VERSION_KEY)
+        .and_then(Value::as_u64)
+        .map(|version| version as u32)
+}
+
+pub(crate) fn migrate_setting_files<S>(
+    paths: Res<SettingsPath<S>>,
+    settings_source: Res<SettingsSource>,
+    migrations: Res<SettingMigrations<S>>,
+) where
+    S: Setting,
+{
+    let files = [
+        paths
+            .get_game_config_path()
+            .map(|path| settings_source.game_source_path.join(path)),
+        paths
+            .get_user_config_path()
+            .map(|path| settings_source.user_source_path.join(path)),
+    ];
+    for path in files.into_iter().flatten().filter(|path| path.exists()) {
+        match migrations.migrate_file(&path) {
+            Ok(Some(backup)) => info!(
+                "migrate setting file {:?} to version {}, backup: {:?}",
+                path,
+                migrations.current_version(),
+                backup
+            ),
+            Ok(None) => {}
+            Err(e) => error!("migrate setting file {:?} failed: {}", path, e),
+        }
+    }
+}
+
+/// 游戏配置的loader，和用户配置相同，读取时迁移到当前版本。
+pub(crate) struct SettingLoader<S>
+where
+    S: Setting,
+{
+    extensions: Vec<&'static str>,
+    migrations: SettingMigrations<S>,
+}
+
+impl<S> SettingLoader<S>
+where
+    S: Setting,
+{
+    pub fn new(migrations: SettingMigrations<S>) -> Self {
+        Self {
+            extensions: vec![SettingsPath::<S>::extension().leak()],
+            migrations,
+        }
+    }
+}
+
+impl<S> AssetLoader for SettingLoader<S>
+where
+    S: Setting,
+{
+    type Asset = S;
+    type Settings = ();
+    type Error = SettingFileError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let value = self.migrations.parse(&String::from_utf8_lossy(&bytes))?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::Asset, prelude::Resource, reflect::Reflect};
+    use serde::{Deserialize, Serialize};
+    use serde_json::{json, Value};
+
+    use crate::{
+        diff::{SettingFileError, SettingOverride},
+        Setting, SettingValidate,
+    };
+
+    use super::{SettingMigrations, SETTING_VERSION_KEY};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Reflect)]
+    enum Scheme {
+        Simple,
+        Neighbor,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Reflect)]
+    struct Range {
+        start: f32,
+        end: f32,
+    }
+
+    /// 版本2。版本0中为`size`和`height: (f32, f32)`，版本1中为`chunk_size`和`height: (f32, f32)`。
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Resource, Asset, Reflect)]
+    struct TestSetting {
+        chunk_size: f32,
+        height: Range,
+        scheme: Scheme,
+    }
+
+    impl Default for TestSetting {
+        fn default() -> Self {
+            Self {
+                chunk_size: 16.0,
+                height: Range {
+                    start: -32.0,
+                    end: 32.0,
+                },
+                scheme: Scheme::Simple,
+            }
+        }
+    }
+
+    impl SettingValidate for TestSetting {}
+
+    impl Setting for TestSetting {}
+
+    /// 所有字段都可以省略，稀疏的用户配置也能按照类型解析。
+    #[derive(
+        Debug, Clone, Default, PartialEq, Serialize, Deserialize, Resource, Asset, Reflect,
+    )]
+    struct OptionalSetting {
+        name: Option<String>,
+        scale: Option<f32>,
+        scheme: Option<Scheme>,
+    }
+
+    impl SettingValidate for OptionalSetting {}
+
+    impl Setting for OptionalSetting {}
+
+    fn migrations() -> SettingMigrations<TestSetting> {
+        SettingMigrations::default()
+            .with_migration(|value| {
+                if let Some(size) = value.as_object_mut().and_then(|map| map.remove("size")) {
+                    value["chunk_size"] = size;
+                }
+            })
+            .with_migration(|value| {
+                if let Some(Value::Array(height)) = value.get("height").cloned() {
+                    value["height"] = json!({ "start": height[0], "end": height[1] });
+                }
+            })
+    }
+
+    #[test]
+    fn migrate_multi_step() {
+        let migrations = migrations();
+        assert_eq!(migrations.current_version(), 2);
+
+        // 版本0，没有版本字段。
+        let value = migrations
+            .parse("(size: 32.0, height: (-8.0, 8.0), scheme: \"Neighbor\")")
+            .unwrap();
+        let setting: TestSetting = serde_json::from_value(value).unwrap();
+        assert_eq!(setting.chunk_size, 32.0);
+        assert_eq!(
+            setting.height,
+            Range {
+                start: -8.0,
+                end: 8.0
+            }
+        );
+        assert_eq!(setting.scheme, Scheme::Neighbor);
+
+        // 版本1的用户配置diff，只迁移存在的字段。
+        let value = migrations
+            .parse("{\"setting_version\": 1, \"height\": (0.0, 4.0)}")
+            .unwrap();
+        assert_eq!(value, json!({ "height": { "start": 0.0, "end": 4.0 } }));
+
+        // 当前版本，保存之后读取不变。
+        let value = serde_json::to_value(setting.clone()).unwrap();
+        let content = migrations.to_ron(&value).unwrap();
+        assert!(content.contains(SETTING_VERSION_KEY));
+        assert_eq!(migrations.parse(&content).unwrap(), value);
+        // 手写的当前版本配置按照S解析。
+        let content = "(setting_version: 2, chunk_size: 32.0, height: (start: -8.0, end: 8.0), scheme: Neighbor)";
+        assert_eq!(migrations.parse(content).unwrap(), value);
+
+        assert!(matches!(
+            migrations.parse("{\"setting_version\": 3}"),
+            Err(SettingFileError::NewerVersion {
+                version: 3,
+                current: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn migrate_file_with_backup() {
+        let dir = std::env::temp_dir().join(format!("settings_migrate_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("user.TestSetting.ron");
+        let old = "{\"size\": 64.0}";
+        std::fs::write(&path, old).unwrap();
+
+        let migrations = migrations();
+        let backup = migrations.migrate_file(&path).unwrap().unwrap();
+        assert_eq!(backup, dir.join("user.TestSetting.ron.v0.bak"));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), old);
+        assert_eq!(
+            migrations
+                .parse(&std::fs::read_to_string(&path).unwrap())
+                .unwrap(),
+            json!({ "chunk_size": 64.0 })
+        );
+        assert!(migrations.migrate_file(&path).unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parse_partial_user_file() {
+        let migrations = SettingMigrations::<OptionalSetting>::default();
+        let value = migrations
+            .parse("(setting_version: 0, scale: Some(2.0), scheme: Some(Neighbor))")
+            .unwrap();
+        assert_eq!(value, json!({ "scale": 2.0, "scheme": "Neighbor" }));
+
+        // 文件中没有的字段使用游戏配置的值。
+        let game = OptionalSetting {
+            name: Some("game".to_string()),
+            scale: Some(1.0),
+            scheme: None,
+        };
+        assert_eq!(
+            SettingOverride::<OptionalSetting>::new(value)
+                .apply(&game)
+                .unwrap(),
+            OptionalSetting {
+                name: Some("game".to_string()),
+                scale: Some(2.0),
+                scheme: Some(Scheme::Neighbor),
+            }
+        );
+    }
+}
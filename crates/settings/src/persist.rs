@@ -1,9 +1,15 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{marker::PhantomData, path::PathBuf, sync::Arc};
 
-use crate::{setting_path::SettingsPath, Setting, SettingsSource};
+use crate::{
+    diff::{SettingFileError, SettingOverride},
+    launch::LaunchSettingOverrides,
+    load::InnerSettingHandle,
+    migrate::SettingMigrations,
+    setting_path::SettingsPath,
+    Setting, SettingsSource,
+};
 use atom_utils::async_event::EventChannelSender;
 use bevy::{prelude::*, tasks::IoTaskPool};
-use ron::ser::PrettyConfig;
 
 #[derive(Event, Default)]
 pub struct PersistSettingEvent<S>
@@ -15,6 +21,29 @@ where
     pub data: Arc<S>,
 }
 
+/// 移除用户配置中字段的覆盖，恢复为游戏配置中的值，并保存用户配置。
+/// 字段路径使用`.`分隔，例如`height_visibility_range.end`。
+#[derive(Event)]
+pub struct ResetSettingFieldEvent<S>
+where
+    S: Setting,
+{
+    pub path: String,
+    _phantom: PhantomData<S>,
+}
+
+impl<S> ResetSettingFieldEvent<S>
+where
+    S: Setting,
+{
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 #[derive(Event, Default)]
 pub struct PersistSettingEndEvent<S>
 where
@@ -26,16 +55,107 @@ where
     pub create_user_setting: bool,
 }
 
+pub(crate) fn reset_setting_field<S>(
+    mut reader: EventReader<ResetSettingFieldEvent<S>>,
+    mut persist_event: EventWriter<PersistSettingEvent<S>>,
+    handle: Res<InnerSettingHandle<S>>,
+    assets: Res<Assets<S>>,
+    paths: Res<SettingsPath<S>>,
+    mut s: ResMut<S>,
+) where
+    S: Setting,
+{
+    if reader.is_empty() {
+        return;
+    }
+    let Some(game) = handle.game_handle.as_ref().and_then(|x| assets.get(x)) else {
+        warn!(
+            "reset setting {} field before game setting loaded",
+            S::short_type_path()
+        );
+        reader.clear();
+        return;
+    };
+
+    let mut user = match SettingOverride::from_setting(game, &s) {
+        Ok(user) => user,
+        Err(e) => {
+            error!("diff setting {} failed: {}", S::short_type_path(), e);
+            reader.clear();
+            return;
+        }
+    };
+    for event in reader.read() {
+        if !user.reset_field(&event.path) {
+            info!(
+                "setting {} field {} is not overridden",
+                S::short_type_path(),
+                event.path
+            );
+        }
+    }
+
+    match user.apply(game) {
+        Ok(setting) => {
+            *s = setting;
+            persist_event.send(PersistSettingEvent {
+                persist_path: paths.clone(),
+                data: Arc::new(s.clone()),
+            });
+        }
+        Err(e) => error!("reset setting {} failed: {}", S::short_type_path(), e),
+    }
+}
+
+/// 用户配置保存为和游戏配置的diff，游戏配置只在文件不存在时创建。
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn persist<S>(
     mut reader: EventReader<PersistSettingEvent<S>>,
     event_channel_sender: Res<EventChannelSender<PersistSettingEndEvent<S>>>,
     settings_source: Res<SettingsSource>,
+    handle: Res<InnerSettingHandle<S>>,
+    assets: Res<Assets<S>>,
+    user_assets: Res<Assets<SettingOverride<S>>>,
+    launch: Res<LaunchSettingOverrides>,
+    migrations: Res<SettingMigrations<S>>,
 ) where
     S: Setting,
 {
     for event in reader.read() {
         let path = event.persist_path.clone();
-        let data = event.data.clone();
+        let data = event.data.as_ref();
+        // 游戏配置还没有加载时，即将创建的游戏配置就是data，已经存在的用户配置不覆盖。
+        let (user, overwrite_user) = match handle.game_handle.as_ref().and_then(|x| assets.get(x)) {
+            Some(game) => {
+                let user = SettingOverride::from_setting(game, data).and_then(|mut user| {
+                    // 启动参数和环境变量覆盖的字段不保存，使用之前的用户配置。
+                    let loaded = handle.user_handle.as_ref().and_then(|x| user_assets.get(x));
+                    let loaded = match loaded {
+                        Some(loaded) => loaded
+                            .migrate_legacy(game)?
+                            .unwrap_or_else(|| loaded.clone()),
+                        None => SettingOverride::default(),
+                    };
+                    for path in launch.setting_paths(data) {
+                        user.restore_field(&loaded, &path);
+                    }
+                    Ok(user)
+                });
+                (user, true)
+            }
+            None => (Ok(SettingOverride::default()), false),
+        };
+        let user = user.and_then(|user| user.to_ron(&migrations));
+        let game = serde_json::to_value(data)
+            .map_err(SettingFileError::from)
+            .and_then(|game| migrations.to_ron(&game));
+        let (user, game) = match (user, game) {
+            (Ok(user), Ok(game)) => (user, game),
+            (Err(e), _) | (_, Err(e)) => {
+                error!("serialize setting {} failed: {}", S::short_type_path(), e);
+                continue;
+            }
+        };
         if let Ok(sender) = event_channel_sender.lock() {
             let sender = sender.clone();
             let thread_pool = IoTaskPool::get();
@@ -43,11 +163,11 @@ pub(crate) fn persist<S>(
             let user_source_path = settings_source.user_source_path.clone();
             thread_pool
                 .spawn(async move {
-                    fn save_setting_to_path<S: Setting>(
+                    fn save_setting_to_path(
                         source_path: PathBuf,
                         dir: &PathBuf,
                         filename: PathBuf,
-                        data: &S,
+                        settings_str: String,
                     ) {
                         if std::fs::create_dir_all(dir).is_err() {
                             error!(
@@ -56,13 +176,6 @@ pub(crate) fn persist<S>(
                             );
                         }
 
-                        let settings_str = ron::ser::to_string_pretty(data, PrettyConfig::default()).unwrap_or_else(|_| {
-                            panic!(
-                                "Couldn't serialize the settings to toml {:?}",
-                                dir.as_os_str()
-                            )
-                        });
-
                         std::fs::write(source_path.join(&filename), settings_str).unwrap_or_else(
                         |err|
                             panic!("couldn't persist the settings {:?} while trying to write the string tg disk: {:?}", filename.as_os_str(), err)
@@ -74,21 +187,27 @@ pub(crate) fn persist<S>(
                     let mut create_user_setting = false;
                     let mut create_game_setting = false;
                     if let Some(ref user_config_dir) = path.user_config_dir {
-                        save_setting_to_path(
-                            user_source_path,
-                            user_config_dir,
-                            path.get_user_config_path().unwrap(),
-                            &*data,
-                        );
+                        let user_config_path = path.get_user_config_path().unwrap();
+                        if overwrite_user || !user_source_path.join(&user_config_path).exists() {
+                            save_setting_to_path(
+                                user_source_path,
+                                user_config_dir,
+                                user_config_path,
+                                user,
+                            );
+                        }
                         create_user_setting = true;
                     }
                     if let Some(ref game_config_dir) = path.game_config_dir {
-                        save_setting_to_path(
-                            game_source_path,
-                            game_config_dir,
-                            path.get_game_config_path().unwrap(),
-                            &*data,
-                        );
+                        let game_config_path = path.get_game_config_path().unwrap();
+                        if !game_source_path.join(&game_config_path).exists() {
+                            save_setting_to_path(
+                                game_source_path,
+                                game_config_dir,
+                                game_config_path,
+                                game,
+                            );
+                        }
                         create_game_setting = true;
                     }
 
@@ -0,0 +1,172 @@
+use std::fmt::{Display, Formatter};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+use crate::Setting;
+
+/// 字段路径使用`.`分隔，数组使用下标，例如`height_visibility_range.end`和`rand_area_setting.0`。
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{path}: {message}")]
+pub struct SettingFieldError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingValidationErrors(pub Vec<SettingFieldError>);
+
+impl SettingValidationErrors {
+    pub fn push(&mut self, path: impl Into<String>, message: impl Into<String>) {
+        self.0.push(SettingFieldError {
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<(), SettingValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl Display for SettingValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (index, error) in self.0.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SettingValidationErrors {}
+
+/// 配置加载和热加载时校验，默认不校验。
+/// 需要自定义校验时，在derive Setting的类型上添加`#[setting(custom_validate)]`，然后实现这个trait。
+pub trait SettingValidate {
+    fn validate(&self) -> Result<(), SettingValidationErrors> {
+        Ok(())
+    }
+}
+
+/// 用户配置校验失败时发送，配置中无效的字段已经回退到上一次有效的值。
+#[derive(Debug, Event)]
+pub struct SettingValidationFailed<S>
+where
+    S: Setting,
+{
+    pub errors: SettingValidationErrors,
+    /// 合并后没有通过校验的配置。
+    pub rejected: S,
+}
+
+/// 无效的字段替换为fallback中的值，替换后仍然无效时(比如字段之间的约束)使用整个fallback。
+pub fn validate_with_fallback<S>(setting: S, fallback: &S) -> (S, Option<SettingValidationErrors>)
+where
+    S: SettingValidate + Serialize + DeserializeOwned + Clone,
+{
+    let Err(errors) = setting.validate() else {
+        return (setting, None);
+    };
+
+    let recovered = replace_fields(&setting, fallback, &errors)
+        .filter(|recovered| recovered.validate().is_ok())
+        .unwrap_or_else(|| fallback.clone());
+    (recovered, Some(errors))
+}
+
+fn replace_fields<S>(setting: &S, fallback: &S, errors: &SettingValidationErrors) -> Option<S>
+where
+    S: Serialize + DeserializeOwned,
+{
+    let mut value = serde_json::to_value(setting).ok()?;
+    let fallback = serde_json::to_value(fallback).ok()?;
+    for error in errors.0.iter() {
+        let pointer = format!("/{}", error.path.replace('.', "/"));
+        *value.pointer_mut(&pointer)? = fallback.pointer(&pointer)?.clone();
+    }
+    serde_json::from_value(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::{validate_with_fallback, SettingValidate, SettingValidationErrors};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Range {
+        start: f32,
+        end: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TestSetting {
+        size: f32,
+        range: Range,
+        max: f32,
+    }
+
+    impl SettingValidate for TestSetting {
+        fn validate(&self) -> Result<(), SettingValidationErrors> {
+            let mut errors = SettingValidationErrors::default();
+            if self.size.log2().fract() != 0.0 {
+                errors.push("size", "must be 2^n");
+            }
+            if self.range.end < self.max {
+                errors.push("range.end", "must be greater than or equal to max");
+            }
+            errors.into_result()
+        }
+    }
+
+    fn valid() -> TestSetting {
+        TestSetting {
+            size: 16.0,
+            range: Range {
+                start: 0.0,
+                end: 32.0,
+            },
+            max: 16.0,
+        }
+    }
+
+    #[test]
+    fn fallback_invalid_fields() {
+        let (setting, errors) = validate_with_fallback(valid(), &valid());
+        assert_eq!(setting, valid());
+        assert!(errors.is_none());
+
+        let mut invalid = valid();
+        invalid.size = 15.0;
+        invalid.range.start = -8.0;
+        invalid.range.end = 8.0;
+        let (setting, errors) = validate_with_fallback(invalid, &valid());
+        let errors = errors.unwrap();
+        assert_eq!(
+            errors.0.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            vec!["size", "range.end"]
+        );
+        assert_eq!(setting.size, 16.0);
+        assert_eq!(setting.range.start, -8.0);
+        assert_eq!(setting.range.end, 32.0);
+
+        // 替换range.end之后仍然小于max，使用整个fallback。
+        let mut invalid = valid();
+        invalid.max = 64.0;
+        let (setting, errors) = validate_with_fallback(invalid, &valid());
+        assert!(errors.is_some());
+        assert_eq!(setting, valid());
+    }
+}
@@ -54,7 +54,7 @@ impl WorldTemperature {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize, Reflect)]
 pub enum TerrainType {
     Seabed,
     Plain,
@@ -69,7 +69,7 @@ pub enum TerrainType {
 
 // 高度范围，湿度范围，温度范围，地形类型
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
 pub struct RangeSelector<T> {
     pub ranges: Vec<Range<f32>>,
     pub values: Vec<T>,
@@ -98,7 +98,7 @@ impl<T> Index<f32> for RangeSelector<T> {
     }
 }
 
-#[derive(Setting, Resource, Debug, Clone, Serialize, Deserialize, TypePath, Asset)]
+#[derive(Setting, Resource, Debug, Clone, Serialize, Deserialize, Reflect, Asset)]
 pub struct TerrainTypeSetting {
     pub terrain_selector: RangeSelector<RangeSelector<RangeSelector<TerrainType>>>,
 }
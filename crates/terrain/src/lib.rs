@@ -19,6 +19,8 @@ pub mod setting;
 pub mod tables;
 pub mod utils;
 
+use std::ops::RangeInclusive;
+
 use bevy::{prelude::*, render::extract_resource::ExtractResourcePlugin};
 use chunk_mgr::plugin::TerrainChunkPlugin;
 use ecology::EcologyPlugin;
@@ -35,6 +37,9 @@ pub struct TerrainSubsystemPlugin;
 impl Plugin for TerrainSubsystemPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(SettingPlugin::<TerrainSetting>::default())
+            // RangeInclusive的反射是opaque的，注册serde后控制台才能读写这个字段。
+            .register_type_data::<RangeInclusive<f32>, ReflectSerialize>()
+            .register_type_data::<RangeInclusive<f32>, ReflectDeserialize>()
             .configure_sets(
                 Update,
                 (
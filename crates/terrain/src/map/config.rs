@@ -11,14 +11,14 @@ use settings::Setting;
 
 use crate::setting::TerrainSetting;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
 pub struct TerrainMapAreaHeightPointSetting {
     pub rand_point_num: Range<usize>,
     pub rand_point_radius: Range<usize>,
     pub rand_point_height: Range<f64>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Reflect)]
 pub struct TerrainMapAreaSetting {
     pub rand_area_range_percent: Range<Vec2>,
     pub rand_area_num: Range<usize>,
@@ -26,7 +26,7 @@ pub struct TerrainMapAreaSetting {
 }
 
 #[derive(
-    Setting, Resource, Debug, Clone, Serialize, Deserialize, TypePath, Asset, ExtractResource,
+    Setting, Resource, Debug, Clone, Serialize, Deserialize, Reflect, Asset, ExtractResource,
 )]
 pub struct TerrainMapSetting {
     pub grid_num: usize,
@@ -1,7 +1,10 @@
 pub mod compute_height;
 pub mod config;
 
-use std::{f64::consts::PI, ops::Not};
+use std::{
+    f64::consts::PI,
+    ops::{Not, Range},
+};
 
 use atom_utils::{
     math::{points_in_triangle, triangle_interpolation},
@@ -63,6 +66,13 @@ pub struct TerrainMapPlugin;
 impl Plugin for TerrainMapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(SettingPlugin::<TerrainMapSetting>::default())
+            // Range的反射是opaque的，注册serde后控制台才能读写这些字段。
+            .register_type_data::<Range<Vec2>, ReflectSerialize>()
+            .register_type_data::<Range<Vec2>, ReflectDeserialize>()
+            .register_type_data::<Range<usize>, ReflectSerialize>()
+            .register_type_data::<Range<usize>, ReflectDeserialize>()
+            .register_type_data::<Range<f64>, ReflectSerialize>()
+            .register_type_data::<Range<f64>, ReflectDeserialize>()
             .insert_resource(TerrainMapContext::new(1234))
             .insert_resource(TerrainInfoMap::default())
             .add_plugins(ExtractResourcePlugin::<TerrainInfoMap>::default())
@@ -980,7 +990,10 @@ pub fn generate_map_image(
     if let Some(x) = terrain_height_image.as_ref() {
         error!(
             "save height image {:?}",
-            map_setting.image_save_path.join("terrain map height.png").to_str()
+            map_setting
+                .image_save_path
+                .join("terrain map height.png")
+                .to_str()
         );
         x.save(map_setting.image_save_path.join("terrain map height.png"))
             .unwrap();
@@ -2,13 +2,14 @@ use std::ops::RangeInclusive;
 
 use bevy::{prelude::*, render::extract_resource::ExtractResource};
 use serde::{Deserialize, Serialize};
-use settings::{Setting, SettingValidate};
+use settings::{Setting, SettingValidate, SettingValidationErrors};
 
 use crate::lod::{lod_octree::LodOctreeDepthType, morton_code::MortonCode};
 
 #[derive(
-    Setting, Resource, Debug, Clone, Serialize, Deserialize, TypePath, Asset, ExtractResource,
+    Setting, Resource, Debug, Clone, Serialize, Deserialize, Reflect, Asset, ExtractResource,
 )]
+#[setting(custom_validate)]
 pub struct TerrainSetting {
     /// chunk大小
     pub chunk_size: f32,
@@ -34,35 +35,38 @@ pub struct TerrainSetting {
     pub stitch_seam_scheme: StitchSeamScheme,
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize, Reflect)]
 pub enum StitchSeamScheme {
     DualContouring,
     NeighborConnect,
 }
 
 impl SettingValidate for TerrainSetting {
-    fn validate(&self) -> bool {
-        let mut validation = true;
+    fn validate(&self) -> Result<(), SettingValidationErrors> {
+        let mut errors = SettingValidationErrors::default();
         let log_2_size = self.chunk_size.log2();
         if log_2_size.fract() != 0.0 {
-            error!("chunk_size must be 2^n");
-            validation = false;
+            errors.push("chunk_size", "chunk_size must be 2^n");
         }
 
         let max_depth = MortonCode::MAX_LEVEL;
         if self.lod_octree_depth > max_depth {
-            error!(
-                "lod_octree_depth value is invalid, must be in [0, {}]",
-                max_depth
+            errors.push(
+                "lod_octree_depth",
+                format!(
+                    "lod_octree_depth value is invalid, must be in [0, {}]",
+                    max_depth
+                ),
             );
-            validation = false;
         }
 
         if *self.height_visibility_range.end() < self.terrain_max_height {
-            error!("height_visibility_range should greater or equal than terrain_max_height",);
-            validation = false;
+            errors.push(
+                "height_visibility_range.end",
+                "height_visibility_range should greater or equal than terrain_max_height",
+            );
         }
-        validation
+        errors.into_result()
     }
 }
 
@@ -213,4 +217,17 @@ mod tests {
         };
         assert_eq!(setting.get_terrain_size(), 16384.0);
     }
+
+    #[test]
+    fn test_terrain_validate() {
+        assert_eq!(TerrainSetting::default().validate(), Ok(()));
+
+        let setting = TerrainSetting {
+            chunk_size: 15.0,
+            ..default()
+        };
+        let errors = setting.validate().unwrap_err();
+        assert_eq!(errors.0.len(), 1);
+        assert_eq!(errors.0[0].path, "chunk_size");
+    }
 }